@@ -1,14 +1,53 @@
 fn main() {
     println!("cargo:rerun-if-env-changed=PROFILE");
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_TRACKING");
     println!("cargo:rerun-if-env-changed=CARGO_FEATURE_USAGE_TRACKING");
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_USAGE_TRACKING_LITE");
     println!("cargo:rerun-if-env-changed=CARGO_FEATURE_NO_USAGE_TRACKING");
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_USAGE_TRACKING_NO_ALLOC");
+    println!("cargo:rerun-if-env-changed=BORROW_TRACKER_CAPACITY");
     println!("cargo::rustc-check-cfg=cfg(usage_tracking_enabled)");
+    println!("cargo::rustc-check-cfg=cfg(usage_tracking_lite_enabled)");
+    println!("cargo::rustc-check-cfg=cfg(field_usage_tracking_enabled)");
 
-    let is_release = std::env::var("PROFILE").map(|v| v == "release").unwrap_or(false);
+    let is_release = std::env::var("PROFILE").is_ok_and(|v| v == "release");
+    let tracking = std::env::var("CARGO_FEATURE_TRACKING").is_ok();
     let usage_tracking = std::env::var("CARGO_FEATURE_USAGE_TRACKING").is_ok();
+    let usage_tracking_lite = std::env::var("CARGO_FEATURE_USAGE_TRACKING_LITE").is_ok();
     let no_usage_tracking = std::env::var("CARGO_FEATURE_NO_USAGE_TRACKING").is_ok();
+    let no_alloc = std::env::var("CARGO_FEATURE_USAGE_TRACKING_NO_ALLOC").is_ok();
 
-    if (!is_release || usage_tracking) && !no_usage_tracking {
+    // The full tracker reports through the `borrow-tracking` crate, enabled by the `tracking`
+    // feature. The `usage_tracking_no_alloc` feature replaces it with the lite one, which uses
+    // static tables only, in all profiles.
+    let full = (!is_release || usage_tracking) && tracking && !no_usage_tracking && !no_alloc;
+    let lite = !full && (usage_tracking_lite || no_alloc) && !no_usage_tracking;
+
+    // Number of slots of the static table of the lite tracker.
+    let default_capacity = 1024;
+    let capacity = match std::env::var("BORROW_TRACKER_CAPACITY") {
+        Err(_) => default_capacity,
+        Ok(value) => match value.parse::<usize>() {
+            Ok(capacity) if capacity > 0 => capacity,
+            _ => {
+                println!(
+                    "cargo:warning=BORROW_TRACKER_CAPACITY must be a positive integer, got \
+                    '{value}'. Using {default_capacity} instead."
+                );
+                default_capacity
+            }
+        },
+    };
+    println!("cargo:rustc-env=BORROW_TRACKER_CAPACITY={capacity}");
+
+    if full {
         println!("cargo:rustc-cfg=usage_tracking_enabled");
     }
+    if lite {
+        println!("cargo:rustc-cfg=usage_tracking_lite_enabled");
+    }
+    // Enabled when fields carry any kind of usage tracker, either the full or the lite one.
+    if full || lite {
+        println!("cargo:rustc-cfg=field_usage_tracking_enabled");
+    }
 }
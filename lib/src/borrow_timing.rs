@@ -0,0 +1,90 @@
+use std::panic::Location;
+use std::time::Duration;
+
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+use std::collections::HashMap;
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+use std::sync::Mutex;
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+use std::sync::PoisonError;
+
+// ====================
+// === BorrowTiming ===
+// ====================
+
+/// How long the partial borrows created at `location` lived, from creation to drop. Reported by
+/// [`for_each_borrow_timing`]. Only borrows of at least one field are recorded, so the rest of a
+/// split is not, as it keeps the fields of its source.
+#[derive(Clone, Copy, Debug)]
+pub struct BorrowTiming {
+    pub location: &'static Location<'static>,
+    /// Number of dropped borrows.
+    pub count: u64,
+    /// The maximum number of fields borrowed by a single borrow.
+    pub max_fields: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl BorrowTiming {
+    /// The average lifetime of the borrows.
+    pub fn mean(&self) -> Duration {
+        let count = u32::try_from(self.count).unwrap_or(u32::MAX).max(1);
+        self.total / count
+    }
+}
+
+// ================
+// === Registry ===
+// ================
+
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+type Registry = HashMap<&'static Location<'static>, BorrowTiming>;
+
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+static REGISTRY: Mutex<Option<Registry>> = Mutex::new(None);
+
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> T) -> T {
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    f(registry.get_or_insert_with(HashMap::new))
+}
+
+/// Records the lifetime of a borrow created at `location`.
+#[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+pub(crate) fn record(location: &'static Location<'static>, fields: usize, duration: Duration) {
+    with_registry(|registry| {
+        let timing = registry.entry(location).or_insert(BorrowTiming {
+            location,
+            count: 0,
+            max_fields: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+        });
+        timing.count += 1;
+        timing.max_fields = timing.max_fields.max(fields);
+        timing.total += duration;
+        timing.max = timing.max.max(duration);
+    });
+}
+
+/// Calls `f` for every callsite creating partial borrows, starting with the callsites whose
+/// borrows were alive for the longest total time. Data is collected only when the `borrow_timing`
+/// feature is enabled and the full usage tracker is enabled (e.g. in debug builds, or with the
+/// `usage_tracking` feature). Otherwise, this function does nothing.
+pub fn for_each_borrow_timing(mut f: impl FnMut(BorrowTiming)) {
+    #[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+    {
+        let mut timings = with_registry(|registry| registry.values().copied().collect::<Vec<_>>());
+        timings.sort_by_key(|timing| std::cmp::Reverse(timing.total));
+        timings.into_iter().for_each(&mut f);
+    }
+    #[cfg(not(all(feature = "borrow_timing", usage_tracking_enabled)))]
+    let _ = &mut f;
+}
+
+/// Removes all the recorded timings, e.g. to measure a single frame.
+pub fn clear_borrow_timings() {
+    #[cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+    with_registry(|registry| registry.clear());
+}
@@ -0,0 +1,99 @@
+use crate::AsRefsMut;
+
+// ==================
+// === Borrowable ===
+// ==================
+
+/// Container exposing the struct only through partial borrows. It does not implement `Clone`,
+/// `Deref`, or `DerefMut`, so the struct can not be cloned, swapped, or replaced through it. This
+/// is useful for roots shared behind a lock or a `RefCell`, where long-lived views are created
+/// from guards, and cloning or replacing the root while they exist is a bug the borrow checker can
+/// not catch.
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// # use borrow::Borrowable;
+/// use std::cell::RefCell;
+///
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+///     edges: Vec<usize>,
+/// }
+///
+/// fn add_node(graph: p!(&<mut nodes> Graph)) {
+///     graph.nodes.push(0);
+/// }
+///
+/// fn main() {
+///     let graph = RefCell::new(Borrowable::new(Graph::default()));
+///     let mut guard = graph.borrow_mut();
+///     let graph_ref: &mut Borrowable<Graph> = &mut guard;
+///     add_node(p!(&mut graph_ref));
+///     drop(guard);
+///     assert_eq!(graph.into_inner().into_inner().nodes, [0]);
+/// }
+/// ```
+///
+/// The struct can not be accessed directly:
+///
+/// ```compile_fail
+/// # use borrow::Borrowable;
+/// #[derive(Clone, Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+/// }
+///
+/// fn main() {
+///     let graph = Borrowable::new(Graph::default());
+///     let copy = graph.clone();
+/// }
+/// ```
+pub struct Borrowable<T> {
+    value: T,
+}
+
+impl<T> Borrowable<T> {
+    #[inline(always)]
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    /// Releases the struct. No views can exist at this point, as they borrow the container.
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Default> Default for Borrowable<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Borrowable<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> std::fmt::Debug for Borrowable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Borrowable").finish_non_exhaustive()
+    }
+}
+
+impl<T: AsRefsMut> AsRefsMut for Borrowable<T> {
+    type Target<'t> = T::Target<'t> where Self: 't;
+    #[track_caller]
+    #[inline(always)]
+    fn as_refs_mut(&mut self) -> Self::Target<'_> {
+        self.value.as_refs_mut()
+    }
+}
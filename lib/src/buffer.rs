@@ -0,0 +1,94 @@
+use std::fmt;
+use std::ops::Range;
+
+// ========================
+// === BufferSplitError ===
+// ========================
+
+/// Error returned by [`split_buffer`] when the requested ranges can not be borrowed separately.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BufferSplitError {
+    /// The range ends after the end of the buffer or is reversed.
+    OutOfBounds { range: Range<usize>, len: usize },
+    /// The ranges share at least one element.
+    Overlap { first: Range<usize>, second: Range<usize> },
+}
+
+impl fmt::Display for BufferSplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { range, len } =>
+                write!(f, "Range {range:?} is out of bounds of a buffer of length {len}."),
+            Self::Overlap { first, second } =>
+                write!(f, "Ranges {first:?} and {second:?} overlap."),
+        }
+    }
+}
+
+impl std::error::Error for BufferSplitError {}
+
+// ====================
+// === split_buffer ===
+// ====================
+
+/// Splits the buffer into disjoint slices given by `ranges`, returned in the order of the ranges.
+/// It is meant for constructing structs whose fields are views into a shared buffer, e.g. packet
+/// scratch space of a network stack, so their fields can be borrowed partially like any other
+/// fields. The ranges can be given in any order, and an error is returned if any of them is out
+/// of bounds, or if two of them overlap.
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// struct Packet<'t> {
+///     #[borrow(external)]
+///     header: &'t mut [u8],
+///     #[borrow(external)]
+///     payload: &'t mut [u8],
+/// }
+///
+/// fn checksum(packet: p!(&<mut header, payload> Packet)) {
+///     packet.header[0] = packet.payload.iter().fold(0, |sum, byte| sum ^ byte);
+/// }
+///
+/// fn main() -> Result<(), borrow::BufferSplitError> {
+///     let mut scratch = [0, 0, 1, 2, 4];
+///     let [header, payload] = borrow::split_buffer(&mut scratch, [0..2, 2..5])?;
+///     let mut packet = Packet { header, payload };
+///     checksum(p!(&mut packet));
+///     assert_eq!(scratch, [7, 0, 1, 2, 4]);
+///     assert!(borrow::split_buffer(&mut scratch, [0..3, 2..5]).is_err());
+///     Ok(())
+/// }
+/// ```
+pub fn split_buffer<T, const N: usize>(
+    buffer: &mut [T],
+    ranges: [Range<usize>; N],
+) -> Result<[&mut [T]; N], BufferSplitError> {
+    let len = buffer.len();
+    if let Some(range) = ranges.iter().find(|range| range.start > range.end || range.end > len) {
+        return Err(BufferSplitError::OutOfBounds { range: range.clone(), len });
+    }
+    let mut order: [usize; N] = std::array::from_fn(|i| i);
+    order.sort_by_key(|&i| (ranges[i].start, ranges[i].end));
+    let mut parts: [Option<&mut [T]>; N] = std::array::from_fn(|_| None);
+    let mut rest = buffer;
+    let mut offset = 0;
+    let mut last: Option<&Range<usize>> = None;
+    // Empty ranges do not share elements with any other range, so they are not checked.
+    for i in order.into_iter().filter(|&i| !ranges[i].is_empty()) {
+        let range = &ranges[i];
+        if let Some(last) = last.filter(|_| range.start < offset) {
+            return Err(BufferSplitError::Overlap { first: last.clone(), second: range.clone() });
+        }
+        let (_, tail) = std::mem::take(&mut rest).split_at_mut(range.start - offset);
+        let (part, tail) = tail.split_at_mut(range.len());
+        parts[i] = Some(part);
+        rest = tail;
+        offset = range.end;
+        last = Some(range);
+    }
+    Ok(parts.map(Option::unwrap_or_default))
+}
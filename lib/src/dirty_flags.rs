@@ -0,0 +1,180 @@
+use crate::HasFieldLabels;
+use crate::Label;
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+// ==================
+// === DirtyFlags ===
+// ==================
+
+/// Dirty bits of the fields of `S`, set whenever a field is used mutably through a partial
+/// borrow. Add it to a struct deriving `borrow::Partial` as a field marked with
+/// `#[borrow(dirty_flags)]`. The field is not a part of partial borrows, so it can be inspected
+/// and cleared by the owner of the struct, e.g. once per frame:
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Scene {
+///     mesh: Vec<f32>,
+///     name: String,
+///     #[borrow(dirty_flags)]
+///     dirty: borrow::DirtyFlags<Scene>,
+/// }
+///
+/// fn add_vertex(scene: p!(&<mut mesh, name> Scene)) {
+///     scene.mesh.push(0.0);
+/// }
+///
+/// fn main() {
+///     let mut scene = Scene::default();
+///     add_vertex(p!(&mut scene));
+///     assert!(scene.dirty.is_dirty("mesh"));
+///     scene.dirty.clear();
+/// }
+/// ```
+///
+/// The flags are set by the usage trackers, so they are exact when usage tracking is enabled,
+/// including the `usage_tracking_lite` feature meant for release builds. Without usage tracking,
+/// every field is marked as dirty when the struct is borrowed. Views borrowed through the trait
+/// generated by `#[borrow(dyn_state = "...")]` do not set the flags.
+pub struct DirtyFlags<S: ?Sized> {
+    state: Arc<DirtyState>,
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S: ?Sized> DirtyFlags<S> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::from_state(Arc::new(DirtyState::new(0)))
+    }
+
+    #[inline(always)]
+    fn from_state(state: Arc<DirtyState>) -> Self {
+        let marker = PhantomData;
+        Self { state, marker }
+    }
+
+    /// Clears the flags of all fields.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.state.bits.store(0, Ordering::Relaxed);
+    }
+
+    /// Checks whether any field was used mutably since the last [`DirtyFlags::clear`].
+    #[inline(always)]
+    pub fn any(&self) -> bool {
+        self.state.bits.load(Ordering::Relaxed) != 0
+    }
+
+    /// Handle setting the flag of the field with the given index. Used by the `borrow::Partial`
+    /// derive macro.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn flag(&self, index: usize) -> DirtyFlag {
+        DirtyFlag { field: Some((self.state.clone(), index)) }
+    }
+
+    /// Flags sharing the state with these ones, unlike [`Clone::clone`]. Used by [`Memo`].
+    ///
+    /// [`Memo`]: crate::Memo
+    #[inline(always)]
+    pub(crate) fn share(&self) -> Self {
+        Self::from_state(self.state.clone())
+    }
+
+    /// Sum of the number of times the fields in `mask` were marked as dirty. It is not affected
+    /// by [`DirtyFlags::clear`], so it changes whenever one of the fields is used mutably.
+    #[inline(always)]
+    pub(crate) fn version(&self, mask: u64) -> u64 {
+        let versions = self.state.versions.iter().enumerate();
+        versions.filter(|(index, _)| mask & (1 << index) != 0)
+            .map(|(_, version)| version.load(Ordering::Relaxed))
+            .fold(0, u64::wrapping_add)
+    }
+}
+
+impl<S: ?Sized + HasFieldLabels> DirtyFlags<S> {
+    /// Checks whether the field was used mutably since the last [`DirtyFlags::clear`]. Returns
+    /// `false` for unknown fields.
+    pub fn is_dirty(&self, field: &str) -> bool {
+        let bits = self.state.bits.load(Ordering::Relaxed);
+        let index = S::FIELD_LABELS.iter().position(|label| *label == field);
+        index.is_some_and(|index| bits & (1 << index) != 0)
+    }
+
+    /// Fields used mutably since the last [`DirtyFlags::clear`], in declaration order.
+    pub fn dirty_fields(&self) -> Vec<Label> {
+        let bits = self.state.bits.load(Ordering::Relaxed);
+        let labels = S::FIELD_LABELS.iter().enumerate();
+        labels.filter(|(index, _)| bits & (1 << index) != 0).map(|(_, label)| *label).collect()
+    }
+}
+
+impl<S: ?Sized> Default for DirtyFlags<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cloned flags are independent of the original ones.
+impl<S: ?Sized> Clone for DirtyFlags<S> {
+    fn clone(&self) -> Self {
+        Self::from_state(Arc::new(DirtyState::new(self.state.bits.load(Ordering::Relaxed))))
+    }
+}
+
+impl<S: ?Sized> std::fmt::Debug for DirtyFlags<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bits = self.state.bits.load(Ordering::Relaxed);
+        f.debug_struct("DirtyFlags").field("bits", &format_args!("{bits:#b}")).finish()
+    }
+}
+
+// ==================
+// === DirtyState ===
+// ==================
+
+/// State shared by [`DirtyFlags`] and the [`DirtyFlag`] handles of the fields.
+#[derive(Debug)]
+struct DirtyState {
+    bits: AtomicU64,
+    /// Number of times every field was marked as dirty. Never cleared, see
+    /// [`DirtyFlags::version`].
+    versions: [AtomicU64; 64],
+}
+
+impl DirtyState {
+    fn new(bits: u64) -> Self {
+        let bits = AtomicU64::new(bits);
+        let versions = std::array::from_fn(|_| AtomicU64::new(0));
+        Self { bits, versions }
+    }
+}
+
+// =================
+// === DirtyFlag ===
+// =================
+
+/// Handle setting the dirty flag of a single field, carried by the field usage trackers.
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub struct DirtyFlag {
+    field: Option<(Arc<DirtyState>, usize)>,
+}
+
+impl DirtyFlag {
+    #[inline(always)]
+    pub fn set(&self) {
+        if let Some((state, index)) = &self.field {
+            state.bits.fetch_or(1 << index, Ordering::Relaxed);
+            if let Some(version) = state.versions.get(*index) {
+                version.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod self_borrow;
 pub mod readability;
-pub mod performance;
\ No newline at end of file
+pub mod performance;
+pub mod generated_items;
\ No newline at end of file
@@ -0,0 +1,78 @@
+//! # 🏷️ Names of Generated Items
+//!
+//! The `borrow::Partial` derive generates items next to the struct. Some of them are meant to be
+//! named by downstream crates, e.g. in trait impls or re-exports, and their names follow the scheme
+//! described below. The scheme is a part of the public API of this crate: it changes only in major
+//! releases, so libraries can rely on it across minor and patch versions of `borrow`.
+//!
+//! For a struct `Graph` with fields `nodes` and `edges`, and a group `topology`:
+//!
+//! | Item                             | Name                                            |
+//! |----------------------------------|-------------------------------------------------|
+//! | Partial borrow struct            | `GraphRef`                                      |
+//! | Fields of the partial borrow     | `nodes`, `edges` (`_0`, `_1` in tuple structs)  |
+//! | Struct macro used by `p!`        | `Graph!`                                        |
+//! | Test view                        | `GraphTestView`                                 |
+//! | Field methods of `GraphRef`      | `borrow_nodes`, `borrow_nodes_mut`, `map_nodes` |
+//! | Group methods of `GraphRef`      | `borrow_topology`, `borrow_topology_mut`        |
+//! | Visitor method of `GraphRef`     | `for_each_selected`                             |
+//! | Methods of `GraphTestView`       | `with_nodes`, `nodes_mut`                       |
+//! | State trait (`dyn_state = "S"`)  | `S`                                             |
+//!
+//! `GraphRef` and `Graph!` are declared with the visibility given by `#[borrow(vis = "...")]`,
+//! `pub` by default, and `GraphTestView` with the visibility of the struct. Field methods of
+//! `GraphRef` have the visibility of the field, unless the struct is marked with
+//! `#[borrow(pub_field_methods)]`. They are all
+//! re-exported next to the struct, so they can be imported from the module of the struct, e.g.
+//! `use graph::GraphRef`. The test view is generated only in `cfg(test)`, unless the `test_view`
+//! feature is enabled. Wrappers marked with `#[borrow(transparent)]` get only the struct macro,
+//! which resolves to the partial borrow struct of the wrapped struct.
+//!
+//! The generic parameters of `GraphRef` are the struct type, the type of every field in the
+//! declaration order, and the tracking flag ([`True`] or [`False`]), which defaults to [`True`].
+//! Their number depends on the fields of the struct, so spell partial borrow types with [`p!`]
+//! instead, which keeps working when fields are added:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! mod graph {
+//!     #[derive(Default, borrow::Partial)]
+//!     #[module(crate::graph)]
+//!     pub struct Graph {
+//!         pub nodes: Vec<usize>,
+//!         pub edges: Vec<(usize, usize)>,
+//!     }
+//! }
+//!
+//! use graph::Graph;
+//! use graph::GraphRef;
+//!
+//! // The same type, spelled with `p!` and with the generated struct.
+//! type Nodes<'t> = p!(&'t <mut nodes> Graph);
+//! type NodesRef<'t> = &'t mut GraphRef<Graph, &'t mut Vec<usize>, borrow::Hidden>;
+//!
+//! fn add_node(graph: Nodes) -> NodesRef {
+//!     graph.nodes.push(0);
+//!     graph
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     add_node(p!(&mut graph));
+//!     assert_eq!(graph.nodes, [0]);
+//! }
+//! ```
+//!
+//! Everything else is an implementation detail, which can change in any release:
+//! - Identifiers starting with a double underscore, e.g. the generic parameters of the generated
+//!   impls and the type aliases of fields marked with `private_mut` or `readonly_in_release`.
+//! - The `GraphMacro` macro exported at the crate root, which `Graph!` re-exports, and the rules
+//!   of both macros. Invoke them only through [`p!`].
+//! - Items of this crate marked with `#[doc(hidden)]`, e.g. the field indexes used by
+//!   [`HasField`].
+//!
+//! [`True`]: crate::True
+//! [`False`]: crate::False
+//! [`p!`]: crate::partial
+//! [`HasField`]: crate::HasField
@@ -0,0 +1,207 @@
+use crate::Hidden;
+use crate::Label;
+use crate::OptUsage;
+use crate::Upgradeable;
+use crate::Usage;
+use std::marker::PhantomData;
+
+// ======================
+// === HasFieldLabels ===
+// ======================
+
+/// Names of the struct fields in declaration order. Implemented by the `borrow::Partial` derive
+/// macro.
+pub trait HasFieldLabels {
+    const FIELD_LABELS: &'static [Label];
+}
+
+// ===================
+// === FieldAccess ===
+// ===================
+
+/// Access to a single field of a partial borrow.
+pub trait FieldAccess {
+    const ACCESS: OptUsage;
+}
+
+impl FieldAccess for Hidden {
+    const ACCESS: OptUsage = None;
+}
+
+impl<T: ?Sized> FieldAccess for &T {
+    const ACCESS: OptUsage = Some(Usage::Ref);
+}
+
+impl<T: ?Sized> FieldAccess for &mut T {
+    const ACCESS: OptUsage = Some(Usage::Mut);
+}
+
+/// Upgradeable fields are borrowed exclusively.
+impl<T: ?Sized> FieldAccess for Upgradeable<'_, T> {
+    const ACCESS: OptUsage = Some(Usage::Mut);
+}
+
+// ====================
+// === HasFieldMask ===
+// ====================
+
+/// Shape of a partial borrow type. Implemented for every `XRef` struct generated by the
+/// `borrow::Partial` derive macro.
+pub trait HasFieldMask {
+    type Struct: ?Sized;
+    fn field_mask() -> FieldMask<Self::Struct>;
+}
+
+// =================
+// === FieldMask ===
+// =================
+
+/// Runtime representation of a partial borrow shape of the struct `S`, i.e. the access to every
+/// field of `S`. It follows the same rules as the partial borrow types, so it can be used to
+/// check the laws of splitting and merging borrows over shapes generated at runtime (e.g. in
+/// property tests, see the `arbitrary` feature).
+pub struct FieldMask<S: ?Sized> {
+    access: Vec<OptUsage>,
+    marker: PhantomData<fn() -> S>,
+}
+
+impl<S: ?Sized> Clone for FieldMask<S> {
+    fn clone(&self) -> Self {
+        Self::from_access(self.access.clone())
+    }
+}
+
+impl<S: ?Sized> PartialEq for FieldMask<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.access == other.access
+    }
+}
+
+impl<S: ?Sized> Eq for FieldMask<S> {}
+
+impl<S: ?Sized + HasFieldLabels> std::fmt::Debug for FieldMask<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<")?;
+        let mut first = true;
+        for (label, access) in self.iter() {
+            let prefix = if first { "" } else { ", " };
+            match access {
+                None => continue,
+                Some(Usage::Ref) => write!(f, "{prefix}{label}")?,
+                Some(Usage::Mut) => write!(f, "{prefix}mut {label}")?,
+            }
+            first = false;
+        }
+        write!(f, ">")
+    }
+}
+
+impl<S: ?Sized> FieldMask<S> {
+    /// Shape with the given access to every field, in the declaration order.
+    #[inline(always)]
+    pub fn from_access(access: Vec<OptUsage>) -> Self {
+        let marker = PhantomData;
+        Self { access, marker }
+    }
+
+    /// Shape of the partial borrow type `V`.
+    #[inline(always)]
+    pub fn of<V: HasFieldMask<Struct = S>>() -> Self {
+        V::field_mask()
+    }
+
+    /// Access to the field with the given index in the declaration order.
+    #[inline(always)]
+    pub fn access(&self, index: usize) -> OptUsage {
+        self.access.get(index).copied().flatten()
+    }
+
+    /// Borrows `target` from `self`, returning the rest, like [`SplitHelper::split`]. Returns
+    /// `None` if `target` requires a field which `self` hides or borrows immutably.
+    ///
+    /// [`SplitHelper::split`]: crate::SplitHelper::split
+    pub fn split(&self, target: &Self) -> Option<Self> {
+        let rest = self.access.iter().zip(&target.access).map(|(source, target)| {
+            match (source, target) {
+                (_, None) => Some(*source),
+                (Some(Usage::Mut), Some(Usage::Mut)) => Some(None),
+                (Some(_), Some(Usage::Ref)) => Some(Some(Usage::Ref)),
+                _ => None,
+            }
+        }).collect::<Option<Vec<_>>>()?;
+        Some(Self::from_access(rest))
+    }
+
+    /// Merges two borrows which can coexist, e.g. the parts returned by [`FieldMask::split`].
+    /// Returns `None` if a field is borrowed mutably by one of them and is not hidden in the other.
+    pub fn merge(&self, other: &Self) -> Option<Self> {
+        let merged = self.access.iter().zip(&other.access).map(|(a, b)| {
+            match (a, b) {
+                (None, _) => Some(*b),
+                (_, None) => Some(*a),
+                (Some(Usage::Ref), Some(Usage::Ref)) => Some(Some(Usage::Ref)),
+                _ => None,
+            }
+        }).collect::<Option<Vec<_>>>()?;
+        Some(Self::from_access(merged))
+    }
+
+    /// Checks whether the borrows can not coexist, i.e. whether a field is borrowed mutably by one
+    /// of them and is not hidden in the other.
+    #[inline(always)]
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.merge(other).is_none()
+    }
+
+    /// Checks whether every field is borrowed by `self` with at most the access of `other`.
+    #[inline(always)]
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.access.iter().zip(&other.access).all(|(a, b)| a <= b)
+    }
+}
+
+impl<S: ?Sized + HasFieldLabels> FieldMask<S> {
+    /// Shape with all fields set to the given access.
+    #[inline(always)]
+    pub fn all(access: OptUsage) -> Self {
+        Self::from_access(vec![access; S::FIELD_LABELS.len()])
+    }
+
+    /// Shape with all fields hidden.
+    #[inline(always)]
+    pub fn hidden() -> Self {
+        Self::all(None)
+    }
+
+    /// Sets the access to the field with the given label. Returns `None` if the struct does not
+    /// have such a field.
+    pub fn with(mut self, label: Label, access: OptUsage) -> Option<Self> {
+        let index = S::FIELD_LABELS.iter().position(|t| *t == label)?;
+        self.access[index] = access;
+        Some(self)
+    }
+
+    /// Iterates over the field labels and their access.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (Label, OptUsage)> + '_ {
+        S::FIELD_LABELS.iter().copied().zip(self.access.iter().copied())
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, S: ?Sized + HasFieldLabels> arbitrary::Arbitrary<'a> for FieldMask<S> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let access = S::FIELD_LABELS.iter().map(|_| {
+            Ok(match u.int_in_range(0..=2_u8)? {
+                0 => None,
+                1 => Some(Usage::Ref),
+                _ => Some(Usage::Mut),
+            })
+        }).collect::<arbitrary::Result<Vec<_>>>()?;
+        Ok(Self::from_access(access))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (S::FIELD_LABELS.len(), Some(S::FIELD_LABELS.len()))
+    }
+}
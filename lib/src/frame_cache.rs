@@ -0,0 +1,110 @@
+use crate::Rebind;
+use crate::Rebound;
+
+// ==================
+// === FrameCache ===
+// ==================
+
+/// Scratch storage for partial borrows created during a single frame. Views are stored in a
+/// [`Frame`] returned by [`FrameCache::begin_frame`], which clears them when dropped, so no view
+/// outlives the frame. The allocation is kept and reused by the next frame.
+///
+/// Similarly to [`OwnedRef`](crate::OwnedRef), the lifetime used in `V` is irrelevant, as views
+/// are stored with the lifetime of the frame. The convention is to use `'static`, e.g.
+/// `FrameCache<p!('static <mut nodes> Graph)>`.
+pub struct FrameCache<V> {
+    /// Always empty, only the allocation is used.
+    buffer: Vec<V>,
+}
+
+impl<V> std::fmt::Debug for FrameCache<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameCache").field("capacity", &self.buffer.capacity()).finish()
+    }
+}
+
+impl<V> Default for FrameCache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FrameCache<V> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        let buffer = Vec::new();
+        Self { buffer }
+    }
+
+    /// Number of views the cache can store without reallocating.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Starts a new frame. Views stored in the frame are cleared when the frame is dropped.
+    pub fn begin_frame<'f>(&'f mut self) -> Frame<'f, V>
+    where V: Rebind<'f> {
+        let views = reuse_allocation(std::mem::take(&mut self.buffer));
+        Frame { cache: self, views }
+    }
+}
+
+/// Converts an empty vector to a vector of a type differing only in lifetimes, keeping the
+/// allocation.
+fn reuse_allocation<T, U>(vec: Vec<T>) -> Vec<U> {
+    vec.into_iter().filter_map(|_| None).collect()
+}
+
+// =============
+// === Frame ===
+// =============
+
+/// Views stored during a single frame. See [`FrameCache`].
+pub struct Frame<'f, V: Rebind<'f>> {
+    cache: &'f mut FrameCache<V>,
+    views: Vec<Rebound<'f, V>>,
+}
+
+impl<'f, V: Rebind<'f>> std::fmt::Debug for Frame<'f, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Frame").field("len", &self.views.len()).finish()
+    }
+}
+
+impl<'f, V: Rebind<'f>> Frame<'f, V> {
+    /// Stores the view and returns its index. Views borrowed for longer than the frame can be
+    /// shortened with [`Rebind::rebind`].
+    #[inline(always)]
+    pub fn push(&mut self, view: Rebound<'f, V>) -> usize {
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Rebound<'f, V>> {
+        self.views.get_mut(index)
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Rebound<'f, V>> {
+        self.views.iter_mut()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+}
+
+impl<'f, V: Rebind<'f>> Drop for Frame<'f, V> {
+    fn drop(&mut self) {
+        self.views.clear();
+        self.cache.buffer = reuse_allocation(std::mem::take(&mut self.views));
+    }
+}
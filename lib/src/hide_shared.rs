@@ -0,0 +1,69 @@
+use crate::Bool;
+use crate::Field;
+use crate::Hidden;
+use crate::Upgradeable;
+
+// =======================
+// === HideSharedField ===
+// =======================
+
+/// Field kept by the rest of a split, given the field of the target partial borrow of type
+/// `Target`. Fields borrowed immutably by the target are hidden, the other fields are kept intact.
+/// Used by [`SplitHelper::split_exclusive`]. Custom field types returned by [`Acquire`]
+/// impls have to implement it to be used with exclusive splits.
+///
+/// [`SplitHelper::split_exclusive`]: crate::SplitHelper::split_exclusive
+/// [`Acquire`]: crate::Acquire
+pub trait HideSharedField<Target>: Sized {
+    type Output;
+    fn hide_shared_field<E: Bool>(field: Field<E, Self>) -> Field<E, Self::Output>;
+}
+
+impl<R> HideSharedField<Hidden> for R {
+    type Output = R;
+    #[inline(always)]
+    fn hide_shared_field<E: Bool>(field: Field<E, Self>) -> Field<E, Self::Output> {
+        field
+    }
+}
+
+impl<T: ?Sized, R> HideSharedField<&T> for R {
+    type Output = Hidden;
+    #[inline(always)]
+    fn hide_shared_field<E: Bool>(field: Field<E, Self>) -> Field<E, Self::Output> {
+        field.clone_as_hidden()
+    }
+}
+
+impl<T: ?Sized, R> HideSharedField<&mut T> for R {
+    type Output = R;
+    #[inline(always)]
+    fn hide_shared_field<E: Bool>(field: Field<E, Self>) -> Field<E, Self::Output> {
+        field
+    }
+}
+
+impl<T: ?Sized, R> HideSharedField<Upgradeable<'_, T>> for R {
+    type Output = R;
+    #[inline(always)]
+    fn hide_shared_field<E: Bool>(field: Field<E, Self>) -> Field<E, Self::Output> {
+        field
+    }
+}
+
+// ==================
+// === HideShared ===
+// ==================
+
+/// Hides the fields of the rest of a split which are borrowed immutably by the target partial
+/// borrow of type `Target`. Implemented by the `borrow::Partial` derive macro.
+pub trait HideShared<Target> {
+    type Output;
+    fn hide_shared(self) -> Self::Output;
+}
+
+/// Type of the rest of `T` after borrowing `Target` with [`SplitHelper::split_exclusive`].
+///
+/// [`SplitHelper::split_exclusive`]: crate::SplitHelper::split_exclusive
+pub type ExclusiveRest<'s, T, Target> =
+    <<T as crate::Partial<'s, Target>>::Rest as HideShared<Target>>::Output;
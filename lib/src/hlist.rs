@@ -100,7 +100,9 @@ pub type SetItemAtResult<T, N, Item> = <T as SetItemAt<N, Item>>::Result;
 // === Macros ===
 // ==============
 
-#[doc(hidden)]
+/// Type of a heterogeneous list with the given item types, e.g. `HList![&'t mut T, Hidden]` is
+/// `Cons<&'t mut T, Cons<Hidden, Nil>>`. Used to name the fields of partial borrows, see
+/// [`AsRefWithFields`](crate::AsRefWithFields).
 #[macro_export]
 macro_rules! HList {
     () => { $crate::hlist::Nil };
@@ -0,0 +1,103 @@
+//! Laws that have to hold for every split of a partial borrow. Downstream crates can instantiate
+//! them for their own types, e.g. to check that custom field types implementing
+//! [`Acquire`](crate::Acquire) do not break the invariants:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! #
+//! # #[derive(borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     nodes: Vec<usize>,
+//! #     edges: Vec<usize>,
+//! # }
+//! #
+//! fn main() {
+//!     borrow::laws::check_split::<p!(<mut nodes> Graph), p!(<mut *> Graph)>();
+//!     borrow::laws::check_split::<p!(<nodes> Graph), p!(<mut nodes, edges> Graph)>();
+//! }
+//! ```
+//!
+//! The laws are checked on the [`FieldMask`]s of the types, so they don't require values of the
+//! struct. Each function panics if its law is violated.
+
+use crate::FieldMask;
+use crate::HasFieldLabels;
+use crate::HasFieldMask;
+use crate::IntoPartial;
+use crate::Usage;
+
+type Rest<Spec, T> = <T as IntoPartial<Spec>>::Rest;
+
+/// The target of the split borrows every field with at most the access of the source.
+pub fn split_is_narrowing<Spec, T>()
+where
+    T: IntoPartial<Spec> + HasFieldMask,
+    T::Struct: HasFieldLabels,
+    Spec: HasFieldMask<Struct = T::Struct> {
+    let source = FieldMask::of::<T>();
+    let target = FieldMask::of::<Spec>();
+    assert!(target.is_subset_of(&source), "{target:?} is borrowed from {source:?}");
+}
+
+/// The target and the rest of the split can be used at the same time.
+pub fn split_is_disjoint<Spec, T>()
+where
+    T: IntoPartial<Spec> + HasFieldMask,
+    T::Struct: HasFieldLabels,
+    Spec: HasFieldMask<Struct = T::Struct>,
+    Rest<Spec, T>: HasFieldMask<Struct = T::Struct> {
+    let target = FieldMask::of::<Spec>();
+    let rest = FieldMask::of::<Rest<Spec, T>>();
+    assert!(!target.conflicts_with(&rest), "{target:?} conflicts with the rest {rest:?}");
+}
+
+/// The rest of the split is the one computed by [`FieldMask::split`].
+pub fn split_rest_matches_mask<Spec, T>()
+where
+    T: IntoPartial<Spec> + HasFieldMask,
+    T::Struct: HasFieldLabels,
+    Spec: HasFieldMask<Struct = T::Struct>,
+    Rest<Spec, T>: HasFieldMask<Struct = T::Struct> {
+    let source = FieldMask::of::<T>();
+    let target = FieldMask::of::<Spec>();
+    let rest = FieldMask::of::<Rest<Spec, T>>();
+    let expected = source.split(&target);
+    assert_eq!(Some(&rest), expected.as_ref(), "rest of {target:?} borrowed from {source:?}");
+}
+
+/// Joining the target and the rest of the split gives back the source. The only exception are
+/// fields borrowed mutably by the source and immutably by the target, which are shared by both
+/// parts and thus can only be joined immutably.
+pub fn split_then_join_is_identity<Spec, T>()
+where
+    T: IntoPartial<Spec> + HasFieldMask,
+    T::Struct: HasFieldLabels,
+    Spec: HasFieldMask<Struct = T::Struct>,
+    Rest<Spec, T>: HasFieldMask<Struct = T::Struct> {
+    let source = FieldMask::of::<T>();
+    let target = FieldMask::of::<Spec>();
+    let rest = FieldMask::of::<Rest<Spec, T>>();
+    let joined = target.merge(&rest);
+    let expected = (0..T::Struct::FIELD_LABELS.len()).map(|i| {
+        match (source.access(i), target.access(i)) {
+            (Some(Usage::Mut), Some(Usage::Ref)) => Some(Usage::Ref),
+            (access, _) => access,
+        }
+    }).collect();
+    let expected = FieldMask::from_access(expected);
+    assert_eq!(joined.as_ref(), Some(&expected), "{target:?} joined with the rest {rest:?}");
+}
+
+/// Checks all the split laws.
+pub fn check_split<Spec, T>()
+where
+    T: IntoPartial<Spec> + HasFieldMask,
+    T::Struct: HasFieldLabels,
+    Spec: HasFieldMask<Struct = T::Struct>,
+    Rest<Spec, T>: HasFieldMask<Struct = T::Struct> {
+    split_is_narrowing::<Spec, T>();
+    split_is_disjoint::<Spec, T>();
+    split_rest_matches_mask::<Spec, T>();
+    split_then_join_is_identity::<Spec, T>();
+}
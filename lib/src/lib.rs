@@ -210,18 +210,17 @@
 //! # pub struct Edge;
 //! # pub struct Group;
 //! #
-//! pub struct GraphRef<__Self__, __Tracking__, Nodes, Edges, Groups> {
+//! pub struct GraphRef<__Self__, Nodes, Edges, Groups, __Tracking__ = borrow::True> {
 //!     pub nodes:  Nodes,
 //!     pub edges:  Edges,
 //!     pub groups: Groups,
-//!     marker:     std::marker::PhantomData<(__Self__, __Tracking__)>,
+//!     __marker__: std::marker::PhantomData<(__Self__, __Tracking__)>,
 //! }
 //!
 //! impl Graph {
 //!     pub fn as_refs_mut(&mut self) ->
 //!        GraphRef<
 //!            Self,
-//!            borrow::True,
 //!            &mut Vec<Node>,
 //!            &mut Vec<Edge>,
 //!            &mut Vec<Group>,
@@ -231,7 +230,7 @@
 //!             nodes:  &mut self.nodes,
 //!             edges:  &mut self.edges,
 //!             groups: &mut self.groups,
-//!             marker: std::marker::PhantomData,
+//!             __marker__: std::marker::PhantomData,
 //!         }
 //!     }
 //! }
@@ -243,7 +242,8 @@
 //! useful when defining traits for partially borrowed types.
 //!
 //! The latter parameter, `__Tracking__`, controls whether the system should emit diagnostics
-//! related to unused borrowed fields.
+//! related to unused borrowed fields. It is the last parameter and defaults to `borrow::True`, so
+//! it is omitted in error messages and can be omitted when naming the type.
 //!
 //! In reality, the `GraphRef` struct is slightly more complex to support runtime diagnostics for
 //! unused borrows. These diagnostics introduce a small performance overhead, but only in debug
@@ -261,27 +261,26 @@
 //! # pub struct Edge;
 //! # pub struct Group;
 //! #
-//! pub struct GraphRef<__Self__, __Tracking__, Nodes, Edges, Groups>
+//! pub struct GraphRef<__Self__, Nodes, Edges, Groups, __Tracking__ = borrow::True>
 //! where __Tracking__: borrow::Bool {
 //!     pub nodes:  borrow::Field<__Tracking__, Nodes>,
 //!     pub edges:  borrow::Field<__Tracking__, Edges>,
 //!     pub groups: borrow::Field<__Tracking__, Groups>,
-//!     marker:     std::marker::PhantomData<__Self__>,
+//!     __marker__: std::marker::PhantomData<fn() -> __Self__>,
 //!     // In release mode this is optimized away.
-//!     usage_tracker: borrow::UsageTracker,
+//!     __usage_tracker__: borrow::UsageTracker,
 //! }
 //!
 //! impl Graph {
 //!     pub fn as_refs_mut(&mut self) ->
 //!        GraphRef<
 //!            Self,
-//!            borrow::True,
 //!            &mut Vec<Node>,
 //!            &mut Vec<Edge>,
 //!            &mut Vec<Group>,
 //!        >
 //!     {
-//!         let usage_tracker = borrow::UsageTracker::new();
+//!         let usage_tracker = borrow::UsageTracker::new_root();
 //!         GraphRef {
 //!             // In release mode this is the same as `&mut self.nodes`.
 //!             nodes: borrow::Field::new(
@@ -304,8 +303,8 @@
 //!                 &mut self.groups,
 //!                 usage_tracker.clone(),
 //!             ),
-//!             marker: std::marker::PhantomData,
-//!             usage_tracker,
+//!             __marker__: std::marker::PhantomData,
+//!             __usage_tracker__: usage_tracker,
 //!         }
 //!     }
 //! }
@@ -315,113 +314,1378 @@
 //! release builds, ensuring zero runtime overhead. They exist solely to provide enhanced
 //! diagnostics about unused field borrows, as explained later in this documentation.
 //!
+//! Tuple structs are supported as well. Their fields are selected by position, and are named
+//! after it in the generated `Ref` struct, e.g. `_0`. The same applies to the generated methods
+//! (e.g. `borrow_0_mut`) and to the field lists of struct attributes (e.g. `"0, 1"`):
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! pub struct Graph(pub Vec<usize>, pub Vec<(usize, usize)>);
+//!
+//! fn connect(graph: p!(&<0, mut 1> Graph)) {
+//!     graph._1.push((graph._0[0], graph._0[1]));
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph(vec![1, 2], vec![]);
+//!     connect(p!(&mut graph));
+//!     assert_eq!(graph.1, [(1, 2)]);
+//! }
+//! ```
+//!
+//! Structs without fields, e.g. unit structs or structs whose fields are all disabled with `cfg`
+//! attributes, are supported too. Their partial borrows hold no fields, and can be selected only
+//! with the empty `<>` and the `<*>` selectors.
+//!
+//! Fields named with raw identifiers, e.g. `r#type`, are selected as written, e.g.
+//! `p!(&<mut r#type> Token)`, while the names of the generated methods drop the `r#` prefix,
+//! e.g. `borrow_type_mut` and `map_type`.
+//!
+//! Zero-sized marker fields, i.e. fields of type `PhantomData<...>`, `PhantomPinned`, or `()`,
+//! hold no state, so they are not borrowed. They can not be selected, do not add parameters to
+//! the `Ref` struct, and are not reported by usage tracking. Other markers, e.g. type aliases of
+//! `PhantomData`, can be marked with `#[borrow(marker)]`:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # use std::marker::PhantomData;
+//! type Unit<T> = PhantomData<T>;
+//!
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! pub struct Distances<T> {
+//!     pub values: Vec<f32>,
+//!     pub kind: PhantomData<T>,
+//!     #[borrow(marker)]
+//!     pub unit: Unit<T>,
+//! }
+//!
+//! fn push<T>(distances: p!(&<mut *> Distances<T>)) {
+//!     distances.values.push(1.0);
+//! }
+//!
+//! fn main() {
+//!     let mut distances = Distances::<u8>::default();
+//!     push(p!(&mut distances));
+//!     assert_eq!(distances.values, [1.0]);
+//! }
+//! ```
+//!
+//! The last field of a struct can be dynamically sized, e.g. `[u8]` or a type parameter declared
+//! as `?Sized`. The `Ref` struct then holds a reference to it, e.g. `&mut [u8]`. Test views store
+//! fields by value, so they are not generated for such structs:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! pub struct Packet<T: ?Sized> {
+//!     pub len: usize,
+//!     pub data: T,
+//! }
+//!
+//! fn fill(packet: p!(&<mut len, mut data> Packet<[u8]>)) {
+//!     packet.data.fill(1);
+//!     **packet.len = packet.data.len();
+//! }
+//!
+//! fn main() {
+//!     let mut packet: Box<Packet<[u8]>> = Box::new(Packet { len: 0, data: [0; 4] });
+//!     let packet: &mut Packet<[u8]> = &mut packet;
+//!     fill(p!(&mut packet));
+//!     assert_eq!((packet.len, &packet.data), (4, &[1; 4][..]));
+//! }
+//! ```
+//!
+//! Single-field wrappers of structs deriving `borrow::Partial`, e.g. newtypes, can be marked with
+//! `#[borrow(transparent)]`. Partial borrows of the wrapper are then partial borrows of the wrapped
+//! struct, so the wrapper is selected with the fields of the wrapped struct, and no `Ref` struct
+//! is generated for it. The wrapped struct is named by a path starting with `crate`, or by a path
+//! relative to the module given in `#[module(...)]`:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! pub struct Graph {
+//!     pub nodes: Vec<usize>,
+//!     pub edges: Vec<(usize, usize)>,
+//! }
+//!
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(transparent)]
+//! pub struct World(pub Graph);
+//!
+//! fn add_node(world: p!(&<mut nodes> World)) {
+//!     world.nodes.push(0);
+//! }
+//!
+//! fn main() {
+//!     let mut world = World::default();
+//!     add_node(p!(&mut world));
+//!     assert_eq!(world.0.nodes, [0]);
+//! }
+//! ```
+//!
+//! If your struct is a part of a public API and you plan to add fields to it in a future minor
+//! release, mark it with `#[borrow(non_exhaustive)]`. Selectors of fields unknown to the struct
+//! are then ignored (the fields are treated as hidden), so downstream `p!` call sites written
+//! against a newer version of the struct keep compiling with the older one:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(non_exhaustive)]
+//! pub struct Graph {
+//!    pub nodes: Vec<usize>,
+//!    pub edges: Vec<usize>,
+//! }
+//!
+//! // The `groups` field is not defined (yet), so it is ignored.
+//! fn pass(graph: p!(&<mut nodes, groups> Graph)) {
+//!     graph.nodes.push(0);
+//! }
+//! ```
+//!
+//! Partial borrows are generic, so conversions between them are compiled in every crate that
+//! uses them. If many downstream crates borrow the same shapes of your struct, mark it with
+//! `#[borrow(monomorphize)]`. The derive then generates non-generic `borrow_all`,
+//! `borrow_all_mut`, `borrow_$group`, and `borrow_$group_mut` methods on the struct, which are
+//! compiled once in the defining crate. Structs with type parameters are not supported.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(monomorphize)]
+//! #[borrow(group(topology = "nodes, edges"))]
+//! pub struct Graph {
+//!    pub nodes: Vec<usize>,
+//!    pub edges: Vec<usize>,
+//!    pub groups: Vec<usize>,
+//! }
+//!
+//! fn pass(graph: p!(&<mut nodes, mut edges> Graph)) {
+//!     graph.edges.push(graph.nodes.len());
+//! }
+//!
+//! fn run(graph: &mut Graph) {
+//!     pass(&mut graph.borrow_topology_mut());
+//! }
+//! ```
+//!
+//! To enforce architectural boundaries, e.g. that only your crate mutates an allocator, list the
+//! fields in `#[borrow(private_mut = "...")]`. Mutable and upgradeable selectors of these fields
+//! (including `*`) then refer to a `pub(crate)` item, so they fail to compile outside the crate
+//! defining the struct, while shared selectors work everywhere. Group and field methods borrowing
+//! the fields mutably are `pub(crate)` as well. Root views created by other crates, e.g. with
+//! `as_refs_mut`, `partial_borrow`, or `split`, borrow the fields as shared, while the defining
+//! crate gets `pub(crate)` inherent methods of the same names borrowing them mutably. Generic
+//! code, e.g. `split_with` or the conversion of `&mut Core`, uses the shared root view in the
+//! defining crate too. This restricts partial borrows only, so in order to prevent direct
+//! mutation, the fields should not be `pub`.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(private_mut = "allocator")]
+//! pub struct Core {
+//!    pub allocator: Vec<usize>,
+//!    pub log: Vec<String>,
+//! }
+//!
+//! // Compiles only in the crate defining `Core`.
+//! fn alloc(core: p!(&<mut allocator> Core)) {
+//!     core.allocator.push(0);
+//! }
+//!
+//! // Compiles everywhere.
+//! fn allocated(core: p!(&<allocator> Core)) -> usize {
+//!     core.allocator.len()
+//! }
+//! ```
+//!
+//! Fields which should be mutated only in debug builds, e.g. profiling or diagnostic scribble
+//! fields, can be listed in `#[borrow(readonly_in_release = "...")]`. Mutable and upgradeable
+//! selectors of these fields (including `*`) fail to compile in builds without
+//! `debug_assertions`, and so do the methods borrowing them mutably, e.g. `borrow_stats_mut`.
+//! Root views, e.g. `as_refs_mut()`, borrow them as shared in these builds. Code mutating them
+//! should be marked with `#[cfg(debug_assertions)]`. The setting of the crate defining the struct
+//! is used.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(readonly_in_release = "stats")]
+//! pub struct Renderer {
+//!    pub meshes: Vec<usize>,
+//!    pub stats: Vec<String>,
+//! }
+//!
+//! #[cfg(debug_assertions)]
+//! fn draw(renderer: p!(&<mut meshes, mut stats> Renderer)) {
+//!     renderer.stats.push("draw".to_string());
+//! }
+//! ```
+//!
+//! Fields which should never be mutated through partial borrows, e.g. configuration, can be marked
+//! with `#[borrow(readonly)]`. Mutable and upgradeable selectors of these fields fail to compile,
+//! while `mut *` and the mutable group methods borrow them as shared. The `borrow_$field_mut`
+//! methods are not generated for them.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! pub struct Renderer {
+//!    pub meshes: Vec<usize>,
+//!    #[borrow(readonly)]
+//!    pub config: String,
+//! }
+//!
+//! fn draw(renderer: p!(&<mut *> Renderer)) {
+//!     renderer.meshes.push(renderer.config.len());
+//! }
+//!
+//! fn main() {
+//!     let mut renderer = Renderer { meshes: vec![], config: "gl".to_string() };
+//!     draw(p!(&mut renderer));
+//!     assert_eq!(renderer.meshes, [2]);
+//! }
+//! ```
+//!
+//! If your state is hidden behind a trait, e.g. to mock it in tests, mark the struct with
+//! `#[borrow(dyn_state = "Name")]`. The derive then generates the `Name` trait with the
+//! `fields_mut` method returning references to all the fields, implements it for the struct, and
+//! allows creating partial borrows of the struct from `dyn Name` trait objects. Fields which the
+//! root view borrows as shared, e.g. readonly and private mut fields, are returned as shared
+//! references. Structs with parameters are not supported.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(dyn_state = "GraphFields")]
+//! pub struct Graph {
+//!    pub nodes: Vec<usize>,
+//!    pub edges: Vec<usize>,
+//! }
+//!
+//! struct MockGraph {
+//!     nodes: Vec<usize>,
+//!     edges: Vec<usize>,
+//!     log: Vec<String>,
+//! }
+//!
+//! impl GraphFields for MockGraph {
+//!     fn fields_mut(&mut self) -> (&mut Vec<usize>, &mut Vec<usize>) {
+//!         (&mut self.nodes, &mut self.edges)
+//!     }
+//! }
+//!
+//! fn pass(graph: p!(&<mut nodes> Graph)) {
+//!     graph.nodes.push(0);
+//! }
+//!
+//! fn run(graph: &mut dyn GraphFields) {
+//!     pass(p!(&mut graph));
+//! }
+//! ```
+//!
+//! Existing code often splits structs by hand, into structs of references to some of the fields.
+//! To migrate it incrementally, declare such a split struct with
+//! `#[borrow(split_struct(ty = "Parts<'a>", fields = "mut field1, field2"))]`, where `'a` is the
+//! lifetime of the references and `mut` marks fields referenced mutably. The fields of the split
+//! struct have to be named as the fields of the struct. The derive then implements [`From`] to
+//! convert partial borrows of the listed fields, owned or passed by `p!(&<...>)`, to the split
+//! struct, and to convert the split struct back to a partial borrow:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(split_struct(ty = "Topology<'a>", fields = "mut nodes, edges"))]
+//! pub struct Graph {
+//!    pub nodes: Vec<usize>,
+//!    pub edges: Vec<usize>,
+//!    pub groups: Vec<usize>,
+//! }
+//!
+//! pub struct Topology<'a> {
+//!     pub nodes: &'a mut Vec<usize>,
+//!     pub edges: &'a Vec<usize>,
+//! }
+//!
+//! fn legacy_pass(topology: Topology) {
+//!     let mut graph: p!(<mut nodes, edges> Graph) = topology.into();
+//!     pass(p!(&mut graph));
+//! }
+//!
+//! fn pass(graph: p!(&<mut nodes, edges> Graph)) {
+//!     let topology = Topology::from(graph);
+//!     topology.nodes.push(topology.edges.len());
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     legacy_pass(Topology { nodes: &mut graph.nodes, edges: &graph.edges });
+//!     assert_eq!(graph.nodes, [0]);
+//! }
+//! ```
+//!
+//! To learn which fields were modified, e.g. to re-render or persist only the changed parts of
+//! the state, add a field of type [`DirtyFlags`] marked with `#[borrow(dirty_flags)]`. It is not
+//! a part of partial borrows, and it records every field used mutably through them until it is
+//! cleared. See [`DirtyFlags`] for details. Results of computations reading some of the fields
+//! can be cached with [`Memo`], which watches the flags and drops the result once one of these
+//! fields is used mutably.
+//!
+//! Fields guarded by a lock can be marked with `#[borrow(wrap = "Mutex")]` (or `"RwLock"`,
+//! `"RefCell"`). Partial borrows then see the value inside of the lock, e.g. `Vec<T>` for a field
+//! of type `Mutex<Vec<T>>`, while code holding shared references to the struct keeps locking it.
+//! See [`Lock`] for details.
+//!
+//! Storages living outside of the struct, e.g. `slotmap::SecondaryMap`s of components owned by
+//! other systems, can be registered as virtual fields. Such a field has the type `&'t mut T` and
+//! is marked with `#[borrow(external)]`. Partial borrows then see it as a field of type `T`, so it
+//! can be selected and split like any other field:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! use std::collections::HashMap;
+//!
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! struct World<'t> {
+//!     nodes: Vec<String>,
+//!     #[borrow(external)]
+//!     positions: &'t mut HashMap<usize, f32>,
+//! }
+//!
+//! fn spawn(world: p!(&<mut nodes, mut positions> World)) {
+//!     world.positions.insert(world.nodes.len(), 0.0);
+//!     world.nodes.push("node".to_string());
+//! }
+//!
+//! fn main() {
+//!     let mut positions = HashMap::new();
+//!     let mut world = World { nodes: vec![], positions: &mut positions };
+//!     spawn(p!(&mut world));
+//!     assert_eq!(positions.len(), 1);
+//! }
+//! ```
+//!
+//! Structs whose fields are views into a shared buffer, e.g. zero-copy parsers, can use external
+//! fields of type `&'t mut [u8]`. The views are created with [`split_buffer`], which checks that
+//! the ranges of the fields do not overlap.
+//!
+//! A field whose type also derives `borrow::Partial` can be marked with
+//! `#[borrow(flatten = "...")]`, listing all fields of the nested struct in their declaration
+//! order. The fields of the nested struct are then borrowed as fields of the outer struct, so a
+//! struct can be decomposed into smaller ones without changing the `p!` call sites. A list not
+//! matching the nested struct is a compile error. The nested fields are borrowed through the
+//! partial borrow of the nested struct, so `#[borrow(wrap = "...")]` and `#[borrow(external)]`
+//! fields are supported, while readonly fields are not, and the dirty flags of the nested struct
+//! are not updated.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! struct RenderCtx {
+//!     geometry: Vec<f32>,
+//!     material: Vec<String>,
+//! }
+//!
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! struct Ctx {
+//!     #[borrow(flatten = "geometry, material")]
+//!     render: RenderCtx,
+//!     scene: Vec<usize>,
+//! }
+//!
+//! fn add_point(ctx: p!(&<mut geometry, scene> Ctx)) {
+//!     ctx.geometry.push(ctx.scene.len() as f32);
+//! }
+//!
+//! fn main() {
+//!     let mut ctx = Ctx::default();
+//!     add_point(p!(&mut ctx));
+//!     assert_eq!(ctx.render.geometry, [0.0]);
+//! }
+//! ```
+//!
+//! The partial borrow struct, e.g. `GraphRef`, can implement [`Debug`] and [`Clone`] with
+//! `#[borrow(derive(Debug, Clone))]`. The impls require the types of the borrowed fields to
+//! implement the trait, so only partial borrows with all fields borrowed as shared can be cloned.
+//! Printing the fields does not register their usage, while usage of the clone is attributed to
+//! the original partial borrow.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(derive(Debug, Clone))]
+//! struct Graph {
+//!     nodes: Vec<usize>,
+//!     edges: Vec<(usize, usize)>,
+//! }
+//!
+//! fn node_count(graph: p!(&<nodes> Graph)) -> usize {
+//!     dbg!(&graph);
+//!     let clone = graph.clone();
+//!     clone.nodes.len()
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     assert_eq!(node_count(p!(&mut graph)), 0);
+//! }
+//! ```
+//!
+//! The generated impls have the bounds of the struct parameters. Additional predicates can be
+//! given with `#[borrow(bound = "...")]`, which adds them to all the generated impls, or with
+//! `#[borrow(bound(as_refs_mut = "..."))]`, which adds them to the [`AsRefsMut`] impl only, so
+//! partial borrows can be created only for the structs satisfying them:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! pub trait Component: 'static {}
+//! impl Component for usize {}
+//!
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(bound(as_refs_mut = "T: Component"))]
+//! struct Storage<T> {
+//!     items: Vec<T>,
+//!     names: Vec<String>,
+//! }
+//!
+//! fn add<T>(storage: p!(&<mut items> Storage<T>), item: T) {
+//!     storage.items.push(item);
+//! }
+//!
+//! fn main() {
+//!     let mut storage = Storage::<usize>::default();
+//!     add(p!(&mut storage), 1);
+//! }
+//! ```
+//!
+//! To unit-test functions taking partial borrows without creating the whole struct, use the test
+//! view generated for every struct, e.g. `GraphRef::test_view().with_nodes(vec![0])`. It owns the
+//! field values, using [`Default`] for the fields which were not provided, and is generated only
+//! in `cfg(test)`, unless the `test_view` feature is enabled. See [`TestField`] for details.
+//!
+//! Structs marked with `#[borrow(schema)]` get a JSON schema of their fields and groups written
+//! to `$OUT_DIR/borrow_schema/$module/$Struct.json` at build time, where `$module` is the path
+//! given in `#[module(...)]` without the leading `crate`, empty for structs without the attribute.
+//! External tools, e.g. code generators of scripting layers, can use it to mirror the borrow
+//! shapes. The crate defining the struct needs a
+//! build script, as `OUT_DIR` is not set otherwise.
+//!
+//! To get a census of the borrow shapes used in your program, e.g. to find near-duplicate
+//! selectors worth replacing with a group or a `borrow::spec!`, set `BORROW_SELECTOR_CENSUS` to an
+//! absolute path when building. Every expanded `p!` with a struct target then appends a JSON line
+//! with the struct, its selectors, and the location of the target to that file:
+//!
+//! ```text
+//! {"struct": "Graph", "type": "Graph", "selectors": ["mut nodes", "edges"], "file": "src/lib.rs", "line": 12, "column": 20}
+//! ```
+//!
+//! Borrows with specs are recorded with the selectors of the spec, and borrows without selectors,
+//! e.g. `p!(&Graph)`, with the `default` selector. Only the crates compiled in the build are
+//! recorded, and Cargo does not rebuild them when the variable changes, so run `cargo clean` first
+//! and remove the file between runs to get the census of the whole program.
+//!
+//! With the `registry` feature, every derived struct registers its name, module, fields, and
+//! groups at link time, so generic development tools, e.g. state inspectors and consoles, can
+//! enumerate all partially borrowable state of the application with `registered_structs`. The
+//! feature is off by default, as it adds the `inventory` dependency and a static per struct.
+//!
+//! The generated partial borrow struct, struct macro, and methods of the struct are `pub`, and the
+//! struct macro is exported with `#[macro_export]`. To keep them out of the public API and docs of
+//! your crate, e.g. for internal-only structs, give them another visibility with
+//! `#[borrow(vis = "pub(crate)")]`. The struct macro is then exported with a `use` of the given
+//! visibility instead, so `p!` can be used only where the struct macro is visible.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[borrow(vis = "pub(crate)")]
+//! pub(crate) struct Cache {
+//!    pub entries: Vec<usize>,
+//!    pub hits: usize,
+//! }
+//!
+//! fn hit(cache: p!(&<mut hits> Cache)) {
+//!     **cache.hits += 1;
+//! }
+//! ```
+//!
+//! Macros exported with `#[macro_export]` live at the root of the crate, so the name of the
+//! exported struct macro includes a hash of the path given in `#[module(...)]`, and `p!` uses it
+//! through its re-export under the name of the struct. Structs with the same name in different
+//! modules do not collide then. Structs without `#[module(...)]` export `$StructMacro`, and two of
+//! them with the same name need distinct names given with `#[borrow(macro_name = ...)]`, e.g.
+//! `#[borrow(macro_name = PhysicsStateMacro)]`. To keep a `pub` struct public while defining its
+//! struct macro next to it instead, mark it with `#[borrow(macro_export = false)]`. The struct
+//! macro is then re-exported with `pub(crate) use`, so `p!` can be used with the struct only within
+//! your crate.
+//!
+//! Structs defined in a function body, e.g. in quick prototypes and tests, can not be named by a
+//! path and can not export macros. Mark them with `#[borrow(local)]` to define the struct macro
+//! next to the struct, without `#[module(...)]`:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! fn main() {
+//!     #[derive(Default, borrow::Partial)]
+//!     #[borrow(local)]
+//!     struct Counter {
+//!         hits: usize,
+//!         misses: usize,
+//!     }
+//!
+//!     fn hit(counter: p!(&<mut hits> Counter)) {
+//!         **counter.hits += 1;
+//!     }
+//!
+//!     let mut counter = Counter::default();
+//!     hit(p!(&mut counter));
+//!     assert_eq!(counter.hits, 1);
+//! }
+//! ```
+//!
+//! The generated code refers to this crate as `borrow`. Frameworks re-exporting it under another
+//! path, and crates renaming it in `Cargo.toml`, can give the path of the crate with
+//! `#[borrow(crate = ...)]`, e.g. `#[borrow(crate = ::framework::borrow)]`, or its alias
+//! `#[borrow(crate_path = "::framework::borrow")]`. The path should start with `::`, as the
+//! struct macro can be expanded in other crates. The `partial!` macro does not refer to the crate
+//! at all, it delegates to the struct macro, so it works with any path.
+//!
+//! The `#[module(...)]` attribute, giving the path of the module defining the struct, is
+//! optional. Without it, partial borrows are named through the [`HasRef`] trait, e.g.
+//! `<Graph as borrow::HasRef<...>>::Ref`, which resolves to the `GraphRef` struct. Inherent impls
+//! of partial borrows, e.g. `impl p!(<mut nodes> Graph) { ... }`, and
+//! `#[borrow(private_mut = "...")]` require the attribute, as do transparent wrappers naming the
+//! wrapped struct by a relative path:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! mod graph {
+//!     #[derive(Default, borrow::Partial)]
+//!     pub struct Graph {
+//!         pub nodes: Vec<usize>,
+//!         pub edges: Vec<(usize, usize)>,
+//!     }
+//! }
+//!
+//! use graph::Graph;
+//!
+//! fn add_node(graph: p!(&<mut nodes> Graph)) {
+//!     graph.nodes.push(0);
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     add_node(p!(&mut graph));
+//!     assert_eq!(graph.nodes.len(), 1);
+//! }
+//! ```
+//!
+//! The derive can also be written as the [`partial_struct`] attribute, taking the module and the
+//! struct options as arguments. The attribute removes the `#[borrow(...)]` attributes from the
+//! struct and its fields, so the struct can be passed to other attribute macros, which would
+//! reject unknown attributes. It is not named `partial`, as this name is taken by the
+//! [`partial!`] macro:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[borrow::partial_struct(module = crate, group(topology = "nodes, edges"))]
+//! #[derive(Default)]
+//! pub struct Graph {
+//!     pub nodes: Vec<usize>,
+//!     pub edges: Vec<(usize, usize)>,
+//!     #[borrow(readonly)]
+//!     pub name: String,
+//! }
+//!
+//! fn add_node(graph: p!(&<mut @topology> Graph)) {
+//!     graph.nodes.push(0);
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     add_node(p!(&mut graph));
+//!     assert_eq!(graph.nodes.len(), 1);
+//! }
+//! ```
+//!
+//! The names of the generated items, e.g. `GraphRef` and `GraphTestView`, follow a documented
+//! scheme which changes only in major releases, see [`doc::generated_items`].
+//!
+//! The derive does not modify the struct, so `#[repr(C)]` structs keep their layout, e.g. when
+//! they are shared with C code or uploaded to the GPU. `#[repr(packed)]` structs are rejected, as
+//! partial borrows reference their fields, which can be unaligned:
+//!
+//! ```compile_fail
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! #[repr(C, packed)]
+//! struct Vertex {
+//!     flags: u8,
+//!     position: [f32; 3],
+//! }
+//! ```
+//!
+//! The derive reports all problems of the struct at once, e.g. an invalid module path, unknown
+//! fields in attributes, and groups colliding with fields, each pointing to the relevant part of
+//! the struct:
+//!
+//! ```compile_fail
+//! #[derive(borrow::Partial)]
+//! #[module(crate::)]
+//! #[borrow(private_mut = "groups", group(nodes = "nodes"))]
+//! struct Graph {
+//!     nodes: Vec<usize>,
+//!     #[borrow(wrap = "Mutex")]
+//!     edges: Vec<usize>,
+//! }
+//! ```
+//!
 //! <br/>
 //! <br/>
 //!
-//! # 📖 `borrow::partial` (`p!`) macro
+//! # 📖 `borrow::partial` (`p!`) macro
+//!
+//! This crate provides the `borrow::partial` macro, which we recommend importing under a shorter
+//! alias, `p`, for convenience. The macro can be used both at the type level to specify the type of
+//! partial borrow, and at the value level to create a partial borrow instance. Let's see how the
+//! macro expands. Given the code:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::Hidden;
+//! # use borrow::traits::*;
+//! #
+//! # struct Node;
+//! # struct Edge;
+//! # struct Group;
+//! #
+//! # #[derive(borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #   pub nodes:  Vec<Node>,
+//! #   pub edges:  Vec<Edge>,
+//! #   pub groups: Vec<Group>,
+//! # }
+//! #
+//! # fn main() {}
+//! #
+//! fn test(graph: p!(&<mut *> Graph)) {
+//!     test2(p!(&mut graph));
+//! }
+//!
+//! fn test2(graph: p!(&<nodes, mut edges> Graph)) {
+//!     // ...
+//! }
+//! ```
+//!
+//! It will expand to the following:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::Hidden;
+//! # use borrow::traits::*;
+//! #
+//! # struct Node;
+//! # struct Edge;
+//! # struct Group;
+//! #
+//! # #[derive(borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #   pub nodes:  Vec<Node>,
+//! #   pub edges:  Vec<Edge>,
+//! #   pub groups: Vec<Group>,
+//! # }
+//! #
+//! # fn main() {}
+//! #
+//! fn test(graph:
+//!     &mut GraphRef<
+//!         Graph,
+//!         &mut Vec<Node>,
+//!         &mut Vec<Edge>,
+//!         &mut Vec<Group>
+//!     >
+//! ) {
+//!     test2(&mut graph.partial_borrow())
+//! }
+//!
+//! fn test2(graph:
+//!     &mut GraphRef<
+//!         Graph,
+//!         &Vec<Node>,
+//!         &mut Vec<Edge>,
+//!         Hidden
+//!     >
+//! ) {
+//!     // ...
+//! }
+//! ```
+//!
+//! <sub></sub>
+//!
+//! More formally, the macro implements syntax as proposed in the
+//! [Rust Internals: Notes on Partial Borrow](https://internals.rust-lang.org/t/notes-on-partial-borrows/20020),
+//! and extends it with utilities for increased flexibility:
+//!
+//! <sub></sub>
+//!
+//! 1. **Field References**<br/>
+//!    You can specify which fields to borrow by naming them explicitly.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    # use borrow::Hidden;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<Node>,
+//!    #   pub edges:  Vec<Edge>,
+//!    #   pub groups: Vec<Group>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    // Contains:
+//!    // 1. Immutable reference to the 'nodes' field.
+//!    // 2. Mutable reference to the 'edges' field.
+//!    fn test(graph: p!(&<nodes, mut edges> Graph)) { /* ... */ }
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! 2. **Field Selectors**<br/>
+//!    Use `*` to include all fields. Later selectors override earlier ones.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    # use borrow::Hidden;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<Node>,
+//!    #   pub edges:  Vec<Edge>,
+//!    #   pub groups: Vec<Group>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    // Contains:
+//!    // 1. Mutable references to all, but the 'edges' field.
+//!    // 2. Immutable reference to the 'edges' field.
+//!    fn test(graph: p!(&<mut *, edges> Graph)) { /* ... */ }
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! 3. **Lifetime Annotations**<br/>
+//!    You can attach lifetimes to each reference. If not specified, `'_` is used by default. You can
+//!    override the default by attaching lifetimes after the `&`.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    # use borrow::Hidden;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<Node>,
+//!    #   pub edges:  Vec<Edge>,
+//!    #   pub groups: Vec<Group>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    // Contains:
+//!    // 1. References with the 'a lifetime to all but the 'mesh' fields.
+//!    // 2. Reference with the 'b lifetime to the 'edges' field.
+//!    fn test<'a, 'b>(graph: p!(&<'a *, 'b edges>Graph)) { /* ... */ }
+//!
+//!    // Contains:
+//!    // 1. Reference with the 't lifetime to the 'nodes' field.
+//!    // 2. Reference with the 't lifetime to the 'edges' field.
+//!    // 3. Reference with the 'm lifetime to the 'groups' field.
+//!    type PathFind<'t, 'm> = p!(&'t<nodes, edges, 'm groups> Graph);
+//!    ```
+//!
+//!    Parameters of `async fn` can use the default `'_` lifetimes. However, functions returning
+//!    futures explicitly have to name the lifetime, as a partial borrow contains several elided
+//!    lifetimes and Rust can't choose which one the returned future captures:
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use std::future::Future;
+//!    # use borrow::partial as p;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<usize>,
+//!    #   pub edges:  Vec<usize>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    async fn pass1(graph: p!(&<mut nodes> Graph)) {
+//!        graph.nodes.push(0);
+//!    }
+//!
+//!    fn pass2<'t>(graph: p!(&'t <mut nodes> Graph)) -> impl Future<Output = ()> + 't {
+//!        graph.nodes.reserve(1);
+//!        async move { graph.nodes.push(0) }
+//!    }
+//!    ```
+//!
+//! 4. **Owned Borrows**<br/>
+//!    You can omit the `&` to create an owned partial borrow. For example:
+//!
+//!    - `p!(&<mut *> Graph)` expands to `&mut GraphRef<...>`.
+//!    - `p!(<mut *> Graph)` expands to `GraphRef<...>`.
+//!
+//!    This is especially useful when defining methods or implementing traits for partial borrows,
+//!    as traits can't be implemented for reference types directly in many cases.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    # use borrow::Hidden;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<Node>,
+//!    #   pub edges:  Vec<Edge>,
+//!    #   pub groups: Vec<Group>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    /// Methods defined on a partially borrowed struct.
+//!    impl p!(<mut edges, mut nodes> Graph) {
+//!        fn detach_all_nodes(&mut self) {
+//!            // ...
+//!        }
+//!    }
+//!    ```
+//!
+//! 5. **Selector Specs**<br/>
+//!    Selectors used in many signatures can be declared once with `borrow::spec!` and used in
+//!    place of the selector list. Unlike field groups, specs do not require modifying the struct,
+//!    so they can be declared in downstream crates. Specs declared with `pub` are exported from the
+//!    crate root.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<Node>,
+//!    #   pub edges:  Vec<Edge>,
+//!    #   pub groups: Vec<Group>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    borrow::spec!(TOPOLOGY = <mut nodes, edges>);
+//!
+//!    // The same as `p!(&<mut nodes, edges> Graph)`.
+//!    fn test(graph: p!(&TOPOLOGY Graph)) { /* ... */ }
+//!    ```
 //!
-//! This crate provides the `borrow::partial` macro, which we recommend importing under a shorter
-//! alias, `p`, for convenience. The macro can be used both at the type level to specify the type of
-//! partial borrow, and at the value level to create a partial borrow instance. Let's see how the
-//! macro expands. Given the code:
+//!    <sub></sub>
 //!
-//! ```
-//! # use std::vec::Vec;
+//! 6. **Upgradeable Fields**<br/>
+//!    Fields selected with `upgradeable` are borrowed as shared, but can be upgraded to mutable
+//!    with the `upgrade` method, or by passing them to a borrow requesting them as mutable. This
+//!    avoids requesting `mut` for fields modified only conditionally, which would otherwise be
+//!    reported by the unused borrows tracker. Under the hood, the field is borrowed mutably, so
+//!    the borrow checker ensures that no other reference to it exists when it is upgraded.
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<usize>,
+//!    #   pub edges:  Vec<usize>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    fn remove_root(graph: p!(&<upgradeable nodes> Graph)) {
+//!        if graph.nodes.contains(&0) {
+//!            graph.nodes.upgrade().retain(|node| *node != 0);
+//!        }
+//!    }
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! 7. **Paths and Macros**<br/>
+//!    The target struct can be given by a path, e.g. `p!(&<mut nodes> crate::graph::Graph)`, so
+//!    it does not need to be imported. This also allows wrapping `p!` in your own `macro_rules!`,
+//!    using `$crate` to refer to the struct from any crate expanding the macro:
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    #
+//!    mod graph {
+//!        #[derive(borrow::Partial)]
+//!        #[module(crate::graph)]
+//!        pub struct Graph {
+//!            pub nodes: Vec<usize>,
+//!            pub edges: Vec<usize>,
+//!        }
+//!    }
+//!
+//!    macro_rules! graph {
+//!        ($($selectors:tt)*) => { p!(&<$($selectors)*> $crate::graph::Graph) };
+//!    }
+//!
+//!    fn add_node(graph: graph!(mut nodes)) {
+//!        graph.nodes.push(0);
+//!    }
+//!    # fn main() {}
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! 8. **Shapes**<br/>
+//!    Functions can be generic over the borrowed fields. `borrow::shape!` declares a marker type
+//!    implementing `borrow::Shape` for the given selectors (or spec) and struct, and
+//!    `borrow::View<T, S>` is the same as `p!(&<selectors> T)`. This is useful for middleware
+//!    logging or forwarding views of any declared shape:
+//!
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    # use borrow::traits::*;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Graph {
+//!    #   pub nodes:  Vec<usize>,
+//!    #   pub edges:  Vec<usize>,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    borrow::shape!(Topology = <mut nodes, edges> Graph);
+//!
+//!    use borrow::View;
+//!
+//!    fn run<S: borrow::Shape<Graph>>(graph: View<Graph, S>, pass: fn(View<Graph, S>)) {
+//!        println!("Running a pass borrowing {:?}", borrow::FieldMask::of::<S::Ref<'_>>());
+//!        pass(graph);
+//!    }
+//!
+//!    fn add_node(graph: p!(&<mut nodes, edges> Graph)) {
+//!        graph.nodes.push(graph.edges.len());
+//!    }
+//!
+//!    fn test(graph: &mut Graph) {
+//!        run::<Topology>(p!(&mut graph), add_node);
+//!    }
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! 9. **Custom Field Types**<br/>
+//!    Partial borrows can hold field types other than references, like the `Upgradeable` fields
+//!    above. Downstream crates can define their own, e.g. a `Dirty<T>` value splitting into the
+//!    reference to the value and a handle to its dirty flag, by implementing
+//!    [`Acquire`] for them. See its documentation for an example.
+//!
+//!    <sub></sub>
+//!
+//! 10. **Transactions**<br/>
+//!     A partial borrow wrapped in [`Transactional`] snapshots the fields it borrows mutably, and
+//!     restores them unless the transaction is committed. As only the borrowed fields are cloned,
+//!     this is much cheaper than cloning the whole struct to implement undo.
+//!
+//!     <sub></sub>
+//!
+//! 11. **Bounds**<br/>
+//!     The macro can be used in trait bounds, e.g. to accept passes borrowing selected fields.
+//!     Lifetimes can be elided in closure arguments, and given by higher-ranked bounds elsewhere.
+//!     Lifetimes in `where` clauses can not be elided:
+//!
+//!     ```
+//!     # use std::vec::Vec;
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # #[derive(borrow::Partial)]
+//!     # #[module(crate)]
+//!     # struct Graph {
+//!     #   pub nodes:  Vec<usize>,
+//!     #   pub edges:  Vec<usize>,
+//!     # }
+//!     #
+//!     # fn main() {}
+//!     #
+//!     fn run_pass<F>(graph: p!(&<mut *> Graph), mut pass: F)
+//!     where F: for<'a> FnMut(p!(&'a <mut nodes> Graph)) {
+//!         pass(p!(&mut graph));
+//!     }
+//!
+//!     fn run_full_pass(graph: &mut Graph, mut pass: impl FnMut(p!(&mut Graph))) {
+//!         pass(p!(&mut graph));
+//!     }
+//!     ```
+//!
+//!     <sub></sub>
+//!
+//! 12. **Brace Syntax**<br/>
+//!     Selectors can be listed in braces instead of angle brackets, e.g. `p!(&{mut nodes} Graph)`.
+//!     Both spellings expand identically. Braces are not mistaken for generic arguments by
+//!     `rustfmt` and syntax highlighters, which keeps long signatures formatted consistently:
+//!
+//!     ```
+//!     # use std::vec::Vec;
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # #[derive(borrow::Partial)]
+//!     # #[module(crate)]
+//!     # struct Graph {
+//!     #   pub nodes:  Vec<usize>,
+//!     #   pub edges:  Vec<usize>,
+//!     # }
+//!     #
+//!     # fn main() {}
+//!     #
+//!     fn count_nodes(graph: p!(&{nodes} Graph)) -> usize {
+//!         graph.nodes.len()
+//!     }
+//!
+//!     fn add_node(graph: p!(&'_ {mut nodes, edges} Graph)) -> usize {
+//!         graph.nodes.push(graph.edges.len());
+//!         count_nodes(p!(&mut graph))
+//!     }
+//!     ```
+//!
+//!     <sub></sub>
+//!
+//! 13. **Moving Views**<br/>
+//!     `p!(&mut graph)` reborrows `graph`, so the new partial borrow can not outlive the
+//!     statement. `p!(move view)` consumes an owned partial borrow instead, which lets the result
+//!     be returned, e.g. from a constructor. The target type is inferred, like with `&mut`. It
+//!     expands to [`into_partial_borrow`](PartialHelper::into_partial_borrow):
+//!
+//!     ```
+//!     # use std::vec::Vec;
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # #[derive(borrow::Partial)]
+//!     # #[module(crate)]
+//!     # struct Graph {
+//!     #   pub nodes:  Vec<usize>,
+//!     #   pub edges:  Vec<usize>,
+//!     # }
+//!     #
+//!     # fn main() {}
+//!     #
+//!     fn nodes_view(graph: &mut Graph) -> p!(<mut nodes> Graph) {
+//!         p!(move graph.as_refs_mut())
+//!     }
+//!
+//!     fn edges_view<'t>(graph: p!(<'t mut *> Graph)) -> p!(<'t edges> Graph) {
+//!         p!(move graph)
+//!     }
+//!     ```
+//!
+//!     Mutable references to the struct can be moved as well, so `p!(move graph)` works for
+//!     `graph: &'t mut Graph` too, and the result borrows the struct for the whole `'t`. For
+//!     long-lived state, e.g. a leaked `Box`, this gives views with the `'static` lifetime:
+//!
+//!     ```
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     # #[derive(Default, borrow::Partial)]
+//!     # #[module(crate)]
+//!     # struct Graph {
+//!     #   pub nodes: Vec<usize>,
+//!     #   pub edges: Vec<usize>,
+//!     # }
+//!     #
+//!     fn main() {
+//!         let graph: &'static mut Graph = Box::leak(Box::default());
+//!         let mut nodes: p!(<'static mut nodes> Graph) = p!(move graph);
+//!         nodes.nodes.push(0);
+//!     }
+//!     ```
+//!
+//!     <sub></sub>
+//!
+//! 14. **Groups and Exclusions**<br/>
+//!     Groups declared with `#[borrow(group(...))]` can be selected with `@`, e.g.
+//!     `p!(&<mut @render> Ctx)`, which selects all fields of the group with the given access.
+//!     Fields and groups can be excluded with `-`, followed by a single field or group, or by a
+//!     list of them, e.g. `p!(&<mut * - <cache, @debug>> Ctx)`. Selectors are applied from left
+//!     to right, so excluded fields can be selected again by the following selectors, and field
+//!     selectors following a group override the access of its fields, e.g.
+//!     `p!(&<mut @render, material> Ctx)` borrows the `render` group as mutable, except for
+//!     `material`, which is borrowed as shared. Fields marked with `mut` in the declaration, e.g.
+//!     `#[borrow(group(frame = "mut geometry, mut mesh, material"))]`, are borrowed as mutable by
+//!     a group selector without an access, so a group can name a whole selector used in many
+//!     signatures, e.g. `p!(&<@frame> Ctx)`. A selector with an access, e.g. `mut @frame`, uses
+//!     it for all fields of the group. This keeps signatures short as structs grow:
+//!
+//!     ```
+//!     # use std::vec::Vec;
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # fn main() {}
+//!     #
+//!     #[derive(borrow::Partial)]
+//!     #[module(crate)]
+//!     #[borrow(group(render = "geometry, material, mesh"))]
+//!     #[borrow(group(frame = "mut geometry, mut mesh, material"))]
+//!     struct Ctx {
+//!         pub geometry: Vec<usize>,
+//!         pub material: Vec<usize>,
+//!         pub mesh:     Vec<usize>,
+//!         pub cache:    Vec<usize>,
+//!     }
+//!
+//!     fn draw(ctx: p!(&<mut @render, material> Ctx)) {
+//!         ctx.mesh.push(ctx.material.len());
+//!     }
+//!
+//!     fn frame(ctx: p!(&<@frame> Ctx)) {
+//!         draw(p!(&mut ctx));
+//!     }
+//!
+//!     fn update(ctx: p!(&<mut * - cache> Ctx)) {
+//!         frame(p!(&mut ctx));
+//!     }
+//!     ```
+//!
+//!     <sub></sub>
+//!
+//! 15. **Generic Targets**<br/>
+//!     Generic arguments of the target are passed to the `Ref` struct as written, in any position
+//!     `p!` is used in: lifetimes (including `'_`), types, const arguments (including blocks, e.g.
+//!     `{ N + 1 }`), and turbofish paths, e.g. `crate::grid::Grid::<T, 4>`. Arguments with defaults
+//!     can be omitted. As `p!` finds the struct macro by the name of the struct, the target can be
+//!     `Self` only in impl blocks marked with `#[borrow::partial_impl]`, which resolves it to the
+//!     self type. Elsewhere, the struct has to be spelled out, e.g. `p!(&<mut cells> Grid<T, N>)`:
+//!
+//!     ```
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # fn main() {}
+//!     #
+//!     #[derive(borrow::Partial)]
+//!     #[module(crate)]
+//!     struct Grid<'a, T, const N: usize = 4> {
+//!         pub cells: [T; N],
+//!         pub names: Vec<&'a str>,
+//!     }
+//!
+//!     impl<'a, T: Copy, const N: usize> Grid<'a, T, N> {
+//!         fn fill(&mut self, value: T) {
+//!             fill(self.partial_borrow::<p!(<mut cells> Grid<'a, T, N>)>(), value);
+//!         }
+//!     }
+//!
+//!     fn fill<T: Copy, const N: usize>(mut grid: p!(<mut cells> Grid<'_, T, N>), value: T) {
+//!         grid.cells.fill(value);
+//!     }
+//!     ```
+//!
+//!     <sub></sub>
+//!
+//! 16. **Default Selectors**<br/>
+//!     Fields borrowed by `p!` without selectors, e.g. `p!(&Graph)`, or with the `_` selector,
+//!     e.g. `p!(&_ Graph)`, can be declared with `#[borrow(default = "mut nodes, edges")]`,
+//!     keeping the signatures of the most common shape short. Structs without the attribute
+//!     borrow no fields then. Explicit selectors, e.g.
+//!     `p!(&<mut *> Graph)`, are not affected:
+//!
+//!     ```
+//!     # use borrow::partial as p;
+//!     # use borrow::traits::*;
+//!     #
+//!     # fn main() {}
+//!     #
+//!     #[derive(borrow::Partial)]
+//!     #[module(crate)]
+//!     #[borrow(default = "mut nodes, edges")]
+//!     struct Graph {
+//!         pub nodes: Vec<usize>,
+//!         pub edges: Vec<(usize, usize)>,
+//!         pub cache: Vec<usize>,
+//!     }
+//!
+//!     fn add_node(graph: p!(&Graph)) {
+//!         graph.nodes.push(graph.edges.len());
+//!     }
+//!
+//!     // The same as `p!(&Graph)`.
+//!     fn add_first_node(graph: p!(&_ Graph)) {
+//!         graph.nodes.insert(0, graph.edges.len());
+//!     }
+//!
+//!     fn rebuild(graph: p!(&<mut *> Graph)) {
+//!         add_node(p!(&mut graph));
+//!         add_first_node(p!(&mut graph));
+//!         graph.cache.clear();
+//!     }
+//!     ```
+//!
+//! Selectors which do not change the borrowed shape are reported as warnings, as they tend to
+//! accumulate during refactors: fields selected again later, which overrides the earlier
+//! selector, fields selected before `*`, which overrides them, and fields selected after `*` with
+//! the same access. The warnings are reported at the redundant selectors as uses of deprecated
+//! items, e.g. `borrow::DuplicateSelector`, as procedural macros can not emit warnings on stable
+//! Rust. With the `deny_redundant_selectors` feature, they are errors instead. Only field
+//! selectors are checked, as `p!` does not know the fields of the struct:
+//!
+//! ```compile_fail
+//! # #![deny(deprecated)]
 //! # use borrow::partial as p;
-//! # use borrow::Hidden;
-//! # use borrow::traits::*;
-//! #
-//! # struct Node;
-//! # struct Edge;
-//! # struct Group;
-//! #
 //! # #[derive(borrow::Partial)]
 //! # #[module(crate)]
 //! # struct Graph {
-//! #   pub nodes:  Vec<Node>,
-//! #   pub edges:  Vec<Edge>,
-//! #   pub groups: Vec<Group>,
+//! #   pub nodes:  Vec<usize>,
+//! #   pub edges:  Vec<usize>,
 //! # }
-//! #
 //! # fn main() {}
-//! #
-//! fn test(graph: p!(&<mut *> Graph)) {
-//!     test2(p!(&mut graph));
+//! fn count_nodes(graph: p!(&<mut *, mut nodes> Graph)) -> usize {
+//!     graph.nodes.len()
 //! }
+//! ```
 //!
-//! fn test2(graph: p!(&<nodes, mut edges> Graph)) {
-//!     // ...
+//! The target of `p!` has to be a struct deriving `borrow::Partial`. References, tuples, and
+//! common standard library types, e.g. `Vec`, are rejected with an error explaining it:
+//!
+//! ```compile_fail
+//! # use borrow::partial as p;
+//! # fn main() {}
+//! fn count_nodes(nodes: p!(&<0> Vec<usize>)) -> usize {
+//!     nodes.len()
 //! }
 //! ```
 //!
-//! It will expand to the following:
+//! For other types, `p!` uses the struct macro generated by the derive, so a missing derive is
+//! reported as a missing macro named after the struct, e.g. ``cannot find macro `Graph` in this
+//! scope``. Add `#[derive(borrow::Partial)]` to the struct, and make sure the
+//! struct is imported, as importing it imports its macro as well:
 //!
-//! ```
-//! # use std::vec::Vec;
+//! ```compile_fail
 //! # use borrow::partial as p;
-//! # use borrow::Hidden;
-//! # use borrow::traits::*;
-//! #
-//! # struct Node;
-//! # struct Edge;
-//! # struct Group;
-//! #
-//! # #[derive(borrow::Partial)]
-//! # #[module(crate)]
-//! # struct Graph {
-//! #   pub nodes:  Vec<Node>,
-//! #   pub edges:  Vec<Edge>,
-//! #   pub groups: Vec<Group>,
-//! # }
-//! #
 //! # fn main() {}
-//! #
-//! fn test(graph:
-//!     &mut GraphRef<
-//!         Graph,
-//!         borrow::True,
-//!         &mut Vec<Node>,
-//!         &mut Vec<Edge>,
-//!         &mut Vec<Group>
-//!     >
-//! ) {
-//!     test2(&mut graph.partial_borrow())
+//! struct Graph {
+//!     nodes: Vec<usize>,
 //! }
 //!
-//! fn test2(graph:
-//!     &mut GraphRef<
-//!         Graph,
-//!         borrow::True,
-//!         &Vec<Node>,
-//!         &mut Vec<Edge>,
-//!         Hidden
-//!     >
-//! ) {
-//!     // ...
+//! fn count_nodes(graph: p!(&<nodes> Graph)) -> usize {
+//!     graph.nodes.len()
 //! }
 //! ```
 //!
-//! <sub></sub>
+//! <br/>
+//! <br/>
 //!
-//! More formally, the macro implements syntax as proposed in the
-//! [Rust Internals: Notes on Partial Borrow](https://internals.rust-lang.org/t/notes-on-partial-borrows/20020),
-//! and extends it with utilities for increased flexibility:
+//! # 📖 The `partial_borrow`, `split`, and `borrow_$field` methods.
+//!
+//! Partially borrowed structs expose a set of methods that allow transforming one partial borrow
+//! into another. The `p!` macro can also be used as shorthand for the `partial_borrow` method.
 //!
 //! <sub></sub>
 //!
-//! 1. **Field References**<br/>
-//!    You can specify which fields to borrow by naming them explicitly.
+//! - `fn partial_borrow<'s, Target>(&'s mut self) -> Target where Self: Partial<'s, Target>`<br/>
+//!   Allows borrowing only the fields specified by the target type. You don’t need to call
+//!   `as_refs_mut` explicitly, `partial_borrow` handles it internally.
 //!
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
-//!    # use borrow::Hidden;
+//!    # use borrow::traits::*;
 //!    #
 //!    # struct Node;
 //!    # struct Edge;
 //!    # struct Group;
 //!    #
-//!    # #[derive(borrow::Partial)]
+//!    # #[derive(borrow::Partial, Default)]
 //!    # #[module(crate)]
 //!    # struct Graph {
 //!    #   pub nodes:  Vec<Node>,
@@ -429,23 +1693,45 @@
 //!    #   pub groups: Vec<Group>,
 //!    # }
 //!    #
-//!    # fn main() {}
-//!    #
-//!    // Contains:
-//!    // 1. Immutable reference to the 'nodes' field.
-//!    // 2. Mutable reference to the 'edges' field.
-//!    fn test(graph: p!(&<nodes, mut edges> Graph)) { /* ... */ }
+//!    fn main() {
+//!        let mut graph = Graph::default();
+//!        // Creating a partial borrow (recommended way):
+//!        test(p!(&mut graph));
+//!
+//!        // Which expands to:
+//!        test(&mut graph.partial_borrow());
+//!
+//!        // Which expands to:
+//!        test(&mut graph.as_refs_mut().partial_borrow());
+//!    }
+//!
+//!    fn test(mut graph: p!(&<mut *> Graph)) {
+//!        // Creating a partial borrow of the current borrow (recommended way):
+//!        test2(p!(&mut graph));
+//!
+//!        // The above is the same as the following:
+//!        test2(&mut graph.partial_borrow());
+//!
+//!        // Which is the same as the most explicit version:
+//!        let graph2 = &mut graph.partial_borrow::<p!(<mut nodes> Graph)>();
+//!        test2(graph2);
+//!    }
+//!
+//!    fn test2(graph: p!(&<mut nodes> Graph)) {
+//!        // ...
+//!        # let _ = graph;
+//!    }
+//!
 //!    ```
 //!
 //!    <sub></sub>
 //!
-//! 2. **Field Selectors**<br/>
-//!    Use `*` to include all fields. Later selectors override earlier ones.
-//!
+//! - `fn split<'s, Target>(&'s mut self) -> (Target, Self::Rest) where Self: Partial<'s, Target>`<br/>
+//!   Similar to `partial_borrow`, but also returns a borrow of the remaining fields.
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
-//!    # use borrow::Hidden;
+//!    # use borrow::traits::*;
 //!    #
 //!    # struct Node;
 //!    # struct Edge;
@@ -461,109 +1747,87 @@
 //!    #
 //!    # fn main() {}
 //!    #
-//!    // Contains:
-//!    // 1. Mutable references to all, but the 'edges' field.
-//!    // 2. Immutable reference to the 'edges' field.
-//!    fn test(graph: p!(&<mut *, edges> Graph)) { /* ... */ }
+//!    fn test(mut graph: p!(&<mut *> Graph)) {
+//!        // The inferred type of `graph3` is `p!(&<mut edges, mut groups> Graph)`.
+//!        let (graph2, graph3) = graph.split::<p!(<mut nodes> Graph)>();
+//!    }
 //!    ```
 //!
 //!    <sub></sub>
 //!
-//! 3. **Lifetime Annotations**<br/>
-//!    You can attach lifetimes to each reference. If not specified, `'_` is used by default. You can
-//!    override the default by attaching lifetimes after the `&`.
-//!
+//! - `fn split_with<'a, Spec>(&'a mut self) -> (Rebound<'a, Spec>, SplitRest<'a, Self, Spec>)`<br/>
+//!   Similar to `split`, but the lifetime of both halves is given explicitly, which makes it easy
+//!   to return them from a function. Lifetimes used in `Spec` are replaced with `'a`, so the
+//!   convention is to use `'static` in type aliases.
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
-//!    # use borrow::Hidden;
-//!    #
-//!    # struct Node;
-//!    # struct Edge;
-//!    # struct Group;
+//!    # use borrow::traits::*;
+//!    # use borrow::SplitRest;
 //!    #
 //!    # #[derive(borrow::Partial)]
 //!    # #[module(crate)]
 //!    # struct Graph {
-//!    #   pub nodes:  Vec<Node>,
-//!    #   pub edges:  Vec<Edge>,
-//!    #   pub groups: Vec<Group>,
+//!    #   pub nodes:  Vec<usize>,
+//!    #   pub edges:  Vec<usize>,
+//!    #   pub groups: Vec<usize>,
 //!    # }
 //!    #
 //!    # fn main() {}
 //!    #
-//!    // Contains:
-//!    // 1. References with the 'a lifetime to all but the 'mesh' fields.
-//!    // 2. Reference with the 'b lifetime to the 'edges' field.
-//!    fn test<'a, 'b>(graph: p!(&<'a *, 'b edges>Graph)) { /* ... */ }
+//!    type Nodes = p!('static <mut nodes> Graph);
 //!
-//!    // Contains:
-//!    // 1. Reference with the 't lifetime to the 'nodes' field.
-//!    // 2. Reference with the 't lifetime to the 'edges' field.
-//!    // 3. Reference with the 'm lifetime to the 'groups' field.
-//!    type PathFind<'t, 'm> = p!(&'t<nodes, edges, 'm groups> Graph);
+//!    fn split_nodes<'a>(
+//!        graph: &'a mut Graph
+//!    ) -> (p!('a <mut nodes> Graph), SplitRest<'a, Graph, Nodes>) {
+//!        graph.split_with::<'a, Nodes>()
+//!    }
 //!    ```
 //!
-//! 4. **Owned Borrows**<br/>
-//!    You can omit the `&` to create an owned partial borrow. For example:
-//!
-//!    - `p!(&<mut *> Graph)` expands to `&mut GraphRef<...>`.
-//!    - `p!(<mut *> Graph)` expands to `GraphRef<...>`.
-//!
-//!    This is especially useful when defining methods or implementing traits for partial borrows,
-//!    as traits can't be implemented for reference types directly in many cases.
+//!    <sub></sub>
 //!
-//!    ```
+//! - `fn split_exclusive<'s, Target>(&'s mut self) -> (Target, ExclusiveRest<'s, Self, Target>)`<br/>
+//!   Similar to `split`, but fields borrowed immutably by the target are hidden in the rest, instead
+//!   of being shared by both halves. Use it to hand off fields exclusively, so that the rest can not
+//!   observe them, even immutably, while the target is alive. `into_split_exclusive` is the
+//!   consuming variant.
+//!    ```compile_fail
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
-//!    # use borrow::Hidden;
-//!    #
-//!    # struct Node;
-//!    # struct Edge;
-//!    # struct Group;
+//!    # use borrow::traits::*;
 //!    #
 //!    # #[derive(borrow::Partial)]
 //!    # #[module(crate)]
 //!    # struct Graph {
-//!    #   pub nodes:  Vec<Node>,
-//!    #   pub edges:  Vec<Edge>,
-//!    #   pub groups: Vec<Group>,
+//!    #   pub nodes:  Vec<usize>,
+//!    #   pub edges:  Vec<usize>,
 //!    # }
 //!    #
 //!    # fn main() {}
 //!    #
-//!    /// Methods defined on a partially borrowed struct.
-//!    impl p!(<mut edges, mut nodes> Graph) {
-//!        fn detach_all_nodes(&mut self) {
-//!            // ...
-//!        }
+//!    fn test(mut graph: p!(&<mut *> Graph)) {
+//!        // The inferred type of `graph3` is `p!(&<mut edges> Graph)`.
+//!        let (graph2, graph3) = graph.split_exclusive::<p!(<nodes> Graph)>();
+//!        // Error, `nodes` is hidden in `graph3`.
+//!        let count = graph3.nodes.len();
 //!    }
 //!    ```
 //!
-//! <br/>
-//! <br/>
-//!
-//! # 📖 The `partial_borrow`, `split`, and `borrow_$field` methods.
-//!
-//! Partially borrowed structs expose a set of methods that allow transforming one partial borrow
-//! into another. The `p!` macro can also be used as shorthand for the `partial_borrow` method.
-//!
-//! <sub></sub>
-//!
-//! - `fn partial_borrow<'s, Target>(&'s mut self) -> Target where Self: Partial<'s, Target>`<br/>
-//!    Allows borrowing only the fields specified by the target type. You don’t need to call
-//!   `as_refs_mut` explicitly, `partial_borrow` handles it internally.
+//!    <sub></sub>
 //!
+//! - `borrow_$field` and `borrow_$field_mut` are like split, but for single field only. They have
+//!   the visibility of the field, e.g. they are private for private fields, so they do not give
+//!   access to fields which are not accessible otherwise. Mark the struct with
+//!   `#[borrow(pub_field_methods)]` to make them `pub` regardless of the field visibility.
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
-//!    # use borrow::traits::*;
 //!    #
 //!    # struct Node;
 //!    # struct Edge;
 //!    # struct Group;
 //!    #
-//!    # #[derive(borrow::Partial, Default)]
+//!    # #[derive(borrow::Partial)]
 //!    # #[module(crate)]
 //!    # struct Graph {
 //!    #   pub nodes:  Vec<Node>,
@@ -571,41 +1835,58 @@
 //!    #   pub groups: Vec<Group>,
 //!    # }
 //!    #
-//!    fn main() {
-//!        let mut graph = Graph::default();
-//!        // Creating a partial borrow (recommended way):
-//!        test(p!(&mut graph));
+//!    # fn main() {}
+//!    #
+//!    fn test(mut graph: p!(&<mut *> Graph)) {
+//!        // Type of `nodes` is `&mut Vec<Node>`. In builds with usage tracking enabled, it is
+//!        // wrapped in `borrow::FieldValue`, which dereferences to `&mut Vec<Node>`.
+//!        // Type of `graph2` is `p!(&<mut edges, mut groups> Graph)`.
+//!        let (nodes, graph2) = graph.borrow_nodes_mut();
 //!
-//!        // Which expands to:
-//!        test(&mut graph.partial_borrow());
+//!        // ...
 //!
-//!        // Which expands to:
-//!        test(&mut graph.as_refs_mut().partial_borrow());
+//!        // Type of `edges` is `&Vec<Edge>`.
+//!        // Type of `graph2` is `p!(&<mut nodes, edges, mut groups> Graph)`.
+//!        let (edges, graph3) = graph.borrow_edges();
 //!    }
+//!    ```
 //!
-//!    fn test(mut graph: p!(&<mut *> Graph)) {
-//!        // Creating a partial borrow of the current borrow (recommended way):
-//!        test2(p!(&mut graph));
-//!
-//!        // The above is the same as the following:
-//!        test2(&mut graph.partial_borrow());
+//!    <sub></sub>
 //!
-//!        // Which is the same as the most explicit version:
-//!        let graph2 = &mut graph.partial_borrow::<p!(<mut nodes> Graph)>();
-//!        test2(graph2);
+//! - `borrow_$group` and `borrow_$group_mut` are like split, but for field groups declared with
+//!   the `#[borrow(group(...))]` attribute. All fields of the group are borrowed as shared or as
+//!   mutable references, respectively.
+//!    ```
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
+//!    #
+//!    # struct Node;
+//!    # struct Edge;
+//!    # struct Group;
+//!    #
+//!    # fn main() {}
+//!    #
+//!    #[derive(borrow::Partial)]
+//!    #[module(crate)]
+//!    #[borrow(group(topology = "nodes, edges"))]
+//!    struct Graph {
+//!      pub nodes:  Vec<Node>,
+//!      pub edges:  Vec<Edge>,
+//!      pub groups: Vec<Group>,
 //!    }
 //!
-//!    fn test2(graph: p!(&<mut nodes> Graph)) {
-//!        // ...
-//!        # let _ = graph;
+//!    fn test(mut graph: p!(&<mut *> Graph)) {
+//!        // Type of `topology` is `p!(<mut nodes, mut edges> Graph)`.
+//!        // Type of `graph2` is `p!(<mut groups> Graph)`.
+//!        let (topology, graph2) = graph.borrow_topology_mut();
 //!    }
-//!
 //!    ```
 //!
 //!    <sub></sub>
 //!
-//! - `fn split<'s, Target>(&'s mut self) -> (Target, Self::Rest) where Self: Partial<'s, Target>`<br/>
-//!    Similar to `partial_borrow`, but also returns a borrow of the remaining fields.
+//! - `map_$field` consumes the partial borrow and replaces the field with a projection of it, e.g.
+//!   a sub-slice or a field of the borrowed value, keeping the other fields intact. Together with
+//!   `split`, it allows splitting within fields in the same way as splitting the struct.
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
@@ -625,18 +1906,61 @@
 //!    #
 //!    # fn main() {}
 //!    #
-//!    fn test(mut graph: p!(&<mut *> Graph)) {
-//!        // The inferred type of `graph3` is `p!(&<mut edges, mut groups> Graph)`.
-//!        let (graph2, graph3) = graph.split::<p!(<mut nodes> Graph)>();
+//!    fn test(mut graph: p!(&<mut nodes, edges> Graph)) {
+//!        let graph2 = graph.partial_borrow::<p!(<mut nodes, edges> Graph)>();
+//!        // Type of `graph3.nodes` is `&mut [Node]`, and `graph3.edges` is still accessible.
+//!        let graph3 = graph2.map_nodes(|nodes| &mut nodes[1..]);
 //!    }
 //!    ```
 //!
 //!    <sub></sub>
 //!
-//! - `borrow_$field` and `borrow_$field_mut` are like split, but for single field only.
+//! - `split_$field_at` consumes the partial borrow and splits a slice or `Vec` field at an index,
+//!   like [`slice::split_at_mut`]. It returns the partial borrow with the field replaced by the
+//!   head, and the tail, so the two disjoint parts of the field can be used together with the
+//!   other fields. Ranges are runtime values, and thus can not be a part of the selector types, so
+//!   range selectors, e.g. `p!(&<mut particles[0..n]> Sim)`, are rejected with an error pointing
+//!   to this method. Both parts are tracked as usages of the field. Other collections can be
+//!   supported by implementing [`SplitAtField`].
 //!    ```
 //!    # use std::vec::Vec;
 //!    # use borrow::partial as p;
+//!    # use borrow::traits::*;
+//!    #
+//!    # #[derive(borrow::Partial)]
+//!    # #[module(crate)]
+//!    # struct Sim {
+//!    #   pub particles: Vec<f32>,
+//!    #   pub gravity:   f32,
+//!    # }
+//!    #
+//!    # fn main() {}
+//!    #
+//!    fn step(sim: p!(&<mut particles, gravity> Sim), n: usize) {
+//!        let sim2 = sim.partial_borrow::<p!(<mut particles, gravity> Sim)>();
+//!        // Type of `sim3.particles` and `rest` is `&mut [f32]`.
+//!        let (mut sim3, rest) = sim2.split_particles_at(n);
+//!        for (head, tail) in sim3.particles.iter_mut().zip(rest.iter()) {
+//!            *head += *tail * *sim3.gravity;
+//!        }
+//!    }
+//!    ```
+//!
+//!    <sub></sub>
+//!
+//! - `for_each_selected` passes every field visible in the partial borrow, with its label, to a
+//!   visitor implementing [`VisitField`] for each field type, e.g. to save everything a pass
+//!   borrowed. Hidden fields are skipped, and fields borrowed immutably are passed as
+//!   [`FieldRef::Ref`]. See [`VisitField`] for an example.
+//!
+//!    <sub></sub>
+//!
+//! - `From<&mut $StructRef<...>>` is implemented for every narrower partial borrow, and thus so is
+//!   `TryFrom`, with the `Infallible` error. Conversions requiring fields that the source hides or
+//!   borrows immutably do not compile, and the error explains which field access is missing.
+//!    ```compile_fail
+//!    # use std::vec::Vec;
+//!    # use borrow::partial as p;
 //!    #
 //!    # struct Node;
 //!    # struct Edge;
@@ -652,16 +1976,13 @@
 //!    #
 //!    # fn main() {}
 //!    #
-//!    fn test(mut graph: p!(&<mut *> Graph)) {
-//!        // Type of `nodes` is `&mut Vec<Node>`.
-//!        // Type of `graph2` is `p!(&<mut edges, mut groups> Graph)`.
-//!        let (nodes, graph2) = graph.borrow_nodes_mut();
-//!
-//!        // ...
+//!    fn test(graph: p!(&<mut nodes, edges> Graph)) {
+//!        // OK.
+//!        let _: p!(<nodes> Graph) = (&mut *graph).into();
 //!
-//!        // Type of `edges` is `&Vec<Edge>`.
-//!        // Type of `graph2` is `p!(&<mut nodes, edges, mut groups> Graph)`.
-//!        let (edges, graph3) = graph.borrow_edges();
+//!        // Error: cannot borrow a field as `&mut Vec<Edge>` from a partial borrow providing
+//!        // `&Vec<Edge>`.
+//!        let _: p!(<mut edges> Graph) = (&mut *graph).into();
 //!    }
 //!    ```
 //!
@@ -963,78 +2284,277 @@
 //! # // === Graph ===
 //! # // =============
 //! #
-//! # #[derive(Debug, borrow::Partial)]
+//! # #[derive(Debug, borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #    nodes: Vec<Node>,
+//! #    edges: Vec<Edge>,
+//! #    groups: Vec<Group>,
+//! # }
+//! #
+//! # impl p!(<mut edges, mut nodes> Graph) {
+//! #     fn detach_all_nodes(&mut self) {
+//! #         let (nodes, mut self2) = self.borrow_nodes_mut();
+//! #         for node in nodes {
+//! #             self2.detach_node(node);
+//! #         }
+//! #     }
+//! # }
+//! #
+//! # impl p!(<mut edges> Graph) {
+//! #     fn detach_node(&mut self, node: &mut Node) {
+//! #         for edge_id in std::mem::take(&mut node.outputs) {
+//! #             self.edges[edge_id].from = None;
+//! #         }
+//! #         for edge_id in std::mem::take(&mut node.inputs) {
+//! #             self.edges[edge_id].to = None;
+//! #         }
+//! #     }
+//! # }
+//! #
+//! // =============
+//! // === Tests ===
+//! // =============
+//!
+//! fn main() {
+//!    // node0 -----> node1 -----> node2 -----> node0
+//!    //       edge0        edge1        edge2
+//!    let mut graph = Graph {
+//!       nodes: vec![
+//!          Node { outputs: vec![0], inputs: vec![2] }, // Node 0
+//!          Node { outputs: vec![1], inputs: vec![0] }, // Node 1
+//!          Node { outputs: vec![2], inputs: vec![1] }, // Node 2
+//!       ],
+//!       edges: vec![
+//!          Edge { from: Some(0), to: Some(1) }, // Edge 0
+//!          Edge { from: Some(1), to: Some(2) }, // Edge 1
+//!          Edge { from: Some(2), to: Some(0) }, // Edge 2
+//!       ],
+//!       groups: vec![],
+//!    };
+//!
+//!    p!(&mut graph).detach_all_nodes();
+//!
+//!    for node in &graph.nodes {
+//!       assert!(node.outputs.is_empty());
+//!       assert!(node.inputs.is_empty());
+//!    }
+//!    for edge in &graph.edges {
+//!       assert!(edge.from.is_none());
+//!       assert!(edge.to.is_none());
+//!    }
+//! }
+//! ```
+//! <br/>
+//! </details>
+//!
+//! Please note, that you do not need to provide the partially borrowed type explicitly, it will be
+//! inferred automatically. For example, the `detach_all_nodes` method requires self to have the
+//! `edges` and `nodes` fields mutably borrowed, but you can simply call it as follows:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #
+//! # #[derive(Default, borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     nodes: Vec<usize>,
+//! #     edges: Vec<usize>,
+//! # }
+//! #
+//! # impl p!(<mut nodes> Graph) {
+//! #     fn detach_all_nodes(&mut self) {}
+//! # }
+//! #
+//! fn main() {
+//!    let mut graph: Graph = Graph::default();
+//!    p!(&mut graph).detach_all_nodes();
+//! }
+//! ```
+//!
+//! For structs with lifetime parameters, mark the impl block with `#[borrow::partial_impl]` to use
+//! `'_` in the target type. The `p!` macro repeats the target type for every borrowed field, and
+//! every `'_` in an impl header is a distinct lifetime, so without the attribute the fields would
+//! refer to the struct with different lifetimes, and splitting the borrow would fail to compile.
+//! The attribute replaces every `'_` with a named lifetime parameter of the impl block.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! struct Graph<'t> {
+//!     nodes: Vec<&'t str>,
+//!     edges: Vec<usize>,
+//! }
+//!
+//! #[borrow::partial_impl]
+//! impl p!(<mut nodes, mut edges> Graph<'_>) {
+//!     fn detach_all_nodes(&mut self) {
+//!         let (mut nodes, mut rest) = self.borrow_nodes_mut();
+//!         nodes.clear();
+//!         rest.edges.clear();
+//!     }
+//! }
+//! #
+//! # fn main() {}
+//! ```
+//!
+//! The attribute can be used on impl blocks of the struct as well, where it resolves `Self` used
+//! as the target of `p!` to the self type, including its generic arguments. This is useful for
+//! methods returning partial borrows of `self`, as `p!` can not resolve `Self` on its own:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(borrow::Partial)]
+//! #[module(crate)]
+//! struct Graph<'t, T> {
+//!     nodes: Vec<&'t T>,
+//!     edges: Vec<usize>,
+//! }
+//!
+//! #[borrow::partial_impl]
+//! impl<T> Graph<'_, T> {
+//!     fn nodes(&mut self) -> p!(<mut nodes> Self) {
+//!         self.as_refs_mut().into_partial_borrow()
+//!     }
+//! }
+//! #
+//! # fn main() {}
+//! ```
+//!
+//! <br/>
+//! <br/>
+//!
+//! # Owned partial borrows
+//!
+//! Partial borrows always borrow the struct, so they can't be moved to detached tasks. If you
+//! need that, wrap a guard owning the struct (e.g. `Box<T>`, `tokio::sync::OwnedMutexGuard<T>`,
+//! or `parking_lot::ArcMutexGuard<T>`) in `borrow::OwnedRef`. It is `'static` if the guard is,
+//! and it exposes only the fields selected by its view type. The lifetime used in the view type
+//! does not matter, as the view is re-borrowed on every `view` call.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::OwnedRef;
+//! #
+//! # #[derive(Default, borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     nodes: Vec<usize>,
+//! #     edges: Vec<usize>,
+//! # }
+//! #
+//! fn add_node(graph: p!(&<mut nodes> Graph)) {
+//!     graph.nodes.push(0);
+//! }
+//!
+//! fn main() {
+//!     let mut nodes = OwnedRef::<_, p!('static <mut nodes> Graph)>::new(Box::<Graph>::default());
+//!     let task = std::thread::spawn(move || {
+//!         add_node(&mut nodes.view());
+//!         nodes.into_inner()
+//!     });
+//!     assert_eq!(task.join().map(|graph| graph.nodes.len()).ok(), Some(1));
+//! }
+//! ```
+//!
+//! Roots shared behind a lock or a `RefCell` can be wrapped in `borrow::Borrowable`, which
+//! exposes the struct only through partial borrows. It can't be cloned, dereferenced, or swapped,
+//! so the root can't be accidentally copied or replaced while long-lived views created from its
+//! guards exist.
+//!
+//! <br/>
+//! <br/>
+//!
+//! # Storing partial borrows per frame
+//!
+//! To collect partial borrows in a scratch structure reused across frames, use
+//! `borrow::FrameCache`. Its `begin_frame` method returns a `borrow::Frame` guard storing the views,
+//! which clears them when dropped, so no view outlives the frame. As with `OwnedRef`, the lifetime
+//! used in the view type does not matter. In generic code, views borrowed for longer than the
+//! frame can be shortened with `borrow::Rebind::rebind`, a checked alternative to transmuting the
+//! lifetime.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # use borrow::FrameCache;
+//! #
+//! # #[derive(Default, borrow::Partial)]
 //! # #[module(crate)]
 //! # struct Graph {
-//! #    nodes: Vec<Node>,
-//! #    edges: Vec<Edge>,
-//! #    groups: Vec<Group>,
+//! #     nodes: Vec<usize>,
+//! #     edges: Vec<usize>,
 //! # }
 //! #
-//! # impl p!(<mut edges, mut nodes> Graph) {
-//! #     fn detach_all_nodes(&mut self) {
-//! #         let (nodes, mut self2) = self.borrow_nodes_mut();
-//! #         for node in nodes {
-//! #             self2.detach_node(node);
-//! #         }
-//! #     }
-//! # }
+//! fn main() {
+//!     let mut graphs = vec![Graph::default(), Graph::default()];
+//!     let mut cache = FrameCache::<p!('static <mut nodes> Graph)>::new();
+//!     for frame_index in 0..3 {
+//!         let mut frame = cache.begin_frame();
+//!         for graph in &mut graphs {
+//!             frame.push(graph.partial_borrow());
+//!         }
+//!         for view in frame.iter_mut() {
+//!             view.nodes.push(frame_index);
+//!         }
+//!     }
+//!     assert_eq!(graphs[1].nodes, vec![0, 1, 2]);
+//! }
+//! ```
+//!
+//! <br/>
+//! <br/>
+//!
+//! # Field masks
+//!
+//! `borrow::FieldMask<S>` is a runtime representation of a partial borrow shape of the struct
+//! `S`. Masks of partial borrow types can be obtained with `FieldMask::of`, and the `split`,
+//! `merge`, and `conflicts_with` methods follow the same rules as the partial borrows. This is
+//! useful for checking borrow shapes known only at runtime, e.g. in a scheduler running passes in
+//! parallel. With the `arbitrary` feature enabled, `FieldMask` implements `arbitrary::Arbitrary`,
+//! so the laws of splitting and merging borrows can be property-tested over random shapes. The
+//! laws themselves can be checked for your own partial borrow types with the functions of the
+//! `borrow::laws` module.
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::FieldMask;
 //! #
-//! # impl p!(<mut edges> Graph) {
-//! #     fn detach_node(&mut self, node: &mut Node) {
-//! #         for edge_id in std::mem::take(&mut node.outputs) {
-//! #             self.edges[edge_id].from = None;
-//! #         }
-//! #         for edge_id in std::mem::take(&mut node.inputs) {
-//! #             self.edges[edge_id].to = None;
-//! #         }
-//! #     }
+//! # #[derive(Default, borrow::Partial)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     nodes: Vec<usize>,
+//! #     edges: Vec<usize>,
 //! # }
 //! #
-//! // =============
-//! // === Tests ===
-//! // =============
-//!
 //! fn main() {
-//!    // node0 -----> node1 -----> node2 -----> node0
-//!    //       edge0        edge1        edge2
-//!    let mut graph = Graph {
-//!       nodes: vec![
-//!          Node { outputs: vec![0], inputs: vec![2] }, // Node 0
-//!          Node { outputs: vec![1], inputs: vec![0] }, // Node 1
-//!          Node { outputs: vec![2], inputs: vec![1] }, // Node 2
-//!       ],
-//!       edges: vec![
-//!          Edge { from: Some(0), to: Some(1) }, // Edge 0
-//!          Edge { from: Some(1), to: Some(2) }, // Edge 1
-//!          Edge { from: Some(2), to: Some(0) }, // Edge 2
-//!       ],
-//!       groups: vec![],
-//!    };
-//!
-//!    p!(&mut graph).detach_all_nodes();
-//!
-//!    for node in &graph.nodes {
-//!       assert!(node.outputs.is_empty());
-//!       assert!(node.inputs.is_empty());
-//!    }
-//!    for edge in &graph.edges {
-//!       assert!(edge.from.is_none());
-//!       assert!(edge.to.is_none());
-//!    }
+//!     let topology = FieldMask::of::<p!(<mut nodes, edges> Graph)>();
+//!     let edges = FieldMask::of::<p!(<edges> Graph)>();
+//!     let rest = topology.split(&edges);
+//!     assert_eq!(rest, Some(FieldMask::of::<p!(<mut nodes, edges> Graph)>()));
+//!     assert!(!edges.conflicts_with(&topology));
+//!     assert!(topology.split(&FieldMask::of::<p!(<mut edges> Graph)>()).is_none());
 //! }
 //! ```
+//!
+//! <br/>
 //! <br/>
-//! </details>
 //!
-//! Please note, that you do not need to provide the partially borrowed type explicitly, it will be
-//! inferred automatically. For example, the `detach_all_nodes` method requires self to have the
-//! `edges` and `nodes` fields mutably borrowed, but you can simply call it as follows:
+//! # Benchmarks
+//!
+//! To measure the cost of migrating a subsystem to partial borrows, the `bench` feature provides
+//! the `borrow::bench!` macro. It generates a [criterion](https://docs.rs/criterion) benchmark
+//! comparing a function taking a partial borrow (`view`) with its equivalent taking the borrowed
+//! fields as separate parameters (`params`), in the order of the selectors. The state is created
+//! once per benchmark, and the crate using the macro has to depend on `criterion`.
 //!
 //! ```
-//! # use std::vec::Vec;
 //! # use borrow::partial as p;
-//! # use borrow::traits::*;
 //! #
 //! # #[derive(Default, borrow::Partial)]
 //! # #[module(crate)]
@@ -1043,16 +2563,32 @@
 //! #     edges: Vec<usize>,
 //! # }
 //! #
-//! # impl p!(<mut nodes> Graph) {
-//! #     fn detach_all_nodes(&mut self) {}
-//! # }
-//! #
-//! fn main() {
-//!    let mut graph: Graph = Graph::default();
-//!    p!(&mut graph).detach_all_nodes();
+//! fn count(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+//!     graph.edges.len() + graph.nodes.len()
+//! }
+//!
+//! fn count_params(nodes: &Vec<usize>, edges: &mut Vec<usize>) -> usize {
+//!     edges.len() + nodes.len()
 //! }
+//!
+//! borrow::bench! {
+//!     bench_count = <nodes, mut edges> Graph {
+//!         state: Graph::default(),
+//!         view: count,
+//!         params: count_params,
+//!     }
+//! }
+//!
+//! criterion::criterion_group!(benches, bench_count);
+//! # fn main() {}
 //! ```
 //!
+//! The zero-overhead promise is enforced by the `codegen` test of this crate, run with
+//! `cargo test --features codegen --test codegen`. It compiles `partial_borrow`, `split`, and
+//! field access in release mode, for `x86_64-unknown-linux-gnu` and `wasm32-unknown-unknown` if
+//! their standard libraries are installed, and checks that the resulting instructions are the
+//! same as the ones of direct field access.
+//!
 //! <br/>
 //! <br/>
 //!
@@ -1069,6 +2605,9 @@
 //! - Disabled in release builds.
 //! - Can be turned off explicitly with the `no_usage_tracking` feature.
 //! - Can be forced on in release with the `usage_tracking` feature.
+//! - Can be replaced in release with a lightweight tracker with the `usage_tracking_lite` feature.
+//! - Can be compiled out entirely by disabling the default `tracking` feature, which also drops
+//!   the dependency on the `borrow-tracking` crate.
 //!
 //! Consider the following code:
 //!
@@ -1144,125 +2683,432 @@
 //!     pass2(p!(&mut graph));
 //! }
 //!
-//! fn pass2(mut graph: p!(&<nodes> Graph)) {
-//!     // Simulate ref usage of nodes.
-//!     let _ = &*graph.nodes;
+//! fn pass2(mut graph: p!(&<nodes> Graph)) {
+//!     // Simulate ref usage of nodes.
+//!     let _ = &*graph.nodes;
+//! }
+//! ```
+//!
+//! Fields in warnings are sorted alphabetically. If your code lists fields in the order they are
+//! declared in the struct, call `borrow::set_field_order(borrow::FieldOrder::Declaration)` at the
+//! start of your program, so applying the suggested fixes does not reorder them. In the example
+//! above, the suggested fix for `pass1` would then be `&<mut nodes, mut edges>`.
+//!
+//! For generic structs, the header of the warning also contains the concrete type of the borrowed
+//! struct, e.g. `Warning [src/main.rs:12] (Storage<u32>):`, so borrows of different
+//! instantiations at the same location can be told apart.
+//!
+//! ### Special Case 1: Trait Interface
+//!
+//! When passing a partial borrow into a trait method you consider an interface, you might not want
+//! the diagnostics to complain about unused fields. You can prefix the `&` with `_` to silence tracking:
+//!
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! struct Node;
+//! struct Edge;
+//! struct Group;
+//!
+//! #[derive(borrow::Partial, Default)]
+//! #[module(crate)]
+//! struct Graph {
+//!     pub nodes:  Vec<Node>,
+//!     pub edges:  Vec<Edge>,
+//!     pub groups: Vec<Group>,
+//! }
+//!
+//! trait RenderPass {
+//!     fn run_pass(graph: p!(_&<mut *> Graph));
+//! }
+//!
+//! struct MyRenderPass;
+//! impl RenderPass for MyRenderPass {
+//!     fn run_pass(graph: p!(_&<mut *> Graph)) {
+//!         // Simulate mut usage of edges.
+//!         let _ = &mut *graph.edges;
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     // No warnings here.
+//!     MyRenderPass::run_pass(p!(&mut graph));
+//! }
+//! ```
+//!
+//! If a struct is mostly passed to such interfaces, e.g. because it's owned by a framework, you can
+//! invert the polarity with `#[borrow(track_by_default = false)]`. Borrows of the struct are then
+//! not tracked, unless the `&` is prefixed with `!`:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! #[derive(borrow::Partial, Default)]
+//! #[module(crate)]
+//! #[borrow(track_by_default = false)]
+//! struct FrameCtx {
+//!     pub time:   f32,
+//!     pub events: Vec<u32>,
+//! }
+//!
+//! // Not tracked.
+//! fn on_frame(ctx: p!(&<mut *> FrameCtx)) {
+//!     let _ = &*ctx.time;
+//! }
+//!
+//! // Tracked, reports that `events` is not used.
+//! fn update(ctx: p!(!&<time, events> FrameCtx)) {
+//!     let _ = &*ctx.time;
+//! }
+//!
+//! fn main() {
+//!     let mut ctx = FrameCtx::default();
+//!     on_frame(p!(&mut ctx));
+//!     update(p!(&mut ctx));
+//! }
+//! ```
+//!
+//! ### Special Case 2: Conditional Use
+//!
+//! If your function uses a borrow only under certain conditions, you can silence the warnings
+//! by manually marking all fields as used:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! struct Node;
+//! struct Edge;
+//! struct Group;
+//!
+//! #[derive(borrow::Partial, Default)]
+//! #[module(crate)]
+//! struct Graph {
+//!     pub nodes:  Vec<Node>,
+//!     pub edges:  Vec<Edge>,
+//!     pub groups: Vec<Group>,
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     pass1(true, p!(&mut graph));
+//!     pass1(false, p!(&mut graph));
+//! }
+//!
+//! fn pass1(run2: bool, mut graph: p!(&<mut edges, nodes> Graph)) {
+//!     // Simulate mut usage of edges.
+//!     let _ = &mut *graph.edges;
+//!     if run2 {
+//!         pass2(p!(&mut graph));
+//!     } else {
+//!         // Disable field usage tracking for this condition.
+//!         graph.mark_all_fields_as_used();
+//!     }
+//! }
+//!
+//! fn pass2(mut graph: p!(&<nodes> Graph)) {
+//!     // Simulate ref usage of nodes.
+//!     let _ = &*graph.nodes;
+//! }
+//! ```
+//!
+//! If the struct isn’t used at all, Clippy will still warn you about the unused variable, but
+//! partial borrow diagnostics will be suppressed.
+//!
+//! ### Wrapper functions
+//!
+//! Warnings point to the place where the partial borrow was created. If you create partial borrows
+//! in your own helper functions, mark them with `#[track_caller]`, so the warnings point to the
+//! callers of the helpers instead:
+//!
+//! ```
+//! # use std::vec::Vec;
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # #[derive(borrow::Partial, Default)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     pub nodes: Vec<usize>,
+//! #     pub edges: Vec<usize>,
+//! # }
+//! #
+//! #[track_caller]
+//! fn topology(graph: &mut Graph) -> p!(<mut nodes, mut edges> Graph) {
+//!     graph.partial_borrow()
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     // Warnings about unused `edges` point to this line.
+//!     topology(&mut graph).nodes.push(0);
+//! }
+//! ```
+//!
+//! ### Closures
+//!
+//! Closures capturing a field, e.g. `graph.nodes`, capture the usage tracking [`Field`] wrapper,
+//! so the usage is registered every time the closure dereferences the field. Usage of a closure
+//! which is called after the partial borrow is moved into it, e.g. an event handler, is reported
+//! when the closure is dropped, and a closure which is never called does not use the field. To
+//! capture the plain value instead, unwrap the field with [`Field::into_inner`]. Its usage is then
+//! registered at the unwrapping, with the access given by the type of the value:
+//!
+//! ```
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # #[derive(borrow::Partial, Default)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     pub nodes: Vec<usize>,
+//! #     pub edges: Vec<usize>,
+//! # }
+//! #
+//! fn on_click<'a>(graph: p!(<'a mut nodes, 'a edges> Graph)) -> impl FnMut(usize) + 'a {
+//!     // Usage of `nodes` is registered here.
+//!     let nodes = graph.nodes.into_inner();
+//!     // Usage of `edges` is registered by the closure, when it is called.
+//!     let edges = graph.edges;
+//!     move |node| {
+//!         if edges.is_empty() {
+//!             nodes.push(node);
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     let mut handler = on_click(graph.partial_borrow());
+//!     handler(0);
+//!     handler(1);
+//!     drop(handler);
+//!     assert_eq!(graph.nodes, [0, 1]);
+//! }
+//! ```
+//!
+//! ### Denying over-borrows
+//!
+//! To enforce minimal borrows on selected functions, e.g. public entry points of a library, mark
+//! them with `#[borrow::deny_over_borrow]`. Partial borrows passed to their parameters spelled
+//! with `p!` then panic instead of printing a warning when they borrow fields which are not used,
+//! or borrow them as mutable while they are used as shared. Other functions keep printing
+//! warnings. As with the warnings, the check is performed only when usage tracking is enabled:
+//!
+//! ```no_run
+//! # use borrow::partial as p;
+//! # use borrow::traits::*;
+//! # #[derive(borrow::Partial, Default)]
+//! # #[module(crate)]
+//! # struct Graph {
+//! #     pub nodes: Vec<usize>,
+//! #     pub edges: Vec<usize>,
+//! # }
+//! #
+//! #[borrow::deny_over_borrow]
+//! pub fn node_count(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+//!     graph.nodes.len()
+//! }
+//!
+//! fn main() {
+//!     let mut graph = Graph::default();
+//!     // Panics in debug builds, as `edges` are borrowed but not used.
+//!     node_count(p!(&mut graph));
 //! }
 //! ```
 //!
-//! ### Special Case 1: Trait Interface
+//! ### Lightweight tracking in release builds
 //!
-//! When passing a partial borrow into a trait method you consider an interface, you might not want
-//! the diagnostics to complain about unused fields. You can prefix the `&` with `_` to silence tracking:
+//! The `usage_tracking_lite` feature enables a tracker meant for production telemetry. Instead of
+//! emitting warnings, it records in a static, fixed-size table which fields were ever requested
+//! and ever used at each callsite. It performs no allocations and its overhead is limited to a few
+//! relaxed atomic operations. The collected data can be queried at any time:
 //!
+//! ```
+//! borrow::for_each_over_borrow(|report| {
+//!     eprintln!(
+//!         "[{}] Field '{}' borrowed as {:?} but used as {:?}.",
+//!         report.location, report.field, report.requested, report.used
+//!     );
+//! });
+//! ```
+//!
+//! The lite tracker is used only when the full one is disabled, so in debug builds you will still
+//! get the regular warnings. On targets where allocations are not available or not desired, the
+//! `usage_tracking_no_alloc` feature uses the lite tracker in all profiles instead of the full one.
+//!
+//! The table has 1024 slots by default. Set the `BORROW_TRACKER_CAPACITY` environment variable when
+//! building to change it, e.g. `BORROW_TRACKER_CAPACITY=256 cargo build`. When the table is full,
+//! the oldest (callsite, field) pairs are dropped to make room for new ones, so their over-borrows
+//! are no longer reported.
+//!
+//! ### Borrow timing
+//!
+//! With the `borrow_timing` feature, the full tracker also records how long the partial borrows
+//! created at each callsite live, from creation to drop. Long-lived borrows of many fields are
+//! the ones preventing other code from running in parallel, so the report lists the callsites
+//! with the longest total time first:
 //!
 //! ```
-//! # use std::vec::Vec;
-//! # use borrow::partial as p;
-//! # use borrow::traits::*;
-//! struct Node;
-//! struct Edge;
-//! struct Group;
+//! borrow::for_each_borrow_timing(|timing| {
+//!     eprintln!(
+//!         "[{}] {} borrows of up to {} fields, {:?} on average, {:?} at most.",
+//!         timing.location, timing.count, timing.max_fields, timing.mean(), timing.max
+//!     );
+//! });
+//! ```
 //!
-//! #[derive(borrow::Partial, Default)]
-//! #[module(crate)]
-//! struct Graph {
-//!     pub nodes:  Vec<Node>,
-//!     pub edges:  Vec<Edge>,
-//!     pub groups: Vec<Group>,
-//! }
+//! ### Custom trackers
 //!
-//! trait RenderPass {
-//!     fn run_pass(graph: p!(_&<mut *> Graph));
-//! }
+//! The full tracker reports the usage of every dropped partial borrow to the tracker installed
+//! with [`tracking::set_tracker`]. The default one prints the warnings shown above. Custom
+//! trackers, e.g. forwarding over-borrows to `tracing` or to metrics, implement
+//! [`tracking::Tracker`] and can be developed in separate crates depending on `borrow-tracking`
+//! only:
 //!
-//! struct MyRenderPass;
-//! impl RenderPass for MyRenderPass {
-//!     fn run_pass(graph: p!(_&<mut *> Graph)) {
-//!         // Simulate mut usage of edges.
-//!         let _ = &mut *graph.edges;
+//! ```
+//! # #[cfg(feature = "tracking")]
+//! use borrow::tracking::BorrowReport;
+//!
+//! struct LogTracker;
+//!
+//! # #[cfg(feature = "tracking")]
+//! impl borrow::tracking::Tracker for LogTracker {
+//!     fn borrow_dropped(&self, report: &BorrowReport) {
+//!         for field in report.not_used() {
+//!             eprintln!("{} Field '{field}' is not used.", report.header());
+//!         }
 //!     }
 //! }
 //!
 //! fn main() {
-//!     let mut graph = Graph::default();
-//!     // No warnings here.
-//!     MyRenderPass::run_pass(p!(&mut graph));
+//!     # #[cfg(feature = "tracking")]
+//!     borrow::tracking::set_tracker(&LogTracker);
 //! }
 //! ```
 //!
-//! ### Special Case 2: Conditional Use
+//! ### Custom containers
 //!
-//! If your function uses a borrow only under certain conditions, you can silence the warnings
-//! by manually marking all fields as used:
+//! Containers handing out their own views, e.g. arena wrappers, can track the usage of the view
+//! fields with a [`UsageScope`]. The usage is reported by all the trackers described above, as a
+//! borrow created at the location passed to the scope, e.g. the caller of the container method.
+//! See [`UsageScope`] for an example.
+//!
+//! ### Mutation hooks
+//!
+//! Fields marked with `#[borrow(on_mut = "path::to::hook")]` call the given [`MutHook`] with the
+//! field label and the location of every mutable access to the field through a partial borrow,
+//! e.g. to report which systems touch the scene graph to a profiler. The hooks are called by the
+//! usage trackers, so they are never called without usage tracking, e.g. in release builds
+//! without the `usage_tracking_lite` feature:
 //!
 //! ```
-//! # use std::vec::Vec;
 //! # use borrow::partial as p;
 //! # use borrow::traits::*;
-//! struct Node;
-//! struct Edge;
-//! struct Group;
+//! use std::panic::Location;
 //!
-//! #[derive(borrow::Partial, Default)]
-//! #[module(crate)]
-//! struct Graph {
-//!     pub nodes:  Vec<Node>,
-//!     pub edges:  Vec<Edge>,
-//!     pub groups: Vec<Group>,
+//! fn scene_touched(field: &'static str, location: &'static Location<'static>) {
+//!     eprintln!("[{location}] '{field}' used mutably.");
 //! }
 //!
-//! fn main() {
-//!     let mut graph = Graph::default();
-//!     pass1(true, p!(&mut graph));
-//!     pass1(false, p!(&mut graph));
+//! #[derive(Default, borrow::Partial)]
+//! #[module(crate)]
+//! struct World {
+//!     #[borrow(on_mut = "scene_touched")]
+//!     scene: Vec<usize>,
+//!     time: f32,
 //! }
 //!
-//! fn pass1(run2: bool, mut graph: p!(&<mut edges, nodes> Graph)) {
-//!     // Simulate mut usage of edges.
-//!     let _ = &mut *graph.edges;
-//!     if run2 {
-//!         pass2(p!(&mut graph));
-//!     } else {
-//!         // Disable field usage tracking for this condition.
-//!         graph.mark_all_fields_as_used();
-//!     }
+//! fn spawn(world: p!(&<mut scene, time> World)) {
+//!     // Calls `scene_touched` in debug builds.
+//!     world.scene.push(0);
 //! }
 //!
-//! fn pass2(mut graph: p!(&<nodes> Graph)) {
-//!     // Simulate ref usage of nodes.
-//!     let _ = &*graph.nodes;
+//! fn main() {
+//!     let mut world = World::default();
+//!     spawn(p!(&mut world));
 //! }
 //! ```
 //!
-//! If the struct isn’t used at all, Clippy will still warn you about the unused variable, but
-//! partial borrow diagnostics will be suppressed.
-//!
 //! <br/>
 //! <br/>
 
 #![cfg_attr(not(usage_tracking_enabled), allow(unused_imports))]
 #![cfg_attr(not(usage_tracking_enabled), allow(dead_code))]
+// Examples intentionally show complete programs, including the test files they are taken from.
+#![allow(clippy::needless_doctest_main)]
+#![allow(clippy::test_attr_in_doctest)]
 
 extern crate self as borrow;
 
 pub mod doc;
 pub mod hlist;
+pub mod laws;
 pub mod reflect;
+mod borrow_timing;
+mod borrowable;
+mod buffer;
+mod dirty_flags;
+mod field_mask;
+mod frame_cache;
+mod hide_shared;
+mod lock;
+mod memo;
+mod owned_ref;
+#[cfg(feature = "registry")]
+mod registry;
+mod shape;
+mod split_at;
+mod test_view;
+mod transactional;
+mod upgradeable;
+mod usage_scope;
+mod visit_field;
 
 #[cfg(usage_tracking_enabled)]
 mod usage_tracker;
 #[cfg(usage_tracking_enabled)]
 pub use usage_tracker::*;
 
-#[cfg(not(usage_tracking_enabled))]
+#[cfg(usage_tracking_lite_enabled)]
+mod usage_tracker_lite;
+#[cfg(usage_tracking_lite_enabled)]
+pub use usage_tracker_lite::*;
+
+#[cfg(not(field_usage_tracking_enabled))]
 mod usage_tracker_mock;
-#[cfg(not(usage_tracking_enabled))]
+#[cfg(not(field_usage_tracking_enabled))]
 pub use usage_tracker_mock::*;
 
 pub use reflect::*;
+pub use borrow_timing::*;
+pub use borrowable::*;
+pub use buffer::*;
+pub use dirty_flags::*;
+pub use field_mask::*;
+pub use frame_cache::*;
+pub use hide_shared::*;
+pub use lock::*;
+pub use memo::*;
+pub use owned_ref::*;
+#[cfg(feature = "registry")]
+pub use registry::*;
+pub use shape::*;
+pub use split_at::*;
+pub use test_view::*;
+pub use transactional::*;
+pub use upgradeable::*;
+pub use usage_scope::*;
+pub use visit_field::*;
 pub use borrow_macro::*;
 
+/// Hooks receiving the usage reports of the full usage tracker, see
+/// [Custom trackers](crate#custom-trackers).
+#[cfg(feature = "tracking")]
+pub use borrow_tracking as tracking;
+
 #[doc(hidden)]
 pub use tstr::TS as Str;
 
@@ -1302,7 +3148,9 @@ pub trait Bool {
     fn bool() -> bool;
 }
 
+#[derive(Debug)]
 pub struct True;
+#[derive(Debug)]
 pub struct False;
 
 impl Bool for True {
@@ -1324,17 +3172,84 @@ impl Bool for False {
 #[doc(hidden)]
 pub type Label = &'static str;
 
+// ===============
+// === MutHook ===
+// ===============
+
+/// Callback given in `#[borrow(on_mut = "...")]`, called with the field label and the location of
+/// every mutable access to the field through a partial borrow, see
+/// [Mutation hooks](crate#mutation-hooks).
+pub type MutHook = fn(Label, &'static std::panic::Location<'static>);
+
 // =============
 // === Usage ===
 // =============
 
-#[doc(hidden)]
+/// Access to a field, or `None` if the field is not borrowed.
 pub type OptUsage = Option<Usage>;
 
-#[doc(hidden)]
+/// Access to a borrowed field.
 #[derive(Clone, Copy, Debug, Eq, PartialOrd, PartialEq, Ord)]
 pub enum Usage { Ref, Mut }
 
+// ==================
+// === OverBorrow ===
+// ==================
+
+/// A field borrowed at `location` with a wider access than it was ever used with. Reported by the
+/// lite usage tracker, see [`for_each_over_borrow`].
+#[derive(Clone, Copy, Debug)]
+pub struct OverBorrow {
+    pub location: &'static std::panic::Location<'static>,
+    pub field: Label,
+    pub requested: Usage,
+    pub used: OptUsage,
+}
+
+/// Calls `f` for every field that was, so far, borrowed with a wider access than it was used with.
+/// Data is collected only when the `usage_tracking_lite` feature is enabled and the full usage
+/// tracker is disabled (e.g. in release builds), or when the `usage_tracking_no_alloc` feature is
+/// enabled. Otherwise, this function does nothing.
+pub fn for_each_over_borrow(mut f: impl FnMut(OverBorrow)) {
+    #[cfg(usage_tracking_lite_enabled)]
+    usage_tracker_lite::for_each_over_borrow(&mut f);
+    #[cfg(not(usage_tracking_lite_enabled))]
+    let _ = &mut f;
+}
+
+// ==================
+// === FieldOrder ===
+// ==================
+
+/// Order of fields listed in unused borrow warnings, including the suggested fixes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FieldOrder {
+    /// Fields are sorted alphabetically.
+    #[default]
+    Alphabetical,
+    /// Fields are listed in the order they are declared in the struct.
+    Declaration,
+}
+
+static FIELD_ORDER: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Sets the order of fields listed in unused borrow warnings. Fields are sorted alphabetically by
+/// default. Use [`FieldOrder::Declaration`] if your code lists fields in the declaration order,
+/// so applying the suggested fixes does not reorder them.
+pub fn set_field_order(order: FieldOrder) {
+    let declaration = order == FieldOrder::Declaration;
+    FIELD_ORDER.store(declaration, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// The order of fields listed in unused borrow warnings. See [`set_field_order`].
+pub fn field_order() -> FieldOrder {
+    if FIELD_ORDER.load(std::sync::atomic::Ordering::Relaxed) {
+        FieldOrder::Declaration
+    } else {
+        FieldOrder::Alphabetical
+    }
+}
+
 // =============================
 // === HasUsageTrackedFields ===
 // =============================
@@ -1345,6 +3260,9 @@ pub trait HasUsageTrackedFields {
     /// can be handy when you pass a partial borrow to a trait method, which can be considered an
     /// interface which does not have to use all the given fields.
     fn mark_all_fields_as_used(&self);
+    /// Report fields borrowed but not used with a panic instead of a warning. Called by functions
+    /// marked with `#[borrow::deny_over_borrow]`.
+    fn deny_over_borrow(&self) {}
 }
 
 // =============
@@ -1352,83 +3270,284 @@ pub trait HasUsageTrackedFields {
 // =============
 
 /// Field that tracks usage of its value. The `Enabled` type parameter is used to determine whether
-/// the tracking is enabled.
-#[doc(hidden)]
+/// the tracking is enabled. Custom field types can be split with [`Field::split_field`], see
+/// [`Acquire`].
 #[derive(Debug)]
-#[cfg_attr(not(usage_tracking_enabled), repr(transparent))]
+#[cfg_attr(not(field_usage_tracking_enabled), repr(transparent))]
 pub struct Field<Enabled: Bool, V> {
     pub value_no_usage_tracking: V,
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     tracker: FieldUsageTracker<Enabled>,
     type_marker: PhantomData<Enabled>,
 }
 
 impl<E: Bool, V> Field<E, V> {
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     pub fn new(label: Label, requested_usage: OptUsage, value: V, tracker: UsageTracker) -> Self {
         let usage_tracker = FieldUsageTracker::new(label, requested_usage, tracker);
         Self::cons(value, usage_tracker)
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     pub fn new(_label: Label, _req_usage: OptUsage, value: V, _tracker: UsageTracker) -> Self {
         Self::cons(value)
     }
 
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn cons(value_no_usage_tracking: V, tracker: FieldUsageTracker<E>) -> Self {
         let type_marker = PhantomData;
         Self { value_no_usage_tracking, tracker, type_marker }
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn cons(value_no_usage_tracking: V) -> Self {
         let type_marker = PhantomData;
         Self { value_no_usage_tracking, type_marker }
     }
 
+    /// Sets the dirty flag of the field whenever it is used mutably. Without usage tracking, the
+    /// flag is set right away. Used by the `borrow::Partial` derive macro.
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn with_dirty_flag(mut self, flag: DirtyFlag) -> Self {
+        self.tracker.set_dirty_flag(flag);
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn with_dirty_flag(self, flag: DirtyFlag) -> Self {
+        flag.set();
+        self
+    }
+
+    /// Calls `hook` whenever the field is used mutably. Without usage tracking, the hook is never
+    /// called. Used by the `borrow::Partial` derive macro.
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn with_mut_hook(mut self, hook: MutHook) -> Self {
+        self.tracker.set_mut_hook(hook);
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn with_mut_hook(self, _hook: MutHook) -> Self {
+        self
+    }
+
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn clone_as_hidden<E2: Bool>(&self) -> Field<E2, Hidden> {
         Field::cons(Hidden, self.tracker.clone_disabled())
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn clone_as_hidden<E2: Bool>(&self) -> Field<E2, Hidden> {
         Field::cons(Hidden)
     }
 
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     pub fn disable_usage_tracking(&self) {
         self.tracker.disable();
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     pub fn disable_usage_tracking(&self) {}
 
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     pub fn mark_as_used(&self) {
         self.tracker.register_usage(Some(Usage::Mut));
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     pub fn mark_as_used(&self) {}
+
+    /// Unwraps the value of the field, e.g. to move it into a closure. Usage of the value is
+    /// registered right away, with the access given by its type, e.g. mutable for `&mut T`,
+    /// whether the value is used afterwards or not.
+    #[inline(always)]
+    #[cfg_attr(field_usage_tracking_enabled, track_caller)]
+    pub fn into_inner(self) -> V where V: FieldAccess {
+        #[cfg(field_usage_tracking_enabled)]
+        self.tracker.register_usage(V::ACCESS);
+        #[cfg(field_usage_tracking_enabled)]
+        if V::ACCESS == Some(Usage::Mut) {
+            self.tracker.mark_dirty();
+        }
+        self.value_no_usage_tracking
+    }
+
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn into_field_value(self) -> FieldValue<E, V> {
+        self
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn into_field_value(self) -> FieldValue<E, V> {
+        self.value_no_usage_tracking
+    }
+
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn rebind<'a>(self) -> Field<E, ReboundField<'a, V>>
+    where V: RebindField<'a> {
+        Field::cons(self.value_no_usage_tracking.rebind_field(), self.tracker)
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn rebind<'a>(self) -> Field<E, ReboundField<'a, V>>
+    where V: RebindField<'a> {
+        Field::cons(self.value_no_usage_tracking.rebind_field())
+    }
+
+    /// Replaces the value of the field with a projection of it, e.g. a sub-slice of a borrowed
+    /// vector. Usage of the projection is attributed to this field. Used by the `map_$field`
+    /// methods generated by the `borrow::Partial` derive macro.
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn map_field<T>(self, f: impl FnOnce(V) -> T) -> Field<E, T> {
+        Field::cons(f(self.value_no_usage_tracking), self.tracker)
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn map_field<T>(self, f: impl FnOnce(V) -> T) -> Field<E, T> {
+        Field::cons(f(self.value_no_usage_tracking))
+    }
+
+    /// Splits the value of the field at `mid` into two disjoint parts, e.g. a vector into two
+    /// slices. Usage of both parts is attributed to this field. Used by the `split_$field_at`
+    /// methods generated by the `borrow::Partial` derive macro.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the length of the value.
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn split_field_at(self, mid: usize) -> (Field<E, V::Part>, Field<E, V::Part>)
+    where V: SplitAtField {
+        let tail_tracker = self.tracker.new_child_disabled();
+        let (head, tail) = self.value_no_usage_tracking.split_at_field(mid);
+        (Field::cons(head, self.tracker), Field::cons(tail, tail_tracker))
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn split_field_at(self, mid: usize) -> (Field<E, V::Part>, Field<E, V::Part>)
+    where V: SplitAtField {
+        let (head, tail) = self.value_no_usage_tracking.split_at_field(mid);
+        (Field::cons(head), Field::cons(tail))
+    }
+
+    /// Clones the field, e.g. a shared reference. Usage of the clone is attributed to this field.
+    /// Used by the `Clone` impls of ref structs marked with `#[borrow(derive(Clone))]`.
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn clone_field(&self) -> Self where V: Clone {
+        Field::cons(self.value_no_usage_tracking.clone(), self.tracker.clone_disabled())
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn clone_field(&self) -> Self where V: Clone {
+        Field::cons(self.value_no_usage_tracking.clone())
+    }
+
+    /// Passes the field to `visitor` with the given label, registering its usage. Hidden fields are
+    /// skipped. Used by the `for_each_selected` methods generated by the `borrow::Partial` derive
+    /// macro.
+    #[inline(always)]
+    #[cfg_attr(field_usage_tracking_enabled, track_caller)]
+    pub fn visit_selected<Vis>(&mut self, label: Label, visitor: &mut Vis)
+    where V: SelectedField<Vis> {
+        #[cfg(field_usage_tracking_enabled)]
+        self.tracker.register_usage(V::USAGE);
+        #[cfg(field_usage_tracking_enabled)]
+        if V::USAGE == Some(Usage::Mut) {
+            self.tracker.mark_dirty();
+        }
+        self.value_no_usage_tracking.visit_selected(label, visitor);
+    }
+
+    /// Splits the field into the part passed to the target of a partial borrow and the part kept
+    /// by the rest of the source. The target is tracked as a new borrow requesting `usage`, or is
+    /// not tracked at all if `usage` is `None`. Usage of the rest is attributed to this field.
+    /// This is the building block of [`Acquire`] impls for custom field types.
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn split_field<E2: Bool, T, R>(
+        self,
+        usage: OptUsage,
+        tracker: UsageTracker,
+        f: impl FnOnce(V) -> (T, R)
+    ) -> (Field<E2, T>, Field<E, R>) {
+        let target_tracker = match usage {
+            Some(usage) => self.tracker.new_child(usage, tracker),
+            None => self.tracker.clone_disabled(),
+        };
+        let rest_tracker = self.tracker.new_child_disabled();
+        let (target, rest) = f(self.value_no_usage_tracking);
+        (Field::cons(target, target_tracker), Field::cons(rest, rest_tracker))
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn split_field<E2: Bool, T, R>(
+        self,
+        _usage: OptUsage,
+        _tracker: UsageTracker,
+        f: impl FnOnce(V) -> (T, R)
+    ) -> (Field<E2, T>, Field<E, R>) {
+        let (target, rest) = f(self.value_no_usage_tracking);
+        (Field::cons(target), Field::cons(rest))
+    }
+}
+
+/// Type of a field value handed out by methods such as `borrow_$field`. It is a usage tracking
+/// [`Field`] when usage tracking is enabled, and the plain value (e.g. `&mut T`) otherwise, so
+/// release-mode signatures and error messages do not mention the wrapper.
+#[cfg(field_usage_tracking_enabled)]
+pub type FieldValue<E, V> = Field<E, V>;
+
+#[cfg(not(field_usage_tracking_enabled))]
+pub type FieldValue<E, V> = <(E, V) as Untracked>::Value;
+
+/// Helper for [`FieldValue`], as type aliases have to use all of their parameters.
+#[doc(hidden)]
+#[cfg(not(field_usage_tracking_enabled))]
+pub trait Untracked {
+    type Value;
+}
+
+#[cfg(not(field_usage_tracking_enabled))]
+impl<E, V> Untracked for (E, V) {
+    type Value = V;
 }
 
 impl<E: Bool, T> Deref for Field<E, T> {
     type Target = T;
     #[inline(always)]
     fn deref(&self) -> &T {
-        #[cfg(usage_tracking_enabled)]
+        #[cfg(field_usage_tracking_enabled)]
         self.tracker.register_usage(Some(Usage::Ref));
         &self.value_no_usage_tracking
     }
@@ -1436,9 +3555,12 @@ impl<E: Bool, T> Deref for Field<E, T> {
 
 impl<E: Bool, T> DerefMut for Field<E, T> {
     #[inline(always)]
+    #[cfg_attr(field_usage_tracking_enabled, track_caller)]
     fn deref_mut(&mut self) -> &mut T {
-        #[cfg(usage_tracking_enabled)]
+        #[cfg(field_usage_tracking_enabled)]
         self.tracker.register_usage(Some(Usage::Mut));
+        #[cfg(field_usage_tracking_enabled)]
+        self.tracker.mark_dirty();
         &mut self.value_no_usage_tracking
     }
 }
@@ -1449,7 +3571,7 @@ where &'t T: IntoIterator {
     type IntoIter = <&'t T as IntoIterator>::IntoIter;
     #[inline(always)]
     fn into_iter(self) -> Self::IntoIter {
-        #[cfg(usage_tracking_enabled)]
+        #[cfg(field_usage_tracking_enabled)]
         self.tracker.register_usage(Some(Usage::Ref));
         self.value_no_usage_tracking.into_iter()
     }
@@ -1460,9 +3582,12 @@ where &'t mut T: IntoIterator {
     type Item = <&'t mut T as IntoIterator>::Item;
     type IntoIter = <&'t mut T as IntoIterator>::IntoIter;
     #[inline(always)]
+    #[cfg_attr(field_usage_tracking_enabled, track_caller)]
     fn into_iter(self) -> Self::IntoIter {
-        #[cfg(usage_tracking_enabled)]
+        #[cfg(field_usage_tracking_enabled)]
         self.tracker.register_usage(Some(Usage::Mut));
+        #[cfg(field_usage_tracking_enabled)]
+        self.tracker.mark_dirty();
         self.value_no_usage_tracking.into_iter()
     }
 }
@@ -1493,78 +3618,56 @@ pub trait CloneField<'s, E: Bool> {
 #[doc(hidden)]
 pub type ClonedField<'s, T, E> = <T as CloneField<'s, E>>::Cloned;
 
-impl<'s, E: Bool> CloneField<'s, E> for Field<E, Hidden> {
-    type Cloned = Hidden;
-    #[cfg(usage_tracking_enabled)]
+impl<'s, E: Bool, V: ReborrowField<'s>> CloneField<'s, E> for Field<E, V> {
+    type Cloned = V::Output;
+    #[cfg(field_usage_tracking_enabled)]
     fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
         let usage_tracker = self.tracker.clone_disabled();
-        Field::cons(self.value_no_usage_tracking, usage_tracker)
+        Field::cons(self.value_no_usage_tracking.reborrow_field(), usage_tracker)
     }
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
-        Field::cons(self.value_no_usage_tracking)
+        Field::cons(self.value_no_usage_tracking.reborrow_field())
     }
 }
 
-impl<'s, 't, E: Bool, T> CloneField<'s, E> for Field<E, &'t T> {
-    type Cloned = &'t T;
-    #[cfg(usage_tracking_enabled)]
-    fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
-        let usage_tracker = self.tracker.clone_disabled();
-        Field::cons(self.value_no_usage_tracking, usage_tracker)
-    }
-    #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
-    fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
-        Field::cons(self.value_no_usage_tracking)
-    }
+// =====================
+// === ReborrowField ===
+// =====================
+
+/// Reborrows a single field of a partial borrow for `'s`, e.g. when the partial borrow is passed
+/// to a function by `p!(&mut view)` and used again afterwards. Custom field types have to
+/// implement it, see [`Acquire`].
+pub trait ReborrowField<'s> {
+    type Output;
+    fn reborrow_field(&'s mut self) -> Self::Output;
 }
 
-impl<'s, E: Bool, T: 's> CloneField<'s, E> for Field<E, &mut T> {
-    type Cloned = &'s mut T;
-    #[cfg(usage_tracking_enabled)]
-    fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
-        let usage_tracker = self.tracker.clone_disabled();
-        Field::cons(self.value_no_usage_tracking, usage_tracker)
-    }
+impl ReborrowField<'_> for Hidden {
+    type Output = Hidden;
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
-    fn clone_field_disabled_usage_tracking(&'s mut self) -> Field<E, Self::Cloned> {
-        Field::cons(self.value_no_usage_tracking)
+    fn reborrow_field(&mut self) -> Self::Output {
+        Hidden
     }
 }
 
-// ====================
-// === HasFieldsExt ===
-// ====================
-
-#[doc(hidden)]
-pub trait HasFieldsExt: HasFields {
-    type FieldsAsHidden;
-    type FieldsAsRef<'t> where Self: 't;
-    type FieldsAsMut<'t> where Self: 't;
+impl<'t, T: ?Sized> ReborrowField<'_> for &'t T {
+    type Output = &'t T;
+    #[inline(always)]
+    fn reborrow_field(&mut self) -> Self::Output {
+        *self
+    }
 }
 
-#[doc(hidden)]
-pub type FieldsAsHidden<T> = <T as HasFieldsExt>::FieldsAsHidden;
-#[doc(hidden)]
-pub type FieldsAsRef<'t, T> = <T as HasFieldsExt>::FieldsAsRef<'t>;
-#[doc(hidden)]
-pub type FieldsAsMut<'t, T> = <T as HasFieldsExt>::FieldsAsMut<'t>;
-
-// =======================
-// === AsRefWithFields ===
-// =======================
-
-#[doc(hidden)]
-pub trait AsRefWithFields<F> {
-    type Output;
+impl<'s, T: ?Sized + 's> ReborrowField<'s> for &mut T {
+    type Output = &'s mut T;
+    #[inline(always)]
+    fn reborrow_field(&'s mut self) -> Self::Output {
+        &mut **self
+    }
 }
 
-#[doc(hidden)]
-pub type RefWithFields<T, F> = <T as AsRefWithFields<F>>::Output;
-
 // ==============
 // === Hidden ===
 // ==============
@@ -1577,10 +3680,87 @@ pub struct Hidden;
 // === Acquire ===
 // ===============
 
-#[doc(hidden)]
+/// The type implementing [`Acquire`] for all field types.
 pub struct AcquireMarker;
 
-#[doc(hidden)]
+/// Splits a field of type `This` into the field of the target partial borrow of type `Target` and
+/// the field kept by the rest of the source of type [`Acquire::Rest`]. It is implemented for
+/// [`AcquireMarker`], so downstream crates can implement it for their own field types, teaching
+/// the splitter how to borrow them. The impls have to be written with [`Field::split_field`],
+/// which keeps the usage tracking consistent regardless of the enabled features. Custom field
+/// types also have to implement [`ReborrowField`], and optionally [`FieldAccess`] and
+/// [`RebindField`].
+///
+/// For example, a value which remembers whether it was borrowed mutably can split into the value
+/// and a handle to its dirty flag:
+///
+/// ```
+/// use borrow::traits::*;
+/// use borrow::Acquire;
+/// use borrow::AcquireMarker;
+/// use borrow::Bool;
+/// use borrow::Field;
+/// use borrow::Hidden;
+/// use borrow::Usage;
+/// use borrow::UsageTracker;
+///
+/// #[derive(Default)]
+/// pub struct Dirty<T> {
+///     value: T,
+///     dirty: bool,
+/// }
+///
+/// pub struct DirtyMut<'t, T> {
+///     pub value: &'t mut T,
+///     pub dirty: &'t mut bool,
+/// }
+///
+/// impl<'t, 'y, T> Acquire<&'t mut Dirty<T>, DirtyMut<'y, T>> for AcquireMarker
+/// where 't: 'y {
+///     type Rest = Hidden;
+///     fn acquire<E1: Bool, E2: Bool>(
+///         this: Field<E1, &'t mut Dirty<T>>,
+///         tracker: UsageTracker
+///     ) -> (Field<E2, DirtyMut<'y, T>>, Field<E1, Hidden>) {
+///         this.split_field(Some(Usage::Mut), tracker, |this| {
+///             (DirtyMut { value: &mut this.value, dirty: &mut this.dirty }, Hidden)
+///         })
+///     }
+/// }
+///
+/// impl<'s, T: 's> borrow::ReborrowField<'s> for DirtyMut<'_, T> {
+///     type Output = DirtyMut<'s, T>;
+///     fn reborrow_field(&'s mut self) -> Self::Output {
+///         DirtyMut { value: self.value, dirty: self.dirty }
+///     }
+/// }
+///
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// pub struct Scene {
+///     pub mesh: Dirty<Vec<f32>>,
+///     pub name: String,
+/// }
+///
+/// type MeshMut<'t> = SceneRef<Scene, DirtyMut<'t, Vec<f32>>, Hidden>;
+///
+/// fn add_vertex(scene: &mut MeshMut) {
+///     scene.mesh.value.push(0.0);
+///     *scene.mesh.dirty = true;
+/// }
+///
+/// fn main() {
+///     let mut scene = Scene::default();
+///     add_vertex(&mut scene.partial_borrow());
+///     assert!(scene.mesh.dirty);
+/// }
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "cannot borrow a field as `{Target}` from a partial borrow providing `{This}`",
+    label = "the target partial borrow requires a field that the source hides or borrows immutably",
+    note = "a partial borrow can only be narrowed: every field of the target has to be borrowed by \
+            the source with at least the same access"
+)]
 pub trait Acquire<This, Target> {
     type Rest;
     fn acquire<E1: Bool, E2: Bool>(
@@ -1589,10 +3769,10 @@ pub trait Acquire<This, Target> {
     ) -> (Field<E2, Target>, Field<E1, Self::Rest>);
 }
 
-impl<'t, T> Acquire<&'t mut T, Hidden> for AcquireMarker {
+impl<'t, T: ?Sized> Acquire<&'t mut T, Hidden> for AcquireMarker {
     type Rest = &'t mut T;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t mut T>,
         _: UsageTracker
@@ -1603,7 +3783,7 @@ impl<'t, T> Acquire<&'t mut T, Hidden> for AcquireMarker {
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t mut T>,
         _: UsageTracker
@@ -1614,10 +3794,10 @@ impl<'t, T> Acquire<&'t mut T, Hidden> for AcquireMarker {
     }
 }
 
-impl<'t, T> Acquire<&'t T, Hidden> for AcquireMarker {
+impl<'t, T: ?Sized> Acquire<&'t T, Hidden> for AcquireMarker {
     type Rest = &'t T;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t T>,
         _: UsageTracker
@@ -1628,7 +3808,7 @@ impl<'t, T> Acquire<&'t T, Hidden> for AcquireMarker {
     }
 
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t T>,
         _: UsageTracker
@@ -1642,7 +3822,7 @@ impl<'t, T> Acquire<&'t T, Hidden> for AcquireMarker {
 impl Acquire<Hidden, Hidden> for AcquireMarker {
     type Rest = Hidden;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, Hidden>,
         _: UsageTracker
@@ -1652,7 +3832,7 @@ impl Acquire<Hidden, Hidden> for AcquireMarker {
         (target, rest)
     }
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, Hidden>,
         _: UsageTracker
@@ -1663,11 +3843,11 @@ impl Acquire<Hidden, Hidden> for AcquireMarker {
     }
 }
 
-impl<'t, 'y, T> Acquire<&'t mut T, &'y mut T> for AcquireMarker
+impl<'t, 'y, T: ?Sized> Acquire<&'t mut T, &'y mut T> for AcquireMarker
 where 't: 'y {
     type Rest = Hidden;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t mut T>,
         tracker: UsageTracker
@@ -1680,7 +3860,7 @@ where 't: 'y {
         (target, rest)
     }
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t mut T>,
         _: UsageTracker
@@ -1691,11 +3871,11 @@ where 't: 'y {
     }
 }
 
-impl<'t, 'y, T> Acquire<&'t mut T, &'y T> for AcquireMarker
+impl<'t, 'y, T: ?Sized> Acquire<&'t mut T, &'y T> for AcquireMarker
 where 't: 'y {
     type Rest = &'t T;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t mut T>,
         tracker: UsageTracker
@@ -1709,7 +3889,7 @@ where 't: 'y {
         )
     }
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E: Bool, E1: Bool>(
         this: Field<E, &'t mut T>,
         _: UsageTracker
@@ -1718,11 +3898,11 @@ where 't: 'y {
     }
 }
 
-impl<'t, 'y, T> Acquire<&'t T, &'y T> for AcquireMarker
+impl<'t, 'y, T: ?Sized> Acquire<&'t T, &'y T> for AcquireMarker
 where 't: 'y {
     type Rest = &'t T;
     #[inline(always)]
-    #[cfg(usage_tracking_enabled)]
+    #[cfg(field_usage_tracking_enabled)]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t T>,
         tracker: UsageTracker
@@ -1735,7 +3915,7 @@ where 't: 'y {
         (target, rest)
     }
     #[inline(always)]
-    #[cfg(not(usage_tracking_enabled))]
+    #[cfg(not(field_usage_tracking_enabled))]
     fn acquire<E1: Bool, E2: Bool>(
         this: Field<E1, &'t T>,
         _: UsageTracker
@@ -1760,11 +3940,27 @@ pub trait AsRefsMut {
 // === Partial ===
 // ===============
 
+#[diagnostic::on_unimplemented(
+    message = "cannot borrow `{Target}` from `{Self}`",
+    label = "the target partial borrow requires a field that the source hides or borrows immutably",
+    note = "a partial borrow can only be narrowed: every field of the target has to be borrowed by \
+            the source with at least the same access"
+)]
 pub trait Partial<'s, Target> {
     type Rest;
     fn split_impl(&'s mut self) -> (Target, Self::Rest);
 }
 
+/// Type of the rest of `T` after borrowing `Spec` with the lifetime `'a`, as returned by
+/// [`SplitHelper::split_with`].
+pub type SplitRest<'a, T, Spec> = <T as Partial<'a, Rebound<'a, Spec>>>::Rest;
+
+#[diagnostic::on_unimplemented(
+    message = "cannot convert `{Self}` into `{Target}`",
+    label = "the target partial borrow requires a field that the source hides or borrows immutably",
+    note = "a partial borrow can only be narrowed: every field of the target has to be borrowed by \
+            the source with at least the same access"
+)]
 pub trait IntoPartial<Target> {
     type Rest;
     fn into_split_impl(self) -> (Target, Self::Rest);
@@ -1778,14 +3974,49 @@ pub trait SplitHelper {
         self.split_impl()
     }
 
+    /// Like `split`, but the lifetime `'a` of both the target and the rest is given explicitly.
+    /// Lifetimes used in `Spec` are replaced with `'a`.
+    #[track_caller]
+    #[inline(always)]
+    fn split_with<'a, Spec>(&'a mut self) -> (Rebound<'a, Spec>, SplitRest<'a, Self, Spec>)
+    where
+        Spec: Rebind<'a>,
+        Self: Partial<'a, Rebound<'a, Spec>> {
+        self.split_impl()
+    }
+
+    /// Like `split`, but fields borrowed immutably by the target are hidden in the rest instead of
+    /// being shared by both halves, so the target is the only borrow of every field it requested.
+    #[track_caller]
+    #[inline(always)]
+    fn split_exclusive<'s, Target>(&'s mut self) -> (Target, ExclusiveRest<'s, Self, Target>)
+    where
+        Self: Partial<'s, Target>,
+        Self::Rest: HideShared<Target> {
+        let (target, rest) = self.split_impl();
+        (target, rest.hide_shared())
+    }
+
     #[track_caller]
     #[inline(always)]
     fn into_split<Target>(self) -> (Target, Self::Rest)
     where Self: Sized + IntoPartial<Target> {
         self.into_split_impl()
     }
+
+    /// Like `into_split`, but fields borrowed immutably by the target are hidden in the rest. See
+    /// `split_exclusive`.
+    #[track_caller]
+    #[inline(always)]
+    fn into_split_exclusive<Target>(self) -> (Target, <Self::Rest as HideShared<Target>>::Output)
+    where
+        Self: Sized + IntoPartial<Target>,
+        Self::Rest: HideShared<Target> {
+        let (target, rest) = self.into_split_impl();
+        (target, rest.hide_shared())
+    }
 }
-impl<T> SplitHelper for T {}
+impl<T: ?Sized> SplitHelper for T {}
 
 pub trait PartialHelper {
     #[track_caller]
@@ -1802,12 +4033,12 @@ pub trait PartialHelper {
         self.into_split_impl().0
     }
 }
-impl<T> PartialHelper for T {}
+impl<T: ?Sized> PartialHelper for T {}
 
 // === Default Impl ===
 
 impl<'s, T, Target> Partial<'s, Target> for T where
-    T: AsRefsMut + 's,
+    T: ?Sized + AsRefsMut + 's,
     <T as AsRefsMut>::Target<'s>: IntoPartial<Target>,
 {
     type Rest = <<T as AsRefsMut>::Target<'s> as IntoPartial<Target>>::Rest;
@@ -1818,6 +4049,21 @@ impl<'s, T, Target> Partial<'s, Target> for T where
     }
 }
 
+/// Mutable references to structs convert into partial borrows living as long as the reference,
+/// e.g. `Box::leak(graph).into_partial_borrow::<p!(<'static mut nodes> Graph)>()` for long-lived
+/// state. The partial borrow owns its usage tracker, like the ones created with `as_refs_mut`.
+impl<'s, T, Target> IntoPartial<Target> for &'s mut T where
+    T: ?Sized + AsRefsMut + 's,
+    <T as AsRefsMut>::Target<'s>: IntoPartial<Target>,
+{
+    type Rest = <<T as AsRefsMut>::Target<'s> as IntoPartial<Target>>::Rest;
+    #[track_caller]
+    #[inline(always)]
+    fn into_split_impl(self) -> (Target, Self::Rest) {
+        self.as_refs_mut().into_split_impl()
+    }
+}
+
 // =====================
 // === Helper Macros ===
 // =====================
@@ -1825,43 +4071,119 @@ impl<'s, T, Target> Partial<'s, Target> for T where
 #[doc(hidden)]
 #[macro_export]
 macro_rules! field {
-    ($s:ty, $n:tt,) => { borrow::Hidden };
-    ($s:ty, $n:tt, $($ts:tt)+) => { $($ts)+ borrow::ItemAt<borrow::$n, borrow::Fields<$s>> };
+    (@checked $s:ty, $n:tt, [@ $alias:ident] upgradeable $lt:lifetime) => {
+        $crate::Upgradeable<$lt, $crate::$alias<$s, $n>>
+    };
+    (@checked $s:ty, $n:tt, [@ $alias:ident] $($ts:tt)+) => { $($ts)+ $crate::$alias<$s, $n> };
+    (@checked $s:ty, $n:tt, [$($alias:tt)*] upgradeable $lt:lifetime) => {
+        $crate::Upgradeable<$lt, $($alias)*<$s>>
+    };
+    (@checked $s:ty, $n:tt, [$($alias:tt)*] $($ts:tt)+) => { $($ts)+ $($alias)*<$s> };
+    (@checked $s:ty, $n:tt, upgradeable $lt:lifetime) => {
+        $crate::Upgradeable<$lt, <$s as $crate::HasField<$n>>::Type>
+    };
+    (@checked $s:ty, $n:tt, $($ts:tt)+) => { $($ts)+ <$s as $crate::HasField<$n>>::Type };
+    ($s:ty, $n:tt, $fp:tt,) => { $crate::Hidden };
+    ($s:ty, $n:tt, $fp:tt, $($ts:tt)+) => {
+        $crate::field!{@checked <$s as $crate::HasFingerprint<$fp>>::Struct, $n, $($ts)+}
+    };
+}
+
+/// Expands to the given statements only if field usage tracking is enabled, so the code generated
+/// for tracking only is skipped by the compiler and IDEs in builds without it.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(field_usage_tracking_enabled)]
+macro_rules! if_field_usage_tracking {
+    ($($ts:tt)*) => { $($ts)* };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(field_usage_tracking_enabled))]
+macro_rules! if_field_usage_tracking {
+    ($($ts:tt)*) => {};
 }
 
 // =============
-// === Tests ===
+// === Specs ===
 // =============
 
-// use borrow::partial as p;
-//
-// pub struct GeometryCtx {}
-// pub struct MaterialCtx {}
-// pub struct MeshCtx {}
-// pub struct SceneCtx {}
-//
-// #[derive(borrow::Partial)]
-// #[module(crate)]
-// pub struct Ctx<'t, T: Debug> {
-//     pub version: &'t T,
-//     pub geometry: GeometryCtx,
-//     pub material: MaterialCtx,
-//     pub mesh: MeshCtx,
-//     pub scene: SceneCtx,
-// }
-//
-// pub fn test() {
-//     let mut ctx = Ctx {
-//         version: &0,
-//         geometry: GeometryCtx {},
-//         material: MaterialCtx {},
-//         mesh: MeshCtx {},
-//         scene: SceneCtx {},
-//     };
-//     test2(p!(&mut ctx));
-// }
-//
-// pub fn test2<'t>(ctx: p!(&<mut *> Ctx<'t, usize>)) {
-//     let _ = &*ctx.version;
-// }
-//
+/// Declares a selector spec used in place of the selector list in [`partial!`]. The path of this
+/// crate is passed to `borrow-macro`, so specs exported from a crate can be used in crates which
+/// do not depend on `borrow` directly or rename it.
+#[macro_export]
+macro_rules! spec {
+    ($($input:tt)*) => { $crate::spec_with_crate! { [$crate] $($input)* } };
+}
+
+// =====================
+// === Version Check ===
+// =====================
+
+/// The version of this crate, compared with the version of `borrow-macro` by
+/// [`check_macro_version`].
+#[doc(hidden)]
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Checks if the version of `borrow-macro` is [`VERSION`]. String comparison is not available in
+/// constant evaluation, so the bytes are compared.
+#[doc(hidden)]
+pub const fn is_macro_version(macro_version: &str) -> bool {
+    let (macro_version, version) = (macro_version.as_bytes(), VERSION.as_bytes());
+    if macro_version.len() != version.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < version.len() {
+        if macro_version[i] != version[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Emitted by the derive with the version of `borrow-macro`. The generated code relies on items of
+/// the same version of this crate, so a skew, e.g. when only one of the crates is patched in a
+/// workspace, is reported by this single error instead of unrelated trait errors:
+///
+/// ```compile_fail
+/// borrow::check_macro_version!("1.0.0");
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_macro_version {
+    ($version:literal) => {
+        const _: () = assert!(
+            $crate::is_macro_version($version),
+            concat!(
+                "The version of borrow-macro (", $version, ") does not match the version of \
+                borrow. Use the same version of both crates, e.g. patch both of them in the \
+                [patch] section of your workspace."
+            )
+        );
+    };
+}
+
+// ===========================
+// === Redundant Selectors ===
+// ===========================
+
+// Procedural macros can not emit warnings on stable Rust, so `p!` wraps borrows with redundant
+// selectors in these deprecated aliases, using the span of the redundant selector.
+
+#[doc(hidden)]
+#[deprecated(note = "The field selector is overridden by a following selector of the same field in \
+    `p!`. Remove the redundant selector.")]
+pub type DuplicateSelector<T> = T;
+
+#[doc(hidden)]
+#[deprecated(note = "The field selector is overridden by the following `*` selector in `p!`. \
+    Remove it, or move it after `*`.")]
+pub type OverriddenSelector<T> = T;
+
+#[doc(hidden)]
+#[deprecated(note = "The field is already selected by the preceding `*` selector with the same \
+    access in `p!`. Remove the redundant selector.")]
+pub type StarSelector<T> = T;
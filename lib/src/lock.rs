@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+// ============
+// === Lock ===
+// ============
+
+/// Lock wrapping a field marked with `#[borrow(wrap = "...")]`, e.g. `#[borrow(wrap = "Mutex")]`
+/// for a field of type `Mutex<T>`. Partial borrows of the struct see the field as the value inside
+/// of the lock. As they are created from exclusive references, they access it with
+/// [`Lock::inner_mut`] without locking at runtime, while code holding shared references to the
+/// struct still locks the field. This allows migrating a struct guarded by a single lock to
+/// partial borrows one field at a time.
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// use std::sync::Mutex;
+///
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Renderer {
+///     frames: usize,
+///     #[borrow(wrap = "Mutex")]
+///     queue: Mutex<Vec<String>>,
+/// }
+///
+/// // Legacy code locking the queue.
+/// fn enqueue(renderer: &Renderer, job: &str) {
+///     renderer.queue.lock().unwrap().push(job.to_string());
+/// }
+///
+/// fn render(renderer: p!(&<mut frames, mut queue> Renderer)) {
+///     **renderer.frames += renderer.queue.drain(..).count();
+/// }
+///
+/// fn main() {
+///     let mut renderer = Renderer::default();
+///     enqueue(&renderer, "draw");
+///     render(p!(&mut renderer));
+///     assert_eq!(renderer.frames, 1);
+/// }
+/// ```
+///
+/// Partial borrows access poisoned locks as if they were not poisoned.
+pub trait Lock {
+    type Inner: ?Sized;
+    fn inner_mut(&mut self) -> &mut Self::Inner;
+}
+
+impl<T: ?Sized> Lock for Mutex<T> {
+    type Inner = T;
+    #[inline(always)]
+    fn inner_mut(&mut self) -> &mut T {
+        self.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: ?Sized> Lock for RwLock<T> {
+    type Inner = T;
+    #[inline(always)]
+    fn inner_mut(&mut self) -> &mut T {
+        self.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: ?Sized> Lock for RefCell<T> {
+    type Inner = T;
+    #[inline(always)]
+    fn inner_mut(&mut self) -> &mut T {
+        self.get_mut()
+    }
+}
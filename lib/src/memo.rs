@@ -0,0 +1,103 @@
+use crate::DirtyFlags;
+use crate::HasFieldMask;
+
+// ============
+// === Memo ===
+// ============
+
+/// Result of a computation reading the fields borrowed by the partial borrow type `V`, cached until
+/// one of these fields is used mutably. The fields are watched through the [`DirtyFlags`] of the
+/// struct, so the struct needs a field marked with `#[borrow(dirty_flags)]`, and the memo is as
+/// exact as the flags: with usage tracking, including the `usage_tracking_lite` feature, only
+/// fields used mutably invalidate it, while without usage tracking every borrow of the struct
+/// does. Clearing the flags does not affect the memo.
+///
+/// Similarly to [`FrameCache`](crate::FrameCache), the lifetime used in `V` is irrelevant, and the
+/// convention is to use `'static`, e.g. `Memo<p!('static <mesh> Scene), f32>`. `V` is meant to
+/// borrow the fields immutably. If it borrows them mutably, using them in the computation
+/// invalidates the result right away.
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Scene {
+///     mesh: Vec<f32>,
+///     name: String,
+///     #[borrow(dirty_flags)]
+///     dirty: borrow::DirtyFlags<Scene>,
+/// }
+///
+/// fn bounds(scene: p!(&<mesh> Scene)) -> f32 {
+///     scene.mesh.iter().copied().fold(0.0, f32::max)
+/// }
+///
+/// fn add_vertex(scene: p!(&<mut mesh> Scene), vertex: f32) {
+///     scene.mesh.push(vertex);
+/// }
+///
+/// fn main() {
+///     let mut scene = Scene::default();
+///     let mut memo = borrow::Memo::<p!('static <mesh> Scene), f32>::new(&scene.dirty);
+///     add_vertex(p!(&mut scene), 2.0);
+///     assert_eq!(*memo.get_or_compute(|| bounds(p!(&mut scene))), 2.0);
+///     add_vertex(p!(&mut scene), 3.0);
+///     assert_eq!(*memo.get_or_compute(|| bounds(p!(&mut scene))), 3.0);
+/// }
+/// ```
+pub struct Memo<V: HasFieldMask, T> {
+    flags: DirtyFlags<V::Struct>,
+    /// Bits of the fields borrowed by `V`.
+    mask: u64,
+    /// The cached value and the version of the fields it was computed from.
+    entry: Option<(u64, T)>,
+}
+
+impl<V: HasFieldMask, T> std::fmt::Debug for Memo<V, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Memo").field("is_valid", &self.is_valid()).finish()
+    }
+}
+
+impl<V: HasFieldMask, T> Memo<V, T> {
+    /// Empty memo watching the fields of the struct owning `flags`.
+    pub fn new(flags: &DirtyFlags<V::Struct>) -> Self {
+        let flags = flags.share();
+        let field_mask = V::field_mask();
+        let mask = (0..64).filter(|index| field_mask.access(*index).is_some())
+            .fold(0, |mask, index| mask | (1 << index));
+        let entry = None;
+        Self { flags, mask, entry }
+    }
+
+    /// Checks whether a value is cached and none of the fields borrowed by `V` was used mutably
+    /// since it was computed.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        self.entry.as_ref().is_some_and(|(version, _)| *version == self.flags.version(self.mask))
+    }
+
+    /// The cached value, if it is still valid.
+    #[inline(always)]
+    pub fn get(&self) -> Option<&T> {
+        self.entry.as_ref().filter(|_| self.is_valid()).map(|(_, value)| value)
+    }
+
+    /// The cached value, computed with `f` first if it is not valid. The version of the fields is
+    /// read before calling `f`, so fields used mutably by `f` invalidate the result.
+    pub fn get_or_compute(&mut self, f: impl FnOnce() -> T) -> &T {
+        let version = self.flags.version(self.mask);
+        let entry = match self.entry.take() {
+            Some(entry) if entry.0 == version => entry,
+            _ => (version, f()),
+        };
+        &self.entry.insert(entry).1
+    }
+
+    /// Drops the cached value.
+    #[inline(always)]
+    pub fn invalidate(&mut self) {
+        self.entry = None;
+    }
+}
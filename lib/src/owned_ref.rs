@@ -0,0 +1,98 @@
+use crate::Hidden;
+use crate::Partial;
+use std::marker::PhantomData;
+use std::ops::DerefMut;
+
+// ==============
+// === Rebind ===
+// ==============
+
+/// Changes the lifetime of all fields of a partial borrow to `'a`. Implemented for every `XRef`
+/// struct generated by the `borrow::Partial` derive macro. The lifetime can only be shortened, so
+/// [`Rebind::rebind`] is a checked replacement for transmuting a view to a shorter lifetime, e.g.
+/// to store it in a [`FrameCache`](crate::FrameCache).
+pub trait Rebind<'a> {
+    type Output;
+    fn rebind(self) -> Self::Output;
+}
+
+pub type Rebound<'a, T> = <T as Rebind<'a>>::Output;
+
+/// Changes the lifetime of a single field of a partial borrow to `'a`.
+pub trait RebindField<'a> {
+    type Output;
+    fn rebind_field(self) -> Self::Output;
+}
+
+pub type ReboundField<'a, T> = <T as RebindField<'a>>::Output;
+
+impl<'a> RebindField<'a> for Hidden {
+    type Output = Hidden;
+    #[inline(always)]
+    fn rebind_field(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'a, 'l: 'a, T: ?Sized> RebindField<'a> for &'l T {
+    type Output = &'a T;
+    #[inline(always)]
+    fn rebind_field(self) -> Self::Output {
+        self
+    }
+}
+
+impl<'a, 'l: 'a, T: ?Sized> RebindField<'a> for &'l mut T {
+    type Output = &'a mut T;
+    #[inline(always)]
+    fn rebind_field(self) -> Self::Output {
+        self
+    }
+}
+
+// ================
+// === OwnedRef ===
+// ================
+
+/// An owned partial borrow. It keeps the guard `G` (e.g. `Box<T>`, `tokio::sync::OwnedMutexGuard`,
+/// or `parking_lot::ArcMutexGuard`) and exposes only the fields selected by the view type `V`.
+/// If the guard is `'static`, so is the `OwnedRef`, which allows moving it into detached tasks,
+/// like the ones created with `tokio::spawn`.
+///
+/// The lifetime used in `V` is irrelevant, as views are re-created with the lifetime of the
+/// `OwnedRef` borrow on every [`OwnedRef::view`] call. The convention is to use `'static`, e.g.
+/// `OwnedRef<G, p!('static <mut nodes> Graph)>`.
+pub struct OwnedRef<G, V> {
+    guard: G,
+    view: PhantomData<fn() -> V>,
+}
+
+impl<G, V> std::fmt::Debug for OwnedRef<G, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedRef").finish_non_exhaustive()
+    }
+}
+
+impl<G: DerefMut, V> OwnedRef<G, V> {
+    #[inline(always)]
+    pub fn new(guard: G) -> Self {
+        let view = PhantomData;
+        Self { guard, view }
+    }
+
+    /// Borrows the selected fields.
+    #[track_caller]
+    #[inline(always)]
+    pub fn view<'s>(&'s mut self) -> Rebound<'s, V>
+    where
+        V: Rebind<'s>,
+        G::Target: Partial<'s, Rebound<'s, V>> {
+        self.guard.split_impl().0
+    }
+
+    /// Releases the guard.
+    #[inline(always)]
+    pub fn into_inner(self) -> G {
+        self.guard
+    }
+}
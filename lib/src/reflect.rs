@@ -1,9 +1,202 @@
+//! Type-level description of the structs deriving `borrow::Partial`: their fields, and the partial
+//! borrow types built from lists of field types. It is meant for adapter crates writing generic
+//! code over such structs, which can not use `p!` without knowing the struct.
+
 // =================
 // === HasFields ===
 // =================
 
 use crate::hlist;
 
+/// Types of the struct fields in the declaration order. Dynamically sized fields, e.g. `[u8]`, are
+/// listed as `PhantomData<[u8]>`, see [`HasField`] for their exact types.
 pub trait HasFields { type Fields; }
 pub type Fields<T> = <T as HasFields>::Fields;
 pub type FieldAt<N, T> = hlist::ItemAt<N, Fields<T>>;
+
+// ====================
+// === HasFieldsExt ===
+// ====================
+
+/// Lists of the field types of the struct as borrowed by partial borrows, in the declaration
+/// order: all fields hidden, all fields borrowed as shared, and all fields borrowed as mutable,
+/// except for fields marked with `#[borrow(readonly)]`, which are borrowed as shared. Together
+/// with [`RefWithFields`], they name partial borrows of any struct deriving `borrow::Partial`,
+/// e.g. in generic code of adapter crates, which can not use `p!` without knowing the struct.
+pub trait HasFieldsExt: HasFields {
+    type FieldsAsHidden;
+    type FieldsAsRef<'t> where Self: 't;
+    type FieldsAsMut<'t> where Self: 't;
+}
+
+pub type FieldsAsHidden<T> = <T as HasFieldsExt>::FieldsAsHidden;
+pub type FieldsAsRef<'t, T> = <T as HasFieldsExt>::FieldsAsRef<'t>;
+pub type FieldsAsMut<'t, T> = <T as HasFieldsExt>::FieldsAsMut<'t>;
+
+// =======================
+// === AsRefWithFields ===
+// =======================
+
+/// The partial borrow of the struct with the given list of field types, e.g. `&'t mut T`, `&'t T`,
+/// or [`Hidden`](crate::Hidden) for each field in the declaration order. The lists can be built
+/// with the [`HList!`](crate::HList) macro, taken from [`HasFieldsExt`], and modified with
+/// [`SetItemAtResult`](hlist::SetItemAtResult):
+///
+/// ```
+/// # use borrow::partial as p;
+/// use borrow::hlist::{N1, SetItemAtResult};
+/// use borrow::{FieldsAsHidden, FieldsAsMut, RefWithFields};
+/// use std::any::TypeId;
+///
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+///     edges: Vec<(usize, usize)>,
+/// }
+///
+/// /// Borrow of all fields, as named by generic code.
+/// type Full<'t, S> = RefWithFields<S, FieldsAsMut<'t, S>>;
+///
+/// /// Borrow of the second field only, as named by generic code.
+/// type Second<'t, S, T> = RefWithFields<S, SetItemAtResult<FieldsAsHidden<S>, N1, &'t T>>;
+///
+/// fn main() {
+///     let full = TypeId::of::<Full<'static, Graph>>();
+///     assert_eq!(full, TypeId::of::<p!(<'static mut *> Graph)>());
+///     let edges = TypeId::of::<Second<'static, Graph, Vec<(usize, usize)>>>();
+///     assert_eq!(edges, TypeId::of::<p!(<'static edges> Graph)>());
+/// }
+/// ```
+pub trait AsRefWithFields<F> {
+    type Output;
+}
+
+pub type RefWithFields<T, F> = <T as AsRefWithFields<F>>::Output;
+
+// ================
+// === HasField ===
+// ================
+
+/// Type of the field with the given index in the declaration order. Unlike [`FieldAt`], it is
+/// resolved with a single impl lookup instead of traversing the fields list, which keeps partial
+/// borrow types cheap to resolve for the compiler and IDEs, and does not limit the number of fields.
+pub trait HasField<const INDEX: usize> { type Type: ?Sized; }
+
+// ====================
+// === MutInRelease ===
+// ====================
+
+/// Used instead of [`HasField`] by mutable selectors of fields marked with
+/// `#[borrow(readonly_in_release = "...")]` in builds without `debug_assertions`. It is not
+/// implemented for any type, so such selectors fail to compile.
+///
+/// ```compile_fail
+/// # fn main() {}
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// #[borrow(readonly_in_release = "stats")]
+/// struct Renderer {
+///     stats: Vec<String>,
+/// }
+///
+/// // The type used by `p!(&<mut stats> Renderer)` in release builds.
+/// fn draw(stats: &mut <Renderer as borrow::MutInRelease<0>>::Type) {}
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "field {INDEX} of `{Self}` can not be borrowed mutably in release builds",
+    label = "the field is marked with `#[borrow(readonly_in_release = \"...\")]`",
+    note = "borrow the field immutably, or mutate it only in code compiled with `debug_assertions`"
+)]
+pub trait MutInRelease<const INDEX: usize> { type Type: ?Sized; }
+
+/// The type used by mutable selectors of fields marked with `#[borrow(readonly_in_release = "...")]`
+/// in structs without `#[module(...)]`. The struct macro of such structs can not refer to the
+/// alias generated next to the struct, so it uses this one, which checks `debug_assertions` of
+/// this crate instead of the crate defining the struct.
+#[doc(hidden)]
+#[cfg(debug_assertions)]
+pub type MutInReleaseType<S, const INDEX: usize> = <S as HasField<INDEX>>::Type;
+
+#[doc(hidden)]
+#[cfg(not(debug_assertions))]
+pub type MutInReleaseType<S, const INDEX: usize> = <S as MutInRelease<INDEX>>::Type;
+
+// ===================
+// === ReadonlyMut ===
+// ===================
+
+/// Used instead of [`HasField`] by mutable selectors of fields marked with `#[borrow(readonly)]`.
+/// It is not implemented for any type, so such selectors fail to compile.
+///
+/// ```compile_fail
+/// # fn main() {}
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// struct Renderer {
+///     #[borrow(readonly)]
+///     config: String,
+/// }
+///
+/// // The type used by `p!(&<mut config> Renderer)`.
+/// fn configure(config: &mut <Renderer as borrow::ReadonlyMut<0>>::Type) {}
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "field {INDEX} of `{Self}` can not be borrowed mutably",
+    label = "the field is marked with `#[borrow(readonly)]`",
+    note = "borrow the field immutably, e.g. with `p!(&<field> Struct)`"
+)]
+pub trait ReadonlyMut<const INDEX: usize> { type Type: ?Sized; }
+
+/// The type used by mutable selectors of fields marked with `#[borrow(readonly)]` in structs without
+/// `#[module(...)]`.
+#[doc(hidden)]
+pub type ReadonlyMutType<S, const INDEX: usize> = <S as ReadonlyMut<INDEX>>::Type;
+
+// ==============
+// === HasRef ===
+// ==============
+
+/// The `Ref` struct of the struct, e.g. `GraphRef<Graph, Nodes, Edges, Track>` for
+/// `<Graph as HasRef<(Nodes, Edges), Track>>::Ref`. The struct macro of structs without
+/// `#[module(...)]` does not know the path of the `Ref` struct, so it names it this way.
+/// Implementing traits for such partial borrows works as usual, but inherent impls, e.g.
+/// `impl p!(<mut nodes> Graph) { ... }`, require `#[module(...)]`, as the compiler does not accept
+/// them for associated types.
+pub trait HasRef<Fields, Track> { type Ref; }
+
+// ======================
+// === HasFingerprint ===
+// ======================
+
+/// Fingerprint of the struct fields, i.e. their count and names in the declaration order. It is
+/// baked into the struct macro generated by the derive and checked when the macro maps selectors
+/// to fields, so a macro generated for another version of the struct fails to compile instead of
+/// borrowing wrong fields.
+///
+/// ```compile_fail
+/// # fn main() {}
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+/// }
+///
+/// // Field type computed with a fingerprint of another version of `Graph`.
+/// fn nodes(nodes: borrow::field!{Graph, 0, 42, &'static}) {}
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "the partial borrow macro of `{Self}` was generated for a different version of it",
+    label = "the fields of `{Self}` changed since its partial borrow macro was generated",
+    note = "rebuild the crate defining `{Self}`, and make sure all crates use the same version of it"
+)]
+pub trait HasFingerprint<const FINGERPRINT: u64> { type Struct: ?Sized; }
+
+// ===================
+// === Transparent ===
+// ===================
+
+/// The struct wrapped by a single-field struct marked with `#[borrow(transparent)]`. Partial
+/// borrows of the wrapper are partial borrows of this struct, so the selectors of the wrapper are
+/// the selectors of this struct.
+pub trait Transparent { type Inner: ?Sized; }
@@ -0,0 +1,66 @@
+use crate::Label;
+
+#[doc(hidden)]
+pub use inventory;
+
+// ========================
+// === RegisteredStruct ===
+// ========================
+
+/// Struct deriving `borrow::Partial`, registered by the derive when the `registry` feature is
+/// enabled. Reported by [`registered_structs`].
+#[derive(Debug)]
+pub struct RegisteredStruct {
+    /// Name of the struct, e.g. `Graph`.
+    pub name: &'static str,
+    /// Path of the module defining the struct, as returned by [`module_path!`].
+    pub module: &'static str,
+    /// Fields in the declaration order.
+    pub fields: &'static [RegisteredField],
+    /// Groups declared with `#[borrow(group(...))]`, i.e. the aliases of field sets.
+    pub groups: &'static [RegisteredGroup],
+}
+
+/// Field of a [`RegisteredStruct`].
+#[derive(Debug)]
+pub struct RegisteredField {
+    /// Field name as written in selectors, e.g. `nodes`, or `0` for tuple structs.
+    pub label: Label,
+    /// Field type as written in the struct, e.g. `Vec<Node>`.
+    pub ty: &'static str,
+    /// Whether the field is marked with `#[borrow(readonly)]`.
+    pub readonly: bool,
+}
+
+/// Group of a [`RegisteredStruct`].
+#[derive(Debug)]
+pub struct RegisteredGroup {
+    pub name: &'static str,
+    pub fields: &'static [Label],
+}
+
+inventory::collect!(RegisteredStruct);
+
+/// Iterates over all structs deriving `borrow::Partial` in the application, in unspecified order.
+/// It is meant for generic development tools, e.g. state inspectors and consoles. Wrappers marked
+/// with `#[borrow(transparent)]` are not registered, as they have no fields of their own.
+///
+/// ```
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// #[borrow(group(topology = "nodes, edges"))]
+/// pub struct Graph {
+///     pub nodes: Vec<usize>,
+///     pub edges: Vec<(usize, usize)>,
+/// }
+///
+/// fn main() {
+///     for registered in borrow::registered_structs() {
+///         let fields = registered.fields.iter().map(|field| field.label).collect::<Vec<_>>();
+///         println!("{}::{} {fields:?}", registered.module, registered.name);
+///     }
+/// }
+/// ```
+pub fn registered_structs() -> impl Iterator<Item = &'static RegisteredStruct> {
+    inventory::iter::<RegisteredStruct>.into_iter()
+}
@@ -0,0 +1,16 @@
+use crate::HasFieldMask;
+
+// =============
+// === Shape ===
+// =============
+
+/// Shape of a partial borrow of `T`, i.e. a marker type declared with [`shape!`](crate::shape)
+/// standing for a selector list. Allows writing functions generic over the borrowed fields without
+/// macros, e.g. middleware logging or forwarding views of any declared shape.
+pub trait Shape<T> {
+    /// The partial borrow type, the same as `p!('a <selectors> T)`.
+    type Ref<'a>: HasFieldMask<Struct = T> where T: 'a;
+}
+
+/// Partial borrow of `T` with the shape `S`, the same as `p!(&<selectors> T)`.
+pub type View<'a, T, S> = &'a mut <S as Shape<T>>::Ref<'a>;
@@ -0,0 +1,48 @@
+// ====================
+// === SplitAtField ===
+// ====================
+
+/// Field values which can be split at an index into two disjoint parts, e.g. a mutable reference
+/// to a vector into two mutable slices. Used by the `split_$field_at` methods generated by the
+/// `borrow::Partial` derive macro.
+pub trait SplitAtField: Sized {
+    type Part;
+    /// Splits the value at `mid`, like [`slice::split_at_mut`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the length of the value.
+    fn split_at_field(self, mid: usize) -> (Self::Part, Self::Part);
+}
+
+impl<'t, T> SplitAtField for &'t mut [T] {
+    type Part = &'t mut [T];
+    #[inline(always)]
+    fn split_at_field(self, mid: usize) -> (Self::Part, Self::Part) {
+        self.split_at_mut(mid)
+    }
+}
+
+impl<'t, T> SplitAtField for &'t mut Vec<T> {
+    type Part = &'t mut [T];
+    #[inline(always)]
+    fn split_at_field(self, mid: usize) -> (Self::Part, Self::Part) {
+        self.split_at_mut(mid)
+    }
+}
+
+impl<'t, T> SplitAtField for &'t [T] {
+    type Part = &'t [T];
+    #[inline(always)]
+    fn split_at_field(self, mid: usize) -> (Self::Part, Self::Part) {
+        self.split_at(mid)
+    }
+}
+
+impl<'t, T> SplitAtField for &'t Vec<T> {
+    type Part = &'t [T];
+    #[inline(always)]
+    fn split_at_field(self, mid: usize) -> (Self::Part, Self::Part) {
+        self.split_at(mid)
+    }
+}
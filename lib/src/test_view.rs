@@ -0,0 +1,83 @@
+// =================
+// === TestField ===
+// =================
+
+/// Value of a field of a test view generated by the `borrow::Partial` derive macro, e.g.
+/// `CtxTestView` created with `CtxRef::test_view()`. Fields provided with the `with_*` builder
+/// methods store the value directly, while the others store [`DefaultField`].
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// #[derive(borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+///     edges: Vec<(usize, usize)>,
+///     // Has no `Default` impl, so it has to be provided.
+///     window: Window,
+/// }
+///
+/// struct Window;
+///
+/// fn connect(graph: p!(&<nodes, mut edges> Graph)) {
+///     graph.edges.push((graph.nodes[0], graph.nodes[1]));
+/// }
+///
+/// // Test views are generated only in `cfg(test)`, unless the `test_view` feature is enabled.
+/// #[cfg(test)]
+/// mod tests {
+///     use super::*;
+///
+///     #[test]
+///     fn test_connect() {
+///         let mut view = GraphRef::test_view().with_nodes(vec![1, 2]).with_window(Window);
+///         connect(p!(&mut view));
+///         assert_eq!(view.edges_mut(), &[(1, 2)]);
+///     }
+/// }
+/// #
+/// # fn main() {}
+/// ```
+pub trait TestField<T> {
+    fn test_field_mut(&mut self) -> &mut T;
+}
+
+impl<T> TestField<T> for T {
+    #[inline(always)]
+    fn test_field_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+// ====================
+// === DefaultField ===
+// ====================
+
+/// Field of a test view which was not provided. Its value is created with [`Default`] the first
+/// time it is accessed.
+#[derive(Debug)]
+pub struct DefaultField<T> {
+    value: Option<T>,
+}
+
+impl<T> DefaultField<T> {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self { value: None }
+    }
+}
+
+impl<T> Default for DefaultField<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default> TestField<T> for DefaultField<T> {
+    #[inline(always)]
+    fn test_field_mut(&mut self) -> &mut T {
+        self.value.get_or_insert_with(T::default)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::Hidden;
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+// ================
+// === Snapshot ===
+// ================
+
+/// Copies and restores the values of the fields borrowed mutably by a partial borrow. Fields
+/// borrowed immutably or hidden are not copied, so a snapshot costs only as much as the borrowed
+/// footprint. Implemented for every `XRef` struct generated by the `borrow::Partial` derive macro.
+pub trait Snapshot {
+    type Snapshot;
+    fn snapshot(&self) -> Self::Snapshot;
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+/// Copies and restores the value of a single field of a partial borrow.
+pub trait SnapshotField {
+    type Snapshot;
+    fn snapshot_field(&self) -> Self::Snapshot;
+    fn restore_field(&mut self, snapshot: Self::Snapshot);
+}
+
+impl SnapshotField for Hidden {
+    type Snapshot = ();
+    #[inline(always)]
+    fn snapshot_field(&self) -> Self::Snapshot {}
+    #[inline(always)]
+    fn restore_field(&mut self, _: Self::Snapshot) {}
+}
+
+/// Immutably borrowed fields can not be modified, so there is nothing to restore.
+impl<T: ?Sized> SnapshotField for &T {
+    type Snapshot = ();
+    #[inline(always)]
+    fn snapshot_field(&self) -> Self::Snapshot {}
+    #[inline(always)]
+    fn restore_field(&mut self, _: Self::Snapshot) {}
+}
+
+impl<T: Clone> SnapshotField for &mut T {
+    type Snapshot = T;
+    #[inline(always)]
+    fn snapshot_field(&self) -> Self::Snapshot {
+        (**self).clone()
+    }
+    #[inline(always)]
+    fn restore_field(&mut self, snapshot: Self::Snapshot) {
+        **self = snapshot;
+    }
+}
+
+// =====================
+// === Transactional ===
+// =====================
+
+/// Partial borrow whose modifications can be rolled back. Creating it snapshots the fields
+/// borrowed mutably by the view, and [`Transactional::rollback`] restores them. The
+/// modifications are kept only if the transaction is committed with [`Transactional::commit`],
+/// so an early return or a panic rolls them back.
+///
+/// ```
+/// # use borrow::partial as p;
+/// # use borrow::traits::*;
+/// # use borrow::Transactional;
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Document {
+///     nodes: Vec<usize>,
+///     history: Vec<String>,
+/// }
+///
+/// fn add_nodes(doc: p!(&<mut nodes, history> Document), count: usize) -> Result<(), String> {
+///     // Only `nodes` is cloned.
+///     let view: p!(<mut nodes> Document) = doc.partial_borrow();
+///     let mut tx = Transactional::new(view);
+///     for node in 0..count {
+///         if node >= 2 {
+///             return Err("Too many nodes.".to_string());
+///         }
+///         tx.nodes.push(node);
+///     }
+///     tx.commit();
+///     Ok(())
+/// }
+///
+/// fn main() {
+///     let mut doc = Document::default();
+///     assert!(add_nodes(p!(&mut doc), 3).is_err());
+///     assert!(doc.nodes.is_empty());
+///     assert!(add_nodes(p!(&mut doc), 2).is_ok());
+///     assert_eq!(doc.nodes, [0, 1]);
+/// }
+/// ```
+#[must_use = "the modifications are rolled back unless the transaction is committed"]
+#[derive(Debug)]
+pub struct Transactional<V: Snapshot> {
+    view: V,
+    snapshot: Option<V::Snapshot>,
+}
+
+impl<V: Snapshot> Transactional<V> {
+    /// Starts a transaction, snapshotting the fields borrowed mutably by the view.
+    #[inline(always)]
+    pub fn new(view: V) -> Self {
+        let snapshot = Some(view.snapshot());
+        Self { view, snapshot }
+    }
+
+    /// Keeps the modifications.
+    #[inline(always)]
+    pub fn commit(mut self) {
+        self.snapshot = None;
+    }
+
+    /// Restores the snapshotted fields. The same happens when the transaction is dropped without
+    /// being committed.
+    #[inline(always)]
+    pub fn rollback(self) {
+        drop(self)
+    }
+}
+
+impl<V: Snapshot> Drop for Transactional<V> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            self.view.restore(snapshot);
+        }
+    }
+}
+
+impl<V: Snapshot> Deref for Transactional<V> {
+    type Target = V;
+    #[inline(always)]
+    fn deref(&self) -> &V {
+        &self.view
+    }
+}
+
+impl<V: Snapshot> DerefMut for Transactional<V> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut V {
+        &mut self.view
+    }
+}
@@ -0,0 +1,160 @@
+use crate::Acquire;
+use crate::AcquireMarker;
+use crate::Bool;
+use crate::Field;
+use crate::Hidden;
+use crate::RebindField;
+use crate::SnapshotField;
+use crate::ReborrowField;
+use crate::Usage;
+use crate::UsageTracker;
+use std::ops::Deref;
+
+// ===================
+// === Upgradeable ===
+// ===================
+
+/// A field borrowed with the `upgradeable` selector, e.g. `p!(&<upgradeable nodes> Graph)`. It
+/// dereferences to a shared reference and can be upgraded to an exclusive one with
+/// [`Upgradeable::upgrade`], similarly to upgradeable reads of `RwLock`s. The usage tracker
+/// requests the field as shared, so it does not report the borrow if the upgrade never happens.
+///
+/// The field is borrowed exclusively under the hood, so the borrow checker guarantees that no
+/// other copy of the field is alive when it is upgraded.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Upgradeable<'t, T: ?Sized> {
+    value: &'t mut T,
+}
+
+impl<'t, T: ?Sized> Upgradeable<'t, T> {
+    #[inline(always)]
+    pub fn new(value: &'t mut T) -> Self {
+        Self { value }
+    }
+
+    /// Upgrades the borrow to an exclusive one.
+    #[inline(always)]
+    pub fn upgrade(&mut self) -> &mut T {
+        self.value
+    }
+
+    /// Consumes the borrow and upgrades it to an exclusive one.
+    #[inline(always)]
+    pub fn into_mut(self) -> &'t mut T {
+        self.value
+    }
+
+}
+
+impl<T: ?Sized> Deref for Upgradeable<'_, T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'a, 'l: 'a, T: ?Sized> RebindField<'a> for Upgradeable<'l, T> {
+    type Output = Upgradeable<'a, T>;
+    #[inline(always)]
+    fn rebind_field(self) -> Self::Output {
+        self
+    }
+}
+
+// =====================
+// === ReborrowField ===
+// =====================
+
+impl<'s, T: ?Sized + 's> ReborrowField<'s> for Upgradeable<'_, T> {
+    type Output = Upgradeable<'s, T>;
+    #[inline(always)]
+    fn reborrow_field(&'s mut self) -> Self::Output {
+        Upgradeable { value: self.value }
+    }
+}
+
+// =====================
+// === SnapshotField ===
+// =====================
+
+/// Upgradeable fields can be modified, so their values are snapshotted.
+impl<T: Clone> SnapshotField for Upgradeable<'_, T> {
+    type Snapshot = T;
+    #[inline(always)]
+    fn snapshot_field(&self) -> Self::Snapshot {
+        self.value.clone()
+    }
+    #[inline(always)]
+    fn restore_field(&mut self, snapshot: Self::Snapshot) {
+        *self.value = snapshot;
+    }
+}
+
+// ===============
+// === Acquire ===
+// ===============
+
+impl<'t, 'y, T: ?Sized> Acquire<&'t mut T, Upgradeable<'y, T>> for AcquireMarker
+where 't: 'y {
+    type Rest = Hidden;
+    #[inline(always)]
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, &'t mut T>,
+        tracker: UsageTracker
+    ) -> (Field<E2, Upgradeable<'y, T>>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Ref), tracker, |value| (Upgradeable::new(value), Hidden))
+    }
+}
+
+impl<'t, 'y, T: ?Sized> Acquire<Upgradeable<'t, T>, Upgradeable<'y, T>> for AcquireMarker
+where 't: 'y {
+    type Rest = Hidden;
+    #[inline(always)]
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, Upgradeable<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, Upgradeable<'y, T>>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Ref), tracker, |value| (value, Hidden))
+    }
+}
+
+/// Passing the field to a borrow requesting it as mutable upgrades it.
+impl<'t, 'y, T: ?Sized> Acquire<Upgradeable<'t, T>, &'y mut T> for AcquireMarker
+where 't: 'y {
+    type Rest = Hidden;
+    #[inline(always)]
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, Upgradeable<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, &'y mut T>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Mut), tracker, |value| (value.into_mut(), Hidden))
+    }
+}
+
+impl<'t, 'y, T: ?Sized> Acquire<Upgradeable<'t, T>, &'y T> for AcquireMarker
+where 't: 'y {
+    type Rest = &'t T;
+    #[inline(always)]
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, Upgradeable<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, &'y T>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Ref), tracker, |value| {
+            let value: &'t T = value.into_mut();
+            (value, value)
+        })
+    }
+}
+
+impl<'t, T: ?Sized> Acquire<Upgradeable<'t, T>, Hidden> for AcquireMarker {
+    type Rest = Upgradeable<'t, T>;
+    #[inline(always)]
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, Upgradeable<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, Hidden>, Field<E1, Self::Rest>) {
+        this.split_field(None, tracker, |value| (Hidden, value))
+    }
+}
@@ -0,0 +1,89 @@
+use crate::Bool;
+use crate::Field;
+use crate::Label;
+use crate::Usage;
+use crate::UsageTracker;
+use std::panic::Location;
+
+#[cfg(field_usage_tracking_enabled)]
+use crate::FieldUsageTracker;
+
+// ==================
+// === UsageScope ===
+// ==================
+
+/// Usage tracking of a view handed out by a custom container, e.g. an arena wrapper. The fields
+/// attached to the scope are tracked like the fields of partial borrows, and their usage is
+/// reported as a single borrow created at the given location, usually the caller of the container
+/// method, once the scope is finished and all its fields are dropped. Scopes are created per
+/// acquisition, so every view is reported separately.
+///
+/// ```
+/// use borrow::Field;
+/// use borrow::True;
+/// use borrow::Usage;
+/// use borrow::UsageScope;
+/// use std::panic::Location;
+///
+/// #[derive(Default)]
+/// pub struct Arena {
+///     items: Vec<u32>,
+///     names: Vec<String>,
+/// }
+///
+/// pub struct ArenaView<'t> {
+///     pub items: Field<True, &'t mut Vec<u32>>,
+///     pub names: Field<True, &'t Vec<String>>,
+/// }
+///
+/// impl Arena {
+///     #[track_caller]
+///     pub fn view(&mut self) -> ArenaView<'_> {
+///         let scope = UsageScope::new(Location::caller());
+///         let items = scope.field("items", Usage::Mut, &mut self.items);
+///         let names = scope.field("names", Usage::Ref, &self.names);
+///         scope.finish();
+///         ArenaView { items, names }
+///     }
+/// }
+///
+/// fn main() {
+///     let mut arena = Arena::default();
+///     // Warning: Borrowed but not used: names.
+///     let mut view = arena.view();
+///     view.items.push(1);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UsageScope {
+    tracker: UsageTracker,
+}
+
+impl UsageScope {
+    /// Creates a scope whose usage is reported at `location`, e.g. [`Location::caller`] in a
+    /// method marked with `#[track_caller]`.
+    #[inline(always)]
+    pub fn new(location: &'static Location<'static>) -> Self {
+        Self { tracker: UsageTracker::new_at(location) }
+    }
+
+    /// Attaches a field labeled `label` to the scope, requesting the given access. Usage of the
+    /// returned field is tracked, but it is reported only if `E` is [`True`](crate::True).
+    #[inline(always)]
+    #[cfg(field_usage_tracking_enabled)]
+    pub fn field<E: Bool, V>(&self, label: Label, access: Usage, value: V) -> Field<E, V> {
+        let tracker = FieldUsageTracker::new_scoped(label, access, self.tracker.clone());
+        Field::cons(value, tracker)
+    }
+
+    #[inline(always)]
+    #[cfg(not(field_usage_tracking_enabled))]
+    pub fn field<E: Bool, V>(&self, _label: Label, _access: Usage, value: V) -> Field<E, V> {
+        Field::cons(value)
+    }
+
+    /// Finishes the scope. No fields can be attached afterwards, and the usage is reported when
+    /// all the fields are dropped.
+    #[inline(always)]
+    pub fn finish(self) {}
+}
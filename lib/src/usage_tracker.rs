@@ -1,64 +1,64 @@
+use borrow_tracking::Access;
+use borrow_tracking::BorrowReport;
+use borrow_tracking::FieldReport;
 use crate::default;
+use crate::DirtyFlag;
+use crate::FieldOrder;
 use crate::Label;
+use crate::MutHook;
 use crate::OptUsage;
 use crate::Usage;
 use crate::Bool;
-use std::cell::Cell;
 use std::marker::PhantomData;
+use std::panic::Location;
 use std::sync::Arc;
-use std::rc::Rc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
 
-// ===============
-// === Logging ===
-// ===============
-
-macro_rules! warning {
-    ($($ts:tt)*) => {
-        warning(&format!($($ts)*));
-    };
-}
-
-fn warning(msg: &str) {
-    if inc_and_check_warning_count() {
-        warning_no_count_check(msg)
-    }
-}
+// ===================
+// === UsageResult ===
+// ===================
 
-fn warning_no_count_check(msg: &str) {
-    #[cfg(feature = "wasm")]
-    web_sys::console::warn_1(&msg.into());
-    #[cfg(not(feature = "wasm"))]
-    eprintln!("{msg}");
+#[derive(Clone, Copy, Debug)]
+struct UsageResult {
+    requested: OptUsage,
+    needed: OptUsage,
 }
 
-/// We don't want to flood users with warnings, especially in interactive apps, where warnings can
-/// be emitted per frame.
-const MAX_WARNING_COUNT: usize = 100;
+// ======================
+// === AtomicOptUsage ===
+// ======================
 
-thread_local! {
-    static WARNING_COUNT: Cell<usize> = const { Cell::new(0) };
+/// Thread-safe [`OptUsage`] cell. The encoding preserves the order of [`OptUsage`] values, so
+/// the maximum usage can be computed atomically.
+#[derive(Debug, Default)]
+struct AtomicOptUsage {
+    value: AtomicU8,
 }
 
-fn inc_and_check_warning_count() -> bool {
-    WARNING_COUNT.with(|count| {
-        let new_count = count.get() + 1;
-        count.set(new_count);
-        let ok = new_count < MAX_WARNING_COUNT;
-        if !ok && new_count == MAX_WARNING_COUNT {
-            warning_no_count_check("Too many warnings, suppressing further ones.");
+impl AtomicOptUsage {
+    fn encode(usage: OptUsage) -> u8 {
+        match usage {
+            None => 0,
+            Some(Usage::Ref) => 1,
+            Some(Usage::Mut) => 2,
         }
-        ok
-    })
-}
+    }
 
-// ===================
-// === UsageResult ===
-// ===================
+    fn get(&self) -> OptUsage {
+        match self.value.load(Ordering::Relaxed) {
+            0 => None,
+            1 => Some(Usage::Ref),
+            _ => Some(Usage::Mut),
+        }
+    }
 
-#[derive(Clone, Copy, Debug)]
-struct UsageResult {
-    requested: OptUsage,
-    needed: OptUsage,
+    fn set_max(&self, usage: OptUsage) {
+        self.value.fetch_max(Self::encode(usage), Ordering::Relaxed);
+    }
 }
 
 // ====================
@@ -69,18 +69,61 @@ struct UsageResult {
 #[cfg(usage_tracking_enabled)]
 #[derive(Clone, Debug)]
 pub struct UsageTracker {
-    data: Rc<std::cell::RefCell<UsageTrackerData>>,
+    data: Arc<Mutex<UsageTrackerData>>,
 }
 
 #[cfg(usage_tracking_enabled)]
 impl UsageTracker {
     #[track_caller]
     pub fn new() -> Self {
-        Self { data: Rc::new(std::cell::RefCell::new(UsageTrackerData::new())) }
+        Self { data: Arc::new(Mutex::new(UsageTrackerData::new(None))) }
+    }
+
+    /// Tracker of a root borrow created by `as_refs_mut`. Root borrows only hand their fields
+    /// over to the target borrows, so their timing is not recorded.
+    #[track_caller]
+    pub fn new_root() -> Self {
+        Self { data: Arc::new(Mutex::new(UsageTrackerData::new(None).untimed())) }
+    }
+
+    /// Tracker of a borrow of the generic struct `S`. Its concrete type is included in warnings,
+    /// so borrows of different instantiations can be told apart.
+    #[track_caller]
+    pub fn new_for<S: ?Sized>() -> Self {
+        let struct_name = std::any::type_name::<S>();
+        Self { data: Arc::new(Mutex::new(UsageTrackerData::new(Some(struct_name)))) }
+    }
+
+    /// Tracker of a root borrow of `S`, which is not timed, like [`Self::new_root`], and includes
+    /// the type name of `S`, like [`Self::new_for`]. Used by views created from trait objects.
+    #[track_caller]
+    pub fn new_root_for<S: ?Sized>() -> Self {
+        let struct_name = std::any::type_name::<S>();
+        Self { data: Arc::new(Mutex::new(UsageTrackerData::new(Some(struct_name)).untimed())) }
+    }
+
+    /// Tracker reporting the usage at the given location instead of the caller. Used by
+    /// [`UsageScope`](crate::UsageScope).
+    pub fn new_at(location: &'static Location<'static>) -> Self {
+        Self { data: Arc::new(Mutex::new(UsageTrackerData::new_at(location, None))) }
+    }
+
+    fn with_data(&self, f: impl FnOnce(&mut UsageTrackerData)) {
+        f(&mut self.data.lock().unwrap_or_else(PoisonError::into_inner));
     }
 
     fn set_usage(&self, label: Label, usage: UsageResult) {
-        self.data.borrow_mut().map.push((label, usage));
+        self.with_data(|data| data.map.push((label, usage)));
+    }
+
+    fn register_field(&self, label: Label) {
+        self.with_data(|data| data.fields.push(label));
+    }
+
+    /// Reports fields borrowed but not used with a panic instead of a warning. Used by
+    /// `#[borrow::deny_over_borrow]`.
+    pub fn deny_over_borrow(&self) {
+        self.with_data(|data| data.deny_over_borrow = true);
     }
 }
 
@@ -95,97 +138,109 @@ impl Default for UsageTracker {
 // === UsageTrackerData ===
 // ========================
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct UsageTrackerData {
-    loc: String,
+    location: &'static Location<'static>,
+    /// Type name of the borrowed struct, set for generic structs only.
+    struct_name: Option<&'static str>,
     map: Vec<(Label, UsageResult)>,
+    /// Labels of tracked fields in the declaration order.
+    fields: Vec<Label>,
+    /// Set for borrows passed to functions marked with `#[borrow::deny_over_borrow]`.
+    deny_over_borrow: bool,
+    /// Callsite and creation time of the borrow, recorded with the `borrow_timing` feature.
+    #[cfg(feature = "borrow_timing")]
+    created: Option<(&'static std::panic::Location<'static>, std::time::Instant)>,
 }
 
 impl UsageTrackerData {
     #[track_caller]
-    fn new() -> Self {
-        let call_loc = std::panic::Location::caller();
-        let loc = format!("{}:{}", call_loc.file(), call_loc.line());
+    fn new(struct_name: Option<&'static str>) -> Self {
+        Self::new_at(Location::caller(), struct_name)
+    }
+
+    fn new_at(location: &'static Location<'static>, struct_name: Option<&'static str>) -> Self {
         let map = default();
-        Self { loc, map }
+        let fields = default();
+        let deny_over_borrow = false;
+        #[cfg(feature = "borrow_timing")]
+        let created = Some((location, std::time::Instant::now()));
+        Self {
+            location,
+            struct_name,
+            map,
+            fields,
+            deny_over_borrow,
+            #[cfg(feature = "borrow_timing")]
+            created,
+        }
     }
-}
 
-#[cfg(not(feature = "wasm"))]
-macro_rules! warning_body {
-    ($s:ident, $($ts:tt)*) => {
-        $s.push_str("\n    ");
-        $s.push_str(&format!($($ts)*));
-    };
-}
+    #[cfg(feature = "borrow_timing")]
+    fn untimed(mut self) -> Self {
+        self.created = None;
+        self
+    }
 
-#[cfg(feature = "wasm")]
-macro_rules! warning_body {
-    ($s:ident, $($ts:tt)*) => {
-        $s.push_str("\n");
-        $s.push_str(&format!($($ts)*));
-    };
+    #[cfg(not(feature = "borrow_timing"))]
+    fn untimed(self) -> Self {
+        self
+    }
+
+    fn declaration_index(&self, label: Label) -> usize {
+        self.fields.iter().position(|field| *field == label).unwrap_or(usize::MAX)
+    }
 }
 
 impl Drop for UsageTrackerData {
     fn drop(&mut self) {
-        let mut not_used = vec![];
-        let mut used_as_ref = vec![];
-        for (label, usage) in &self.map {
-            if usage.requested > usage.needed {
-                if usage.needed.is_none() {
-                    not_used.push(*label)
-                } else {
-                    used_as_ref.push(*label)
-                }
-            }
+        #[cfg(feature = "borrow_timing")]
+        if let Some((location, created)) = self.created.filter(|_| !self.fields.is_empty()) {
+            crate::borrow_timing::record(location, self.fields.len(), created.elapsed());
         }
 
-        let mut msg = String::new();
-        if !not_used.is_empty() {
-            not_used.sort();
-            warning_body!(msg, "Borrowed but not used: {}.", not_used.join(", "));
+        let mut fields = self.map.iter().map(|&(label, usage)| FieldReport {
+            label,
+            requested: usage.requested.map(access),
+            used: usage.needed.map(access),
+        }).collect::<Vec<_>>();
+        match crate::field_order() {
+            FieldOrder::Alphabetical => fields.sort_by_key(|field| field.label),
+            FieldOrder::Declaration => fields.sort_by_key(|field| self.declaration_index(field.label)),
         }
-        if !used_as_ref.is_empty() {
-            used_as_ref.sort();
-            warning_body!(msg, "Borrowed as mut but used as ref: {}.", used_as_ref.join(", "));
+        let report = BorrowReport { location: self.location, struct_name: self.struct_name, fields: &fields };
+        // Panicking while unwinding would abort, so the report is passed to the tracker instead.
+        if self.deny_over_borrow && report.is_over_borrow() && !std::thread::panicking() {
+            deny_over_borrow_panic(&report.header(), &report.message());
         }
+        borrow_tracking::tracker().borrow_dropped(&report);
+    }
+}
 
-        if !msg.is_empty() {
-            let mut required = vec![];
-            for (label, usage) in &self.map {
-                if let Some(usage2) = usage.needed {
-                    required.push((label, usage2));
-                }
-            }
-            // If required is empty, we probably are in a conditional code, where the borrow was not
-            // used. Otherwise, Clippy will complain about unused variable, so we don't need to
-            // report it.
-            if !required.is_empty() {
-                required.sort_by(|a, b| a.0.cmp(b.0));
-                let out = required.into_iter().map(|(label, usage)| {
-                    match usage {
-                        Usage::Ref => label.to_string(),
-                        Usage::Mut => format!("mut {label}"),
-                    }
-                }).collect::<Vec<_>>();
-                warning_body!(msg, "To fix the issue, use: &<{}>.", out.join(", "));
-                warning!("Warning [{}]:{}", self.loc, msg);
-            }
-        }
+fn access(usage: Usage) -> Access {
+    match usage {
+        Usage::Ref => Access::Ref,
+        Usage::Mut => Access::Mut,
     }
 }
 
+#[allow(clippy::panic)]
+fn deny_over_borrow_panic(header: &str, msg: &str) {
+    panic!("Over-borrow in a function marked with #[borrow::deny_over_borrow] {header}:{msg}");
+}
+
 // === FieldUsageTracker ===
 
 #[derive(Debug)]
 pub(crate) struct FieldUsageTracker<Enabled: Bool> {
     label: Label,
     requested_usage: OptUsage,
-    needed_usage: Arc<Cell<OptUsage>>,
-    parent_needed_usage: Option<Arc<Cell<OptUsage>>>,
-    disabled: Cell<bool>,
+    needed_usage: Arc<AtomicOptUsage>,
+    parent_needed_usage: Option<Arc<AtomicOptUsage>>,
+    disabled: AtomicBool,
     tracker: Option<UsageTracker>,
+    dirty_flag: DirtyFlag,
+    mut_hook: Option<MutHook>,
     enabled_marker: PhantomData<Enabled>,
 }
 
@@ -193,7 +248,7 @@ impl<Enabled: Bool> Drop for FieldUsageTracker<Enabled> {
     fn drop(&mut self) {
         let needed = self.needed_usage.get();
         self.register_parent_needed_usage(needed);
-        let enabled = !self.disabled.get() && Enabled::bool();
+        let enabled = !self.disabled.load(Ordering::Relaxed) && Enabled::bool();
         if enabled {
             let requested = self.requested_usage;
             let usage = UsageResult { requested, needed };
@@ -211,31 +266,53 @@ impl<Enabled: Bool> FieldUsageTracker<Enabled> {
         let needed_usage = default();
         let parent_needed_usage = None;
         let disabled = default();
+        tracker.register_field(label);
         let tracker = Some(tracker);
+        let dirty_flag = default();
+        let mut_hook = None;
         let enabled_marker = PhantomData;
-        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, enabled_marker }
+        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, dirty_flag, mut_hook, enabled_marker }
+    }
+
+    /// Field attached to a [`UsageScope`](crate::UsageScope). Unlike root fields, it is reported.
+    pub(crate) fn new_scoped(label: Label, requested_usage: Usage, tracker: UsageTracker) -> Self {
+        Self::new(label, Some(requested_usage), tracker)
     }
 
+    /// Shared children can outlive this field, e.g. when a shared field is handed to several
+    /// consumers returned to the caller, so their usage would be registered after this field
+    /// reported it. Instead, their usage is attributed to this field at creation time, like in the
+    /// lite tracker, which aggregates the usage of all sibling shared children.
     pub(crate) fn new_child<E: Bool>(&self, requested_usage: Usage, tracker: UsageTracker) -> FieldUsageTracker<E> {
+        if requested_usage == Usage::Ref {
+            self.register_usage(Some(Usage::Ref));
+        }
         let label = self.label;
         let needed_usage = default();
         let parent_needed_usage = Some(self.needed_usage.clone());
         let disabled = default();
         let requested_usage = Some(requested_usage);
         let enabled_marker = PhantomData;
+        tracker.register_field(label);
         let tracker = Some(tracker);
-        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, enabled_marker }
+        let dirty_flag = self.dirty_flag.clone();
+        let mut_hook = self.mut_hook;
+        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, dirty_flag, mut_hook, enabled_marker }
     }
 
+    /// Usage of the disabled child, and of its own children, is attributed to this field directly,
+    /// so it is not lost if the disabled child is dropped before its children.
     pub(crate) fn new_child_disabled<E: Bool>(&self) -> FieldUsageTracker<E> {
         let label = self.label;
         let requested_usage = Some(Usage::Mut);
-        let needed_usage = default();
-        let parent_needed_usage = Some(self.needed_usage.clone());
-        let disabled = Cell::new(true);
+        let needed_usage = self.needed_usage.clone();
+        let parent_needed_usage = self.parent_needed_usage.clone();
+        let disabled = AtomicBool::new(true);
         let enabled_marker = PhantomData;
         let tracker = None;
-        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, enabled_marker }
+        let dirty_flag = self.dirty_flag.clone();
+        let mut_hook = self.mut_hook;
+        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, dirty_flag, mut_hook, enabled_marker }
     }
 
     pub(crate) fn clone_disabled<E: Bool>(&self) -> FieldUsageTracker<E> {
@@ -243,23 +320,43 @@ impl<Enabled: Bool> FieldUsageTracker<Enabled> {
         let requested_usage = self.requested_usage;
         let needed_usage = self.needed_usage.clone();
         let parent_needed_usage = self.parent_needed_usage.clone();
-        let disabled = Cell::new(true);
+        let disabled = AtomicBool::new(true);
         let enabled_marker = PhantomData;
         let tracker = None;
-        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, enabled_marker }
+        let dirty_flag = self.dirty_flag.clone();
+        let mut_hook = self.mut_hook;
+        FieldUsageTracker { label, requested_usage, needed_usage, parent_needed_usage, disabled, tracker, dirty_flag, mut_hook, enabled_marker }
     }
 
     pub(crate) fn disable(&self) {
-        self.disabled.set(true);
+        self.disabled.store(true, Ordering::Relaxed);
     }
 
     pub(crate) fn register_usage(&self, usage: OptUsage) {
-        self.needed_usage.set(self.needed_usage.get().max(usage));
+        self.needed_usage.set_max(usage);
+    }
+
+    pub(crate) fn set_dirty_flag(&mut self, flag: DirtyFlag) {
+        self.dirty_flag = flag;
+    }
+
+    pub(crate) fn set_mut_hook(&mut self, hook: MutHook) {
+        self.mut_hook = Some(hook);
+    }
+
+    /// Sets the dirty flag and calls the hook given in `#[borrow(on_mut = "...")]` with the
+    /// location of the mutable access.
+    #[track_caller]
+    pub(crate) fn mark_dirty(&self) {
+        self.dirty_flag.set();
+        if let Some(hook) = self.mut_hook {
+            hook(self.label, Location::caller());
+        }
     }
 
     pub(crate) fn register_parent_needed_usage(&self, usage: OptUsage) {
         if let Some(parent) = self.parent_needed_usage.as_ref() {
-            parent.set(parent.get().max(usage));
+            parent.set_max(usage);
         }
     }
 }
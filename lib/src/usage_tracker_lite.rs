@@ -0,0 +1,423 @@
+#![cfg(usage_tracking_lite_enabled)]
+
+use crate::DirtyFlag;
+use crate::Label;
+use crate::MutHook;
+use crate::OptUsage;
+use crate::Usage;
+use crate::Bool;
+use std::marker::PhantomData;
+use std::panic::Location;
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+// ================
+// === Registry ===
+// ================
+
+/// Maximum number of (callsite, field) pairs the lite tracker can observe, set with the
+/// `BORROW_TRACKER_CAPACITY` environment variable at build time. When the slots a pair can be
+/// placed in are all taken, the oldest pair among them is dropped, so the tracker never allocates.
+const CAPACITY: usize = parse_capacity(env!("BORROW_TRACKER_CAPACITY"));
+
+/// Maximum number of slots inspected when looking up a (callsite, field) pair.
+const MAX_PROBES: usize = 32;
+
+const REF_BIT: u8 = 0b01;
+const MUT_BIT: u8 = 0b10;
+
+static SLOTS: [Slot; CAPACITY] = [const { Slot::new() }; CAPACITY];
+
+/// Counter ordering the pairs by the time they were placed in the registry.
+static NEXT_AGE: AtomicUsize = AtomicUsize::new(0);
+
+/// The capacity is validated by the build script.
+const fn parse_capacity(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    let mut capacity = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        capacity = capacity * 10 + (bytes[index] - b'0') as usize;
+        index += 1;
+    }
+    capacity
+}
+
+#[inline(always)]
+fn usage_bit(usage: Usage) -> u8 {
+    match usage {
+        Usage::Ref => REF_BIT,
+        Usage::Mut => MUT_BIT,
+    }
+}
+
+#[inline(always)]
+fn usage_from_bits(bits: u8) -> OptUsage {
+    if bits & MUT_BIT != 0 {
+        Some(Usage::Mut)
+    } else if bits & REF_BIT != 0 {
+        Some(Usage::Ref)
+    } else {
+        None
+    }
+}
+
+fn hash(location: &Location<'static>, label: Label) -> usize {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |byte: u8| {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    };
+    location.line().to_le_bytes().into_iter().for_each(&mut feed);
+    location.column().to_le_bytes().into_iter().for_each(&mut feed);
+    label.bytes().for_each(&mut feed);
+    hash as usize
+}
+
+/// Finds or allocates the slot of the given (callsite, field) pair. If all the probed slots are
+/// taken, the oldest pair among them is dropped and its slot is reused.
+fn slot(location: &'static Location<'static>, label: Label) -> Option<SlotRef> {
+    let start = hash(location, label);
+    let mut oldest: Option<(&'static Slot, usize, usize)> = None;
+    for probe in 0..MAX_PROBES {
+        let slot = &SLOTS[(start + probe) % CAPACITY];
+        loop {
+            let seq = slot.seq.load(Ordering::Acquire);
+            if seq == EMPTY {
+                if slot.claim(seq) {
+                    return Some(slot.init(seq + 1, location, label));
+                }
+            } else if seq % 2 == 1 {
+                std::hint::spin_loop();
+            } else if let Some((seq, slot_location, slot_label)) = slot.snapshot() {
+                if slot_location == location && slot_label == label {
+                    return Some(SlotRef { slot, seq });
+                }
+                let age = slot.age.load(Ordering::Relaxed);
+                if oldest.is_none_or(|(_, _, oldest_age)| age < oldest_age) {
+                    oldest = Some((slot, seq, age));
+                }
+                break;
+            }
+        }
+    }
+    let (slot, seq, _) = oldest?;
+    slot.claim(seq).then(|| slot.init(seq + 1, location, label))
+}
+
+// ============
+// === Slot ===
+// ============
+
+/// Sequence number of slots which were never used.
+const EMPTY: usize = 0;
+
+/// A (callsite, field) pair and its usage. The pair is guarded by a sequence lock, as the slot can
+/// be reused for another pair when the registry is full. The sequence number is odd while the
+/// slot is being written and it changes every time the slot is reused.
+#[derive(Debug)]
+struct Slot {
+    seq: AtomicUsize,
+    age: AtomicUsize,
+    location: AtomicPtr<Location<'static>>,
+    label_ptr: AtomicPtr<u8>,
+    label_len: AtomicUsize,
+    requested: AtomicU8,
+    used: AtomicU8,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(EMPTY),
+            age: AtomicUsize::new(0),
+            location: AtomicPtr::new(std::ptr::null_mut()),
+            label_ptr: AtomicPtr::new(std::ptr::null_mut()),
+            label_len: AtomicUsize::new(0),
+            requested: AtomicU8::new(0),
+            used: AtomicU8::new(0),
+        }
+    }
+
+    /// Marks the slot as being written if its sequence number is still `seq`.
+    fn claim(&self, seq: usize) -> bool {
+        self.seq.compare_exchange(seq, seq + 1, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+    }
+
+    /// Must be called only after a successful [`Self::claim`], with `seq` being the odd sequence
+    /// number set by it.
+    fn init(
+        &'static self,
+        seq: usize,
+        location: &'static Location<'static>,
+        label: Label
+    ) -> SlotRef {
+        std::sync::atomic::fence(Ordering::Release);
+        let location_ptr = location as *const Location<'static> as *mut Location<'static>;
+        self.location.store(location_ptr, Ordering::Relaxed);
+        self.label_ptr.store(label.as_ptr() as *mut u8, Ordering::Relaxed);
+        self.label_len.store(label.len(), Ordering::Relaxed);
+        self.requested.store(0, Ordering::Relaxed);
+        self.used.store(0, Ordering::Relaxed);
+        self.age.store(NEXT_AGE.fetch_add(1, Ordering::Relaxed), Ordering::Relaxed);
+        self.seq.store(seq + 1, Ordering::Release);
+        SlotRef { slot: self, seq: seq + 1 }
+    }
+
+    /// The sequence number, location, and label of the pair in the slot, or [`None`] if the slot
+    /// is empty or it is being written.
+    fn snapshot(&self) -> Option<(usize, &'static Location<'static>, Label)> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq == EMPTY || seq % 2 == 1 {
+            return None;
+        }
+        let location = self.location.load(Ordering::Relaxed);
+        let label_ptr = self.label_ptr.load(Ordering::Relaxed);
+        let label_len = self.label_len.load(Ordering::Relaxed);
+        std::sync::atomic::fence(Ordering::Acquire);
+        if self.seq.load(Ordering::Relaxed) != seq {
+            return None;
+        }
+        // SAFETY: The pointers and length were created from a `&'static Location` and a
+        // `&'static str` while the slot was being written with the sequence number `seq`, and the
+        // unchanged sequence number proves that they were not modified since then.
+        let location = unsafe { &*location };
+        let label = unsafe {
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(label_ptr, label_len))
+        };
+        Some((seq, location, label))
+    }
+}
+
+// ===============
+// === SlotRef ===
+// ===============
+
+/// The slot of a (callsite, field) pair. Usage is not recorded after the slot is reused for
+/// another pair. Usage recorded concurrently with reusing the slot may be attributed to the new
+/// pair, which is acceptable for diagnostics.
+#[derive(Clone, Copy, Debug)]
+struct SlotRef {
+    slot: &'static Slot,
+    seq: usize,
+}
+
+impl SlotRef {
+    #[inline(always)]
+    fn is_current(&self) -> bool {
+        self.slot.seq.load(Ordering::Relaxed) == self.seq
+    }
+
+    #[inline(always)]
+    fn request(&self, usage: Usage) {
+        let bit = usage_bit(usage);
+        if self.is_current() && self.slot.requested.load(Ordering::Relaxed) & bit == 0 {
+            self.slot.requested.fetch_or(bit, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    fn mark_used(&self, usage: OptUsage) {
+        if let Some(usage) = usage {
+            let bit = usage_bit(usage);
+            if self.is_current() && self.slot.used.load(Ordering::Relaxed) & bit == 0 {
+                self.slot.used.fetch_or(bit, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn mark_all_requested_as_used(&self) {
+        if self.is_current() {
+            let requested = self.slot.requested.load(Ordering::Relaxed);
+            self.slot.used.fetch_or(requested, Ordering::Relaxed);
+        }
+    }
+}
+
+// ==================
+// === OverBorrow ===
+// ==================
+
+/// Calls the provided function for every (callsite, field) pair whose field was requested with a
+/// wider access than it was ever used with. See [`crate::for_each_over_borrow`].
+pub(crate) fn for_each_over_borrow(f: &mut dyn FnMut(crate::OverBorrow)) {
+    for slot in &SLOTS {
+        let Some((seq, location, field)) = slot.snapshot() else { continue };
+        let requested = usage_from_bits(slot.requested.load(Ordering::Relaxed));
+        let used = usage_from_bits(slot.used.load(Ordering::Relaxed));
+        if slot.seq.load(Ordering::Acquire) != seq {
+            continue;
+        }
+        if let Some(requested) = requested {
+            if Some(requested) > used {
+                f(crate::OverBorrow { location, field, requested, used });
+            }
+        }
+    }
+}
+
+// ====================
+// === UsageTracker ===
+// ====================
+
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub struct UsageTracker {
+    location: &'static Location<'static>,
+}
+
+impl UsageTracker {
+    #[track_caller]
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self { location: Location::caller() }
+    }
+
+    /// Root borrows are not distinguished by the lite tracker.
+    #[track_caller]
+    #[inline(always)]
+    pub fn new_root() -> Self {
+        Self::new()
+    }
+
+    /// The struct type is not reported by the lite tracker.
+    #[track_caller]
+    #[inline(always)]
+    pub fn new_for<S: ?Sized>() -> Self {
+        Self::new()
+    }
+
+    /// The struct type is not reported by the lite tracker.
+    #[track_caller]
+    #[inline(always)]
+    pub fn new_root_for<S: ?Sized>() -> Self {
+        Self::new()
+    }
+
+    #[inline(always)]
+    pub fn new_at(location: &'static Location<'static>) -> Self {
+        Self { location }
+    }
+
+    /// Over-borrows are only recorded by the lite tracker, see [`crate::for_each_over_borrow`].
+    #[inline(always)]
+    pub fn deny_over_borrow(&self) {}
+}
+
+impl Default for UsageTracker {
+    #[track_caller]
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =========================
+// === FieldUsageTracker ===
+// =========================
+
+/// The lite counterpart of the full field usage tracker. Instead of computing usage per borrow,
+/// it accumulates the "ever requested" and "ever used" bits of every (callsite, field) pair in a
+/// static registry. Usage of child borrows is attributed to their parents at creation time, as a
+/// parent forwarding a field to a child needs at least the access the child requests.
+#[derive(Debug)]
+pub(crate) struct FieldUsageTracker<Enabled: Bool> {
+    label: Label,
+    slot: Option<SlotRef>,
+    dirty_flag: DirtyFlag,
+    mut_hook: Option<MutHook>,
+    enabled_marker: PhantomData<Enabled>,
+}
+
+impl<Enabled: Bool> FieldUsageTracker<Enabled> {
+    /// Root fields are never reported, just like in the full tracker, where they are disabled
+    /// right after creation.
+    #[inline(always)]
+    pub(crate) fn new(label: Label, _requested_usage: OptUsage, _tracker: UsageTracker) -> Self {
+        Self::cons(label, None, DirtyFlag::default(), None)
+    }
+
+    /// Field attached to a [`UsageScope`](crate::UsageScope). Unlike root fields, it is recorded
+    /// in the registry, like the fields of child borrows.
+    #[inline(always)]
+    pub(crate) fn new_scoped(label: Label, requested_usage: Usage, tracker: UsageTracker) -> Self {
+        let slot = Enabled::bool().then(|| slot(tracker.location, label)).flatten();
+        if let Some(slot) = slot {
+            slot.request(requested_usage);
+        }
+        Self::cons(label, slot, DirtyFlag::default(), None)
+    }
+
+    #[inline(always)]
+    fn cons<E: Bool>(
+        label: Label,
+        slot: Option<SlotRef>,
+        dirty_flag: DirtyFlag,
+        mut_hook: Option<MutHook>,
+    ) -> FieldUsageTracker<E> {
+        let enabled_marker = PhantomData;
+        FieldUsageTracker { label, slot, dirty_flag, mut_hook, enabled_marker }
+    }
+
+    #[inline(always)]
+    pub(crate) fn new_child<E: Bool>(
+        &self,
+        requested_usage: Usage,
+        tracker: UsageTracker
+    ) -> FieldUsageTracker<E> {
+        self.register_usage(Some(requested_usage));
+        let slot = E::bool().then(|| slot(tracker.location, self.label)).flatten();
+        if let Some(slot) = slot {
+            slot.request(requested_usage);
+        }
+        Self::cons(self.label, slot, self.dirty_flag.clone(), self.mut_hook)
+    }
+
+    /// Usage of the disabled child is attributed to this field.
+    #[inline(always)]
+    pub(crate) fn new_child_disabled<E: Bool>(&self) -> FieldUsageTracker<E> {
+        Self::cons(self.label, self.slot, self.dirty_flag.clone(), self.mut_hook)
+    }
+
+    #[inline(always)]
+    pub(crate) fn clone_disabled<E: Bool>(&self) -> FieldUsageTracker<E> {
+        Self::cons(self.label, self.slot, self.dirty_flag.clone(), self.mut_hook)
+    }
+
+    /// As the registry accumulates usage of all borrows created at the same callsite, disabling
+    /// a tracker marks all the requested usages of its callsite as used.
+    #[inline(always)]
+    pub(crate) fn disable(&self) {
+        if let Some(slot) = self.slot {
+            slot.mark_all_requested_as_used();
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn register_usage(&self, usage: OptUsage) {
+        if let Some(slot) = self.slot {
+            slot.mark_used(usage);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_dirty_flag(&mut self, flag: DirtyFlag) {
+        self.dirty_flag = flag;
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_mut_hook(&mut self, hook: MutHook) {
+        self.mut_hook = Some(hook);
+    }
+
+    #[inline(always)]
+    #[track_caller]
+    pub(crate) fn mark_dirty(&self) {
+        self.dirty_flag.set();
+        if let Some(hook) = self.mut_hook {
+            hook(self.label, Location::caller());
+        }
+    }
+}
@@ -1,6 +1,7 @@
-#![cfg(not(usage_tracking_enabled))]
+#![cfg(not(field_usage_tracking_enabled))]
 
 use borrow::Label;
+use std::panic::Location;
 
 #[derive(Copy, Debug)]
 #[repr(transparent)]
@@ -11,6 +12,29 @@ impl UsageTracker {
     pub fn new() -> Self {
         UsageTracker
     }
+
+    #[inline(always)]
+    pub fn new_root() -> Self {
+        UsageTracker
+    }
+
+    #[inline(always)]
+    pub fn new_for<S: ?Sized>() -> Self {
+        UsageTracker
+    }
+
+    #[inline(always)]
+    pub fn new_root_for<S: ?Sized>() -> Self {
+        UsageTracker
+    }
+
+    #[inline(always)]
+    pub fn new_at(_location: &'static Location<'static>) -> Self {
+        UsageTracker
+    }
+
+    #[inline(always)]
+    pub fn deny_over_borrow(&self) {}
 }
 
 impl Clone for UsageTracker {
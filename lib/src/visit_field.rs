@@ -0,0 +1,138 @@
+use crate::Hidden;
+use crate::Label;
+use crate::OptUsage;
+use crate::Upgradeable;
+use crate::Usage;
+use std::ops::Deref;
+
+// ================
+// === FieldRef ===
+// ================
+
+/// A field passed to [`VisitField::visit_field`], borrowed as shared or exclusive depending on
+/// the selector used for the field in the partial borrow.
+#[derive(Debug)]
+pub enum FieldRef<'t, T: ?Sized> {
+    Ref(&'t T),
+    Mut(&'t mut T),
+}
+
+impl<T: ?Sized> FieldRef<'_, T> {
+    /// Exclusive reference to the field, if it was borrowed mutably.
+    #[inline(always)]
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Self::Ref(_) => None,
+            Self::Mut(value) => Some(value),
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for FieldRef<'_, T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        match self {
+            Self::Ref(value) => value,
+            Self::Mut(value) => value,
+        }
+    }
+}
+
+// ==================
+// === VisitField ===
+// ==================
+
+/// Visitor of the fields of a partial borrow, used by the `for_each_selected` method generated by
+/// the `borrow::Partial` derive macro. The visitor is called once per borrowed field, with the
+/// field label and its value. Implement it for each field type to get static dispatch:
+///
+/// ```
+/// use borrow::partial as p;
+/// use borrow::traits::*;
+/// use borrow::FieldRef;
+/// use borrow::Label;
+/// use borrow::VisitField;
+///
+/// #[derive(Default, borrow::Partial)]
+/// #[module(crate)]
+/// struct Graph {
+///     nodes: Vec<usize>,
+///     edges: Vec<(usize, usize)>,
+///     name: String,
+/// }
+///
+/// #[derive(Default)]
+/// struct Save {
+///     lines: Vec<String>,
+/// }
+///
+/// impl<T: std::fmt::Debug> VisitField<Vec<T>> for Save {
+///     fn visit_field(&mut self, label: Label, field: FieldRef<'_, Vec<T>>) {
+///         self.lines.push(format!("{label}: {:?}", *field));
+///     }
+/// }
+///
+/// impl VisitField<String> for Save {
+///     fn visit_field(&mut self, label: Label, field: FieldRef<'_, String>) {
+///         self.lines.push(format!("{label}: {}", *field));
+///     }
+/// }
+///
+/// fn save(graph: p!(&<nodes, mut name> Graph), save: &mut Save) {
+///     graph.for_each_selected(save);
+/// }
+///
+/// fn main() {
+///     let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+///     let mut out = Save::default();
+///     save(p!(&mut graph), &mut out);
+///     assert_eq!(out.lines, ["nodes: [0, 1]", "name: "]);
+/// }
+/// ```
+pub trait VisitField<T: ?Sized> {
+    fn visit_field(&mut self, label: Label, field: FieldRef<'_, T>);
+}
+
+// =====================
+// === SelectedField ===
+// =====================
+
+/// Value of a field of a partial borrow that can be passed to a [`VisitField`] visitor. Hidden
+/// fields are skipped. Implement it for custom field types, see [`Acquire`](crate::Acquire).
+pub trait SelectedField<V> {
+    /// Usage registered by the usage tracker when the field is visited.
+    const USAGE: OptUsage;
+    fn visit_selected(&mut self, label: Label, visitor: &mut V);
+}
+
+impl<V> SelectedField<V> for Hidden {
+    const USAGE: OptUsage = None;
+    #[inline(always)]
+    fn visit_selected(&mut self, _label: Label, _visitor: &mut V) {}
+}
+
+impl<T: ?Sized, V: VisitField<T>> SelectedField<V> for &T {
+    const USAGE: OptUsage = Some(Usage::Ref);
+    #[inline(always)]
+    fn visit_selected(&mut self, label: Label, visitor: &mut V) {
+        visitor.visit_field(label, FieldRef::Ref(self));
+    }
+}
+
+impl<T: ?Sized, V: VisitField<T>> SelectedField<V> for &mut T {
+    const USAGE: OptUsage = Some(Usage::Mut);
+    #[inline(always)]
+    fn visit_selected(&mut self, label: Label, visitor: &mut V) {
+        visitor.visit_field(label, FieldRef::Mut(self));
+    }
+}
+
+/// Upgradeable fields are visited as shared, so visiting them does not report an upgrade.
+impl<T: ?Sized, V: VisitField<T>> SelectedField<V> for Upgradeable<'_, T> {
+    const USAGE: OptUsage = Some(Usage::Ref);
+    #[inline(always)]
+    fn visit_selected(&mut self, label: Label, visitor: &mut V) {
+        visitor.visit_field(label, FieldRef::Ref(self));
+    }
+}
@@ -0,0 +1,118 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::future::Future;
+use std::pin::Pin;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            std::task::Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }).await;
+}
+
+// ==============
+// === Passes ===
+// ==============
+
+async fn pass1(graph: p!(&<mut nodes, edges, mut groups> Graph)) {
+    pass2(p!(&mut graph)).await;
+    yield_now().await;
+    p!(&mut graph).pass3().await;
+    graph.groups.push(0);
+}
+
+async fn pass2(graph: p!(&<mut nodes, edges> Graph)) {
+    yield_now().await;
+    graph.nodes.push(graph.edges.len());
+}
+
+impl p!(<mut nodes> Graph) {
+    async fn pass3(&mut self) {
+        yield_now().await;
+        self.nodes.push(1);
+    }
+}
+
+// When the future is returned explicitly, the lifetime has to be named, as the partial borrow
+// contains several elided lifetimes.
+fn pass4<'t>(graph: p!(&'t <mut nodes> Graph)) -> impl Future<Output = ()> + 't {
+    // Executed before the future is polled.
+    graph.nodes.reserve(1);
+    async move {
+        yield_now().await;
+        graph.nodes.push(2);
+    }
+}
+
+trait Pass {
+    fn run<'t>(&self, graph: p!(&'t <mut nodes> Graph)) -> Pin<Box<dyn Future<Output = ()> + 't>>;
+}
+
+struct PushPass(usize);
+
+impl Pass for PushPass {
+    fn run<'t>(&self, graph: p!(&'t <mut nodes> Graph)) -> Pin<Box<dyn Future<Output = ()> + 't>> {
+        let value = self.0;
+        Box::pin(async move {
+            yield_now().await;
+            graph.nodes.push(value);
+        })
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_async_fn() {
+    let mut graph = Graph::default();
+    block_on(pass1(p!(&mut graph)));
+    assert_eq!(graph.nodes, vec![0, 1]);
+    assert_eq!(graph.groups, vec![0]);
+}
+
+#[test]
+fn test_returned_future() {
+    let mut graph = Graph::default();
+    block_on(pass4(p!(&mut graph)));
+    let passes: Vec<Box<dyn Pass>> = vec![Box::new(PushPass(3)), Box::new(PushPass(4))];
+    for pass in &passes {
+        block_on(pass.run(p!(&mut graph)));
+    }
+    assert_eq!(graph.nodes, vec![2, 3, 4]);
+}
@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+pub trait System {
+    fn update(&mut self, frame: usize);
+    fn frame(&self) -> usize;
+}
+
+#[derive(Debug, Default)]
+pub struct Physics {
+    frame: usize,
+}
+
+impl System for Physics {
+    fn update(&mut self, frame: usize) {
+        self.frame = frame;
+    }
+
+    fn frame(&self) -> usize {
+        self.frame
+    }
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct State {
+    pub physics: Box<dyn System + Send>,
+    pub shared: Box<dyn System + Send + Sync>,
+    pub systems: Vec<Box<dyn System + Send>>,
+    pub frame: usize,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            physics: Box::new(Physics::default()),
+            shared: Box::new(Physics::default()),
+            systems: vec![Box::new(Physics::default())],
+            frame: 0,
+        }
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn assert_send<T: Send>(_: &T) {}
+fn assert_sync<T: Sync>(_: &T) {}
+fn assert_unpin<T: Unpin>(_: &T) {}
+
+fn update(state: p!(&<mut physics, mut systems, frame> State)) {
+    state.physics.update(**state.frame);
+    for system in state.systems.iter_mut() {
+        system.update(**state.frame);
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_trait_object_fields() {
+    let mut state = State { frame: 3, ..Default::default() };
+    update(p!(&mut state));
+    assert_eq!(state.physics.frame(), 3);
+    assert_eq!(state.systems[0].frame(), 3);
+}
+
+#[test]
+fn test_auto_traits() {
+    let mut state = State::default();
+    let mut state_ref = state.as_refs_mut();
+    assert_send(&state_ref);
+    assert_unpin(&state_ref);
+    let shared = state_ref.partial_borrow::<p!(<shared, frame> State)>();
+    assert_send(&shared);
+    assert_sync(&shared);
+    assert_unpin(&shared);
+}
+
+#[test]
+fn test_send_to_thread() {
+    let mut state = State { frame: 5, ..Default::default() };
+    let mut state_ref = state.as_refs_mut();
+    let (mut physics, _) = state_ref.split::<p!(<mut physics, mut systems, frame> State)>();
+    std::thread::scope(|scope| {
+        scope.spawn(|| update(&mut physics));
+    });
+    drop(physics);
+    assert_eq!(state.physics.frame(), 5);
+}
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use criterion::Criterion;
+use std::time::Duration;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+impl Graph {
+    fn new(size: usize) -> Self {
+        Self { nodes: (0..size).collect(), edges: vec![0; size], groups: vec![] }
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn connect(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+    for (edge, node) in graph.edges.iter_mut().zip(graph.nodes.iter()) {
+        *edge = *node;
+    }
+    graph.edges.len()
+}
+
+fn connect_params(nodes: &[usize], edges: &mut [usize]) -> usize {
+    for (edge, node) in edges.iter_mut().zip(nodes.iter()) {
+        *edge = *node;
+    }
+    edges.len()
+}
+
+borrow::bench! {
+    bench_connect = <nodes, mut edges> Graph {
+        state: Graph::new(16),
+        view: connect,
+        params: |nodes: &Vec<usize>, edges: &mut Vec<usize>| connect_params(nodes, edges),
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_bench_runs_both_versions() {
+    let mut criterion = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(1))
+        .measurement_time(Duration::from_millis(10));
+    bench_connect(&mut criterion);
+}
@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+/// Struct with more fields than the type-level numbers defined in `borrow::hlist`, used as a
+/// stress test of the generated code.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Big {
+    pub f0: usize,
+    pub f1: usize,
+    pub f2: usize,
+    pub f3: usize,
+    pub f4: usize,
+    pub f5: usize,
+    pub f6: usize,
+    pub f7: usize,
+    pub f8: usize,
+    pub f9: usize,
+    pub f10: usize,
+    pub f11: usize,
+    pub f12: usize,
+    pub f13: usize,
+    pub f14: usize,
+    pub f15: usize,
+    pub f16: usize,
+    pub f17: usize,
+    pub f18: usize,
+    pub f19: usize,
+    pub f20: usize,
+    pub f21: usize,
+    pub f22: usize,
+    pub f23: usize,
+    pub f24: usize,
+    pub f25: usize,
+    pub f26: usize,
+    pub f27: usize,
+    pub f28: usize,
+    pub f29: usize,
+    pub f30: usize,
+    pub f31: usize,
+    pub f32: usize,
+    pub f33: usize,
+    pub f34: usize,
+    pub f35: usize,
+    pub f36: usize,
+    pub f37: usize,
+    pub f38: usize,
+    pub f39: usize,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn first_and_last(big: p!(&<f0, mut f39> Big)) {
+    **big.f39 = *big.f0 + 1;
+}
+
+fn last(big: p!(&<f39> Big)) -> usize {
+    **big.f39
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_big_struct() {
+    let mut big = Big::default();
+    first_and_last(p!(&mut big));
+    assert_eq!(last(p!(&mut big)), 1);
+}
+
+#[test]
+fn test_big_struct_split() {
+    let mut big = Big::default();
+    let mut big_ref = big.as_refs_mut();
+    let (last_ref, mut rest) = big_ref.split::<p!(<f39> Big)>();
+    **rest.f38 = *last_ref.f39 + 2;
+    assert_eq!(big.f38, 2);
+}
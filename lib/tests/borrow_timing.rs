@@ -0,0 +1,63 @@
+#![cfg(all(feature = "borrow_timing", usage_tracking_enabled))]
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::time::Duration;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+struct Graph {
+    nodes: Vec<usize>,
+    edges: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+#[track_caller]
+fn nodes(graph: &mut Graph) -> p!(<mut nodes> Graph) {
+    graph.partial_borrow()
+}
+
+#[track_caller]
+fn all(graph: &mut Graph) -> p!(<mut nodes, mut edges> Graph) {
+    graph.partial_borrow()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_borrow_timing() {
+    let mut graph = Graph::default();
+    let line = line!() + 1;
+    let mut view = all(&mut graph);
+    std::thread::sleep(Duration::from_millis(20));
+    view.nodes.push(0);
+    view.edges.push(0);
+    drop(view);
+    for _ in 0..3 {
+        nodes(&mut graph).nodes.push(1);
+    }
+
+    let mut timings = vec![];
+    borrow::for_each_borrow_timing(|timing| {
+        if timing.location.file() == file!() {
+            timings.push(timing);
+        }
+    });
+    assert_eq!(timings.len(), 2);
+    assert_eq!(timings[0].location.line(), line);
+    assert_eq!(timings[0].count, 1);
+    assert_eq!(timings[0].max_fields, 2);
+    assert!(timings[0].max >= Duration::from_millis(20));
+    assert_eq!(timings[1].count, 3);
+    assert_eq!(timings[1].max_fields, 1);
+}
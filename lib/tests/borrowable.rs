@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+#![allow(clippy::unwrap_used)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::Borrowable;
+use std::sync::Mutex;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Clone, Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes> Graph)) {
+    let node = graph.nodes.len();
+    graph.nodes.push(node);
+}
+
+fn connect(graph: p!(&<nodes, mut edges> Graph)) {
+    graph.edges.extend(graph.nodes.iter().copied());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_borrowable_views() {
+    let mut graph = Borrowable::new(Graph::default());
+    add_node(p!(&mut graph));
+    add_node(p!(&mut graph));
+    connect(p!(&mut graph));
+    let graph = graph.into_inner();
+    assert_eq!(graph.nodes, [0, 1]);
+    assert_eq!(graph.edges, [0, 1]);
+}
+
+#[test]
+fn test_borrowable_behind_lock() {
+    let graph = Mutex::new(Borrowable::<Graph>::default());
+    let mut guard = graph.lock().unwrap();
+    let graph_ref: &mut Borrowable<Graph> = &mut guard;
+    let mut view = graph_ref.as_refs_mut();
+    let (mut edges, mut rest) = view.borrow_edges_mut();
+    add_node(p!(&mut rest));
+    edges.push(3);
+    drop(guard);
+    let graph = graph.into_inner().unwrap().into_inner();
+    assert_eq!(graph.nodes, [0]);
+    assert_eq!(graph.edges, [3]);
+}
+
+#[test]
+fn test_borrowable_debug_hides_struct() {
+    let graph = Borrowable::from(Graph::default());
+    assert_eq!(format!("{graph:?}"), "Borrowable { .. }");
+}
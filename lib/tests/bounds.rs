@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+pub trait Pass {
+    fn name() -> &'static str;
+}
+
+impl Pass for p!(<mut nodes> Graph) {
+    fn name() -> &'static str {
+        "nodes"
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+/// Runs the pass with the lifetime of the borrow given in a higher-ranked bound.
+fn run_nodes_pass<F>(graph: p!(&<mut *> Graph), mut pass: F)
+where for<'a> F: FnMut(p!(&'a <mut nodes> Graph)) {
+    pass(p!(&mut graph));
+}
+
+/// Runs the pass with the lifetime of the borrow elided.
+fn run_edges_pass(graph: p!(&<mut *> Graph), mut pass: impl FnMut(p!(&<nodes, mut edges> Graph))) {
+    pass(p!(&mut graph));
+}
+
+/// Runs the pass borrowing all fields.
+fn run_full_pass<F>(graph: &mut Graph, mut pass: F)
+where F: for<'a> FnMut(p!(&'a mut Graph)) {
+    pass(&mut graph.partial_borrow());
+}
+
+fn pass_name() -> &'static str
+where for<'a> p!('a <mut nodes> Graph): Pass {
+    <p!(<mut nodes> Graph)>::name()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_partial_borrows_in_bounds() {
+    let mut graph = Graph::default();
+    run_nodes_pass(p!(&mut graph), |graph| graph.nodes.extend([0, 1]));
+    run_edges_pass(p!(&mut graph), |graph| graph.edges.push((graph.nodes[0], graph.nodes[1])));
+    run_full_pass(&mut graph, |graph| {
+        graph.nodes.push(2);
+        graph.edges.push((1, 2));
+    });
+    assert_eq!(graph.nodes, [0, 1, 2]);
+    assert_eq!(graph.edges, [(0, 1), (1, 2)]);
+    assert_eq!(pass_name(), "nodes");
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub groups: Vec<usize>,
+}
+
+borrow::spec!(TOPOLOGY = {mut nodes, edges});
+
+// =============
+// === Utils ===
+// =============
+
+fn connect(graph: p!(&{nodes, mut edges} Graph)) {
+    graph.edges.push((graph.nodes[0], graph.nodes[1]));
+}
+
+fn add_nodes<'a>(graph: p!(&'a {mut *, groups} Graph)) {
+    graph.nodes.extend([0, 1]);
+    connect(p!(&mut graph));
+}
+
+fn count(graph: p!(&TOPOLOGY Graph)) -> usize {
+    graph.nodes.len() + graph.edges.len()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_brace_selectors() {
+    let mut graph = Graph::default();
+    add_nodes(p!(&mut graph));
+    assert_eq!(count(p!(&mut graph)), 3);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_brace_selectors_expand_like_angled_ones() {
+    let mut graph = Graph::default();
+    let angled: p!(<mut nodes, edges> Graph) = graph.partial_borrow();
+    let _: p!({mut nodes, edges} Graph) = angled;
+    let empty: p!(<> Graph) = graph.partial_borrow();
+    let _: p!({} Graph) = empty;
+}
@@ -0,0 +1,80 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::split_buffer;
+use borrow::BufferSplitError;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Packet<'t> {
+    #[borrow(external)]
+    pub header: &'t mut [u8],
+    #[borrow(external)]
+    pub payload: &'t mut [u8],
+    #[borrow(external)]
+    pub trailer: &'t mut [u8],
+    pub sent: bool,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn parse(scratch: &mut [u8]) -> Result<Packet<'_>, BufferSplitError> {
+    let len = scratch.len();
+    let [trailer, header, payload] = split_buffer(scratch, [len - 1..len, 0..2, 2..len - 1])?;
+    Ok(Packet { header, payload, trailer, sent: false })
+}
+
+fn encode(packet: p!(&<mut header, payload, mut trailer> Packet)) {
+    packet.header.copy_from_slice(&(packet.payload.len() as u16).to_be_bytes());
+    packet.trailer[0] = packet.payload.iter().fold(0, |sum, byte| sum ^ byte);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_split_buffer() -> Result<(), BufferSplitError> {
+    let mut scratch = [0, 0, 1, 2, 4, 0];
+    let mut packet = parse(&mut scratch)?;
+    encode(p!(&mut packet));
+    assert_eq!(scratch, [0, 3, 1, 2, 4, 7]);
+    Ok(())
+}
+
+#[test]
+fn test_split_buffer_empty_ranges() -> Result<(), BufferSplitError> {
+    let mut scratch = [1, 2];
+    let [a, b, c] = split_buffer(&mut scratch, [1..1, 0..2, 0..0])?;
+    assert_eq!((a.len(), b.len(), c.len()), (0, 2, 0));
+    Ok(())
+}
+
+#[test]
+fn test_split_buffer_errors() {
+    let mut scratch = [0; 4];
+    assert_eq!(
+        split_buffer(&mut scratch, [0..2, 1..3]).map(|_| ()),
+        Err(BufferSplitError::Overlap { first: 0..2, second: 1..3 })
+    );
+    assert_eq!(
+        split_buffer(&mut scratch, [2..4, 0..3]).map(|_| ()),
+        Err(BufferSplitError::Overlap { first: 0..3, second: 2..4 })
+    );
+    assert_eq!(
+        split_buffer(&mut scratch, [0..2, 2..5]).map(|_| ()),
+        Err(BufferSplitError::OutOfBounds { range: 2..5, len: 4 })
+    );
+    let (start, end) = (3, 2);
+    assert_eq!(
+        split_buffer(&mut scratch, [0..1, start..end]).map(|_| ()),
+        Err(BufferSplitError::OutOfBounds { range: start..end, len: 4 })
+    );
+}
@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+#[cfg(feature = "tracking")]
+use borrow::tracking::BorrowReport;
+#[cfg(feature = "tracking")]
+use std::sync::Mutex;
+#[cfg(feature = "tracking")]
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Ui {
+    pub clicks: Vec<usize>,
+    pub hovers: Vec<usize>,
+    pub theme: String,
+}
+
+/// Records the fields of over-borrows created in this file.
+#[cfg(feature = "tracking")]
+struct RecordingTracker {
+    not_used: Mutex<Vec<&'static str>>,
+}
+
+#[cfg(feature = "tracking")]
+impl borrow::tracking::Tracker for RecordingTracker {
+    fn borrow_dropped(&self, report: &BorrowReport) {
+        if report.is_over_borrow() && report.location.file() == file!() {
+            let mut not_used = self.not_used.lock().unwrap_or_else(PoisonError::into_inner);
+            not_used.extend(report.not_used());
+        }
+    }
+}
+
+#[cfg(feature = "tracking")]
+static TRACKER: RecordingTracker = RecordingTracker { not_used: Mutex::new(vec![]) };
+
+// =============
+// === Utils ===
+// =============
+
+fn on_click<'a>(ui: p!(<'a mut clicks, 'a theme> Ui)) -> impl FnMut(usize) + 'a {
+    let clicks = ui.clicks.into_inner();
+    let theme = ui.theme;
+    move |id| {
+        if !theme.is_empty() {
+            clicks.push(id);
+        }
+    }
+}
+
+fn on_hover<'a>(ui: p!(<'a mut hovers> Ui)) -> impl FnMut(usize) + 'a {
+    let mut hovers = ui.hovers;
+    move |id| hovers.push(id)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_into_inner() {
+    let mut ui = Ui { theme: "dark".into(), ..Default::default() };
+    let mut handler = on_click(ui.partial_borrow());
+    handler(1);
+    handler(2);
+    drop(handler);
+    assert_eq!(ui.clicks, [1, 2]);
+}
+
+#[test]
+fn test_captured_field() {
+    let mut ui = Ui::default();
+    let mut handler = on_hover(ui.partial_borrow());
+    handler(3);
+    drop(handler);
+    assert_eq!(ui.hovers, [3]);
+}
+
+#[cfg(feature = "tracking")]
+#[test]
+fn test_captured_field_usage() {
+    borrow::tracking::set_tracker(&TRACKER);
+    let mut ui = Ui { theme: "dark".into(), ..Default::default() };
+    // The unwrapped field is used at once, and the captured one when the handler is called.
+    let handler = on_click(ui.partial_borrow());
+    drop(handler);
+    let mut handler = on_hover(ui.partial_borrow());
+    handler(4);
+    drop(handler);
+    let not_used = TRACKER.not_used.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let expected: &[&str] = if cfg!(usage_tracking_enabled) { &["theme"] } else { &[] };
+    assert_eq!(not_used, expected);
+}
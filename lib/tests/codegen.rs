@@ -0,0 +1,170 @@
+//! Checks that partial borrows are zero-overhead. The functions in `codegen/fixture.rs` are
+//! compiled in release mode for every checked target, and every `partial_*` function has to compile
+//! to the same instructions as its `direct_*` counterpart. The instructions are also compared with
+//! the golden files in `codegen/$target`. Field offsets are replaced with `N` in the goldens, so
+//! they do not depend on the layout of the standard library types. Run the test with
+//! `BORROW_CODEGEN_BLESS=1` to write them.
+//!
+//! The `x86_64` target is checked only on `x86_64` Linux hosts, as the goldens are generated for
+//! `x86_64-unknown-linux-gnu`. Targets whose standard library is not installed are skipped. The
+//! wasm32 test is ignored by default, run it with `--ignored` after
+//! `rustup target add wasm32-unknown-unknown`.
+#![cfg(feature = "codegen")]
+#![allow(clippy::panic)]
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+// ============
+// === Data ===
+// ============
+
+/// Names of the fixture functions, without the `partial_` and `direct_` prefixes.
+const FUNCTIONS: &[&str] = &["field_access", "borrow", "split"];
+
+// =============
+// === Utils ===
+// =============
+
+fn is_target_installed(target: &str) -> bool {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let output = Command::new(rustc).args(["--print", "sysroot"]).output();
+    let sysroot = output.map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string());
+    sysroot.is_ok_and(|sysroot| Path::new(&sysroot).join("lib/rustlib").join(target).exists())
+}
+
+/// Compiles the fixture in release mode and returns its assembly.
+fn emit_asm(target: &str) -> String {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("codegen");
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let status = Command::new(env!("CARGO"))
+        .args(["rustc", "--release", "--example", "codegen", "--features", "codegen"])
+        .arg("--manifest-path").arg(&manifest)
+        .args(["--target", target])
+        .arg("--target-dir").arg(&target_dir)
+        .args(["--", "--emit", "asm"])
+        .status();
+    assert!(status.is_ok_and(|status| status.success()), "Failed to compile the fixture.");
+    let examples = target_dir.join(target).join("release/examples");
+    // Builds with other flags leave their assembly in the same directory, so the newest is used.
+    let asm_file = std::fs::read_dir(&examples).into_iter().flatten().flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "s"))
+        .max_by_key(|entry| entry.metadata().and_then(|meta| meta.modified()).ok())
+        .map(|entry| entry.path());
+    let Some(asm_file) = asm_file else {
+        panic!("No assembly file in {}.", examples.display())
+    };
+    std::fs::read_to_string(asm_file).unwrap_or_default()
+}
+
+/// Instructions of the function, without directives and comments. Local labels are renamed in
+/// the order of appearance, so equal functions have equal bodies.
+fn function_body(asm: &str, name: &str) -> String {
+    let start = format!("{name}:");
+    let lines = asm.lines().map(str::trim).skip_while(|line| *line != start).skip(1);
+    let lines = lines.take_while(|line| !line.starts_with(".Lfunc_end") && *line != "end_function");
+    let mut labels = HashMap::new();
+    let mut body = String::new();
+    for line in lines {
+        let line = line.split('#').next().unwrap_or_default().trim_end();
+        let is_label = line.ends_with(':');
+        if line.is_empty() || (line.starts_with('.') && !is_label) {
+            continue;
+        }
+        body.push_str(&normalize_offsets(&rename_local_labels(line, &mut labels)));
+        body.push('\n');
+    }
+    body
+}
+
+fn rename_local_labels(line: &str, labels: &mut HashMap<String, usize>) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(index) = rest.find(".LBB") {
+        out.push_str(&rest[..index]);
+        let label_len = rest[index + 1..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(rest.len() - index, |len| len + 1);
+        let label = &rest[index..index + label_len];
+        let next_index = labels.len();
+        let label_index = *labels.entry(label.to_string()).or_insert(next_index);
+        out.push_str(&format!(".L{label_index}"));
+        rest = &rest[index + label_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replaces the memory offsets of the instruction with `N`. These are the `40` in x86 operands like
+/// `40(%rdi)` and the immediates of wasm loads and stores, like `i32.load 40`.
+fn normalize_offsets(line: &str) -> String {
+    let mnemonic = line.split_whitespace().next().unwrap_or_default();
+    if mnemonic.contains(".load") || mnemonic.contains(".store") {
+        let operand = line.trim_end_matches(|c: char| c.is_ascii_digit());
+        return if operand.len() < line.len() { format!("{operand}N") } else { line.to_string() };
+    }
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(index) = rest.find('(') {
+        let prefix = &rest[..index];
+        let offset = prefix.trim_end_matches(|c: char| c.is_ascii_digit() || c == '-');
+        let is_offset =
+            offset.len() < prefix.len() && !offset.ends_with(|c: char| c.is_alphanumeric());
+        out.push_str(if is_offset { offset } else { prefix });
+        out.push_str(if is_offset { "N(" } else { "(" });
+        rest = &rest[index + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn golden_dir(target: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/codegen").join(target)
+}
+
+fn check_target(target: &str) {
+    if !is_target_installed(target) {
+        eprintln!("Skipping {target}, its standard library is not installed.");
+        return;
+    }
+    let asm = emit_asm(target);
+    let bless = std::env::var_os("BORROW_CODEGEN_BLESS").is_some();
+    let golden_dir = golden_dir(target);
+    for function in FUNCTIONS {
+        let partial = function_body(&asm, &format!("partial_{function}"));
+        let direct = function_body(&asm, &format!("direct_{function}"));
+        assert!(!partial.is_empty(), "Function 'partial_{function}' not found for {target}.");
+        let message = format!("Partial borrows in '{function}' are not zero-cost for {target}.");
+        assert_eq!(partial, direct, "{message}");
+        let golden_file = golden_dir.join(format!("{function}.s"));
+        if bless {
+            let written = std::fs::create_dir_all(&golden_dir)
+                .and_then(|()| std::fs::write(&golden_file, &partial));
+            assert!(written.is_ok(), "Failed to write {}.", golden_file.display());
+        } else {
+            let Ok(golden) = std::fs::read_to_string(&golden_file) else {
+                let golden_file = golden_file.display();
+                panic!("Missing {golden_file}. Run the test with BORROW_CODEGEN_BLESS=1.")
+            };
+            assert_eq!(partial, golden, "Codegen of '{function}' changed for {target}.");
+        }
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+#[cfg(all(target_arch = "x86_64", target_os = "linux", target_env = "gnu"))]
+fn test_codegen_x86_64() {
+    check_target("x86_64-unknown-linux-gnu");
+}
+
+#[test]
+#[ignore = "requires the wasm32-unknown-unknown standard library"]
+fn test_codegen_wasm32() {
+    check_target("wasm32-unknown-unknown");
+}
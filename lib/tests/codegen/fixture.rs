@@ -0,0 +1,67 @@
+//! Functions compiled by the `codegen` test. Every `partial_*` function has to compile to the same
+//! instructions as its `direct_*` counterpart, which uses the fields of the struct directly.
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+// ====================
+// === Field access ===
+// ====================
+
+#[no_mangle]
+pub fn direct_field_access(graph: &mut Graph) -> usize {
+    graph.nodes.len() + graph.edges.len()
+}
+
+#[no_mangle]
+pub fn partial_field_access(graph: &mut Graph) -> usize {
+    let view = graph.as_refs_mut();
+    view.nodes.len() + view.edges.len()
+}
+
+// ======================
+// === Partial borrow ===
+// ======================
+
+#[no_mangle]
+pub fn direct_borrow(graph: &mut Graph) {
+    if let Some(edge) = graph.edges.first_mut() {
+        *edge = graph.nodes.len();
+    }
+}
+
+#[no_mangle]
+pub fn partial_borrow(graph: &mut Graph) {
+    let mut view = graph.partial_borrow::<p!(<nodes, mut edges> Graph)>();
+    if let Some(edge) = view.edges.first_mut() {
+        *edge = view.nodes.len();
+    }
+}
+
+// =============
+// === Split ===
+// =============
+
+#[no_mangle]
+pub fn direct_split(graph: &mut Graph) {
+    if let Some(edge) = graph.edges.first_mut() {
+        *edge = graph.nodes.len() + graph.groups.len();
+    }
+}
+
+#[no_mangle]
+pub fn partial_split(graph: &mut Graph) {
+    let mut view = graph.as_refs_mut();
+    let (mut edges, rest) = view.split::<p!(<mut edges> Graph)>();
+    if let Some(edge) = edges.edges.first_mut() {
+        *edge = rest.nodes.len() + rest.groups.len();
+    }
+}
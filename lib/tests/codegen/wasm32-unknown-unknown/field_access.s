@@ -0,0 +1,5 @@
+local.get	0
+i32.load	N
+local.get	0
+i32.load	N
+i32.add
@@ -0,0 +1,15 @@
+block
+local.get	0
+i32.load	N
+i32.eqz
+br_if   	0
+local.get	0
+i32.load	N
+local.get	0
+i32.load	N
+local.get	0
+i32.load	N
+i32.add
+i32.store	N
+.L0:
+end_block
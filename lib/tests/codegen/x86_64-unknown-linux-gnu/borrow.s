@@ -0,0 +1,7 @@
+cmpq	$0, N(%rdi)
+je	.L0
+movq	N(%rdi), %rax
+movq	N(%rdi), %rcx
+movq	%rax, (%rcx)
+.L0:
+retq
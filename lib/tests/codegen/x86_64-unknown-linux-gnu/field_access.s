@@ -0,0 +1,3 @@
+movq	N(%rdi), %rax
+addq	N(%rdi), %rax
+retq
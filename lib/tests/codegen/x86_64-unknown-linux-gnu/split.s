@@ -0,0 +1,8 @@
+cmpq	$0, N(%rdi)
+je	.L0
+movq	N(%rdi), %rax
+movq	N(%rdi), %rcx
+addq	N(%rdi), %rcx
+movq	%rcx, (%rax)
+.L0:
+retq
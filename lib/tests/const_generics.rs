@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct Meta {
+    pub updates: usize,
+}
+
+#[derive(Debug, borrow::Partial)]
+#[module(crate)]
+pub struct Grid<'t, T, const N: usize> {
+    pub cells: [T; N],
+    pub meta: Meta,
+    pub name: &'t str,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn fill<T: Copy, const N: usize>(grid: p!(&<mut cells, mut meta> Grid<'_, T, N>), value: T) {
+    grid.cells.fill(value);
+    grid.meta.updates += 1;
+}
+
+fn name_len<T, const N: usize>(grid: p!(&<name> Grid<'_, T, N>)) -> usize {
+    grid.name.len()
+}
+
+fn fill_all<T: Copy, const N: usize>(grid: p!(&<mut *> Grid<'_, T, N>), value: T) -> usize {
+    fill(p!(&mut grid), value);
+    name_len(p!(&mut grid))
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_const_generic_borrow() {
+    let mut grid = Grid { cells: [0; 3], meta: Meta::default(), name: "grid" };
+    assert_eq!(fill_all(p!(&mut grid), 7), 4);
+    assert_eq!(grid.cells, [7, 7, 7]);
+    assert_eq!(grid.meta.updates, 1);
+}
+
+#[test]
+fn test_const_generic_split() {
+    let mut grid = Grid { cells: ['a'; 2], meta: Meta::default(), name: "grid" };
+    let mut grid_ref = grid.as_refs_mut();
+    let (mut cells, mut rest) = grid_ref.borrow_cells_mut();
+    cells[1] = 'b';
+    assert_eq!(name_len(p!(&mut rest)), 4);
+    assert_eq!(grid.cells, ['a', 'b']);
+}
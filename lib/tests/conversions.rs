@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+type AddNodeRef<'t> = p!('t <mut nodes, edges> Graph);
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes, edges> Graph)) {
+    let id = graph.nodes.len() + graph.edges.len();
+    graph.nodes.push(id);
+}
+
+fn count_nodes(graph: p!(&<nodes> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+// Generic adapter code can rely on `TryFrom`, which is provided for all narrowing conversions.
+fn adapt<Source, Target: TryFrom<Source>>(source: Source) -> Option<Target> {
+    source.try_into().ok()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_from() {
+    let mut graph = Graph::default();
+    let mut graph_ref = graph.as_refs_mut();
+    let mut narrow: p!(<mut nodes, edges> Graph) = (&mut graph_ref).into();
+    add_node(&mut narrow);
+    let mut narrower = <p!(<nodes> Graph)>::from(&mut narrow);
+    assert_eq!(count_nodes(&mut narrower), 1);
+}
+
+#[test]
+fn test_try_from() {
+    let mut graph = Graph::default();
+    let mut graph_ref = graph.as_refs_mut();
+    let narrow: Option<AddNodeRef> = adapt(&mut graph_ref);
+    if let Some(mut narrow) = narrow {
+        add_node(&mut narrow);
+    }
+    assert_eq!(graph.nodes.len(), 1);
+}
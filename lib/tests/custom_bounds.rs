@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+pub trait Component: 'static {
+    const NAME: &'static str;
+}
+
+impl Component for usize {
+    const NAME: &'static str = "usize";
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(bound = "T: Clone", bound(as_refs_mut = "T: Component, U: Default"))]
+pub struct Storage<T, U> {
+    pub items: Vec<T>,
+    pub extra: Vec<U>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(transparent, bound(as_refs_mut = "T: Component"))]
+pub struct World<T>(pub crate::Storage<T, ()>);
+
+// =============
+// === Utils ===
+// =============
+
+fn duplicate<T: Clone, U>(storage: p!(&<mut items> Storage<T, U>)) {
+    let items = storage.items.clone();
+    storage.items.extend(items);
+}
+
+fn names<T: Component + Clone, U>(storage: p!(&<items, mut extra> Storage<T, U>)) -> Vec<&'static str> {
+    storage.items.iter().map(|_| T::NAME).collect()
+}
+
+fn is_borrowable<T: borrow::AsRefsMut>(_value: &T) -> bool {
+    true
+}
+
+fn add_extra<U: Default>(storage: p!(&<mut extra> Storage<usize, U>)) {
+    storage.extra.push(U::default());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_custom_bounds() {
+    let mut storage = Storage::<usize, String> { items: vec![1], extra: vec![] };
+    duplicate(p!(&mut storage));
+    add_extra(p!(&mut storage));
+    assert_eq!(names(p!(&mut storage)), ["usize", "usize"]);
+    assert_eq!(storage.extra, [""]);
+    assert!(is_borrowable(&storage));
+}
+
+#[test]
+fn test_custom_bounds_transparent() {
+    let mut world = World(Storage { items: vec![1], extra: vec![] });
+    duplicate(p!(&mut world));
+    assert_eq!(world.0.items, [1, 1]);
+    assert!(is_borrowable(&world));
+}
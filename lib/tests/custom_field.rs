@@ -0,0 +1,155 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::Acquire;
+use borrow::AcquireMarker;
+use borrow::Bool;
+use borrow::Field;
+use borrow::FieldAccess;
+use borrow::FieldMask;
+use borrow::Hidden;
+use borrow::OptUsage;
+use borrow::ReborrowField;
+use borrow::Usage;
+use borrow::UsageTracker;
+
+// =============
+// === Dirty ===
+// =============
+
+/// Value remembering whether it was modified.
+#[derive(Debug, Default)]
+pub struct Dirty<T> {
+    value: T,
+    dirty: bool,
+}
+
+/// Handle setting the dirty flag of a [`Dirty`] value.
+#[derive(Debug)]
+pub struct DirtyFlagHandle<'t> {
+    flag: &'t mut bool,
+}
+
+impl DirtyFlagHandle<'_> {
+    fn set(&mut self) {
+        *self.flag = true;
+    }
+}
+
+/// Field borrowed from a [`Dirty`] value.
+#[derive(Debug)]
+pub struct DirtyMut<'t, T> {
+    value: &'t mut T,
+    flag: DirtyFlagHandle<'t>,
+}
+
+impl<T> DirtyMut<'_, T> {
+    fn modify(&mut self) -> &mut T {
+        self.flag.set();
+        self.value
+    }
+}
+
+impl<'s, T: 's> ReborrowField<'s> for DirtyMut<'_, T> {
+    type Output = DirtyMut<'s, T>;
+    fn reborrow_field(&'s mut self) -> Self::Output {
+        let flag = DirtyFlagHandle { flag: self.flag.flag };
+        DirtyMut { value: self.value, flag }
+    }
+}
+
+impl<T> FieldAccess for DirtyMut<'_, T> {
+    const ACCESS: OptUsage = Some(Usage::Mut);
+}
+
+impl<'t, 'y, T> Acquire<&'t mut Dirty<T>, DirtyMut<'y, T>> for AcquireMarker
+where 't: 'y {
+    type Rest = Hidden;
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, &'t mut Dirty<T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, DirtyMut<'y, T>>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Mut), tracker, |this| {
+            let flag = DirtyFlagHandle { flag: &mut this.dirty };
+            (DirtyMut { value: &mut this.value, flag }, Hidden)
+        })
+    }
+}
+
+impl<'t, 'y, T> Acquire<DirtyMut<'t, T>, DirtyMut<'y, T>> for AcquireMarker
+where 't: 'y {
+    type Rest = Hidden;
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, DirtyMut<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, DirtyMut<'y, T>>, Field<E1, Self::Rest>) {
+        this.split_field(Some(Usage::Mut), tracker, |this| (this, Hidden))
+    }
+}
+
+impl<'t, T> Acquire<DirtyMut<'t, T>, Hidden> for AcquireMarker {
+    type Rest = DirtyMut<'t, T>;
+    fn acquire<E1: Bool, E2: Bool>(
+        this: Field<E1, DirtyMut<'t, T>>,
+        tracker: UsageTracker
+    ) -> (Field<E2, Hidden>, Field<E1, Self::Rest>) {
+        this.split_field(None, tracker, |this| (Hidden, this))
+    }
+}
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Scene {
+    pub mesh: Dirty<Vec<f32>>,
+    pub name: String,
+}
+
+type MeshMut<'t> = SceneRef<Scene, DirtyMut<'t, Vec<f32>>, Hidden>;
+type MeshAndNameMut<'t> = SceneRef<Scene, DirtyMut<'t, Vec<f32>>, &'t mut String>;
+
+// =============
+// === Utils ===
+// =============
+
+fn add_vertex(scene: &mut MeshMut) {
+    scene.mesh.modify().push(0.0);
+}
+
+fn add_named_vertex(scene: &mut MeshAndNameMut) {
+    add_vertex(&mut scene.partial_borrow());
+    scene.name.push_str("vertex");
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_custom_field_split() {
+    let mut scene = Scene::default();
+    add_vertex(&mut scene.partial_borrow());
+    assert_eq!(scene.mesh.value.len(), 1);
+    assert!(scene.mesh.dirty);
+}
+
+#[test]
+fn test_custom_field_resplit() {
+    let mut scene = Scene::default();
+    add_named_vertex(p!(&mut scene));
+    add_named_vertex(p!(&mut scene));
+    assert_eq!(scene.mesh.value.len(), 2);
+    assert_eq!(scene.name, "vertexvertex");
+    assert!(scene.mesh.dirty);
+}
+
+#[test]
+fn test_custom_field_mask() {
+    let mask = FieldMask::of::<MeshMut>();
+    assert_eq!(mask.access(0), Some(Usage::Mut));
+    assert_eq!(mask.access(1), None);
+}
@@ -0,0 +1,140 @@
+#![cfg(feature = "tracking")]
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::tracking::Access;
+use borrow::tracking::BorrowReport;
+use borrow::tracking::FieldReport;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub groups: Vec<usize>,
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Storage<T> {
+    pub items: Vec<T>,
+    pub len: usize,
+}
+
+/// Records the fields and headers of over-borrows created in this file.
+struct RecordingTracker {
+    not_used: Mutex<Vec<&'static str>>,
+    headers: Mutex<Vec<String>>,
+}
+
+impl borrow::tracking::Tracker for RecordingTracker {
+    fn borrow_dropped(&self, report: &BorrowReport) {
+        if report.is_over_borrow() && report.location.file() == file!() {
+            let mut not_used = self.not_used.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut headers = self.headers.lock().unwrap_or_else(PoisonError::into_inner);
+            if report.struct_name.is_none() {
+                not_used.extend(report.not_used());
+            }
+            headers.push(report.header());
+        }
+    }
+}
+
+static TRACKER: RecordingTracker =
+    RecordingTracker { not_used: Mutex::new(vec![]), headers: Mutex::new(vec![]) };
+
+// =============
+// === Utils ===
+// =============
+
+fn node_count(graph: p!(&<nodes, edges> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+fn item_count<T>(storage: p!(&<items, len> Storage<T>)) -> usize {
+    storage.items.len()
+}
+
+/// Header of a report of the given struct type.
+fn header_for<S: ?Sized>() -> String {
+    let struct_name = Some(std::any::type_name::<S>());
+    BorrowReport { location: std::panic::Location::caller(), struct_name, fields: &[] }.header()
+}
+
+fn header_for_val<S: ?Sized>(_: &S) -> String {
+    header_for::<S>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_custom_tracker() {
+    borrow::tracking::set_tracker(&TRACKER);
+    let mut graph = Graph::default();
+    assert_eq!(node_count(p!(&mut graph)), 0);
+    let not_used = TRACKER.not_used.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let expected: &[&str] = if cfg!(usage_tracking_enabled) { &["edges"] } else { &[] };
+    assert_eq!(not_used, expected);
+}
+
+#[test]
+fn test_report_message() {
+    let fields = [
+        FieldReport { label: "edges", requested: Some(Access::Ref), used: None },
+        FieldReport { label: "groups", requested: Some(Access::Mut), used: Some(Access::Ref) },
+        FieldReport { label: "nodes", requested: Some(Access::Mut), used: Some(Access::Mut) },
+    ];
+    let report = BorrowReport { location: std::panic::Location::caller(), struct_name: None, fields: &fields };
+    assert!(report.is_over_borrow());
+    assert_eq!(report.used_as_ref().collect::<Vec<_>>(), ["groups"]);
+    let message = report.message();
+    assert!(message.contains("Borrowed but not used: edges."));
+    assert!(message.contains("Borrowed as mut but used as ref: groups."));
+    assert!(message.contains("To fix the issue, use: &<groups, mut nodes>."));
+}
+
+#[test]
+fn test_report_header() {
+    let closure = |x: usize| x + 1;
+    assert!(header_for::<Storage<String>>().ends_with("] (Storage<String>)"));
+    assert!(header_for::<std::collections::BTreeMap<String, std::rc::Rc<Vec<u8>>>>()
+        .ends_with("] (BTreeMap<String, Rc<Vec<u8>>>)"));
+    assert!(header_for::<Storage<(std::cell::Cell<u8>, [std::num::Wrapping<i8>; 2])>>()
+        .ends_with("] (Storage<(Cell<u8>, [Wrapping<i8>; 2])>)"));
+    assert!(header_for_val(&closure).ends_with("] ({{closure}})"));
+}
+
+#[test]
+fn test_generic_struct_header() {
+    fn header_with<T>(items: Vec<T>) -> usize {
+        let mut storage = Storage { len: items.len(), items };
+        item_count(p!(&mut storage))
+    }
+    borrow::tracking::set_tracker(&TRACKER);
+    let closure = |x: usize| x + 1;
+    header_with(vec![String::new()]);
+    header_with(vec![std::collections::BTreeMap::<String, Vec<u8>>::new()]);
+    header_with(vec![closure]);
+    let headers = TRACKER.headers.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let headers = headers.into_iter().filter(|header| header.contains("(Storage<"));
+    let headers = headers.collect::<Vec<_>>();
+    let expected: &[&str] = if cfg!(usage_tracking_enabled) {
+        &["(Storage<String>)", "(Storage<BTreeMap<String, Vec<u8>>>)", "(Storage<{{closure}}>)"]
+    } else {
+        &[]
+    };
+    for expected in expected {
+        let found = headers.iter().any(|header| header.ends_with(expected));
+        assert!(found, "Missing {expected} in {headers:#?}");
+    }
+    assert_eq!(headers.len(), expected.len());
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::fmt::Debug;
+
+// ============
+// === Data ===
+// ============
+
+pub trait Backend: Debug + Default {
+    fn draw(&mut self) -> usize;
+}
+
+#[derive(Debug, Default)]
+pub struct Gl {
+    pub calls: usize,
+}
+
+impl Backend for Gl {
+    fn draw(&mut self) -> usize {
+        self.calls += 1;
+        self.calls
+    }
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Ctx<B: Backend = Gl> {
+    pub backend: B,
+    pub frames: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+/// Uses the default backend, without generic arguments.
+fn draw(ctx: p!(&<mut backend, mut frames> Ctx)) {
+    let calls = ctx.backend.draw();
+    ctx.frames.push(calls);
+}
+
+fn draw_generic<B: Backend>(ctx: p!(&<mut backend, frames> Ctx<B>)) -> usize {
+    ctx.backend.draw() + ctx.frames.len()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_default_params() {
+    let mut ctx = Ctx::<Gl>::default();
+    draw(p!(&mut ctx));
+    draw(p!(&mut ctx));
+    assert_eq!(ctx.frames, [1, 2]);
+    assert_eq!(draw_generic(p!(&mut ctx)), 5);
+}
+
+#[test]
+fn test_default_params_split() {
+    let mut ctx: Ctx = Ctx::default();
+    let mut ctx_ref = ctx.as_refs_mut();
+    let (mut backend, mut rest) = ctx_ref.borrow_backend_mut();
+    rest.frames.push(backend.draw());
+    assert_eq!(ctx.frames, [1]);
+}
@@ -0,0 +1,78 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(default = "mut nodes, edges")]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub cache: Vec<usize>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn add_node_untracked(graph: p!(_&Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn add_node_explicit<'t>(graph: p!(&'t _ Graph)) -> &'t [usize] {
+    graph.nodes.push(graph.edges.len());
+    &graph.nodes
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_default_selectors() {
+    let mut graph = Graph::default();
+    graph.edges.push((0, 1));
+    add_node(p!(&mut graph));
+    add_node_untracked(p!(&mut graph));
+    assert_eq!(add_node_explicit(p!(&mut graph)), [1, 1, 1]);
+    type Expected = p!(<'static mut nodes, 'static edges> Graph);
+    assert!(same_type::<p!('static Graph), Expected>());
+    assert!(same_type::<p!('static _ Graph), Expected>());
+}
+
+#[test]
+fn test_default_selectors_split() {
+    let mut graph = Graph::default();
+    let mut graph_ref = graph.as_refs_mut();
+    let (mut nodes, mut rest) = graph_ref.split::<p!(Graph)>();
+    add_node(p!(&mut nodes));
+    rest.cache.push(0);
+    assert_eq!((graph.nodes.len(), graph.cache.len()), (1, 1));
+}
+
+#[test]
+fn test_no_default_selectors() {
+    type Empty = p!(<> Config);
+    assert!(same_type::<p!(Config), Empty>());
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+#[borrow::deny_over_borrow]
+fn node_count(graph: p!(&<nodes> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+#[borrow::deny_over_borrow]
+fn node_count_over_borrowed(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+fn node_count_not_denied(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_minimal_borrow() {
+    let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+    assert_eq!(node_count(p!(&mut graph)), 2);
+}
+
+#[test]
+fn test_over_borrow_not_denied() {
+    let mut graph = Graph::default();
+    assert_eq!(node_count_not_denied(p!(&mut graph)), 0);
+}
+
+#[test]
+#[cfg_attr(usage_tracking_enabled, should_panic(expected = "Borrowed but not used: edges."))]
+fn test_over_borrow_denied() {
+    let mut graph = Graph::default();
+    assert_eq!(node_count_over_borrowed(p!(&mut graph)), 0);
+}
@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Scene {
+    pub mesh: Vec<f32>,
+    pub name: String,
+    pub lights: Vec<f32>,
+    #[borrow(dirty_flags)]
+    pub dirty: borrow::DirtyFlags<Scene>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_vertex(scene: p!(&<mut mesh, name> Scene)) {
+    let _ = scene.name.len();
+    scene.mesh.push(0.0);
+}
+
+fn dim_lights(scene: p!(&<mut lights> Scene)) {
+    for light in scene.lights.iter_mut() {
+        *light *= 0.5;
+    }
+}
+
+fn update(scene: p!(&<mut mesh, name, mut lights> Scene)) {
+    add_vertex(p!(&mut scene));
+    dim_lights(p!(&mut scene));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_dirty_flags_are_cleared() {
+    let mut scene = Scene::default();
+    update(p!(&mut scene));
+    assert!(scene.dirty.any());
+    scene.dirty.clear();
+    assert!(!scene.dirty.any());
+    assert!(scene.dirty.dirty_fields().is_empty());
+}
+
+#[test]
+fn test_dirty_flags_of_nested_borrows() {
+    let mut scene = Scene::default();
+    update(p!(&mut scene));
+    assert!(scene.dirty.is_dirty("mesh"));
+    assert!(scene.dirty.is_dirty("lights"));
+    assert!(!scene.dirty.is_dirty("dirty"));
+}
+
+#[test]
+#[cfg(field_usage_tracking_enabled)]
+fn test_dirty_flags_mark_only_mutably_used_fields() {
+    let mut scene = Scene::default();
+    add_vertex(p!(&mut scene));
+    assert_eq!(scene.dirty.dirty_fields(), ["mesh"]);
+}
+
+#[test]
+#[cfg(not(field_usage_tracking_enabled))]
+fn test_dirty_flags_without_usage_tracking() {
+    let mut scene = Scene::default();
+    add_vertex(p!(&mut scene));
+    assert_eq!(scene.dirty.dirty_fields(), ["mesh", "name", "lights"]);
+}
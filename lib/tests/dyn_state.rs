@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+/// Fields of the document state, used to borrow views of any state implementation.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(dyn_state = "DocumentFields")]
+pub struct Document {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Settings with fields which can not be mutated through the state object.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(dyn_state = "SettingsFields", private_mut = "theme")]
+pub struct Settings {
+    #[borrow(readonly)]
+    pub version: usize,
+    pub theme: String,
+    pub zoom: usize,
+}
+
+/// Document state abstracted for testing.
+pub trait DocumentState: DocumentFields {
+    fn title(&self) -> &str;
+}
+
+/// State keeping the fields next to other data.
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub title: String,
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl DocumentFields for MockState {
+    fn fields_mut(&mut self) -> (&mut Vec<usize>, &mut Vec<(usize, usize)>) {
+        (&mut self.nodes, &mut self.edges)
+    }
+}
+
+impl DocumentState for MockState {
+    fn title(&self) -> &str {
+        &self.title
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(doc: p!(&<mut nodes> Document)) {
+    doc.nodes.push(0);
+}
+
+fn connect(doc: p!(&<nodes, mut edges> Document)) {
+    doc.edges.push((0, doc.nodes.len()));
+}
+
+fn zoom_in(settings: p!(&<version, mut zoom> Settings)) {
+    **settings.zoom += **settings.version;
+}
+
+fn edit(state: &mut dyn DocumentState) {
+    let state: &mut dyn DocumentFields = state;
+    add_node(p!(&mut state));
+    connect(p!(&mut state));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_dyn_state() {
+    let mut state = MockState::default();
+    edit(&mut state);
+    assert_eq!(state.nodes.len(), 1);
+    assert_eq!(state.edges, vec![(0, 1)]);
+}
+
+#[test]
+fn test_concrete_state() {
+    let mut doc = Document::default();
+    add_node(p!(&mut doc));
+    let state: &mut dyn DocumentFields = &mut doc;
+    connect(p!(&mut state));
+    assert_eq!(doc.edges, vec![(0, 1)]);
+}
+
+#[test]
+fn test_dyn_state_shared_fields() {
+    let mut settings = Settings { version: 1, ..Default::default() };
+    let state: &mut dyn SettingsFields = &mut settings;
+    let (version, theme, zoom): (&usize, &String, &mut usize) = state.fields_mut();
+    *zoom = 2;
+    assert_eq!((*version, theme.as_str()), (1, ""));
+    zoom_in(p!(&mut state));
+    assert_eq!(settings.zoom, 3);
+}
@@ -0,0 +1,64 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::marker::PhantomData;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Unit;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(derive(Debug, Clone))]
+pub struct EmptyTuple();
+
+/// A struct whose only field is disabled, as in macro-generated code.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Disabled<T> {
+    #[cfg(any())]
+    pub value: T,
+    marker: PhantomData<T>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn use_unit(_unit: p!(&<*> Unit)) {}
+
+fn use_empty_tuple(_tuple: p!(&<mut *> EmptyTuple)) {}
+
+fn use_disabled<T>(_disabled: p!(&<> Disabled<T>)) {}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_unit_struct() {
+    let mut unit = Unit;
+    use_unit(p!(&mut unit));
+    let mut unit_ref = unit.as_refs_mut();
+    let (mut all, _rest) = unit_ref.split::<p!(<*> Unit)>();
+    use_unit(p!(&mut all));
+}
+
+#[test]
+fn test_empty_tuple_struct() {
+    let mut tuple = EmptyTuple();
+    use_empty_tuple(p!(&mut tuple));
+    assert_eq!(format!("{:?}", tuple.as_refs_mut()), "EmptyTupleRef");
+}
+
+#[test]
+fn test_disabled_fields() {
+    let mut disabled = Disabled::<String>::default();
+    use_disabled(p!(&mut disabled));
+    let _view = disabled.partial_borrow::<p!(<> Disabled<String>)>();
+}
@@ -0,0 +1,81 @@
+//! Measures the code generated by the derive, which IDEs expand for every struct, and the struct
+//! macro, which they expand for every `p!`. The sizes are counted in tokens, as a proxy of the cost
+//! of the expansion, and compared with the baselines below, so a regression is caught for small
+//! and large structs alike. Run the test with `--nocapture` to print the sizes, and update the
+//! baselines when the generated code shrinks.
+
+// ============
+// === Data ===
+// ============
+
+/// Number of fields, derive output size, and struct macro size, in tokens.
+const BASELINES: [(usize, usize, usize); 3] = [
+    (4, 8_630, 1_354),
+    (16, 36_866, 3_394),
+    (64, 299_570, 11_554),
+];
+
+/// Optional features of `borrow-macro`, e.g. `registry`, add up to 1% to the derive output.
+const DERIVE_TOLERANCE_PERCENT: usize = 2;
+
+// =============
+// === Utils ===
+// =============
+
+/// Sizes of the derive output and of the struct macro, in tokens.
+fn sizes() -> [(usize, (usize, usize)); 3] {
+    [
+        (4, borrow::expansion_size! {
+            #[module(crate)]
+            pub struct Struct4 {
+                pub field0: usize, pub field1: usize, pub field2: usize, pub field3: usize,
+            }
+        }),
+        (16, borrow::expansion_size! {
+            #[module(crate)]
+            pub struct Struct16 {
+                pub field0: usize, pub field1: usize, pub field2: usize, pub field3: usize,
+                pub field4: usize, pub field5: usize, pub field6: usize, pub field7: usize,
+                pub field8: usize, pub field9: usize, pub field10: usize, pub field11: usize,
+                pub field12: usize, pub field13: usize, pub field14: usize, pub field15: usize,
+            }
+        }),
+        (64, borrow::expansion_size! {
+            #[module(crate)]
+            pub struct Struct64 {
+                pub field0: usize, pub field1: usize, pub field2: usize, pub field3: usize,
+                pub field4: usize, pub field5: usize, pub field6: usize, pub field7: usize,
+                pub field8: usize, pub field9: usize, pub field10: usize, pub field11: usize,
+                pub field12: usize, pub field13: usize, pub field14: usize, pub field15: usize,
+                pub field16: usize, pub field17: usize, pub field18: usize, pub field19: usize,
+                pub field20: usize, pub field21: usize, pub field22: usize, pub field23: usize,
+                pub field24: usize, pub field25: usize, pub field26: usize, pub field27: usize,
+                pub field28: usize, pub field29: usize, pub field30: usize, pub field31: usize,
+                pub field32: usize, pub field33: usize, pub field34: usize, pub field35: usize,
+                pub field36: usize, pub field37: usize, pub field38: usize, pub field39: usize,
+                pub field40: usize, pub field41: usize, pub field42: usize, pub field43: usize,
+                pub field44: usize, pub field45: usize, pub field46: usize, pub field47: usize,
+                pub field48: usize, pub field49: usize, pub field50: usize, pub field51: usize,
+                pub field52: usize, pub field53: usize, pub field54: usize, pub field55: usize,
+                pub field56: usize, pub field57: usize, pub field58: usize, pub field59: usize,
+                pub field60: usize, pub field61: usize, pub field62: usize, pub field63: usize,
+            }
+        }),
+    ]
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_expansion_size() {
+    for ((fields, (total, struct_macro)), baseline) in sizes().into_iter().zip(BASELINES) {
+        println!("{fields} fields: {total} tokens, struct macro: {struct_macro} tokens");
+        let (baseline_fields, baseline_total, baseline_struct_macro) = baseline;
+        assert_eq!(fields, baseline_fields);
+        assert_eq!(struct_macro, baseline_struct_macro, "Struct macro size of {fields} fields.");
+        let max_total = baseline_total * (100 + DERIVE_TOLERANCE_PERCENT) / 100;
+        assert!(total <= max_total, "Derive output of {fields} fields: {total} > {max_total}.");
+    }
+}
@@ -0,0 +1,100 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use slotmap::SecondaryMap;
+use slotmap::SlotMap;
+
+// ============
+// === Data ===
+// ============
+
+slotmap::new_key_type! {
+    pub struct NodeId;
+}
+
+#[derive(Debug, Default)]
+pub struct Node {
+    pub name: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Position {
+    pub x: f32,
+}
+
+#[derive(Debug, Default)]
+pub struct Velocity {
+    pub x: f32,
+}
+
+/// Nodes owned by the world, and components stored in secondary maps owned by other systems.
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct World<'t> {
+    pub nodes: SlotMap<NodeId, Node>,
+    #[borrow(external)]
+    pub positions: &'t mut SecondaryMap<NodeId, Position>,
+    #[borrow(external)]
+    pub velocities: &'t mut SecondaryMap<NodeId, Velocity>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn spawn(world: p!(&<mut nodes, mut positions, mut velocities> World), name: &str, v: f32) {
+    let id = world.nodes.insert(Node { name: name.to_string() });
+    world.positions.insert(id, Position::default());
+    world.velocities.insert(id, Velocity { x: v });
+}
+
+fn integrate(world: p!(&<mut positions, velocities> World)) {
+    for (id, position) in world.positions.iter_mut() {
+        position.x += world.velocities[id].x;
+    }
+}
+
+fn names(world: p!(&<nodes> World)) -> Vec<String> {
+    world.nodes.values().map(|node| node.name.clone()).collect()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_external_fields() {
+    let mut positions = SecondaryMap::new();
+    let mut velocities = SecondaryMap::new();
+    let mut world = World {
+        nodes: SlotMap::with_key(),
+        positions: &mut positions,
+        velocities: &mut velocities,
+    };
+    spawn(p!(&mut world), "a", 1.0);
+    spawn(p!(&mut world), "b", 2.0);
+    integrate(p!(&mut world));
+    integrate(p!(&mut world));
+    assert_eq!(names(p!(&mut world)), ["a", "b"]);
+    drop(world);
+    let xs = positions.values().map(|position| position.x).collect::<Vec<_>>();
+    assert_eq!(xs, [2.0, 4.0]);
+}
+
+#[test]
+fn test_external_fields_split() {
+    let mut positions = SecondaryMap::new();
+    let mut velocities = SecondaryMap::new();
+    let mut world = World {
+        nodes: SlotMap::with_key(),
+        positions: &mut positions,
+        velocities: &mut velocities,
+    };
+    spawn(p!(&mut world), "a", 3.0);
+    let mut world_ref = world.as_refs_mut();
+    let (mut nodes, mut rest) = world_ref.borrow_nodes_mut();
+    nodes.clear();
+    integrate(p!(&mut rest));
+    assert_eq!(rest.positions.values().next().map(|position| position.x), Some(3.0));
+}
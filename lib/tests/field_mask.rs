@@ -0,0 +1,105 @@
+#![allow(dead_code)]
+#![allow(clippy::unwrap_used)]
+
+use borrow::partial as p;
+use borrow::FieldMask;
+use borrow::Usage;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+type Mask = FieldMask<Graph>;
+
+// =============
+// === Utils ===
+// =============
+
+/// All shapes of the `Graph` struct.
+fn all_masks() -> Vec<Mask> {
+    let access = [None, Some(Usage::Ref), Some(Usage::Mut)];
+    let mut masks = vec![];
+    for nodes in access {
+        for edges in access {
+            for groups in access {
+                masks.push(Mask::from_access(vec![nodes, edges, groups]));
+            }
+        }
+    }
+    masks
+}
+
+/// Laws which have to hold for every source and target shape.
+fn check_laws(source: &Mask, target: &Mask) {
+    let rest = source.split(target);
+    assert_eq!(rest.is_some(), target.is_subset_of(source), "{target:?} from {source:?}");
+    if let Some(rest) = rest {
+        assert!(rest.is_subset_of(source), "{rest:?} left from {source:?}");
+        assert!(!target.conflicts_with(&rest), "{target:?} conflicts with {rest:?}");
+        // Merging the parts never exceeds the source. It only loses the mutability of fields
+        // borrowed immutably from a mutable source.
+        let merged = target.merge(&rest);
+        assert!(merged.is_some_and(|merged| merged.is_subset_of(source)));
+    }
+    // Borrowing nothing leaves the source unchanged.
+    assert_eq!(source.split(&Mask::hidden()).as_ref(), Some(source));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_static_shapes() {
+    let mask = Mask::of::<p!(<mut nodes, edges> Graph)>();
+    let expected = Mask::hidden()
+        .with("nodes", Some(Usage::Mut)).unwrap()
+        .with("edges", Some(Usage::Ref)).unwrap();
+    assert_eq!(mask, expected);
+    assert_eq!(format!("{mask:?}"), "<mut nodes, edges>");
+    assert_eq!(Mask::of::<p!(<mut *> Graph)>(), Mask::all(Some(Usage::Mut)));
+    assert!(Mask::hidden().with("unknown", None).is_none());
+}
+
+#[test]
+fn test_split() {
+    let source = Mask::of::<p!(<mut nodes, edges> Graph)>();
+    let rest = source.split(&Mask::of::<p!(<nodes> Graph)>());
+    assert_eq!(rest, Some(Mask::of::<p!(<nodes, edges> Graph)>()));
+    let rest = source.split(&Mask::of::<p!(<mut nodes> Graph)>());
+    assert_eq!(rest, Some(Mask::of::<p!(<edges> Graph)>()));
+    assert_eq!(source.split(&Mask::of::<p!(<mut edges> Graph)>()), None);
+    assert_eq!(source.split(&Mask::of::<p!(<groups> Graph)>()), None);
+}
+
+#[test]
+fn test_laws() {
+    let masks = all_masks();
+    for source in &masks {
+        for target in &masks {
+            check_laws(source, target);
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary_laws() {
+    use arbitrary::Arbitrary;
+    use arbitrary::Unstructured;
+    let bytes = (0..=255).collect::<Vec<u8>>();
+    let mut u = Unstructured::new(&bytes);
+    while !u.is_empty() {
+        let source = Mask::arbitrary(&mut u).unwrap();
+        let target = Mask::arbitrary(&mut u).unwrap();
+        check_laws(&source, &target);
+    }
+}
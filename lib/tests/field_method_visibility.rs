@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+mod allocator {
+    use borrow::partial as p;
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::allocator)]
+    pub struct Allocator {
+        pub used: Vec<usize>,
+        free: Vec<usize>,
+    }
+
+    impl Allocator {
+        pub fn new(free: Vec<usize>) -> Self {
+            Self { used: vec![], free }
+        }
+    }
+
+    /// The private field can be borrowed in the module defining it.
+    pub fn alloc(allocator: p!(&<mut *> Allocator)) -> Option<usize> {
+        let (mut free, mut rest) = allocator.borrow_free_mut();
+        let (mut used, _) = rest.borrow_used_mut();
+        let index = free.pop()?;
+        used.push(index);
+        Some(index)
+    }
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::allocator)]
+    #[borrow(pub_field_methods)]
+    pub struct Pool {
+        pub used: Vec<usize>,
+        free: Vec<usize>,
+    }
+
+    impl Pool {
+        pub fn new(free: Vec<usize>) -> Self {
+            Self { used: vec![], free }
+        }
+    }
+}
+
+use allocator::Allocator;
+use allocator::Pool;
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_field_methods_of_private_fields() {
+    let mut allocator = Allocator::new(vec![1, 2]);
+    assert_eq!(allocator::alloc(p!(&mut allocator)), Some(2));
+    let mut view = allocator.as_refs_mut();
+    let (used, _) = view.borrow_used();
+    assert_eq!(used.len(), 1);
+}
+
+#[test]
+fn test_pub_field_methods() {
+    let mut pool = Pool::new(vec![1, 2]);
+    let mut view = pool.as_refs_mut();
+    let (mut free, _) = view.borrow_free_mut();
+    assert_eq!(free.pop(), Some(2));
+}
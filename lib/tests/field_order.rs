@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::FieldOrder;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+/// The declaration order of the fields differs from the alphabetical order.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Scene {
+    pub meshes: Vec<usize>,
+    pub lights: Vec<usize>,
+    pub cameras: Vec<usize>,
+}
+
+/// The field order is global, so tests changing it can not run in parallel.
+static ORDER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Records the messages of over-borrows created in this file.
+#[cfg(feature = "tracking")]
+struct RecordingTracker {
+    messages: Mutex<Vec<String>>,
+}
+
+#[cfg(feature = "tracking")]
+impl borrow::tracking::Tracker for RecordingTracker {
+    fn borrow_dropped(&self, report: &borrow::tracking::BorrowReport) {
+        if report.is_over_borrow() && report.location.file() == file!() {
+            let mut messages = self.messages.lock().unwrap_or_else(PoisonError::into_inner);
+            messages.push(report.message());
+        }
+    }
+}
+
+#[cfg(feature = "tracking")]
+static TRACKER: RecordingTracker = RecordingTracker { messages: Mutex::new(vec![]) };
+
+// =============
+// === Utils ===
+// =============
+
+fn render(scene: p!(&<mut meshes, lights, mut cameras> Scene)) {
+    scene.meshes.push(scene.cameras.len());
+}
+
+/// Message of the over-borrow of `render` with the given field order.
+#[cfg(feature = "tracking")]
+fn render_message(order: FieldOrder) -> Option<String> {
+    borrow::set_field_order(order);
+    let mut scene = Scene::default();
+    render(p!(&mut scene));
+    TRACKER.messages.lock().unwrap_or_else(PoisonError::into_inner).pop()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_field_order() {
+    let _lock = ORDER_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    assert_eq!(borrow::field_order(), FieldOrder::Alphabetical);
+    borrow::set_field_order(FieldOrder::Declaration);
+    assert_eq!(borrow::field_order(), FieldOrder::Declaration);
+    borrow::set_field_order(FieldOrder::Alphabetical);
+    assert_eq!(borrow::field_order(), FieldOrder::Alphabetical);
+}
+
+#[test]
+#[cfg(feature = "tracking")]
+fn test_field_order_report() {
+    let _lock = ORDER_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    borrow::tracking::set_tracker(&TRACKER);
+    let declaration = render_message(FieldOrder::Declaration);
+    let alphabetical = render_message(FieldOrder::Alphabetical);
+    if cfg!(usage_tracking_enabled) {
+        let not_used = "\n    Borrowed but not used: lights.";
+        let used_as_ref = "\n    Borrowed as mut but used as ref: cameras.";
+        let fix = |selectors| format!("{not_used}{used_as_ref}\n    To fix the issue, use: &<{selectors}>.");
+        assert_eq!(declaration, Some(fix("mut meshes, cameras")));
+        assert_eq!(alphabetical, Some(fix("cameras, mut meshes")));
+    } else {
+        assert_eq!((declaration, alphabetical), (None, None));
+    }
+}
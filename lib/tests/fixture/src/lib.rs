@@ -0,0 +1,14 @@
+//! Structs used by the UI tests in `lib/tests/ui`. Some of the checked rules apply only outside of
+//! the crate defining the struct, so the structs are defined here.
+
+// ============
+// === Core ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(private_mut = "allocator")]
+pub struct Core {
+    pub allocator: Vec<usize>,
+    pub log: Vec<String>,
+}
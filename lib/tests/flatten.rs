@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+use std::sync::Mutex;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct RenderCtx {
+    pub geometry: Vec<f32>,
+    pub material: Vec<String>,
+    #[borrow(wrap = "Mutex")]
+    pub log: Mutex<Vec<String>>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(render = "geometry, material"))]
+pub struct Ctx {
+    pub frame: usize,
+    #[borrow(flatten = "geometry, material, log")]
+    pub render: RenderCtx,
+    pub scene: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_point(ctx: p!(&<mut geometry, scene> Ctx)) {
+    ctx.geometry.push(ctx.scene.len() as f32);
+}
+
+fn add_material(ctx: p!(&<mut material, frame> Ctx)) {
+    ctx.material.push(format!("material {}", **ctx.frame));
+}
+
+fn render(ctx: p!(&<mut @render, mut log> Ctx)) {
+    ctx.geometry.push(1.0);
+    ctx.material.push("default".to_string());
+    ctx.log.push("render".to_string());
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_flattened_fields() {
+    let mut ctx = Ctx::default();
+    ctx.scene.push(1);
+    add_point(p!(&mut ctx));
+    add_material(p!(&mut ctx));
+    assert_eq!(ctx.render.geometry, [1.0]);
+    assert_eq!(ctx.render.material, ["material 0"]);
+}
+
+#[test]
+fn test_flattened_group_and_wrap() {
+    let mut ctx = Ctx::default();
+    render(p!(&mut ctx));
+    assert_eq!(ctx.render.geometry, [1.0]);
+    assert_eq!(ctx.render.log.lock().map(|log| log.len()).ok(), Some(1));
+}
+
+#[test]
+fn test_flattened_split() {
+    let mut ctx = Ctx::default();
+    let mut ctx_ref = ctx.as_refs_mut();
+    let (mut geometry_ctx, mut rest) = ctx_ref.split::<p!(<mut geometry> Ctx)>();
+    geometry_ctx.geometry.push(2.0);
+    add_material(p!(&mut rest));
+    assert_eq!(ctx.render.geometry, [2.0]);
+    assert_eq!(ctx.render.material.len(), 1);
+}
+
+#[test]
+fn test_flattened_field_types() {
+    assert!(same_type::<<Ctx as borrow::HasField<1>>::Type, Vec<f32>>());
+    assert!(same_type::<<Ctx as borrow::HasField<3>>::Type, Vec<String>>());
+}
@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::FieldRef;
+use borrow::Label;
+use borrow::VisitField;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub name: String,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Pair(pub usize, pub String);
+
+/// Records visited fields and clears the ones borrowed mutably.
+#[derive(Debug, Default)]
+pub struct Save {
+    pub lines: Vec<String>,
+}
+
+impl<T: std::fmt::Debug> VisitField<Vec<T>> for Save {
+    fn visit_field(&mut self, label: Label, mut field: FieldRef<'_, Vec<T>>) {
+        self.lines.push(format!("{label}: {:?}", *field));
+        if let Some(field) = field.as_mut() {
+            field.clear();
+        }
+    }
+}
+
+impl VisitField<String> for Save {
+    fn visit_field(&mut self, label: Label, mut field: FieldRef<'_, String>) {
+        let access = if field.as_mut().is_some() { "mut " } else { "" };
+        self.lines.push(format!("{access}{label}: {}", *field));
+    }
+}
+
+impl VisitField<usize> for Save {
+    fn visit_field(&mut self, label: Label, field: FieldRef<'_, usize>) {
+        self.lines.push(format!("{label}: {}", *field));
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn save(graph: p!(&<nodes, mut name> Graph)) -> Vec<String> {
+    let mut save = Save::default();
+    graph.for_each_selected(&mut save);
+    save.lines
+}
+
+fn save_and_clear(graph: p!(&<mut *> Graph)) -> Vec<String> {
+    let mut save = Save::default();
+    graph.for_each_selected(&mut save);
+    save.lines
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_for_each_selected() {
+    let mut graph = Graph { nodes: vec![0, 1], edges: vec![(0, 1)], name: "g".into() };
+    assert_eq!(save(p!(&mut graph)), ["nodes: [0, 1]", "mut name: g"]);
+    assert_eq!(save_and_clear(p!(&mut graph)), [
+        "nodes: [0, 1]",
+        "edges: [(0, 1)]",
+        "mut name: g",
+    ]);
+    assert!(graph.nodes.is_empty());
+    assert!(graph.edges.is_empty());
+}
+
+#[test]
+fn test_for_each_selected_tuple_struct() {
+    let mut pair = Pair(1, "a".into());
+    let mut save = Save::default();
+    pair.as_refs_mut().for_each_selected(&mut save);
+    assert_eq!(save.lines, ["0: 1", "mut 1: a"]);
+}
@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::FrameCache;
+use borrow::Rebind;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn connect(graph: p!(&<mut nodes, edges> Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_frame_cache() {
+    let mut graphs: Vec<Graph> = (0..4).map(|_| Graph::default()).collect();
+    let mut cache = FrameCache::<p!('static <mut nodes, edges> Graph)>::new();
+    for _ in 0..3 {
+        let mut frame = cache.begin_frame();
+        for graph in &mut graphs {
+            frame.push(graph.partial_borrow());
+        }
+        assert_eq!(frame.len(), 4);
+        for view in frame.iter_mut() {
+            connect(p!(&mut view));
+        }
+        if let Some(view) = frame.get_mut(0) {
+            view.nodes.push(1);
+        }
+    }
+    // The views are cleared at the end of every frame, but the allocation is reused.
+    assert!(cache.capacity() >= 4);
+    assert_eq!(graphs[0].nodes, vec![0, 1, 0, 1, 0, 1]);
+    assert_eq!(graphs[3].nodes, vec![0, 0, 0]);
+}
+
+#[test]
+fn test_rebind() {
+    let mut graph = Graph::default();
+    let mut cache = FrameCache::<p!('static <mut nodes, edges> Graph)>::new();
+    let mut view = graph.partial_borrow::<p!(<mut nodes, edges> Graph)>();
+    {
+        let mut frame = cache.begin_frame();
+        let reborrowed = view.partial_borrow::<p!(<mut nodes, edges> Graph)>();
+        frame.push(reborrowed.rebind());
+        for view in frame.iter_mut() {
+            connect(p!(&mut view));
+        }
+    }
+    connect(p!(&mut view));
+    assert_eq!(graph.nodes, vec![0, 0]);
+}
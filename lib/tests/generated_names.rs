@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+//! Names of the generated items are a part of the public API, see `borrow::doc::generated_items`.
+//! Every item is named explicitly here, so renaming it fails to compile.
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(group(topology = "nodes, edges"), dyn_state = "GraphState")]
+    pub struct Graph {
+        pub nodes: Vec<usize>,
+        pub edges: Vec<(usize, usize)>,
+        pub name: String,
+    }
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    pub struct Pair(pub usize, pub usize);
+}
+
+use graph::Graph;
+use graph::GraphRef;
+use graph::GraphState;
+use graph::GraphTestView;
+use graph::PairRef;
+
+type Full<'t> = GraphRef<
+    Graph,
+    &'t mut Vec<usize>,
+    &'t mut Vec<(usize, usize)>,
+    &'t mut String,
+>;
+
+type Names<'t> = GraphRef<Graph, borrow::Hidden, borrow::Hidden, &'t String>;
+
+// =============
+// === Utils ===
+// =============
+
+fn name_len(graph: &mut Names) -> usize {
+    graph.name.len()
+}
+
+fn topology(graph: &mut Full) -> usize {
+    let (topology, _) = graph.borrow_topology_mut();
+    topology.nodes.len() + topology.edges.len()
+}
+
+fn nodes_via_macro(graph: p!(&<mut nodes> graph::Graph)) -> usize {
+    graph.nodes.len()
+}
+
+fn state_name(state: &mut dyn GraphState) -> String {
+    state.fields_mut().2.clone()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_generated_names() {
+    let mut graph = Graph { nodes: vec![0], edges: vec![], name: "g".into() };
+    let mut full: Full = graph.as_refs_mut();
+    assert_eq!(name_len(&mut full.partial_borrow()), 1);
+    assert_eq!(topology(&mut full), 1);
+    let (nodes, mut rest) = full.borrow_nodes_mut();
+    let (edges, _) = rest.borrow_edges();
+    assert_eq!((nodes.len(), edges.len()), (1, 0));
+    let full: Full = graph.as_refs_mut();
+    let mut full = full.map_name(|name| &mut name[..]);
+    full.name.make_ascii_uppercase();
+    assert_eq!(nodes_via_macro(p!(&mut graph)), 1);
+    assert_eq!(state_name(&mut graph), "G");
+}
+
+#[test]
+fn test_generated_names_tuple_struct() {
+    let mut pair = graph::Pair(1, 2);
+    let mut pair_ref: PairRef<graph::Pair, &mut usize, &mut usize> =
+        pair.as_refs_mut();
+    let pair_ref: p!(&<0, 1> graph::Pair) = &mut pair_ref.partial_borrow();
+    assert_eq!(**pair_ref._0 + **pair_ref._1, 3);
+}
+
+#[test]
+fn test_generated_test_view_names() {
+    let mut view: GraphTestView<Vec<usize>, _, _> = GraphRef::test_view().with_nodes(vec![1]);
+    view.name_mut().push('g');
+    assert_eq!(nodes_via_macro(p!(&mut view)), 1);
+    assert_eq!(view.name_mut(), "g");
+}
@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+pub mod graph {
+    /// A struct with a lifetime, a bounded type parameter, and a const parameter with a default.
+    #[derive(Debug, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(group(topology = "nodes, labels"))]
+    pub struct Graph<'a, T: Clone, const N: usize = 2> {
+        pub nodes: Vec<T>,
+        pub labels: Vec<&'a str>,
+        pub slots: [usize; N],
+    }
+
+    impl<T: Clone, const N: usize> Default for Graph<'_, T, N> {
+        fn default() -> Self {
+            Self { nodes: vec![], labels: vec![], slots: [0; N] }
+        }
+    }
+}
+
+use graph::Graph;
+
+borrow::spec!(NODES = <mut nodes>);
+
+type NodesView<'x, 'a, T> = p!(<'x mut nodes> Graph<'a, T>);
+
+// =============
+// === Utils ===
+// =============
+
+fn push_named<'a, T: Clone>(graph: p!(&<mut nodes, mut labels> Graph<'a, T>), node: T, label: &'a str) {
+    graph.nodes.push(node);
+    graph.labels.push(label);
+}
+
+fn push_elided<T: Clone>(graph: p!(&<mut nodes> Graph<'_, T>), node: T) {
+    graph.nodes.push(node);
+}
+
+fn push_trailing_comma<'a, T: Clone>(graph: p!(&<mut nodes> Graph<'a, T,>), node: T) {
+    graph.nodes.push(node);
+}
+
+fn push_nested(graph: p!(&<mut nodes> Graph<'static, Vec<Option<u8>>>)) {
+    graph.nodes.push(vec![Some(1)]);
+}
+
+fn push_path(graph: p!(&<mut nodes> crate::graph::Graph::<'static, (u8, u16), 3>)) {
+    graph.nodes.push((1, 2));
+}
+
+fn set_slots<const M: usize>(graph: p!(&<mut slots> Graph<'static, u8, M>)) {
+    graph.slots.fill(M);
+}
+
+fn set_block_slots(graph: p!(&<mut slots> Graph<'static, u8, { 1 + 2 }>)) {
+    graph.slots.fill(1);
+}
+
+fn push_group<'a, T: Clone>(graph: p!(&<mut @topology> Graph<'a, T>), node: T, label: &'a str) {
+    push_named(p!(&mut graph), node, label);
+}
+
+fn push_spec<'a, T: Clone>(graph: p!(&NODES Graph<'a, T>), node: T) {
+    push_elided(p!(&mut graph), node);
+}
+
+fn push_excluding<'a, T: Clone>(graph: p!(&<mut * - slots> Graph<'a, T>), node: T) {
+    push_spec(p!(&mut graph), node);
+}
+
+fn nodes_view<'x, 'a, T: Clone>(graph: &'x mut Graph<'a, T>) -> NodesView<'x, 'a, T> {
+    graph.partial_borrow()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_lifetime_and_type_arguments() {
+    let mut graph = Graph::<u8>::default();
+    push_named(p!(&mut graph), 1, "a");
+    push_elided(p!(&mut graph), 2);
+    push_trailing_comma(p!(&mut graph), 3);
+    push_group(p!(&mut graph), 4, "b");
+    push_excluding(p!(&mut graph), 5);
+    nodes_view(&mut graph).nodes.push(6);
+    assert_eq!(graph.nodes, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(graph.labels, ["a", "b"]);
+}
+
+#[test]
+fn test_nested_and_path_arguments() {
+    let mut nested = Graph::<Vec<Option<u8>>>::default();
+    push_nested(p!(&mut nested));
+    assert_eq!(nested.nodes, [vec![Some(1)]]);
+    let mut tuples = Graph::<(u8, u16), 3>::default();
+    push_path(p!(&mut tuples));
+    assert_eq!(tuples.nodes, [(1, 2)]);
+}
+
+#[test]
+fn test_const_arguments() {
+    let mut graph = Graph::<u8, 4>::default();
+    set_slots(p!(&mut graph));
+    assert_eq!(graph.slots, [4; 4]);
+    let mut graph = Graph::<u8, 3>::default();
+    set_block_slots(p!(&mut graph));
+    assert_eq!(graph.slots, [1; 3]);
+}
+
+#[test]
+fn test_generic_turbofish() {
+    let mut graph = Graph::<u8>::default();
+    let (mut nodes, mut rest) = graph.split::<p!(<mut nodes> Graph<'_, u8, 2>)>();
+    nodes.nodes.push(1);
+    rest.labels.push("a");
+    assert_eq!((graph.nodes.len(), graph.labels.len()), (1, 1));
+}
@@ -0,0 +1,145 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::fmt::Debug;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct GeometryCtx {
+    pub points: Vec<f32>,
+}
+
+#[derive(Debug, Default)]
+pub struct MaterialCtx {
+    pub names: Vec<String>,
+}
+
+/// A struct with a lifetime, a bounded type parameter, a parameter with a default, and a where
+/// clause, exercised through all the public APIs below.
+#[derive(Debug, borrow::Partial)]
+#[module(crate)]
+pub struct Ctx<'t, T: Debug, M = MaterialCtx>
+where M: Default {
+    pub version: &'t T,
+    pub geometry: GeometryCtx,
+    pub material: M,
+    pub log: Vec<String>,
+}
+
+impl<'t, T: Debug> Ctx<'t, T> {
+    fn new(version: &'t T) -> Self {
+        let geometry = GeometryCtx::default();
+        let material = MaterialCtx::default();
+        let log = vec![];
+        Self { version, geometry, material, log }
+    }
+}
+
+/// A view type alias with the struct parameters.
+type GeometryView<'a, 't, T> = p!(<'a mut geometry, 'a version> Ctx<'t, T>);
+
+// =============
+// === Utils ===
+// =============
+
+fn add_point<'t, T: Debug>(ctx: p!(&<mut geometry, version> Ctx<'t, T>)) {
+    let point = ctx.geometry.points.len() as f32;
+    ctx.geometry.points.push(point);
+    let _ = format!("{:?}", *ctx.version);
+}
+
+fn add_material<'t, T: Debug, M: Default + Extend<String>>(ctx: p!(&<mut material> Ctx<'t, T, M>)) {
+    ctx.material.extend(["default".to_string()]);
+}
+
+fn log_version<'t, T: Debug>(ctx: p!(&<mut log, version> Ctx<'t, T>)) {
+    let version = format!("{:?}", *ctx.version);
+    ctx.log.push(version);
+}
+
+fn render<'t, T: Debug>(ctx: p!(&<mut *> Ctx<'t, T>)) {
+    add_point(p!(&mut ctx));
+    log_version(p!(&mut ctx));
+}
+
+fn geometry_view<'a, 't, T: Debug>(ctx: &'a mut Ctx<'t, T>) -> GeometryView<'a, 't, T> {
+    p!(move ctx.as_refs_mut())
+}
+
+impl<'a, 't, T: Debug> p!(<'a mut geometry, 'a version> Ctx<'t, T>) {
+    fn point_count(&self) -> usize {
+        self.geometry.points.len()
+    }
+}
+
+#[borrow::partial_impl]
+impl<T: Debug> p!(<mut log, version> Ctx<'_, T>) {
+    fn log_twice(&mut self) {
+        log_version(p!(&mut self));
+        log_version(p!(&mut self));
+    }
+}
+
+impl Extend<String> for MaterialCtx {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.names.extend(iter);
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_generic_value_position() {
+    let version = 1;
+    let mut ctx = Ctx::new(&version);
+    render(p!(&mut ctx));
+    add_material(p!(&mut ctx));
+    assert_eq!(ctx.geometry.points, [0.0]);
+    assert_eq!(ctx.material.names, ["default"]);
+    assert_eq!(ctx.log, ["1"]);
+}
+
+#[test]
+fn test_generic_default_param() {
+    let version = "v1";
+    let mut ctx = Ctx::<&str, Vec<String>> {
+        version: &version,
+        geometry: GeometryCtx::default(),
+        material: vec![],
+        log: vec![],
+    };
+    add_material(p!(&mut ctx));
+    assert_eq!(ctx.material, ["default"]);
+}
+
+#[test]
+fn test_generic_split() {
+    let version = 2;
+    let mut ctx = Ctx::new(&version);
+    let mut ctx_ref = ctx.as_refs_mut();
+    let (mut geometry, mut rest) = ctx_ref.split::<p!(<mut geometry> Ctx<usize>)>();
+    geometry.geometry.points.push(1.0);
+    log_version(p!(&mut rest));
+    let (mut log, _) = rest.borrow_log_mut();
+    log.push("done".into());
+    assert_eq!(ctx.geometry.points, [1.0]);
+    assert_eq!(ctx.log, ["2", "done"]);
+}
+
+#[test]
+fn test_generic_alias_and_impl() {
+    let version = 3;
+    let mut ctx = Ctx::new(&version);
+    let mut view = geometry_view(&mut ctx);
+    add_point(p!(&mut view));
+    assert_eq!(view.point_count(), 1);
+    let mut log = ctx.partial_borrow::<p!(<mut log, version> Ctx<usize>)>();
+    log.log_twice();
+    assert_eq!(ctx.log, ["3", "3"]);
+}
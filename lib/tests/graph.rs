@@ -97,3 +97,14 @@ fn test() {
         assert!(edge.to.is_none());
     }
 }
+
+#[test]
+#[cfg(not(field_usage_tracking_enabled))]
+fn test_borrowed_field_is_plain_reference() {
+    let mut graph = Graph { nodes: vec![], edges: vec![], groups: vec![] };
+    let mut graph_ref = graph.as_refs_mut();
+    let (nodes, _) = graph_ref.borrow_nodes_mut();
+    let nodes: &mut Vec<Node> = nodes;
+    nodes.push(Node { outputs: vec![], inputs: vec![] });
+    assert_eq!(graph.nodes.len(), 1);
+}
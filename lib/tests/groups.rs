@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct Geometry;
+
+#[derive(Debug, Default)]
+pub struct Material;
+
+#[derive(Debug, Default)]
+pub struct Mesh;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(render = "geometry, material, mesh"))]
+#[borrow(group(assets = "geometry, material"))]
+#[borrow(group(draw = "mut mesh, geometry, mut log"))]
+pub struct Ctx {
+    pub geometry: Vec<Geometry>,
+    pub material: Vec<Material>,
+    pub mesh: Vec<Mesh>,
+    pub log: Vec<String>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn render(ctx: p!(&<mut geometry, mut material, mut mesh> Ctx)) {
+    ctx.geometry.push(Geometry);
+    ctx.material.push(Material);
+    ctx.mesh.push(Mesh);
+}
+
+fn count_assets(ctx: p!(&<geometry, material> Ctx)) -> usize {
+    ctx.geometry.len() + ctx.material.len()
+}
+
+fn log(ctx: p!(&<mut log> Ctx), msg: &str) {
+    ctx.log.push(msg.to_string());
+}
+
+fn render_with_shared_material(ctx: p!(&<mut @render, material> Ctx)) {
+    ctx.mesh.push(Mesh);
+    ctx.geometry.push(Geometry);
+    let _ = ctx.material.len();
+}
+
+fn draw(ctx: p!(&<@draw> Ctx)) {
+    ctx.mesh.push(Mesh);
+    ctx.log.push(format!("drawn {}", ctx.geometry.len()));
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_group_mut() {
+    let mut ctx = Ctx::default();
+    let mut ctx_ref = ctx.as_refs_mut();
+    let (mut render_ctx, mut rest) = ctx_ref.borrow_render_mut();
+    log(p!(&mut rest), "render start");
+    render(&mut render_ctx);
+    log(p!(&mut rest), "render end");
+    assert_eq!(ctx.mesh.len(), 1);
+    assert_eq!(ctx.log, vec!["render start", "render end"]);
+}
+
+#[test]
+fn test_group_ref() {
+    let mut ctx = Ctx::default();
+    ctx.geometry.push(Geometry);
+    let mut ctx_ref = ctx.as_refs_mut();
+    let (mut assets, mut rest) = ctx_ref.borrow_assets();
+    let count = count_assets(&mut assets);
+    // Shared fields stay accessible in the rest.
+    assert_eq!(count_assets(p!(&mut rest)), count);
+    log(p!(&mut rest), "counted");
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_group_field_override() {
+    let mut ctx = Ctx::default();
+    render_with_shared_material(p!(&mut ctx));
+    assert_eq!((ctx.geometry.len(), ctx.material.len(), ctx.mesh.len()), (1, 0, 1));
+    type Expected = p!(<'static mut geometry, 'static material, 'static mut mesh> Ctx);
+    assert!(same_type::<p!(<'static mut @render, 'static material> Ctx), Expected>());
+}
+
+#[test]
+fn test_group_field_override_precedence() {
+    // Later selectors override earlier ones, so the group overrides the preceding field selector.
+    type AllMut = p!(<'static mut geometry, 'static mut material, 'static mut mesh> Ctx);
+    assert!(same_type::<p!(<'static material, 'static mut @render> Ctx), AllMut>());
+    type AssetsShared = p!(<'static geometry, 'static material, 'static mut mesh> Ctx);
+    assert!(same_type::<p!(<'static mut @render, 'static @assets> Ctx), AssetsShared>());
+    type MaterialMut = p!(<'static geometry, 'static mut material> Ctx);
+    assert!(same_type::<p!(<'static @assets, 'static mut material> Ctx), MaterialMut>());
+}
+
+#[test]
+fn test_group_declared_access() {
+    let mut ctx = Ctx::default();
+    ctx.geometry.push(Geometry);
+    draw(p!(&mut ctx));
+    assert_eq!((ctx.mesh.len(), ctx.log.as_slice()), (1, ["drawn 1".to_string()].as_slice()));
+    type Declared = p!(<'static mut mesh, 'static geometry, 'static mut log> Ctx);
+    assert!(same_type::<p!(<'static @draw> Ctx), Declared>());
+    // An explicit access overrides the accesses given in the declaration.
+    type AllMut = p!(<'static mut mesh, 'static mut geometry, 'static mut log> Ctx);
+    assert!(same_type::<p!(<'static mut @draw> Ctx), AllMut>());
+    type DeclaredExceptLog = p!(<'static mut mesh, 'static geometry> Ctx);
+    assert!(same_type::<p!(<'static @draw - log> Ctx), DeclaredExceptLog>());
+}
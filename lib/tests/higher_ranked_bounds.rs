@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Pipeline<F, G: for<'b> Fn(&'b str) -> &'b str>
+where F: for<'a> Fn(&'a str) -> usize, for<'c> &'c G: Copy {
+    pub measure: F,
+    pub trim: G,
+    pub log: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn run<F, G>(pipeline: p!(&<measure, trim, mut log> Pipeline<F, G>), input: &str)
+where
+    F: for<'a> Fn(&'a str) -> usize,
+    G: for<'b> Fn(&'b str) -> &'b str,
+    for<'c> &'c G: Copy,
+{
+    let len = (pipeline.measure)((pipeline.trim)(input));
+    pipeline.log.push(len);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_higher_ranked_bounds() {
+    let mut pipeline = Pipeline { measure: str::len, trim: str::trim, log: vec![] };
+    run(p!(&mut pipeline), " abc ");
+    let mut pipeline_ref = pipeline.as_refs_mut();
+    let (mut log, mut rest) = pipeline_ref.borrow_log_mut();
+    log.push((rest.measure)("de"));
+    assert_eq!(pipeline.log, [3, 2]);
+}
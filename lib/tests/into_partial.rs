@@ -0,0 +1,89 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Keeps a partial borrow alive across statements.
+pub struct NodeBuilder<'t> {
+    graph: p!(<'t mut nodes, 't edges> Graph),
+}
+
+impl<'t> NodeBuilder<'t> {
+    fn new(graph: &'t mut Graph) -> Self {
+        Self { graph: p!(move graph.as_refs_mut()) }
+    }
+
+    fn from_struct(graph: &'t mut Graph) -> Self {
+        Self { graph: graph.into_partial_borrow() }
+    }
+
+    fn add(&mut self) -> usize {
+        let id = self.graph.nodes.len() + self.graph.edges.len();
+        self.graph.nodes.push(id);
+        id
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn edges_view<'t>(graph: p!(<'t mut *> Graph)) -> p!(<'t mut edges> Graph) {
+    p!(move graph)
+}
+
+fn nodes_view(graph: &mut Graph) -> p!(<nodes> Graph) {
+    p!(move graph.as_refs_mut())
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_move_from_struct() {
+    let mut graph = Graph { edges: vec![(0, 0)], ..Default::default() };
+    let mut builder = NodeBuilder::new(&mut graph);
+    builder.add();
+    builder.add();
+    assert_eq!(graph.nodes, [1, 2]);
+}
+
+#[test]
+fn test_move_narrows_view() {
+    let mut graph = Graph::default();
+    let mut edges = edges_view(graph.as_refs_mut());
+    edges.edges.push((0, 1));
+    assert_eq!(graph.edges, [(0, 1)]);
+    assert_eq!(nodes_view(&mut graph).nodes.len(), 0);
+}
+
+#[test]
+fn test_into_partial_from_struct() {
+    let mut graph = Graph { edges: vec![(0, 0)], ..Default::default() };
+    let mut builder = NodeBuilder::from_struct(&mut graph);
+    builder.add();
+    let (mut nodes, rest) = (&mut graph).into_split::<p!(<mut nodes> Graph)>();
+    nodes.nodes.push(0);
+    assert_eq!(rest.edges.len(), 1);
+    assert_eq!(graph.nodes, [1, 0]);
+}
+
+#[test]
+fn test_into_partial_from_leaked_box() {
+    let graph: &'static mut Graph = Box::leak(Box::default());
+    let mut nodes = graph.into_partial_borrow::<p!(<'static mut nodes> Graph)>();
+    nodes.nodes.push(0);
+    assert_eq!(nodes.nodes.len(), 1);
+}
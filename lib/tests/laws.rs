@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+use borrow::laws;
+use borrow::partial as p;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_split_laws() {
+    laws::check_split::<p!(<mut nodes> Graph), p!(<mut *> Graph)>();
+    laws::check_split::<p!(<nodes, edges> Graph), p!(<mut *> Graph)>();
+    laws::check_split::<p!(<nodes> Graph), p!(<nodes, mut edges> Graph)>();
+    laws::check_split::<p!(<> Graph), p!(<mut nodes, groups> Graph)>();
+    laws::check_split::<p!(<mut *> Graph), p!(<mut *> Graph)>();
+}
+
+#[test]
+fn test_upgradeable_split_laws() {
+    laws::check_split::<p!(<upgradeable nodes> Graph), p!(<mut *> Graph)>();
+    laws::check_split::<p!(<nodes> Graph), p!(<upgradeable nodes, edges> Graph)>();
+    laws::check_split::<p!(<mut nodes> Graph), p!(<upgradeable *> Graph)>();
+    laws::check_split::<p!(<upgradeable nodes> Graph), p!(<upgradeable *> Graph)>();
+}
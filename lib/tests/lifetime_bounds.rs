@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Arena<'a, 'b: 'a, T>
+where 'b: 'a, T: 'b {
+    pub items: &'a mut Vec<&'b T>,
+    pub pinned: &'b T,
+    pub log: Vec<&'a str>,
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Nested<'a, 'b, 'c>
+where 'a: 'b, 'b: 'c {
+    pub outer: &'c &'b &'a str,
+    pub lens: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn push_pinned<'a, 'b: 'a, T>(arena: p!(&<mut items, pinned> Arena<'a, 'b, T>)) {
+    arena.items.push(*arena.pinned);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_lifetime_bounds() {
+    let pinned = 3;
+    let mut items = vec![];
+    let mut arena = Arena { items: &mut items, pinned: &pinned, log: vec![] };
+    push_pinned(p!(&mut arena));
+    let mut arena_ref = arena.as_refs_mut();
+    let (mut log, rest) = arena_ref.borrow_log_mut();
+    log.push("pushed");
+    assert_eq!(rest.items.len(), 1);
+    assert_eq!(arena.log, ["pushed"]);
+    assert_eq!(items, [&3]);
+}
+
+#[test]
+fn test_lifetime_bounds_chain() {
+    let name = "a";
+    let name_ref = &name;
+    let mut nested = Nested { outer: &name_ref, lens: vec![] };
+    let mut nested_ref = nested.as_refs_mut();
+    let (mut lens, rest) = nested_ref.borrow_lens_mut();
+    lens.push(rest.outer.len());
+    assert_eq!(nested.lens, [1]);
+}
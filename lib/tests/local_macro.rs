@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+// Structs with the same name in different modules. Their struct macros would collide at the crate
+// root if they were exported with `#[macro_export]`.
+
+pub mod render {
+    pub mod config {
+        #[derive(Debug, Default, borrow::Partial)]
+        #[module(crate::render::config)]
+        #[borrow(macro_export = false, group(output = "width, height"))]
+        pub struct Config {
+            pub width: usize,
+            pub height: usize,
+            pub title: String,
+        }
+    }
+}
+
+pub mod audio {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(macro_export = false)]
+    pub struct Config {
+        pub volume: usize,
+        pub device: String,
+    }
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(macro_export = false, transparent)]
+    pub struct Settings(pub crate::audio::Config);
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn resize(config: p!(&<mut @output> render::config::Config)) {
+    **config.width *= 2;
+    **config.height *= 2;
+}
+
+fn mute(config: p!(&<mut volume> audio::Config)) {
+    **config.volume = 0;
+}
+
+fn mute_settings(settings: p!(&<mut volume> audio::Settings)) {
+    mute(p!(&mut settings));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_local_macro() {
+    let mut video = render::config::Config { width: 2, height: 1, ..Default::default() };
+    resize(p!(&mut video));
+    assert_eq!((video.width, video.height), (4, 2));
+    let mut audio = audio::Settings(audio::Config { volume: 3, ..Default::default() });
+    mute_settings(p!(&mut audio));
+    assert_eq!(audio.0.volume, 0);
+}
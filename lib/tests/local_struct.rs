@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_local_struct() {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(local)]
+    struct Graph {
+        nodes: Vec<usize>,
+        edges: Vec<(usize, usize)>,
+    }
+
+    fn add_node(graph: p!(&<mut nodes> Graph)) -> usize {
+        let node = graph.nodes.len();
+        graph.nodes.push(node);
+        node
+    }
+
+    fn connect(graph: p!(&<mut *> Graph)) {
+        let from = add_node(p!(&mut graph));
+        let to = add_node(p!(&mut graph));
+        graph.edges.push((from, to));
+    }
+
+    let mut graph = Graph::default();
+    connect(p!(&mut graph));
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_local_generic_struct() {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(local, group(data = "items, count"))]
+    struct Storage<T> {
+        items: Vec<T>,
+        count: usize,
+        #[borrow(readonly)]
+        name: String,
+    }
+
+    fn push<T>(storage: p!(&<mut @data, name> Storage<T>), item: T) {
+        storage.items.push(item);
+        **storage.count += 1;
+        let _ = storage.name.len();
+    }
+
+    let mut storage = Storage::<&str> { name: "storage".into(), ..Default::default() };
+    push(p!(&mut storage), "item");
+    let mut storage_ref = storage.as_refs_mut();
+    let (items, _) = storage_ref.split::<p!(<items> Storage<&str>)>();
+    assert_eq!(**items.items, ["item"]);
+    assert_eq!(storage.count, 1);
+}
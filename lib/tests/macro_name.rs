@@ -0,0 +1,100 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+// Structs with the same name in different modules, with struct macros exported with
+// `#[macro_export]`.
+
+pub mod physics {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::physics)]
+    pub struct State {
+        pub positions: Vec<f32>,
+        pub velocities: Vec<f32>,
+    }
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::physics)]
+    #[borrow(transparent)]
+    pub struct World(pub State);
+}
+
+pub mod input {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::input)]
+    pub struct State {
+        pub keys: Vec<char>,
+        pub mouse: (f32, f32),
+    }
+}
+
+pub mod audio {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(macro_name = AudioStateMacro)]
+    pub struct State {
+        pub volume: f32,
+    }
+}
+
+pub mod video {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(macro_name = VideoStateMacro)]
+    pub struct State {
+        pub brightness: f32,
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn step(state: p!(&<mut positions, velocities> physics::State)) {
+    for (position, velocity) in state.positions.iter_mut().zip(state.velocities.iter()) {
+        *position += velocity;
+    }
+}
+
+fn step_world(world: p!(&<mut positions, velocities> physics::World)) {
+    step(p!(&mut world));
+}
+
+fn press(state: p!(&<mut keys> input::State), key: char) {
+    state.keys.push(key);
+}
+
+fn mute(state: p!(&<mut volume> audio::State)) {
+    **state.volume = 0.0;
+}
+
+fn dim(state: p!(&<mut brightness> video::State)) {
+    **state.brightness /= 2.0;
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_same_name_in_modules() {
+    let mut world = physics::World(physics::State { positions: vec![1.0], velocities: vec![2.0] });
+    step_world(p!(&mut world));
+    assert_eq!(world.0.positions, [3.0]);
+    let mut input = input::State::default();
+    press(p!(&mut input), 'a');
+    assert_eq!(input.keys, ['a']);
+}
+
+#[test]
+fn test_macro_name() {
+    let mut audio = audio::State { volume: 1.0 };
+    mute(p!(&mut audio));
+    assert_eq!(audio.volume, 0.0);
+    let mut video = video::State { brightness: 1.0 };
+    dim(p!(&mut video));
+    assert_eq!(video.brightness, 0.5);
+}
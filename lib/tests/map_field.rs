@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::ops::Range;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct Node {
+    pub weight: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Meta {
+    pub visits: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<(usize, usize)>,
+    pub meta: Meta,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn new_graph() -> Graph {
+    let nodes = (0..4).map(|weight| Node { weight }).collect();
+    Graph { nodes, edges: vec![(0, 1)], meta: Meta::default() }
+}
+
+/// Doubles the weights of the nodes in the range and counts the visit.
+fn double_weights(graph: p!(&<mut nodes, mut meta> Graph), range: Range<usize>) {
+    let view = graph.partial_borrow::<p!(<mut nodes, mut meta> Graph)>();
+    let mut view = view
+        .map_nodes(|nodes| &mut nodes[range])
+        .map_meta(|meta| &mut meta.visits);
+    for node in view.nodes.iter_mut() {
+        node.weight *= 2;
+    }
+    **view.meta += 1;
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_map_field_sub_slice() {
+    let mut graph = new_graph();
+    double_weights(p!(&mut graph), 1..3);
+    let weights = graph.nodes.iter().map(|node| node.weight).collect::<Vec<_>>();
+    assert_eq!(weights, [0, 2, 4, 3]);
+    assert_eq!(graph.meta.visits, 1);
+}
+
+#[test]
+fn test_map_field_keeps_other_fields() {
+    let mut graph = new_graph();
+    let mut view = graph.as_refs_mut().map_meta(|meta| &mut meta.name);
+    view.meta.push_str("graph");
+    view.edges.clear();
+    assert_eq!(view.nodes.len(), 4);
+    assert_eq!(graph.meta.name, "graph");
+    assert!(graph.edges.is_empty());
+}
+
+#[test]
+fn test_map_field_shared() {
+    let mut graph = new_graph();
+    let mut graph_ref = graph.as_refs_mut();
+    let view = graph_ref.partial_borrow::<p!(<nodes> Graph)>();
+    let view = view.map_nodes(|nodes| nodes.last());
+    assert_eq!(view.nodes.map(|node| node.weight), Some(3));
+}
@@ -0,0 +1,82 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+use std::marker::PhantomData;
+use std::marker::PhantomPinned;
+
+// ============
+// === Data ===
+// ============
+
+type Unit<T> = PhantomData<fn() -> T>;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(values = "nodes, edges"))]
+pub struct Graph<T> {
+    pub nodes: Vec<usize>,
+    pub kind: PhantomData<T>,
+    pub edges: Vec<(usize, usize)>,
+    pub pinned: PhantomPinned,
+    #[borrow(marker)]
+    pub unit: Unit<T>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Pair<T>(pub PhantomData<T>, pub usize, pub usize);
+
+// =============
+// === Utils ===
+// =============
+
+fn connect<T>(graph: p!(&<nodes, mut edges> Graph<T>)) {
+    graph.edges.push((graph.nodes[0], graph.nodes[1]));
+}
+
+fn add_node<T>(graph: p!(&<mut *> Graph<T>)) {
+    let id = graph.nodes.len();
+    graph.nodes.push(id);
+    if id > 0 {
+        connect(p!(&mut graph));
+    }
+}
+
+fn swap<T>(pair: p!(&<mut 1, mut 2> Pair<T>)) {
+    std::mem::swap(&mut **pair._1, &mut **pair._2);
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_marker_fields_are_not_borrowed() {
+    let mut graph = Graph::<char>::default();
+    add_node(p!(&mut graph));
+    add_node(p!(&mut graph));
+    assert_eq!(graph.nodes, [0, 1]);
+    assert_eq!(graph.edges, [(0, 1)]);
+    let mut graph_ref = graph.as_refs_mut();
+    let (values, _) = graph_ref.borrow_values();
+    assert_eq!(values.nodes.len(), 2);
+}
+
+#[test]
+fn test_marker_fields_not_in_ref_struct() {
+    type Full = GraphRef<Graph<char>, &'static mut Vec<usize>, &'static mut Vec<(usize, usize)>>;
+    assert!(same_type::<p!(<'static mut *> Graph<char>), Full>());
+}
+
+#[test]
+fn test_marker_fields_in_tuple_struct() {
+    let mut pair = Pair::<char>(PhantomData, 1, 2);
+    swap(p!(&mut pair));
+    assert_eq!((pair.1, pair.2), (2, 1));
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::Memo;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Scene {
+    pub mesh: Vec<f32>,
+    pub name: String,
+    pub lights: Vec<f32>,
+    #[borrow(dirty_flags)]
+    pub dirty: borrow::DirtyFlags<Scene>,
+}
+
+type BoundsMemo = Memo<p!('static <mesh, name> Scene), (usize, f32)>;
+
+// =============
+// === Utils ===
+// =============
+
+/// Returns the number of computations as well, so tests can check whether the memo was used.
+fn bounds(scene: p!(&<mesh, name> Scene), count: &mut usize) -> (usize, f32) {
+    *count += 1;
+    (*count, scene.mesh.iter().copied().fold(scene.name.len() as f32, f32::max))
+}
+
+fn add_vertex(scene: p!(&<mut mesh> Scene), vertex: f32) {
+    scene.mesh.push(vertex);
+}
+
+fn dim_lights(scene: p!(&<mut lights> Scene)) {
+    for light in scene.lights.iter_mut() {
+        *light *= 0.5;
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_memo_invalidated_by_mutable_usage() {
+    let mut scene = Scene::default();
+    let mut memo = BoundsMemo::new(&scene.dirty);
+    let mut count = 0;
+    assert!(memo.get().is_none());
+    add_vertex(p!(&mut scene), 2.0);
+    assert_eq!(*memo.get_or_compute(|| bounds(p!(&mut scene), &mut count)), (1, 2.0));
+    add_vertex(p!(&mut scene), 3.0);
+    assert!(!memo.is_valid());
+    assert_eq!(*memo.get_or_compute(|| bounds(p!(&mut scene), &mut count)), (2, 3.0));
+}
+
+#[test]
+fn test_memo_invalidate() {
+    let mut scene = Scene::default();
+    let mut memo = BoundsMemo::new(&scene.dirty);
+    let mut count = 0;
+    memo.get_or_compute(|| bounds(p!(&mut scene), &mut count));
+    memo.invalidate();
+    assert_eq!(memo.get_or_compute(|| bounds(p!(&mut scene), &mut count)).0, 2);
+}
+
+#[test]
+#[cfg(field_usage_tracking_enabled)]
+fn test_memo_kept_when_other_fields_change() {
+    let mut scene = Scene { lights: vec![1.0], ..Default::default() };
+    let mut memo = BoundsMemo::new(&scene.dirty);
+    let mut count = 0;
+    memo.get_or_compute(|| bounds(p!(&mut scene), &mut count));
+    dim_lights(p!(&mut scene));
+    scene.dirty.clear();
+    assert!(memo.is_valid());
+    assert_eq!(memo.get(), Some(&(1, 0.0)));
+    assert_eq!(memo.get_or_compute(|| bounds(p!(&mut scene), &mut count)).0, 1);
+}
+
+#[test]
+#[cfg(not(field_usage_tracking_enabled))]
+fn test_memo_without_usage_tracking() {
+    let mut scene = Scene::default();
+    let mut memo = BoundsMemo::new(&scene.dirty);
+    let mut count = 0;
+    memo.get_or_compute(|| bounds(p!(&mut scene), &mut count));
+    dim_lights(p!(&mut scene));
+    assert!(!memo.is_valid());
+}
@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(monomorphize)]
+#[borrow(group(topology = "nodes, edges"))]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+#[derive(Debug, borrow::Partial)]
+#[module(crate)]
+#[borrow(monomorphize)]
+pub struct Ctx<'t> {
+    pub name: &'t str,
+    pub log:  Vec<String>,
+}
+
+// ==============
+// === Passes ===
+// ==============
+
+fn fill(graph: p!(&<mut *> Graph)) {
+    graph.nodes.push(0);
+    graph.edges.push(0);
+    graph.groups.push(0);
+}
+
+fn count(graph: p!(&<*> Graph)) -> usize {
+    graph.nodes.len() + graph.edges.len() + graph.groups.len()
+}
+
+fn connect(graph: p!(&<mut nodes, mut edges> Graph)) {
+    graph.edges.push(graph.nodes.len());
+}
+
+fn count_topology(graph: p!(&<nodes, edges> Graph)) -> usize {
+    graph.nodes.len() + graph.edges.len()
+}
+
+fn log(ctx: p!(&<name, mut log> Ctx)) {
+    ctx.log.push(ctx.name.to_string());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_monomorphized_shapes() {
+    let mut graph = Graph::default();
+    fill(&mut graph.borrow_all_mut());
+    connect(&mut graph.borrow_topology_mut());
+    assert_eq!(count(&mut graph.borrow_all()), 4);
+    assert_eq!(count_topology(&mut graph.borrow_topology()), 3);
+
+    // The shapes can be narrowed further, like any other partial borrow.
+    let mut topology = graph.borrow_topology_mut();
+    connect(p!(&mut topology));
+    assert_eq!(graph.edges, vec![0, 1, 1]);
+}
+
+#[test]
+fn test_monomorphized_shapes_with_lifetimes() {
+    let name = String::from("ctx");
+    let mut ctx = Ctx { name: &name, log: vec![] };
+    let mut all = ctx.borrow_all_mut();
+    log(p!(&mut all));
+    assert_eq!(ctx.log, vec!["ctx"]);
+}
@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+/// The first version of a library struct. The next minor release adds the `cache` field, and
+/// call sites written against it should keep compiling with this version.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(non_exhaustive)]
+pub struct Scene {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(scene: p!(&<mut nodes, mut cache> Scene)) {
+    scene.nodes.push(0);
+}
+
+fn count(scene: p!(&<nodes, edges, cache> Scene)) -> usize {
+    scene.nodes.len() + scene.edges.len()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_unknown_fields_are_hidden() {
+    let mut scene = Scene::default();
+    add_node(p!(&mut scene));
+    assert_eq!(count(p!(&mut scene)), 1);
+}
+
+#[test]
+fn test_unknown_fields_do_not_change_type() {
+    let mut scene = Scene::default();
+    let mut scene_ref = scene.as_refs_mut();
+    let view: &mut p!(<mut nodes, cache> Scene) = p!(&mut scene_ref);
+    let view: &mut p!(<mut nodes> Scene) = view;
+    view.nodes.push(0);
+    assert_eq!(scene.nodes.len(), 1);
+}
@@ -0,0 +1,57 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::panic::Location;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct World {
+    #[borrow(on_mut = "record_touch")]
+    pub scene: Vec<usize>,
+    pub time: f32,
+}
+
+/// Fields and lines of the mutable accesses recorded by the hook, in this file only.
+static TOUCHES: Mutex<Vec<(&'static str, u32)>> = Mutex::new(vec![]);
+
+// =============
+// === Utils ===
+// =============
+
+fn record_touch(field: &'static str, location: &'static Location<'static>) {
+    if location.file() == file!() {
+        TOUCHES.lock().unwrap_or_else(PoisonError::into_inner).push((field, location.line()));
+    }
+}
+
+/// Returns the line of the mutable access to the scene.
+fn spawn(world: p!(&<mut scene, mut time> World)) -> u32 {
+    world.scene.push(0); let line = line!();
+    **world.time += 1.0;
+    line
+}
+
+fn count(world: p!(&<scene> World)) -> usize {
+    world.scene.len()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_on_mut() {
+    let mut world = World::default();
+    let line = spawn(p!(&mut world));
+    assert_eq!(count(p!(&mut world)), 1);
+    let touches = TOUCHES.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let expected = if cfg!(field_usage_tracking_enabled) { vec![("scene", line)] } else { vec![] };
+    assert_eq!(touches, expected);
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+#![allow(clippy::unwrap_used)]
+
+use borrow::partial as p;
+use borrow::OwnedRef;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+type NodesRef<G> = OwnedRef<G, p!('static <mut nodes, edges> Graph)>;
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes, edges> Graph)) {
+    let id = graph.nodes.len() + graph.edges.len();
+    graph.nodes.push(id);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_moved_to_thread() {
+    let mut nodes: NodesRef<Box<Graph>> = OwnedRef::new(Box::default());
+    let handle = std::thread::spawn(move || {
+        add_node(&mut nodes.view());
+        add_node(&mut nodes.view());
+        nodes
+    });
+    let graph = handle.join().unwrap().into_inner();
+    assert_eq!(graph.nodes, vec![0, 1]);
+}
+
+#[test]
+fn test_mutex_guard() {
+    let graph = Arc::new(Mutex::new(Graph::default()));
+    let mut nodes: NodesRef<_> = OwnedRef::new(graph.lock().unwrap());
+    add_node(&mut nodes.view());
+    drop(nodes);
+    assert_eq!(graph.lock().unwrap().nodes, vec![0]);
+}
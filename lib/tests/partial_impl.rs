@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, borrow::Partial)]
+#[module(crate)]
+pub struct Graph<'t, 's, T> {
+    pub nodes: Vec<&'t T>,
+    pub names: Vec<&'s str>,
+    pub edges: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn name_count<'t, 's, T>(graph: p!(&<names> Graph<'t, 's, T>)) -> usize {
+    graph.names.len()
+}
+
+#[borrow::partial_impl]
+impl<T> p!(<mut nodes, mut names, edges> Graph<'_, '_, T>) {
+    fn first(&self) -> Option<&T> {
+        self.nodes.first().copied()
+    }
+
+    fn clear_nodes(&mut self) -> usize {
+        let (mut nodes, mut rest) = self.borrow_nodes_mut();
+        nodes.clear();
+        name_count(p!(&mut rest))
+    }
+}
+
+#[borrow::partial_impl]
+impl<T> p!(<mut names> Graph<'_, '_, T>) {
+    fn rename(&mut self, name: &'static str) {
+        self.names.push(name);
+    }
+}
+
+#[borrow::partial_impl]
+impl<T> Graph<'_, '_, T> {
+    fn nodes_view(&mut self) -> p!(<mut nodes> Self) {
+        self.as_refs_mut().into_partial_borrow()
+    }
+
+    fn name_count(&mut self) -> usize {
+        name_count(&mut self.partial_borrow::<p!(<names> Self)>())
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_partial_impl_with_elided_lifetimes() {
+    let node = 1;
+    let mut graph = Graph { nodes: vec![&node], names: vec!["a"], edges: vec![] };
+    let mut graph_ref = graph.as_refs_mut();
+    let mut view = graph_ref.partial_borrow::<p!(<mut nodes, mut names, edges> Graph<usize>)>();
+    assert_eq!(view.first(), Some(&1));
+    assert_eq!(view.clear_nodes(), 1);
+    view.partial_borrow::<p!(<mut names> Graph<usize>)>().rename("b");
+    assert!(graph.nodes.is_empty());
+    assert_eq!(graph.names, ["a", "b"]);
+}
+
+#[test]
+fn test_partial_impl_with_self_target() {
+    let node = 1;
+    let mut graph = Graph { nodes: vec![], names: vec!["a"], edges: vec![] };
+    graph.nodes_view().nodes.push(&node);
+    assert_eq!(graph.name_count(), 1);
+    assert_eq!(graph.nodes, [&1]);
+}
@@ -0,0 +1,58 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[borrow::partial_struct(module = crate, group(topology = "nodes, edges"))]
+#[derive(Debug, Default)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    #[borrow(readonly)]
+    pub name: String,
+}
+
+pub mod data {
+    #[borrow::partial_struct(module = crate::data, macro_export = false)]
+    #[derive(Debug, Default)]
+    pub struct Config {
+        pub width: usize,
+        pub height: usize,
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_edge(graph: p!(&<mut @topology, name> Graph)) {
+    graph.nodes.extend([0, 1]);
+    graph.edges.push((0, 1));
+    let _ = graph.name.len();
+}
+
+fn resize(config: p!(&<mut width, height> data::Config)) {
+    **config.width = **config.height * 2;
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_partial_struct() {
+    let mut graph = Graph { name: "graph".into(), ..Default::default() };
+    add_edge(p!(&mut graph));
+    assert_eq!((graph.nodes.len(), graph.edges.len()), (2, 1));
+}
+
+#[test]
+fn test_partial_struct_in_module() {
+    let mut config = data::Config { width: 0, height: 2 };
+    resize(p!(&mut config));
+    assert_eq!(config.width, 4);
+}
@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(private_mut = "allocator")]
+#[borrow(group(memory = "allocator, log"))]
+pub struct Core {
+    pub allocator: Vec<usize>,
+    pub log: Vec<String>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn alloc(core: p!(&<mut allocator, mut log> Core)) -> usize {
+    let index = core.allocator.len();
+    core.allocator.push(index);
+    core.log.push("alloc".to_string());
+    index
+}
+
+fn allocated(core: p!(&<allocator> Core)) -> usize {
+    core.allocator.len()
+}
+
+fn alloc_all(core: p!(&<mut *> Core)) {
+    alloc(p!(&mut core));
+}
+
+fn alloc_if_empty(core: p!(&<upgradeable allocator> Core)) {
+    if core.allocator.is_empty() {
+        core.allocator.upgrade().push(0);
+    }
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_private_mut_selectors_in_defining_crate() {
+    let mut core = Core::default();
+    alloc_if_empty(p!(&mut core));
+    alloc_all(p!(&mut core));
+    assert_eq!(allocated(p!(&mut core)), 2);
+    assert_eq!(core.log.len(), 1);
+}
+
+#[test]
+fn test_private_mut_selectors_do_not_change_type() {
+    type Target = CoreRef<Core, &'static mut Vec<usize>, borrow::Hidden>;
+    assert!(same_type::<p!('static <mut allocator> Core), Target>());
+}
+
+#[test]
+fn test_private_mut_group() {
+    let mut core = Core::default();
+    let mut core_ref = core.as_refs_mut();
+    let (mut memory, _) = core_ref.borrow_memory_mut();
+    memory.allocator.push(0);
+    assert_eq!(core.allocator.len(), 1);
+}
+
+#[test]
+fn test_private_mut_crate_root() {
+    let mut core = Core::default();
+    core.as_refs_mut().allocator.push(0);
+    let (mut allocator, mut rest) = core.split::<p!(<'_ mut allocator> Core)>();
+    allocator.allocator.push(1);
+    rest.log.push("split".to_string());
+    let mut memory = core.partial_borrow::<p!(<'_ mut allocator, log> Core)>();
+    memory.allocator.push(2);
+    assert_eq!(core.allocator, [0, 1, 2]);
+    assert_eq!(core.log.len(), 1);
+}
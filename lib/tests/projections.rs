@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::sync::Mutex;
+
+// ============
+// === Data ===
+// ============
+
+pub trait Backend {
+    type Output: Default;
+    type Iter;
+}
+
+pub trait Store<'t> {
+    type Item: 't;
+}
+
+#[derive(Debug, Default)]
+pub struct Gl;
+
+impl Backend for Gl {
+    type Output = Vec<usize>;
+    type Iter = std::vec::IntoIter<usize>;
+}
+
+impl<'t> Store<'t> for Gl {
+    type Item = &'t str;
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+#[borrow(group(render = "output, frames"), private_mut = "backend")]
+pub struct Ctx<'t, T>
+where T: Backend + Store<'t>, T::Iter: Clone {
+    pub backend: T,
+    pub output: T::Output,
+    pub items: Vec<<T as Store<'t>>::Item>,
+    pub shared: &'t T::Output,
+    #[borrow(wrap = "Mutex")]
+    pub locked: Mutex<<T as Backend>::Output>,
+    #[borrow(external)]
+    pub external: &'t mut T::Output,
+    pub frames: usize,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn render<'t, T>(ctx: p!(&<mut output, mut items, shared, mut locked> Ctx<'t, T>)) -> usize
+where T: Backend + Store<'t>, T::Iter: Clone {
+    **ctx.output = std::mem::take(&mut **ctx.locked);
+    let _: &T::Output = *ctx.shared;
+    let items = ctx.items.len();
+    ctx.items.clear();
+    items
+}
+
+fn render_all<'t, T>(ctx: p!(&<mut *> Ctx<'t, T>)) -> usize
+where T: Backend + Store<'t>, T::Iter: Clone {
+    let items = render(p!(&mut ctx));
+    let (mut external, mut rest) = ctx.borrow_external_mut();
+    std::mem::swap::<T::Output>(&mut external, &mut rest.output);
+    let (mut render, _) = rest.borrow_render_mut();
+    **render.frames += 1;
+    items
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_associated_type_fields() {
+    let shared = vec![1, 2];
+    let mut external = vec![];
+    let mut ctx = Ctx {
+        backend: Gl,
+        output: vec![],
+        items: vec!["a"],
+        shared: &shared,
+        locked: Mutex::new(vec![3]),
+        external: &mut external,
+        frames: 0,
+    };
+    assert_eq!(render_all(p!(&mut ctx)), 1);
+    assert!(ctx.items.is_empty());
+    assert_eq!(ctx.frames, 1);
+    drop(ctx);
+    assert_eq!(external, [3]);
+}
@@ -0,0 +1,51 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(r#loop = "r#type, r#move"), private_mut = "r#ref")]
+pub struct Token {
+    pub r#type: String,
+    pub r#move: Vec<usize>,
+    pub r#ref: usize,
+    pub text: String,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn rename(token: p!(&<mut r#type, r#move> Token)) {
+    token.r#type.push_str(&token.r#move.len().to_string());
+}
+
+fn all(token: p!(&<mut *> Token)) {
+    let (mut ty, mut rest) = token.borrow_type_mut();
+    ty.push('!');
+    rest.r#move.push(1);
+    rename(p!(&mut token));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_raw_identifier_fields() {
+    let mut token = Token::default();
+    all(p!(&mut token));
+    assert_eq!(token.r#type, "!1");
+    assert_eq!(token.r#move, [1]);
+    let mut token_ref = token.as_refs_mut();
+    let (mut group, _) = token_ref.borrow_loop_mut();
+    group.r#move.clear();
+    let mut view = TokenRef::test_view().with_type("a".into());
+    rename(p!(&mut view));
+    assert_eq!(view.type_mut(), "a0");
+}
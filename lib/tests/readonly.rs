@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(frame = "meshes, config"))]
+pub struct Renderer {
+    pub meshes: Vec<usize>,
+    #[borrow(readonly)]
+    pub config: String,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn mesh_count(renderer: p!(&<meshes, config> Renderer)) -> usize {
+    renderer.meshes.len() + renderer.config.len()
+}
+
+fn draw(renderer: p!(&<mut meshes, config> Renderer)) {
+    renderer.meshes.push(renderer.config.len());
+}
+
+fn draw_all(renderer: p!(&<mut *> Renderer)) {
+    draw(p!(&mut renderer));
+}
+
+fn draw_frame(renderer: p!(&<upgradeable *> Renderer)) {
+    let config_len = renderer.config.len();
+    renderer.meshes.upgrade().push(config_len);
+}
+
+fn draw_with_methods(renderer: &mut Renderer) -> &str {
+    let mut renderer_ref = renderer.as_refs_mut();
+    let (mut frame, _) = renderer_ref.borrow_frame_mut();
+    frame.meshes.push(frame.config.len());
+    let (config, _) = renderer_ref.borrow_config();
+    config.as_str()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_readonly_field_borrowed_as_shared() {
+    let mut renderer = Renderer { config: "gl".to_string(), ..Default::default() };
+    draw_all(p!(&mut renderer));
+    draw_frame(p!(&mut renderer));
+    assert_eq!(mesh_count(p!(&mut renderer)), 4);
+    assert_eq!(renderer.meshes, [2, 2]);
+}
+
+#[test]
+fn test_readonly_field_star_selector() {
+    type All = p!(<'static mut *> Renderer);
+    type Expected = p!(<'static mut meshes, 'static config> Renderer);
+    assert_eq!(TypeId::of::<All>(), TypeId::of::<Expected>());
+}
+
+#[test]
+fn test_readonly_field_methods() {
+    let mut renderer = Renderer { config: "gl".to_string(), ..Default::default() };
+    assert_eq!(draw_with_methods(&mut renderer), "gl");
+    assert_eq!(renderer.meshes, [2]);
+}
+
+#[test]
+fn test_readonly_field_test_view() {
+    let mut view = RendererRef::test_view().with_config("vk".to_string());
+    draw(p!(&mut view));
+    assert_eq!(view.meshes_mut(), &[2]);
+}
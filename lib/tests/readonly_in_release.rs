@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(readonly_in_release = "stats")]
+#[borrow(group(frame = "meshes, stats"))]
+pub struct Renderer {
+    pub meshes: Vec<usize>,
+    pub stats: Vec<String>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn mesh_count(renderer: p!(&<meshes, stats> Renderer)) -> usize {
+    renderer.meshes.len() + renderer.stats.len()
+}
+
+#[cfg(debug_assertions)]
+fn draw(renderer: p!(&<mut meshes, mut stats> Renderer)) {
+    renderer.meshes.push(0);
+    renderer.stats.push("draw".to_string());
+}
+
+#[cfg(debug_assertions)]
+fn draw_all(renderer: p!(&<mut *> Renderer)) {
+    draw(p!(&mut renderer));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_readonly_in_release_shared_selectors() {
+    let mut renderer = Renderer { stats: vec!["init".to_string()], ..Default::default() };
+    assert_eq!(mesh_count(p!(&mut renderer)), 1);
+    let mut renderer_ref = renderer.as_refs_mut();
+    let (frame, _) = renderer_ref.borrow_frame();
+    assert_eq!(frame.stats.len(), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_readonly_in_release_mut_selectors_in_debug() {
+    let mut renderer = Renderer::default();
+    draw_all(p!(&mut renderer));
+    let mut renderer_ref = renderer.as_refs_mut();
+    let (mut stats, _) = renderer_ref.borrow_stats_mut();
+    stats.push("debug".to_string());
+    let (mut frame, _) = renderer_ref.borrow_frame_mut();
+    frame.meshes.push(1);
+    assert_eq!(renderer.stats, ["draw", "debug"]);
+    assert_eq!(renderer.meshes, [0, 1]);
+}
+
+/// The root view borrows the fields as shared in release builds, so they can not be mutated
+/// through `as_refs_mut()` either. The test fails to compile if the field is borrowed mutably.
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_readonly_in_release_root_view_in_release() {
+    fn shared<'t, E: borrow::Bool, T>(field: &borrow::Field<E, &'t T>) -> &'t T {
+        field.value_no_usage_tracking
+    }
+    let mut renderer = Renderer { stats: vec!["init".to_string()], ..Default::default() };
+    let renderer_ref = renderer.as_refs_mut();
+    assert_eq!(shared(&renderer_ref.stats).len(), 1);
+}
@@ -0,0 +1,45 @@
+#![allow(dead_code)]
+#![allow(deprecated)]
+
+use borrow::partial as p;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_redundant_selectors_keep_shape() {
+    type Nodes = p!('static <mut nodes> Graph);
+    assert!(same_type::<p!('static <mut nodes, mut nodes> Graph), Nodes>());
+    assert!(same_type::<p!('static <nodes, mut nodes> Graph), Nodes>());
+    assert!(same_type::<p!('static <edges, mut nodes, - edges> Graph), Nodes>());
+    type All = p!('static <mut *> Graph);
+    assert!(same_type::<p!('static <nodes, mut *> Graph), All>());
+    assert!(same_type::<p!('static <mut *, mut nodes> Graph), All>());
+}
+
+#[test]
+fn test_redundant_selectors_wrapped() {
+    type Nodes = p!('static <mut nodes> Graph);
+    assert!(same_type::<borrow::DuplicateSelector<Nodes>, Nodes>());
+}
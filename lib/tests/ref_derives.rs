@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(derive(Debug, Clone))]
+pub struct Graph<T> {
+    pub nodes: Vec<T>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(derive(Debug))]
+pub struct Pair(pub usize, pub String);
+
+// =============
+// === Utils ===
+// =============
+
+fn describe<T: std::fmt::Debug>(graph: p!(&<nodes, mut edges> Graph<T>)) -> String {
+    graph.edges.push((0, graph.nodes.len()));
+    format!("{graph:?}")
+}
+
+fn node_counts<T>(graph: p!(&<nodes> Graph<T>)) -> (usize, usize) {
+    let clone = graph.clone();
+    (graph.nodes.len(), clone.nodes.len())
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_ref_debug() {
+    let mut graph = Graph { nodes: vec!['a'], ..Default::default() };
+    assert_eq!(describe(p!(&mut graph)), "GraphRef { nodes: ['a'], edges: [(0, 1)] }");
+    let mut pair = Pair(1, "one".to_string());
+    let pair_ref: p!(<0> Pair) = pair.partial_borrow();
+    assert_eq!(format!("{pair_ref:?}"), "PairRef { _0: 1, _1: Hidden }");
+    assert_eq!(**pair_ref._0, 1);
+}
+
+#[test]
+fn test_ref_clone() {
+    let mut graph = Graph { nodes: vec![1, 2], ..Default::default() };
+    assert_eq!(node_counts(p!(&mut graph)), (2, 2));
+}
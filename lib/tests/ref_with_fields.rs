@@ -0,0 +1,71 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::hlist::N0;
+use borrow::hlist::SetItemAtResult;
+use borrow::AsRefWithFields;
+use borrow::AsRefsMut;
+use borrow::FieldsAsHidden;
+use borrow::FieldsAsMut;
+use borrow::FieldsAsRef;
+use borrow::HasFieldsExt;
+use borrow::Hidden;
+use borrow::RefWithFields;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph<T> {
+    pub nodes: Vec<T>,
+    pub edges: Vec<(usize, usize)>,
+    #[borrow(readonly)]
+    pub name: String,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+/// Generic glue of an adapter, creating the borrow of all fields of any struct.
+fn full_borrow<'t, S>(value: &'t mut S) -> RefWithFields<S, FieldsAsMut<'t, S>>
+where S: HasFieldsExt + AsRefWithFields<FieldsAsMut<'t, S>>
+    + AsRefsMut<Target<'t> = RefWithFields<S, FieldsAsMut<'t, S>>> {
+    value.as_refs_mut()
+}
+
+/// Borrow of the first field of any struct, as named by generic code.
+type First<'t, S, T> = RefWithFields<S, SetItemAtResult<FieldsAsHidden<S>, N0, &'t mut T>>;
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_ref_with_fields_types() {
+    type Full = RefWithFields<Graph<usize>, FieldsAsMut<'static, Graph<usize>>>;
+    assert!(same_type::<Full, p!(<'static mut *> Graph<usize>)>());
+    type Shared = RefWithFields<Graph<usize>, FieldsAsRef<'static, Graph<usize>>>;
+    assert!(same_type::<Shared, p!(<'static *> Graph<usize>)>());
+    type Empty = RefWithFields<Graph<usize>, FieldsAsHidden<Graph<usize>>>;
+    assert!(same_type::<Empty, p!(<> Graph<usize>)>());
+    type Nodes = First<'static, Graph<usize>, Vec<usize>>;
+    assert!(same_type::<Nodes, p!(<'static mut nodes> Graph<usize>)>());
+    type Edges = borrow::HList![Hidden, &'static Vec<(usize, usize)>, Hidden];
+    assert!(same_type::<RefWithFields<Graph<usize>, Edges>, p!(<'static edges> Graph<usize>)>());
+}
+
+#[test]
+fn test_ref_with_fields_generic_glue() {
+    let mut graph = Graph::<usize> { name: "g".to_string(), ..Default::default() };
+    let mut full = full_borrow(&mut graph);
+    full.nodes.push(full.name.len());
+    assert_eq!(graph.nodes, [1]);
+}
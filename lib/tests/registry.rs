@@ -0,0 +1,62 @@
+#![cfg(feature = "registry")]
+#![allow(dead_code)]
+
+use borrow::RegisteredStruct;
+
+// ============
+// === Data ===
+// ============
+
+pub mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(group(topology = "nodes, edges"))]
+    pub struct Graph<T> {
+        pub nodes: Vec<T>,
+        pub edges: Vec<(usize, usize)>,
+        #[borrow(readonly)]
+        pub name: String,
+    }
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(first = "0"))]
+pub struct Pair(pub usize, pub usize);
+
+// =============
+// === Utils ===
+// =============
+
+fn find(name: &str) -> Option<&'static RegisteredStruct> {
+    borrow::registered_structs().find(|registered| registered.name == name)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_registry_struct() -> Result<(), String> {
+    let graph = find("Graph").ok_or("Graph is not registered")?;
+    assert_eq!(graph.module, "registry::graph");
+    let fields = graph.fields.iter().map(|f| (f.label, f.ty, f.readonly)).collect::<Vec<_>>();
+    assert_eq!(fields, [
+        ("nodes", "Vec<T>", false),
+        ("edges", "Vec<(usize, usize)>", false),
+        ("name", "String", true),
+    ]);
+    assert_eq!(graph.groups.len(), 1);
+    assert_eq!((graph.groups[0].name, graph.groups[0].fields), ("topology", &["nodes", "edges"][..]));
+    Ok(())
+}
+
+#[test]
+fn test_registry_tuple_struct() -> Result<(), String> {
+    let pair = find("Pair").ok_or("Pair is not registered")?;
+    assert_eq!(pair.module, "registry");
+    let labels = pair.fields.iter().map(|f| f.label).collect::<Vec<_>>();
+    assert_eq!(labels, ["0", "1"]);
+    assert_eq!(pair.groups[0].fields, ["0"]);
+    Ok(())
+}
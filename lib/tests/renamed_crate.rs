@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+
+// Shadows the `borrow` crate, so that the generated code has to use the crate path.
+mod borrow {}
+
+mod framework {
+    pub use ::borrow as pb;
+}
+
+use framework::pb::partial as p;
+use framework::pb::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, framework::pb::Partial)]
+#[module(crate)]
+#[borrow(crate = ::borrow, group(topology = "nodes, edges"))]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    #[borrow(readonly)]
+    pub name: String,
+}
+
+#[derive(Debug, Default, framework::pb::Partial)]
+#[module(crate)]
+#[borrow(crate = ::borrow, transparent)]
+pub struct World(pub Graph);
+
+#[derive(Debug, Default, framework::pb::Partial)]
+#[module(crate)]
+#[borrow(crate = "::borrow")]
+pub struct Pair(pub usize, pub usize);
+
+#[derive(Debug, Default, framework::pb::Partial)]
+#[module(crate)]
+#[borrow(crate_path = "::borrow")]
+pub struct Counter {
+    pub hits: usize,
+}
+
+framework::pb::spec!(TOPOLOGY = <mut nodes, edges>);
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes, name> Graph)) {
+    graph.nodes.push(graph.name.len());
+}
+
+fn connect(graph: p!(_&<nodes, mut edges> Graph)) {
+    graph.edges.push((graph.nodes[0], graph.nodes[1]));
+}
+
+fn push_edge_count(graph: p!(&TOPOLOGY Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn run(world: p!(&<mut *> World)) {
+    add_node(p!(&mut world));
+    add_node(p!(&mut world));
+    connect(p!(&mut world));
+}
+
+#[allow(deprecated)]
+fn add_node_to_world(world: p!(&<nodes, mut nodes> World)) {
+    world.nodes.push(0);
+}
+
+fn hit(counter: p!(&<mut hits> Counter)) {
+    **counter.hits += 1;
+}
+
+#[allow(deprecated)]
+fn swap(pair: p!(&<0, mut 0, mut 1> Pair)) {
+    std::mem::swap(&mut **pair._0, &mut **pair._1);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_renamed_crate() {
+    let mut world = World(Graph { name: "g".to_string(), ..Default::default() });
+    run(p!(&mut world));
+    let graph = &mut world.0;
+    let mut graph_ref = graph.as_refs_mut();
+    let (topology, _) = graph_ref.borrow_topology_mut();
+    assert_eq!((topology.nodes.len(), topology.edges.len()), (2, 1));
+    assert_eq!(world.0.nodes, [1, 1]);
+    assert_eq!(world.0.edges, [(1, 1)]);
+}
+
+#[test]
+fn test_renamed_crate_redundant_selectors() {
+    let mut world = World::default();
+    add_node_to_world(p!(&mut world));
+    assert_eq!(world.0.nodes, [0]);
+}
+
+#[test]
+fn test_renamed_crate_path_alias() {
+    let mut counter = Counter::default();
+    hit(p!(&mut counter));
+    assert_eq!(counter.hits, 1);
+}
+
+#[test]
+fn test_renamed_crate_quoted_path() {
+    let mut pair = Pair(1, 2);
+    swap(p!(&mut pair));
+    assert_eq!((pair.0, pair.1), (2, 1));
+}
+
+#[test]
+fn test_renamed_crate_spec() {
+    let mut graph = Graph { edges: vec![(0, 1)], ..Default::default() };
+    push_edge_count(p!(&mut graph));
+    assert_eq!(graph.nodes, [1]);
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::mem::offset_of;
+use std::mem::size_of;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[repr(C)]
+pub struct Vertex {
+    pub flags: u8,
+    pub position: [f32; 3],
+    pub id: u16,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[repr(C, align(16))]
+pub struct Uniforms {
+    pub time: f32,
+    pub frame: u32,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn move_vertex(vertex: p!(&<mut position, flags> Vertex)) {
+    vertex.position[0] += f32::from(**vertex.flags);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_repr_c_layout() {
+    assert_eq!(offset_of!(Vertex, flags), 0);
+    assert_eq!(offset_of!(Vertex, position), 4);
+    assert_eq!(offset_of!(Vertex, id), 16);
+    assert_eq!(size_of::<Vertex>(), 20);
+    assert_eq!(size_of::<Uniforms>(), 16);
+}
+
+#[test]
+fn test_repr_c_borrow() {
+    let mut vertex = Vertex { flags: 2, ..Default::default() };
+    move_vertex(p!(&mut vertex));
+    assert_eq!(vertex.position, [2.0, 0.0, 0.0]);
+}
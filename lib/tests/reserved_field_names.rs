@@ -0,0 +1,47 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+/// Fields named like the internal fields of the generated structs.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(tracking = "marker, usage_tracker"))]
+pub struct Scene {
+    pub marker: Vec<usize>,
+    pub usage_tracker: String,
+    pub nodes: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn place(scene: p!(&<mut marker, nodes> Scene)) {
+    scene.marker.extend(scene.nodes.iter().copied());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_reserved_field_names() {
+    let mut scene = Scene { nodes: vec![1, 2], ..Default::default() };
+    place(p!(&mut scene));
+    let mut scene_ref = scene.as_refs_mut();
+    let (mut tracking, rest) = scene_ref.borrow_tracking_mut();
+    tracking.usage_tracker.push_str("on");
+    let mut rest = rest.map_nodes(|nodes| &mut nodes[1..]);
+    rest.nodes[0] = 3;
+    assert_eq!(scene.marker, [1, 2]);
+    assert_eq!(scene.usage_tracker, "on");
+    assert_eq!(scene.nodes, [1, 3]);
+    let mut view = SceneRef::test_view().with_nodes(vec![4]);
+    place(p!(&mut view));
+    assert_eq!(view.marker_mut(), &[4]);
+}
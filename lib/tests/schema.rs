@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use std::sync::Mutex;
+
+// ============
+// === Data ===
+// ============
+
+pub mod graph {
+    use super::*;
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(schema)]
+    #[borrow(group(topology = "nodes, edges"))]
+    #[borrow(private_mut = "edges")]
+    #[borrow(readonly_in_release = "log")]
+    pub struct Graph<T> {
+        pub nodes: Vec<T>,
+        pub edges: Vec<(usize, usize)>,
+        #[borrow(wrap = "Mutex")]
+        pub log: Mutex<Vec<String>>,
+        #[borrow(readonly)]
+        pub config: String,
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+const SCHEMA: &str = include_str!(concat!(env!("OUT_DIR"), "/borrow_schema/graph/Graph.json"));
+
+#[test]
+fn test_schema_of_struct() {
+    assert_eq!(SCHEMA, r#"{
+  "struct": "Graph",
+  "module": "crate::graph",
+  "params": ["T"],
+  "fingerprint": FINGERPRINT,
+  "non_exhaustive": false,
+  "track_by_default": true,
+  "fields": [
+    {"index": 0, "name": "nodes", "type": "Vec<T>", "wrap": null, "private_mut": false, "readonly_in_release": false, "readonly": false},
+    {"index": 1, "name": "edges", "type": "Vec<(usize, usize)>", "wrap": null, "private_mut": true, "readonly_in_release": false, "readonly": false},
+    {"index": 2, "name": "log", "type": "Mutex<Vec<String>>", "wrap": "Mutex", "private_mut": false, "readonly_in_release": true, "readonly": false},
+    {"index": 3, "name": "config", "type": "String", "wrap": null, "private_mut": false, "readonly_in_release": false, "readonly": true}
+  ],
+  "groups": [
+    {"name": "topology", "fields": ["nodes", "edges"]}
+  ]
+}
+"#.replace("FINGERPRINT", &fingerprint().to_string()));
+}
+
+fn fingerprint() -> u64 {
+    let desc = "4:nodes,edges,log,config";
+    desc.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
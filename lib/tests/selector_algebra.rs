@@ -0,0 +1,86 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(render = "geometry, material, mesh"))]
+#[borrow(group(debug = "cache, log"))]
+pub struct Ctx {
+    pub geometry: Vec<usize>,
+    pub material: Vec<usize>,
+    pub mesh: Vec<usize>,
+    pub cache: Vec<usize>,
+    pub log: Vec<String>,
+    #[borrow(readonly)]
+    pub config: String,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(frame = "0, 1"))]
+pub struct Pair(pub usize, #[borrow(readonly)] pub usize);
+
+// =============
+// === Utils ===
+// =============
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+fn draw(ctx: p!(&<mut @render - material, material> Ctx)) {
+    ctx.mesh.push(ctx.material.len());
+}
+
+fn frame(ctx: p!(&{mut * - <cache, @debug>} Ctx)) {
+    draw(p!(&mut ctx));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_group_selector() {
+    type Group = p!('static <mut @render> Ctx);
+    type Fields = p!('static <mut geometry, mut material, mut mesh> Ctx);
+    assert!(same_type::<Group, Fields>());
+}
+
+#[test]
+fn test_exclude_field() {
+    type Excluded = p!('static <mut @render - material> Ctx);
+    type Fields = p!('static <mut geometry, mut mesh> Ctx);
+    assert!(same_type::<Excluded, Fields>());
+}
+
+#[test]
+fn test_exclude_list() {
+    type Excluded = p!('static <mut * - <cache, log, config>> Ctx);
+    type Fields = p!('static <mut geometry, mut material, mut mesh> Ctx);
+    assert!(same_type::<Excluded, Fields>());
+    type ExcludedGroup = p!('static <mut * - <@debug, @render>> Ctx);
+    type Config = p!('static <config> Ctx);
+    assert!(same_type::<ExcludedGroup, Config>());
+}
+
+#[test]
+fn test_group_with_readonly_field() {
+    type Group = p!('static <mut @frame> Pair);
+    type Fields = p!('static <mut 0, 1> Pair);
+    assert!(same_type::<Group, Fields>());
+}
+
+#[test]
+fn test_selector_algebra_borrow() {
+    let mut ctx = Ctx { material: vec![1, 2], ..Default::default() };
+    frame(p!(&mut ctx));
+    assert_eq!(ctx.mesh, [2]);
+}
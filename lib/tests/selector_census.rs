@@ -0,0 +1,71 @@
+//! Checks the selectors recorded to the census file. The file is written only when the tests are
+//! compiled with `BORROW_SELECTOR_CENSUS` set, otherwise the test does nothing.
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(topology = "nodes, edges"))]
+pub struct Graph<T> {
+    pub nodes: Vec<T>,
+    pub edges: Vec<(usize, usize)>,
+    pub cache: Vec<usize>,
+}
+
+borrow::spec!(NODES = <mut nodes>);
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes, edges> Graph<usize>)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn clear_topology(graph: p!(&<mut @topology, upgradeable cache> Graph<usize>)) {
+    graph.nodes.clear();
+    graph.edges.clear();
+}
+
+fn clear_all(graph: p!(&<mut *, - <cache, @topology>> Graph<usize>)) {
+    let _ = graph;
+}
+
+fn clear_nodes(graph: p!(&NODES Graph<usize>)) {
+    graph.nodes.clear();
+}
+
+/// Lines of the census recorded for this file.
+fn census_lines(path: &str) -> Vec<String> {
+    let census = std::fs::read_to_string(path).unwrap_or_default();
+    let file = format!("\"file\": \"{}\"", file!());
+    census.lines().filter(|line| line.contains(&file)).map(String::from).collect()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_selector_census() {
+    let Some(path) = option_env!("BORROW_SELECTOR_CENSUS") else { return };
+    let lines = census_lines(path);
+    let expected = [
+        r#""struct": "Graph", "type": "Graph<usize>", "selectors": ["mut nodes", "edges"], "#,
+        r#""struct": "Graph", "type": "Graph<usize>", "selectors": ["mut @topology", "upgradeable cache"], "#,
+        r#""struct": "Graph", "type": "Graph<usize>", "selectors": ["mut *", "- <cache, @topology>"], "#,
+        r#""struct": "Graph", "type": "Graph<usize>", "selectors": ["mut nodes"], "#,
+    ];
+    for expected in expected {
+        assert!(lines.iter().any(|line| line.contains(expected)), "Missing {expected} in {lines:#?}");
+    }
+    let mut graph = Graph::<usize>::default();
+    add_node(p!(&mut graph));
+    clear_nodes(p!(&mut graph));
+}
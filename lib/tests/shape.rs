@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::FieldMask;
+use borrow::Shape;
+use borrow::View;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub groups: Vec<usize>,
+}
+
+borrow::spec!(NODES = <nodes>);
+borrow::shape!(Topology = <mut nodes, edges> Graph);
+borrow::shape!(Nodes = NODES Graph);
+
+// =============
+// === Utils ===
+// =============
+
+/// Middleware logging the shape of every view passed to a pass.
+fn logged<S: Shape<Graph>>(log: &mut Vec<String>, graph: View<Graph, S>, pass: fn(View<Graph, S>)) {
+    log.push(format!("{:?}", FieldMask::of::<S::Ref<'_>>()));
+    pass(graph);
+}
+
+fn add_node(graph: p!(&<mut nodes, edges> Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn count_nodes(graph: p!(&<nodes> Graph)) {
+    assert_eq!(graph.nodes.len(), 1);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_shape_views() {
+    let mut graph = Graph::default();
+    let mut log = vec![];
+    logged::<Topology>(&mut log, p!(&mut graph), add_node);
+    logged::<Nodes>(&mut log, p!(&mut graph), count_nodes);
+    assert_eq!(log, ["<mut nodes, edges>", "<nodes>"]);
+}
+
+#[test]
+fn test_shape_is_the_partial_borrow_type() {
+    let mut graph = Graph::default();
+    let view: &mut <Topology as Shape<Graph>>::Ref<'_> = p!(&mut graph);
+    let view: p!(&<mut nodes, edges> Graph) = view;
+    view.nodes.push(0);
+    assert_eq!(graph.nodes.len(), 1);
+}
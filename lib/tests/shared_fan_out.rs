@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn node_count(graph: p!(&<nodes> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+/// Hands the shared `nodes` field to several consumers, which are used after this borrow ends.
+#[borrow::deny_over_borrow]
+fn fan_out<'t>(graph: p!(&<'t nodes, mut edges> Graph)) -> Vec<p!(<'t nodes> Graph)> {
+    let mut consumers = vec![];
+    let (first, mut rest) = graph.split::<p!(<'t nodes> Graph)>();
+    consumers.push(first);
+    let (second, mut rest) = rest.split::<p!(<'t nodes> Graph)>();
+    consumers.push(second);
+    let (third, mut rest) = rest.split::<p!(<'t nodes> Graph)>();
+    consumers.push(third);
+    rest.edges.push((0, 1));
+    consumers
+}
+
+#[borrow::deny_over_borrow]
+fn fan_out_in_place(graph: p!(&<nodes, mut edges> Graph)) -> usize {
+    let (mut first, mut rest) = graph.split::<p!(<nodes> Graph)>();
+    let (mut second, mut rest) = rest.split::<p!(<nodes> Graph)>();
+    rest.edges.push((0, 1));
+    node_count(&mut first) + node_count(&mut second)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_shared_fan_out() {
+    let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+    let mut graph_ref = graph.as_refs_mut();
+    let consumers = fan_out(p!(&mut graph_ref));
+    let counts = consumers.into_iter().map(|mut consumer| node_count(&mut consumer)).sum::<usize>();
+    assert_eq!(counts, 6);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_shared_fan_out_in_place() {
+    let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+    assert_eq!(fan_out_in_place(p!(&mut graph)), 4);
+}
@@ -0,0 +1,71 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct Geometry;
+
+#[derive(Debug, Default)]
+pub struct Material;
+
+#[derive(Debug, Default)]
+pub struct Mesh;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Ctx {
+    pub geometry: Vec<Geometry>,
+    pub material: Vec<Material>,
+    pub mesh: Vec<Mesh>,
+    pub log: Vec<String>,
+}
+
+// =============
+// === Specs ===
+// =============
+
+mod specs {
+    borrow::spec!(RENDER = <mut geometry, mut material, mesh>);
+}
+
+borrow::spec!(pub LOG = <mut log>);
+
+// =============
+// === Utils ===
+// =============
+
+fn render(ctx: p!(&specs::RENDER Ctx)) {
+    ctx.geometry.push(Geometry);
+    ctx.material.push(Material);
+    let _ = ctx.mesh.len();
+}
+
+fn log<'t>(ctx: p!(&'t LOG Ctx), msg: &str) -> &'t [String] {
+    ctx.log.push(msg.to_string());
+    &ctx.log
+}
+
+impl p!(specs::RENDER Ctx) {
+    fn render_twice(&mut self) {
+        render(p!(&mut self));
+        render(p!(&mut self));
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_spec() {
+    let mut ctx = Ctx::default();
+    render(p!(&mut ctx));
+    p!(&mut ctx).render_twice();
+    assert_eq!(log(p!(&mut ctx), "rendered").len(), 1);
+    assert_eq!(ctx.geometry.len(), 3);
+}
@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Sim {
+    pub particles: Vec<f32>,
+    pub velocities: Vec<f32>,
+    pub gravity: f32,
+}
+
+impl Sim {
+    fn new() -> Self {
+        Self { particles: vec![1.0, 2.0, 3.0, 4.0], velocities: vec![0.0; 4], gravity: 2.0 }
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+/// Moves the first `n` particles towards the following ones.
+fn attract(sim: p!(&<mut particles, mut velocities, gravity> Sim), n: usize) {
+    let view = sim.partial_borrow::<p!(<mut particles, mut velocities, gravity> Sim)>();
+    let (mut view, rest) = view.split_particles_at(n);
+    let pairs = view.particles.iter_mut().zip(view.velocities.iter_mut());
+    for ((particle, velocity), other) in pairs.zip(rest.iter()) {
+        *velocity = (*other - *particle) * *view.gravity;
+        *particle += *velocity;
+    }
+}
+
+fn sum_halves(sim: p!(&<particles> Sim)) -> (f32, f32) {
+    let view = sim.partial_borrow::<p!(<particles> Sim)>();
+    let len = view.particles.len();
+    let (view, rest) = view.split_particles_at(len / 2);
+    (view.particles.iter().sum(), rest.iter().sum())
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_split_mut_field_at() {
+    let mut sim = Sim::new();
+    attract(p!(&mut sim), 2);
+    assert_eq!(sim.particles, [5.0, 6.0, 3.0, 4.0]);
+    assert_eq!(sim.velocities, [4.0, 4.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_split_ref_field_at() {
+    let mut sim = Sim::new();
+    assert_eq!(sum_halves(p!(&mut sim)), (3.0, 7.0));
+}
+
+#[test]
+fn test_split_field_at_bounds() {
+    let mut sim = Sim::new();
+    let view = sim.as_refs_mut();
+    let (view, rest) = view.split_particles_at(4);
+    assert_eq!((view.particles.len(), rest.len()), (4, 0));
+}
+
+#[test]
+#[should_panic]
+fn test_split_field_at_out_of_bounds() {
+    let mut sim = Sim::new();
+    let _ = sim.as_refs_mut().split_particles_at(5);
+}
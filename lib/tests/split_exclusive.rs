@@ -0,0 +1,81 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::FieldMask;
+use borrow::HasFieldMask;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub groups: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn node_count(graph: p!(&<nodes> Graph)) -> usize {
+    graph.nodes.len()
+}
+
+fn add_edge(graph: p!(&<mut edges> Graph)) {
+    graph.edges.push((0, 1));
+}
+
+fn mask_of<T: HasFieldMask>(_: &T) -> FieldMask<T::Struct> {
+    T::field_mask()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_split_keeps_shared_fields() {
+    let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+    let expected = mask_of(&graph.partial_borrow::<p!(<nodes, mut edges, mut groups> Graph)>());
+    let mut graph_ref = graph.as_refs_mut();
+    let (mut nodes, rest) = graph_ref.split::<p!(<nodes> Graph)>();
+    assert_eq!(mask_of(&rest), expected);
+    assert_eq!(node_count(p!(&mut nodes)), 2);
+}
+
+#[test]
+fn test_split_exclusive_hides_shared_fields() {
+    let mut graph = Graph { nodes: vec![0, 1], ..Default::default() };
+    let mut graph_ref = graph.as_refs_mut();
+    let (mut nodes, mut rest) = graph_ref.split_exclusive::<p!(<nodes> Graph)>();
+    add_edge(p!(&mut rest));
+    assert_eq!(node_count(p!(&mut nodes)), 2);
+    let expected = mask_of(&graph.partial_borrow::<p!(<mut edges, mut groups> Graph)>());
+    let mut graph_ref = graph.as_refs_mut();
+    let (_, rest) = graph_ref.split_exclusive::<p!(<nodes> Graph)>();
+    assert_eq!(mask_of(&rest), expected);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_split_exclusive_keeps_mut_fields_hidden() {
+    let mut graph = Graph::default();
+    let mut graph_ref = graph.as_refs_mut();
+    let (mut edges, mut rest) = graph_ref.split_exclusive::<p!(<mut edges> Graph)>();
+    add_edge(p!(&mut edges));
+    assert_eq!(node_count(p!(&mut rest)), 0);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_into_split_exclusive() {
+    let mut graph = Graph { nodes: vec![0], ..Default::default() };
+    let (mut nodes, mut rest) = graph.as_refs_mut().into_split_exclusive::<p!(<nodes> Graph)>();
+    add_edge(p!(&mut rest));
+    assert_eq!(node_count(p!(&mut nodes)), 1);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
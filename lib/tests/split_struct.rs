@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(split_struct(ty = "TopologyParts<'a>", fields = "mut nodes, edges"))]
+#[borrow(split_struct(ty = "GroupParts<'a>", fields = "mut groups"))]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub groups: Vec<usize>,
+}
+
+/// Hand-rolled split struct of the existing code.
+pub struct TopologyParts<'a> {
+    pub nodes: &'a mut Vec<usize>,
+    pub edges: &'a Vec<(usize, usize)>,
+}
+
+pub struct GroupParts<'a> {
+    pub groups: &'a mut Vec<usize>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(split_struct(ty = "ItemsParts<'a, T>", fields = "mut items"))]
+pub struct Storage<T> {
+    pub items: Vec<T>,
+    pub count: usize,
+}
+
+pub struct ItemsParts<'a, T> {
+    pub items: &'a mut Vec<T>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn legacy_add_node(parts: TopologyParts) {
+    let node = parts.nodes.len() + parts.edges.len();
+    parts.nodes.push(node);
+}
+
+fn add_node(graph: p!(&<mut nodes, edges> Graph)) {
+    legacy_add_node(graph.into());
+}
+
+fn add_group(graph: p!(&<mut nodes, edges, mut groups> Graph)) {
+    let (topology, mut rest) = graph.split::<p!(<mut nodes, edges> Graph)>();
+    legacy_add_node(topology.into());
+    rest.groups.push(rest.edges.len());
+}
+
+fn legacy_add_group(parts: TopologyParts) {
+    let mut view: p!(<mut nodes, edges> Graph) = parts.into();
+    add_node(p!(&mut view));
+}
+
+fn push<T: Default>(storage: p!(&<mut items> Storage<T>)) {
+    let parts = ItemsParts::from(storage);
+    parts.items.push(T::default());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_view_into_split_struct() {
+    let mut graph = Graph::default();
+    add_node(p!(&mut graph));
+    add_group(p!(&mut graph));
+    assert_eq!(graph.nodes, [0, 1]);
+    assert_eq!(graph.groups, [0]);
+}
+
+#[test]
+fn test_owned_view_into_split_struct() {
+    let mut graph = Graph::default();
+    let parts = GroupParts::from(graph.partial_borrow::<p!(<mut groups> Graph)>());
+    parts.groups.push(1);
+    assert_eq!(graph.groups, [1]);
+}
+
+#[test]
+fn test_split_struct_into_view() {
+    let mut graph = Graph::default();
+    legacy_add_group(TopologyParts { nodes: &mut graph.nodes, edges: &graph.edges });
+    assert_eq!(graph.nodes, [0]);
+}
+
+#[test]
+fn test_generic_split_struct() {
+    let mut storage = Storage::<String>::default();
+    push(p!(&mut storage));
+    assert_eq!(storage.items, [""]);
+}
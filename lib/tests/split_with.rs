@@ -0,0 +1,78 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::SplitRest;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+type Nodes = p!('static <mut nodes> Graph);
+type Topology = p!('static <mut nodes, edges> Graph);
+type All<'g> = p!('g <mut *> Graph);
+
+borrow::spec!(GROUPS = <mut groups>);
+
+type NodesSplit<'a> = (p!('a <mut nodes> Graph), SplitRest<'a, Graph, Nodes>);
+// The rest can also be named explicitly.
+type TopologySplit<'a> = (p!('a <mut nodes, edges> Graph), p!('a <edges, mut groups> Graph));
+type GroupsSplit<'a, 'g> = (p!('a GROUPS Graph), SplitRest<'a, All<'g>, p!('static GROUPS Graph)>);
+
+// =============
+// === Utils ===
+// =============
+
+fn split_nodes<'a>(graph: &'a mut Graph) -> NodesSplit<'a> {
+    graph.split_with::<'a, Nodes>()
+}
+
+fn split_topology<'a>(graph: &'a mut All<'_>) -> TopologySplit<'a> {
+    graph.split_with::<'a, Topology>()
+}
+
+fn split_groups<'a, 'g>(graph: &'a mut All<'g>) -> GroupsSplit<'a, 'g> {
+    graph.split_with::<'a, p!('static GROUPS Graph)>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_split_with() {
+    let mut graph = Graph::default();
+    let (mut nodes, mut rest) = split_nodes(&mut graph);
+    nodes.nodes.push(0);
+    rest.edges.push(1);
+    rest.groups.push(2);
+    assert_eq!((graph.nodes.len(), graph.edges.len(), graph.groups.len()), (1, 1, 1));
+}
+
+#[test]
+fn test_split_view_with() {
+    let mut graph = Graph::default();
+    let mut view = graph.partial_borrow::<p!(<mut *> Graph)>();
+    {
+        let (mut topology, mut rest) = split_topology(&mut view);
+        topology.nodes.push(topology.edges.len());
+        rest.groups.push(rest.edges.len());
+    };
+    {
+        let (mut groups, mut rest) = split_groups(&mut view);
+        groups.groups.push(1);
+        rest.nodes.push(1);
+        rest.edges.push(1);
+    };
+    drop(view);
+    assert_eq!(graph.nodes, vec![0, 1]);
+    assert_eq!(graph.groups, vec![0, 1]);
+}
@@ -0,0 +1,69 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+/// Field without a `Default` impl.
+#[derive(Debug)]
+pub struct Device {
+    pub id: usize,
+}
+
+#[derive(Debug, borrow::Partial)]
+#[module(crate)]
+pub struct Renderer<T> {
+    pub device: Device,
+    pub meshes: Vec<T>,
+    pub frame: usize,
+    #[borrow(dirty_flags)]
+    pub dirty: borrow::DirtyFlags<Renderer<T>>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn draw<T: Clone>(renderer: p!(&<device, mut meshes, mut frame> Renderer<T>)) {
+    **renderer.frame += renderer.device.id;
+    let first = renderer.meshes.first().cloned();
+    renderer.meshes.extend(first);
+}
+
+fn next_frame<T>(renderer: p!(&<mut frame> Renderer<T>)) {
+    **renderer.frame += 1;
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_view_with_provided_fields() {
+    let mut view = RendererRef::test_view().with_device(Device { id: 2 }).with_meshes(vec!['a']);
+    draw(p!(&mut view));
+    assert_eq!(view.meshes_mut(), &['a', 'a']);
+    assert_eq!(*view.frame_mut(), 2);
+}
+
+#[test]
+fn test_view_with_default_fields() {
+    let mut view = RendererRef::test_view().with_device(Device { id: 0 });
+    next_frame::<char>(p!(&mut view));
+    next_frame::<char>(p!(&mut view));
+    assert_eq!(*view.frame_mut(), 2);
+}
+
+#[test]
+fn test_view_split() {
+    let mut view = RendererRef::test_view().with_device(Device { id: 1 }).with_meshes(vec![1]);
+    let mut view_ref = view.as_refs_mut();
+    let (mut frame, mut rest) = view_ref.split::<p!(<mut frame> Renderer<usize>)>();
+    rest.meshes.push(2);
+    next_frame(p!(&mut frame));
+    assert_eq!(view.meshes_mut(), &[1, 2]);
+    assert_eq!(*view.frame_mut(), 1);
+}
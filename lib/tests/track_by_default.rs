@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+/// Interface struct owned by a framework, passed to user callbacks.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(track_by_default = false)]
+pub struct FrameCtx {
+    pub time: f32,
+    pub events: Vec<u32>,
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+borrow::spec!(TIME = <time>);
+
+// =============
+// === Utils ===
+// =============
+
+fn on_frame(ctx: p!(&<mut *> FrameCtx)) {
+    let _ = &*ctx.time;
+}
+
+fn update(ctx: p!(!&<mut time, events> FrameCtx)) {
+    **ctx.time += 1.0;
+    let _ = &*ctx.events;
+}
+
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_borrows_are_untracked_by_default() {
+    assert!(same_type::<p!('static <time> FrameCtx), p!(_ 'static <time> FrameCtx)>());
+    assert!(!same_type::<p!('static <time> FrameCtx), p!(!'static <time> FrameCtx)>());
+    assert!(same_type::<p!('static TIME FrameCtx), p!(_ 'static TIME FrameCtx)>());
+    assert!(same_type::<p!(!'static TIME FrameCtx), p!(!'static <time> FrameCtx)>());
+}
+
+#[test]
+fn test_other_structs_are_tracked_by_default() {
+    assert!(same_type::<p!('static <nodes> Graph), p!(!'static <nodes> Graph)>());
+    assert!(!same_type::<p!('static <nodes> Graph), p!(_ 'static <nodes> Graph)>());
+}
+
+#[test]
+fn test_tracked_and_untracked_borrows() {
+    let mut ctx = FrameCtx::default();
+    on_frame(p!(&mut ctx));
+    update(p!(&mut ctx));
+    assert_eq!(ctx.time, 1.0);
+}
@@ -0,0 +1,71 @@
+//! Checks that reports of borrows created in `#[track_caller]` wrappers point at the callers of the
+//! wrappers. See `usage_tracking_lite.rs` for the lite tracker.
+#![cfg(feature = "tracking")]
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+/// Records the lines of over-borrows created in this file.
+struct RecordingTracker {
+    lines: Mutex<Vec<u32>>,
+}
+
+impl borrow::tracking::Tracker for RecordingTracker {
+    fn borrow_dropped(&self, report: &borrow::tracking::BorrowReport) {
+        if report.is_over_borrow() && report.location.file() == file!() {
+            self.lines.lock().unwrap_or_else(PoisonError::into_inner).push(report.location.line());
+        }
+    }
+}
+
+static TRACKER: RecordingTracker = RecordingTracker { lines: Mutex::new(vec![]) };
+
+// =============
+// === Utils ===
+// =============
+
+#[track_caller]
+fn topology(graph: &mut Graph) -> p!(<mut nodes, mut edges> Graph) {
+    graph.partial_borrow()
+}
+
+#[track_caller]
+fn topology_split(graph: &mut Graph) -> p!(<mut nodes, mut edges> Graph) {
+    let (topology, _) = graph.split();
+    topology
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_track_caller() {
+    borrow::tracking::set_tracker(&TRACKER);
+    let mut graph = Graph::default();
+    let topology_line = line!() + 1;
+    topology(&mut graph).nodes.push(0);
+    let topology_split_line = line!() + 1;
+    topology_split(&mut graph).nodes.push(0);
+    let lines = TRACKER.lines.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let expected = if cfg!(usage_tracking_enabled) {
+        vec![topology_line, topology_split_line]
+    } else {
+        vec![]
+    };
+    assert_eq!(lines, expected);
+}
@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::Snapshot;
+use borrow::Transactional;
+use std::cell::Cell;
+
+// ============
+// === Data ===
+// ============
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Value counting its clones.
+#[derive(Debug, Default, PartialEq)]
+pub struct Counted(usize);
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        CLONES.with(|clones| clones.set(clones.get() + 1));
+        Counted(self.0)
+    }
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Document {
+    pub nodes: Vec<usize>,
+    pub title: Counted,
+    pub style: Counted,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_nodes(doc: p!(&<mut nodes, title> Document), count: usize) -> Result<(), usize> {
+    let view: p!(<mut nodes, title> Document) = doc.partial_borrow();
+    let mut tx = Transactional::new(view);
+    for node in 0..count {
+        if node >= tx.title.0 {
+            return Err(node);
+        }
+        tx.nodes.push(node);
+    }
+    tx.commit();
+    Ok(())
+}
+
+fn rename(doc: p!(&<upgradeable title> Document), title: usize) {
+    let view: p!(<upgradeable title> Document) = doc.partial_borrow();
+    let mut tx = Transactional::new(view);
+    tx.title.upgrade().0 = title;
+    tx.rollback();
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_commit_keeps_modifications() {
+    let mut doc = Document { title: Counted(2), ..Default::default() };
+    assert_eq!(add_nodes(p!(&mut doc), 2), Ok(()));
+    assert_eq!(doc.nodes, [0, 1]);
+}
+
+#[test]
+fn test_drop_rolls_back_modifications() {
+    let mut doc = Document { nodes: vec![7], title: Counted(2), ..Default::default() };
+    assert_eq!(add_nodes(p!(&mut doc), 3), Err(2));
+    assert_eq!(doc.nodes, [7]);
+}
+
+#[test]
+fn test_rollback_of_upgradeable_fields() {
+    let mut doc = Document { title: Counted(1), ..Default::default() };
+    rename(p!(&mut doc), 5);
+    assert_eq!(doc.title, Counted(1));
+}
+
+#[test]
+fn test_only_mutable_fields_are_snapshotted() {
+    let mut doc = Document::default();
+    let view: p!(<mut nodes, title> Document) = doc.partial_borrow();
+    CLONES.with(|clones| clones.set(0));
+    let _snapshot = view.snapshot();
+    assert_eq!(CLONES.with(|clones| clones.get()), 0);
+
+    let view: p!(<mut title, style> Document) = doc.partial_borrow();
+    let _snapshot = view.snapshot();
+    assert_eq!(CLONES.with(|clones| clones.get()), 1);
+}
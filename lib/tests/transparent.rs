@@ -0,0 +1,110 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+pub mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(group(topology = "nodes, edges"))]
+    pub struct Graph<T> {
+        pub nodes: Vec<T>,
+        pub edges: Vec<(usize, usize)>,
+        pub name: String,
+    }
+
+    /// Wrapped struct named by a path relative to the module.
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(transparent)]
+    pub struct Named<T> {
+        pub graph: Graph<T>,
+    }
+}
+
+pub mod world {
+    /// Wrapped struct named by a path starting with `crate`.
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::world)]
+    #[borrow(transparent)]
+    pub struct World<T>(pub crate::graph::Graph<T>);
+
+    /// Wrapper of a wrapper.
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::world)]
+    #[borrow(transparent)]
+    pub struct Universe(pub World<char>);
+}
+
+use graph::Graph;
+use graph::Named;
+use world::Universe;
+use world::World;
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node<T>(graph: p!(&<mut nodes> Graph<T>), node: T) -> usize {
+    graph.nodes.push(node);
+    graph.nodes.len() - 1
+}
+
+fn connect<T>(world: p!(&<nodes, mut edges> World<T>), from: usize, to: usize) {
+    assert!(from < world.nodes.len() && to < world.nodes.len());
+    world.edges.push((from, to));
+}
+
+fn add_connected_node<T>(world: p!(&<mut *> World<T>), node: T) {
+    let index = add_node(p!(&mut world), node);
+    connect(p!(&mut world), 0, index);
+}
+
+fn rename(universe: p!(&<mut name> Universe), name: &str) {
+    universe.name.clear();
+    universe.name.push_str(name);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_transparent_borrow() {
+    let mut world = World::default();
+    add_connected_node(p!(&mut world), 'a');
+    add_connected_node(p!(&mut world), 'b');
+    assert_eq!(world.0.nodes, ['a', 'b']);
+    assert_eq!(world.0.edges, [(0, 0), (0, 1)]);
+}
+
+#[test]
+fn test_transparent_named_field() {
+    let mut named = Named::default();
+    let index = add_node(p!(&mut named), 1);
+    assert_eq!((index, &named.graph.nodes), (0, &vec![1]));
+}
+
+#[test]
+fn test_transparent_nested() {
+    let mut universe = Universe::default();
+    add_connected_node(p!(&mut universe), 'a');
+    rename(p!(&mut universe), "universe");
+    assert_eq!(universe.0 .0.nodes, ['a']);
+    assert_eq!(universe.0 .0.name, "universe");
+}
+
+#[test]
+fn test_transparent_same_type_as_inner() {
+    let mut world = World::default();
+    // The partial borrows of the wrapper are the partial borrows of the wrapped struct.
+    let graph: p!(&<mut nodes> Graph<usize>) = p!(&mut world);
+    graph.nodes.push(1);
+    let mut world_ref = world.as_refs_mut();
+    let (topology, _) = world_ref.borrow_topology();
+    assert_eq!(topology.nodes.len(), 1);
+}
@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(topology = "0, 1"))]
+pub struct Graph<T>(pub Vec<T>, pub Vec<(usize, usize)>, pub usize);
+
+// =============
+// === Utils ===
+// =============
+
+fn connect<T>(graph: p!(&<0, mut 1> Graph<T>)) {
+    let last = graph._0.len() - 1;
+    graph._1.push((0, last));
+}
+
+fn add_node<T>(graph: p!(&<mut 0, mut 2> Graph<T>), node: T) {
+    graph._0.push(node);
+    **graph._2 += 1;
+}
+
+fn add_connected_node<T>(graph: p!(&<mut *> Graph<T>), node: T) {
+    add_node(p!(&mut graph), node);
+    connect(p!(&mut graph));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_tuple_struct_borrow() {
+    let mut graph = Graph::default();
+    add_connected_node(p!(&mut graph), 'a');
+    add_connected_node(p!(&mut graph), 'b');
+    assert_eq!(graph.0, ['a', 'b']);
+    assert_eq!(graph.1, [(0, 0), (0, 1)]);
+    assert_eq!(graph.2, 2);
+}
+
+#[test]
+fn test_tuple_struct_split() {
+    let mut graph = Graph(vec![1], vec![], 0);
+    let mut graph_ref = graph.as_refs_mut();
+    let (mut edges, mut rest) = graph_ref.borrow_1_mut();
+    edges.push((0, 1));
+    add_node(p!(&mut rest), 2);
+    let (topology, _) = graph_ref.borrow_topology();
+    assert_eq!(topology._0.len(), 2);
+    assert_eq!(graph.1, [(0, 1)]);
+    assert_eq!(graph.2, 1);
+}
@@ -0,0 +1,13 @@
+//! Compile error tests. Every file in `ui` has to fail to compile with the errors in the `.stderr`
+//! file next to it. Run the test with `TRYBUILD=overwrite` to write them. Structs which have to be
+//! defined in another crate are defined in the `fixture` crate.
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,23 @@
+// All unsupported selectors are reported at once.
+use borrow::partial as p;
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+fn connect(graph: p!(&<mut *> Graph)) {
+    let _ = graph;
+}
+
+borrow::bench! {
+    bench_connect = <upgradeable nodes, mut *> Graph {
+        state: Graph { nodes: vec![], edges: vec![] },
+        view: connect,
+        params: |nodes: &Vec<usize>| nodes.len(),
+    }
+}
+
+fn main() {}
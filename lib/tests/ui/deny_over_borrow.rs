@@ -0,0 +1,22 @@
+// All problems of the function are reported at once.
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+#[borrow::deny_over_borrow(strict)]
+fn add_node(
+    graph: borrow::partial!(&<mut nodes> Graph),
+    (edges, _): borrow::partial!(&<edges> Graph),
+) {
+    graph.nodes.push(edges.len());
+}
+
+#[borrow::deny_over_borrow]
+fn add_nodes(nodes: &mut Vec<usize>) {
+    nodes.push(0);
+}
+
+fn main() {}
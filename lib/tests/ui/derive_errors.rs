@@ -0,0 +1,11 @@
+// The derive reports all problems of the struct at once.
+#[derive(borrow::Partial)]
+#[module(crate::)]
+#[borrow(private_mut = "groups", group(nodes = "nodes"))]
+struct Graph {
+    nodes: Vec<usize>,
+    #[borrow(wrap = "Mutex")]
+    edges: Vec<usize>,
+}
+
+fn main() {}
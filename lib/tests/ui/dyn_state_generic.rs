@@ -0,0 +1,9 @@
+// State objects are not supported for structs with parameters.
+#[derive(borrow::Partial)]
+#[module(crate)]
+#[borrow(dyn_state = "GraphFields")]
+pub struct Graph<T> {
+    pub nodes: Vec<T>,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// Readonly fields can not be mutated through the state object.
+#[derive(Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(dyn_state = "SettingsFields")]
+pub struct Settings {
+    #[borrow(readonly)]
+    pub version: usize,
+    pub zoom: usize,
+}
+
+fn main() {
+    let mut settings = Settings::default();
+    let state: &mut dyn SettingsFields = &mut settings;
+    let (version, _) = state.fields_mut();
+    *version = 2;
+}
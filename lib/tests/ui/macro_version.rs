@@ -0,0 +1,4 @@
+// Code generated by another version of borrow-macro fails to compile.
+borrow::check_macro_version!("0.0.0");
+
+fn main() {}
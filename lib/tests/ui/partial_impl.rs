@@ -0,0 +1,11 @@
+// The attribute does not accept arguments.
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+}
+
+#[borrow::partial_impl(Graph)]
+impl Graph {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// Mutable selectors of private mut fields fail to compile outside of the crate defining the struct.
+use borrow::partial as p;
+use borrow_test_fixture::Core;
+
+fn alloc(core: p!(&<mut allocator> Core)) {
+    core.allocator.push(0);
+}
+
+fn main() {}
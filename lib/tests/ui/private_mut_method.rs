@@ -0,0 +1,9 @@
+// Methods borrowing private mut fields mutably are private to the crate defining the struct.
+use borrow::traits::*;
+use borrow_test_fixture::Core;
+
+fn main() {
+    let mut core = Core::default();
+    let mut core_ref = core.as_refs_mut();
+    core_ref.borrow_allocator_mut();
+}
@@ -0,0 +1,8 @@
+// The root view borrows private mut fields as shared outside of the crate defining the struct.
+use borrow::traits::*;
+use borrow_test_fixture::Core;
+
+fn main() {
+    let mut core = Core::default();
+    core.as_refs_mut().allocator.push(0);
+}
@@ -0,0 +1,15 @@
+// Range selectors are not supported, fields are split with `split_$field_at` instead.
+use borrow::partial as p;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Sim {
+    pub particles: Vec<f32>,
+    pub gravity: f32,
+}
+
+fn step(_sim: p!(&<mut particles[0..2], gravity> Sim)) {}
+
+fn step_braced(_sim: p!(&{mut particles[2..], gravity} Sim)) {}
+
+fn main() {}
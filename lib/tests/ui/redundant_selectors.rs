@@ -0,0 +1,24 @@
+// Redundant selectors are reported at the selector which does not change the borrowed shape.
+#![deny(deprecated)]
+use borrow::partial as p;
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+fn duplicate(graph: p!(&<nodes, edges, mut nodes> Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn overridden(graph: p!(&<nodes, mut *> Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn same_as_star(graph: p!(&<mut *, mut nodes> Graph)) {
+    graph.nodes.push(graph.edges.len());
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+// Test views are generated only in `cfg(test)` without the `test_view` feature.
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes: Vec<usize>,
+}
+
+fn main() {
+    let _view = GraphRef::test_view().with_nodes(vec![0]);
+}
@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default)]
+pub struct Header {
+    pub len: usize,
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Buf {
+    pub header: Header,
+    pub data: [u8],
+}
+
+#[derive(borrow::Partial)]
+#[module(crate)]
+pub struct Tagged<T: ?Sized> {
+    pub tag: &'static str,
+    pub value: T,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn fill(buf: p!(&<mut header, mut data> Buf), byte: u8) {
+    buf.data.fill(byte);
+    buf.header.len = buf.data.len();
+}
+
+fn describe<T: ?Sized + std::fmt::Debug>(tagged: p!(&<tag, value> Tagged<T>)) -> String {
+    format!("{}: {:?}", **tagged.tag, &**tagged.value)
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_unsized_last_field() {
+    let mut boxed: Box<Tagged<[u8]>> = Box::new(Tagged { tag: "bytes", value: [1, 2] });
+    let tagged: &mut Tagged<[u8]> = &mut boxed;
+    assert_eq!(describe(&mut tagged.partial_borrow()), "bytes: [1, 2]");
+    let mut tagged_ref = tagged.as_refs_mut();
+    let (mut value, rest) = tagged_ref.borrow_value_mut();
+    value.fill(rest.tag.len() as u8);
+    assert_eq!(tagged.value, [5, 5]);
+}
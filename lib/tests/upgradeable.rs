@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+pub struct Graph {
+    pub nodes:  Vec<usize>,
+    pub edges:  Vec<usize>,
+    pub groups: Vec<usize>,
+}
+
+// ==============
+// === Passes ===
+// ==============
+
+/// Removes the node `0`, if present. The nodes are modified only if needed.
+fn remove_root(graph: p!(&<upgradeable nodes, edges> Graph)) -> usize {
+    if graph.nodes.contains(&0) {
+        graph.nodes.upgrade().retain(|node| *node != 0);
+        count(p!(&mut graph))
+    } else {
+        count(p!(&mut graph))
+    }
+}
+
+fn count(graph: p!(&<nodes, edges> Graph)) -> usize {
+    graph.nodes.len() + graph.edges.len()
+}
+
+fn add_node(graph: p!(&<mut nodes> Graph)) {
+    let id = graph.nodes.len();
+    graph.nodes.push(id);
+}
+
+fn add_node_if_empty(graph: p!(&<upgradeable nodes> Graph)) {
+    if graph.nodes.is_empty() {
+        // Passing the field to a mutable borrow upgrades it.
+        add_node(p!(&mut graph));
+    }
+}
+
+fn nested(graph: p!(&<upgradeable *> Graph)) {
+    add_node_if_empty(p!(&mut graph));
+    let group_count = graph.groups.len();
+    graph.edges.upgrade().push(group_count);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_upgrade() {
+    let mut graph = Graph { nodes: vec![0, 1, 2], edges: vec![0], ..Default::default() };
+    assert_eq!(remove_root(p!(&mut graph)), 3);
+    assert_eq!(graph.nodes, vec![1, 2]);
+    assert_eq!(remove_root(p!(&mut graph)), 3);
+}
+
+#[test]
+fn test_upgrade_by_mutable_borrow() {
+    let mut graph = Graph::default();
+    add_node_if_empty(p!(&mut graph));
+    add_node_if_empty(p!(&mut graph));
+    assert_eq!(graph.nodes, vec![0]);
+    graph.nodes.clear();
+    nested(p!(&mut graph));
+    assert_eq!(graph.nodes, vec![0]);
+    assert_eq!(graph.edges, vec![0]);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "tracking")]
+#![allow(dead_code)]
+
+use borrow::tracking::BorrowReport;
+use borrow::Field;
+use borrow::True;
+use borrow::Usage;
+use borrow::UsageScope;
+use std::panic::Location;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+// ============
+// === Data ===
+// ============
+
+/// A container handing out its own views instead of deriving `borrow::Partial`.
+#[derive(Debug, Default)]
+pub struct Arena {
+    items: Vec<u32>,
+    names: Vec<String>,
+}
+
+pub struct ArenaView<'t> {
+    pub items: Field<True, &'t mut Vec<u32>>,
+    pub names: Field<True, &'t Vec<String>>,
+}
+
+impl Arena {
+    #[track_caller]
+    pub fn view(&mut self) -> ArenaView<'_> {
+        let scope = UsageScope::new(Location::caller());
+        let items = scope.field("items", Usage::Mut, &mut self.items);
+        let names = scope.field("names", Usage::Ref, &self.names);
+        scope.finish();
+        ArenaView { items, names }
+    }
+}
+
+/// Records the lines and the unused fields of the borrows created in this file.
+struct RecordingTracker {
+    reports: Mutex<Vec<(u32, Vec<&'static str>)>>,
+}
+
+impl borrow::tracking::Tracker for RecordingTracker {
+    fn borrow_dropped(&self, report: &BorrowReport) {
+        if report.location.file() == file!() {
+            let mut reports = self.reports.lock().unwrap_or_else(PoisonError::into_inner);
+            reports.push((report.location.line(), report.not_used().collect()));
+        }
+    }
+}
+
+static TRACKER: RecordingTracker = RecordingTracker { reports: Mutex::new(vec![]) };
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_usage_scope() {
+    borrow::tracking::set_tracker(&TRACKER);
+    let mut arena = Arena::default();
+    let line = line!() + 1;
+    let mut view = arena.view();
+    view.items.push(1);
+    drop(view);
+    let mut view = arena.view();
+    view.items.push(view.names.len() as u32);
+    drop(view);
+    assert_eq!(arena.items, [1, 0]);
+    let reports = TRACKER.reports.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    let expected: &[(u32, Vec<&str>)] = if cfg!(usage_tracking_enabled) {
+        &[(line, vec!["names"]), (line + 3, vec![])]
+    } else {
+        &[]
+    };
+    assert_eq!(reports, expected);
+    let mut over_borrows = vec![];
+    borrow::for_each_over_borrow(|report| {
+        if report.location.file() == file!() {
+            over_borrows.push((report.location.line(), report.field));
+        }
+    });
+    let expected: &[(u32, &str)] = if cfg!(usage_tracking_lite_enabled) { &[(line, "names")] } else { &[] };
+    assert_eq!(over_borrows, expected);
+}
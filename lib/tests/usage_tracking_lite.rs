@@ -0,0 +1,65 @@
+#![cfg(usage_tracking_lite_enabled)]
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use borrow::Usage;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+struct Graph {
+    nodes:  Vec<usize>,
+    edges:  Vec<usize>,
+    groups: Vec<usize>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn pass1(graph: p!(&<mut nodes, mut edges, groups> Graph)) {
+    graph.edges.push(0);
+    pass2(p!(&mut graph));
+}
+
+fn pass2(graph: p!(&<mut nodes> Graph)) {
+    let _ = graph.nodes.len();
+}
+
+#[track_caller]
+fn topology(graph: &mut Graph) -> p!(<mut nodes, mut edges> Graph) {
+    graph.partial_borrow()
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test() {
+    let mut graph = Graph::default();
+    pass1(p!(&mut graph));
+    let topology_line = line!() + 1;
+    topology(&mut graph).nodes.push(0);
+
+    let mut reports = vec![];
+    borrow::for_each_over_borrow(|report| {
+        if report.location.file() == file!() {
+            reports.push((report.field, report.requested, report.used));
+            if report.field == "edges" {
+                // Reported at the caller of the `#[track_caller]` wrapper.
+                assert_eq!(report.location.line(), topology_line);
+            }
+        }
+    });
+    reports.sort();
+    assert_eq!(reports, vec![
+        ("edges", Usage::Mut, None),
+        ("groups", Usage::Ref, None),
+        ("nodes", Usage::Mut, Some(Usage::Ref)),
+    ]);
+}
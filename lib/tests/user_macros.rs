@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    pub struct Graph<T> {
+        pub nodes: Vec<T>,
+        pub edges: Vec<(usize, usize)>,
+    }
+}
+
+// ==============
+// === Macros ===
+// ==============
+
+/// Partial borrow of the graph, usable without importing `Graph`.
+macro_rules! graph_view {
+    ($t:ty; $($selectors:tt)*) => {
+        borrow::partial!(&<$($selectors)*> $crate::graph::Graph<$t>)
+    };
+}
+
+/// Function borrowing the nodes of the graph.
+macro_rules! node_counter {
+    ($name:ident) => {
+        fn $name(graph: borrow::partial!(&<nodes> crate::graph::Graph<usize>)) -> usize {
+            graph.nodes.len()
+        }
+    };
+}
+
+node_counter!(count_nodes);
+
+// =============
+// === Utils ===
+// =============
+
+mod passes {
+    pub fn add_node(graph: graph_view!(usize; mut nodes)) {
+        graph.nodes.push(0);
+    }
+
+    pub fn connect(graph: graph_view!(usize; nodes, mut edges)) {
+        graph.edges.push((0, graph.nodes.len()));
+    }
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_user_macros() {
+    let mut graph = graph::Graph::<usize>::default();
+    passes::add_node(borrow::partial!(&mut graph));
+    passes::connect(borrow::partial!(&mut graph));
+    assert_eq!(count_nodes(borrow::partial!(&mut graph)), 1);
+    assert_eq!(graph.edges, vec![(0, 1)]);
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+
+// ============
+// === Data ===
+// ============
+
+mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(vis = "pub(crate)", monomorphize, group(topology = "nodes, edges"))]
+    pub(crate) struct Graph {
+        pub nodes: Vec<usize>,
+        pub edges: Vec<(usize, usize)>,
+    }
+
+    #[derive(Debug, Default, borrow::Partial)]
+    #[module(crate::graph)]
+    #[borrow(vis = "pub(crate)", transparent)]
+    pub(crate) struct World(pub Graph);
+}
+
+use graph::Graph;
+use graph::World;
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes> Graph)) {
+    let id = graph.nodes.len();
+    graph.nodes.push(id);
+}
+
+fn connect(world: p!(&<nodes, mut edges> World)) {
+    world.edges.push((world.nodes[0], world.nodes[1]));
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_crate_visible_items() {
+    let mut world = World::default();
+    let graph = &mut world.0;
+    add_node(p!(&mut graph));
+    add_node(p!(&mut graph));
+    connect(p!(&mut world));
+    let topology = world.0.borrow_topology_mut();
+    assert_eq!(topology.edges.len(), 1);
+    let view: graph::GraphRef<Graph, _, _> = world.0.as_refs_mut();
+    assert_eq!(view.nodes.len(), 2);
+}
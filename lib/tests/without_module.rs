@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::any::TypeId;
+
+// ============
+// === Data ===
+// ============
+
+pub mod graph {
+    #[derive(Debug, Default, borrow::Partial)]
+    #[borrow(group(topology = "nodes, edges"))]
+    pub struct Graph<T> {
+        pub nodes: Vec<T>,
+        pub edges: Vec<(usize, usize)>,
+        #[borrow(readonly)]
+        pub name: String,
+    }
+
+    pub mod world {
+        #[derive(Debug, Default, borrow::Partial)]
+        #[borrow(transparent)]
+        pub struct World(pub crate::graph::Graph<usize>);
+    }
+}
+
+#[derive(Debug, Default, borrow::Partial)]
+pub(crate) struct Pair(pub usize, pub usize);
+
+use graph::world::World;
+use graph::Graph;
+
+trait Pass {
+    fn run(&mut self);
+}
+
+impl Pass for p!(<mut nodes, edges> Graph<usize>) {
+    fn run(&mut self) {
+        let count = self.edges.len();
+        self.nodes.push(count);
+    }
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn add_node(graph: p!(&<mut nodes, name> Graph<usize>)) {
+    graph.nodes.push(graph.name.len());
+}
+
+fn connect<T>(graph: p!(&<nodes, mut edges> Graph<T>)) {
+    graph.edges.push((0, graph.nodes.len()));
+}
+
+fn build(graph: p!(&<mut *> Graph<usize>)) {
+    add_node(p!(&mut graph));
+    connect(p!(&mut graph));
+}
+
+fn run(world: p!(&<mut @topology> World)) {
+    connect(p!(&mut world));
+}
+
+fn swap(pair: p!(&<mut 0, mut 1> Pair)) {
+    std::mem::swap(&mut **pair._0, &mut **pair._1);
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_without_module() {
+    let mut graph = Graph { name: "g".to_string(), ..Default::default() };
+    build(p!(&mut graph));
+    p!(&mut graph).run();
+    assert_eq!(graph.nodes, [1, 1]);
+    assert_eq!(graph.edges, [(0, 1)]);
+}
+
+#[test]
+fn test_without_module_ref_type() {
+    type Ref = graph::GraphRef<Graph<usize>, &'static mut Vec<usize>, borrow::Hidden, &'static String>;
+    assert_eq!(TypeId::of::<p!(<'static mut nodes, 'static name> Graph<usize>)>(), TypeId::of::<Ref>());
+}
+
+#[test]
+fn test_without_module_transparent() {
+    let mut world = World(Graph::default());
+    run(p!(&mut world));
+    assert_eq!(world.0.edges, [(0, 0)]);
+}
+
+#[test]
+fn test_without_module_tuple_struct() {
+    let mut pair = Pair(1, 2);
+    swap(p!(&mut pair));
+    assert_eq!((pair.0, pair.1), (2, 1));
+}
@@ -0,0 +1,93 @@
+#![allow(dead_code)]
+#![allow(clippy::unwrap_used)]
+
+use borrow::partial as p;
+use borrow::traits::*;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+
+// ============
+// === Data ===
+// ============
+
+#[derive(Debug, Default, borrow::Partial)]
+#[module(crate)]
+#[borrow(group(io = "input, output"))]
+pub struct Engine<T> {
+    pub frame: usize,
+    #[borrow(wrap = "Mutex")]
+    pub input: Mutex<Vec<T>>,
+    #[borrow(wrap = "RwLock")]
+    pub output: RwLock<Vec<T>>,
+    #[borrow(wrap = "RefCell")]
+    pub log: RefCell<Vec<String>>,
+}
+
+// =============
+// === Utils ===
+// =============
+
+fn process(engine: p!(&<mut frame, mut input, mut output, log> Engine<usize>)) {
+    **engine.frame += 1;
+    let input = std::mem::take(&mut **engine.input);
+    engine.output.extend(input);
+    let _ = engine.log.len();
+}
+
+fn log(engine: p!(&<mut log> Engine<usize>), message: &str) {
+    engine.log.push(message.to_string());
+}
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_wrapped_fields_in_views() {
+    let mut engine = Engine::<usize>::default();
+    engine.input.lock().unwrap().push(1);
+    process(p!(&mut engine));
+    log(p!(&mut engine), "processed");
+    assert_eq!(*engine.output.read().unwrap(), [1]);
+    assert_eq!(*engine.log.borrow(), ["processed"]);
+}
+
+#[test]
+fn test_wrapped_fields_shared_with_legacy_code() {
+    let engine = Arc::new(Mutex::new(Engine::<usize>::default()));
+    let handle = std::thread::spawn({
+        let engine = engine.clone();
+        move || engine.lock().unwrap().input.get_mut().unwrap().push(2)
+    });
+    handle.join().unwrap();
+    let mut guard = engine.lock().unwrap();
+    let engine_ref: &mut Engine<usize> = &mut guard;
+    process(p!(&mut engine_ref));
+    drop(guard);
+    assert_eq!(*engine.lock().unwrap().output.read().unwrap(), [2]);
+}
+
+#[test]
+fn test_wrapped_fields_in_groups() {
+    let mut engine = Engine::<usize>::default();
+    let mut engine_ref = engine.as_refs_mut();
+    let (mut io, _) = engine_ref.borrow_io_mut();
+    io.input.push(3);
+    io.output.push(4);
+    assert_eq!(*engine.input.lock().unwrap(), [3]);
+    assert_eq!(*engine.output.read().unwrap(), [4]);
+}
+
+#[test]
+fn test_poisoned_wrapped_fields() {
+    let mut engine = Engine::<usize>::default();
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = engine.input.lock().unwrap();
+        std::panic::resume_unwind(Box::new("poison"));
+    }));
+    assert!(engine.input.is_poisoned());
+    process(p!(&mut engine));
+    assert_eq!(engine.frame, 1);
+}
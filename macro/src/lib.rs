@@ -1,6 +1,4 @@
-#![allow(clippy::panic)]
 #![allow(clippy::unwrap_used)]
-#![allow(clippy::expect_used)]
 
 use std::fmt::Debug;
 use quote::quote;
@@ -9,6 +7,8 @@ use itertools::Itertools;
 use proc_macro2::TokenStream;
 use proc_macro2::Span;
 use syn::Token;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 use syn::parse::Parse;
 use syn::parse::ParseStream;
 
@@ -30,828 +30,3929 @@ fn internal(s: &str) -> String {
     format!("__{s}")
 }
 
-fn get_fields(input: &DeriveInput) -> Vec<&syn::Field> {
+/// Combines the errors into a single one, so they are all reported, each at its own span.
+fn combine_errors(errors: impl IntoIterator<Item = syn::Error>) -> Option<syn::Error> {
+    errors.into_iter().reduce(|mut error, next| {
+        error.combine(next);
+        error
+    })
+}
+
+/// Identifier of the tuple struct field at the given position in partial borrows, e.g. `_0`.
+fn positional_ident(index: usize) -> Ident {
+    Ident::new(&format!("_{index}"), Span::call_site())
+}
+
+/// Fields of the struct. Fields of tuple structs are named after their position, e.g. `_0`.
+fn get_all_fields(input: &DeriveInput) -> Vec<syn::Field> {
     if let Data::Struct(data) = &input.data {
-        if let Fields::Named(fields) = &data.fields {
-            fields.named.iter().collect::<Vec<_>>()
-        } else {
-            Vec::new()
+        match &data.fields {
+            Fields::Named(fields) => fields.named.iter().cloned().collect(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().enumerate().map(|(i, field)| {
+                syn::Field { ident: Some(positional_ident(i)), ..field.clone() }
+            }).collect(),
+            Fields::Unit => Vec::new(),
         }
     } else {
         Vec::new()
     }
 }
 
-fn get_params(input: &DeriveInput) -> TokenStream {
-    let lifetimes = input.generics.params.iter().filter_map(|t| {
-        if let syn::GenericParam::Lifetime(lt) = t {
-            Some(lt)
-        } else {
-            None
-        }
-    }).collect_vec();
+/// Fields which can be partially borrowed, i.e. all fields except the one marked with
+/// `#[borrow(dirty_flags)]` and the marker fields, see [`is_marker_field`]. Fields marked with
+/// `#[borrow(flatten = "...")]` are replaced with the fields of the nested struct, see
+/// [`flattened_fields`].
+fn get_fields(input: &DeriveInput) -> Vec<syn::Field> {
+    get_all_fields(input).into_iter()
+        .filter(|f| !parse_field_attrs(f).dirty_flags && !is_marker_field(f))
+        .flat_map(|f| match parse_field_attrs(&f).flatten {
+            Some(names) => flattened_fields(&f, &names),
+            None => vec![f],
+        })
+        .collect()
+}
 
-    let ty_params = input.generics.params.iter().filter_map(|t| {
-        if let syn::GenericParam::Type(ty) = t {
-            Some(ty.ident.clone())
-        } else {
-            None
+/// Fields of the nested struct inlined by a field marked with `#[borrow(flatten = "...")]`. Their
+/// types are taken from the `HasField` impls of the nested struct, through its `HasFingerprint`
+/// impl, so the listed names have to match the fields of the nested struct. The fields are marked
+/// with the internal `#[__borrow_flattened(field)]` attribute, so they are borrowed from the root
+/// view of the nested struct, see [`field_view_mut`].
+fn flattened_fields(field: &syn::Field, names: &[Ident]) -> Vec<syn::Field> {
+    let ty = &field.ty;
+    let member = field_member(field);
+    let fingerprint = proc_macro2::Literal::u64_unsuffixed(
+        fields_fingerprint(&names.iter().collect_vec())
+    );
+    names.iter().enumerate().map(|(i, name)| {
+        let n = proc_macro2::Literal::usize_unsuffixed(i);
+        syn::Field {
+            attrs: vec![syn::parse_quote! { #[__borrow_flattened(#member)] }],
+            vis: field.vis.clone(),
+            mutability: syn::FieldMutability::None,
+            ident: Some(name.clone()),
+            colon_token: Some(Default::default()),
+            ty: syn::parse_quote! {
+                <<#ty as borrow::HasFingerprint<#fingerprint>>::Struct as borrow::HasField<#n>>::Type
+            },
         }
-    }).collect_vec();
-    quote! {#(#lifetimes,)* #(#ty_params,)*}
+    }).collect()
 }
 
-fn get_bounds(input: &DeriveInput) -> TokenStream {
-    let inline_bounds = input.generics.params.iter().filter_map(|t| {
-        if let syn::GenericParam::Type(ty) = t {
-            (!ty.bounds.is_empty()).then_some(quote!{#ty})
-        } else {
-            None
-        }
-    }).collect_vec();
+/// Root views of the nested structs of fields marked with `#[borrow(flatten = "...")]`, bound
+/// to local variables, e.g. `let __flattened_render = { use borrow::traits::*;
+/// self.render.as_refs_mut() };`. The flattened fields are moved out of them, see
+/// [`field_view_mut`]. The method call syntax picks the crate root view of nested structs with
+/// private mut fields defined in the same crate.
+fn flattened_root_views(input: &DeriveInput) -> TokenStream {
+    let views = get_all_fields(input).into_iter()
+        .filter(|f| parse_field_attrs(f).flatten.is_some())
+        .map(|f| {
+            let member = field_member(&f);
+            let view = flattened_view_ident(&member);
+            quote! { let #view = { use borrow::traits::*; self.#member.as_refs_mut() }; }
+        });
+    quote! { #(#views)* }
+}
 
-    let where_bounds = input.generics.where_clause.as_ref().map(|t|
-        t.predicates.iter().map(|t| quote!{#t}).collect_vec()
-    ).unwrap_or_default();
+fn flattened_view_ident(member: &syn::Member) -> Ident {
+    let label = match member {
+        syn::Member::Named(ident) => ident.unraw().to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
+    };
+    Ident::new(&internal(&format!("flattened_{label}")), Span::call_site())
+}
 
-    quote! {#(#inline_bounds,)* #(#where_bounds,)*}
+/// Checks whether the field is a zero-sized marker, i.e. it is marked with `#[borrow(marker)]`,
+/// or its type is `PhantomData<...>`, `PhantomPinned`, or `()`. Markers hold no state, so they
+/// are not borrowed, and they do not add parameters to the `Ref` struct.
+fn is_marker_field(field: &syn::Field) -> bool {
+    let is_marker_type = match &field.ty {
+        Type::Tuple(tuple) => tuple.elems.is_empty(),
+        Type::Path(type_path) => type_path.qself.is_none() && type_path.path.segments.last()
+            .is_some_and(|s| s.ident == "PhantomData" || s.ident == "PhantomPinned"),
+        _ => false,
+    };
+    is_marker_type || parse_field_attrs(field).marker
 }
 
+/// Field storing the dirty flags of the other fields, marked with `#[borrow(dirty_flags)]`.
+fn get_dirty_flags_field(input: &DeriveInput) -> Option<syn::Member> {
+    let field = get_all_fields(input).into_iter().find(|f| parse_field_attrs(f).dirty_flags);
+    field.map(|f| field_member(&f))
+}
 
-fn get_module_tokens(attr: &syn::Attribute) -> Option<TokenStream> {
-    if !attr.path().is_ident("module") {
-        return None;
+/// Member accessing the field of the struct, e.g. `nodes` or `0`. Fields of tuple structs have no
+/// colon token, and are named by [`get_all_fields`] after their position.
+fn field_member(field: &syn::Field) -> syn::Member {
+    let ident = field.ident.as_ref().unwrap();
+    if field.colon_token.is_some() {
+        return syn::Member::Named(ident.clone());
     }
+    let index = ident.to_string()[1..].parse::<u32>().unwrap();
+    syn::Member::Unnamed(syn::Index { index, span: ident.span() })
+}
 
-    // Parse as Meta::List to get access to the tokens inside
-    match &attr.meta {
-        syn::Meta::List(syn::MetaList { tokens, .. }) => Some(tokens.clone()),
-        _ => None,
+/// Name of the field used in method names and labels, e.g. `nodes` or `0`. Raw identifiers are
+/// used without the `r#` prefix, e.g. `type` for `r#type`, so the methods are `borrow_type` etc.
+fn field_label(field: &syn::Field) -> String {
+    match field_member(field) {
+        syn::Member::Named(ident) => ident.unraw().to_string(),
+        syn::Member::Unnamed(index) => index.index.to_string(),
     }
 }
 
-// ===================
-// === Meta Derive ===
-// ===================
-
-fn meta_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let ident = &input.ident;
-    let fields = get_fields(&input);
-    let params = get_params(&input);
-    let bounds = get_bounds(&input);
-    let field_types = fields.iter().map(|f| &f.ty).collect_vec();
+/// Options provided with the `#[borrow(...)]` field attribute.
+#[derive(Default)]
+struct FieldAttrs {
+    /// Set by `#[borrow(dirty_flags)]`. The field stores the dirty flags of the other fields.
+    dirty_flags: bool,
+    /// Set by `#[borrow(wrap = "Mutex")]`. The field is a lock, and partial borrows access the
+    /// value inside of it.
+    wrap: Option<syn::LitStr>,
+    /// Set by `#[borrow(external)]`. The field is a mutable reference to a storage living outside
+    /// of the struct, and partial borrows access the storage as if it was a field of the struct.
+    external: bool,
+    /// Set by `#[borrow(readonly)]`. The field can be borrowed only as shared, even by the root
+    /// view of the struct.
+    readonly: bool,
+    /// Set by `#[borrow(marker)]`. The field is a zero-sized marker, and is not borrowed.
+    marker: bool,
+    /// Set by `#[borrow(on_mut = "path::to::hook")]`. Function called on every mutable access to
+    /// the field through a partial borrow.
+    on_mut: Option<syn::Path>,
+    /// Set by `#[borrow(flatten = "field1, field2")]`. The field is a struct deriving
+    /// `borrow::Partial`, and its fields are borrowed as if they were fields of this struct.
+    flatten: Option<Vec<Ident>>,
+    /// Set on the fields inlined by [`flattened_fields`]. The field of this struct holding the
+    /// nested struct.
+    flattened_from: Option<syn::Member>,
+}
 
-    let has_fields_for_struct = quote! {
-        impl<#params> borrow::HasFields for #ident<#params>
-        where #bounds {
-            type Fields = borrow::HList![#(#field_types,)*];
-        }
-    };
+/// Parses the field attributes, skipping the invalid ones. Errors are reported by [`validate`].
+fn parse_field_attrs(field: &syn::Field) -> FieldAttrs {
+    parse_field_attrs_with_errors(field, &mut vec![])
+}
 
-    let has_fields_ext_for_struct = {
-        let fields_hidden = field_types.iter().map(|_| quote! {borrow::Hidden});
-        let fields_ref    = field_types.iter().map(|t| quote! {&'__a #t});
-        let fields_mut    = field_types.iter().map(|t| quote! {&'__a mut #t});
-        quote! {
-            impl<#params> borrow::HasFieldsExt for #ident<#params>
-            where #bounds {
-                type FieldsAsHidden = borrow::HList![ #(#fields_hidden,)* ];
-                type FieldsAsRef<'__a> = borrow::HList![ #(#fields_ref,)* ] where Self: '__a;
-                type FieldsAsMut<'__a> = borrow::HList![ #(#fields_mut,)* ] where Self: '__a;
+fn parse_field_attrs_with_errors(field: &syn::Field, errors: &mut Vec<syn::Error>) -> FieldAttrs {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("__borrow_flattened")) {
+        attrs.flattened_from = attr.parse_args().ok();
+    }
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("borrow")) {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("dirty_flags") {
+                attrs.dirty_flags = true;
+                Ok(())
+            } else if meta.path.is_ident("wrap") {
+                attrs.wrap = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("external") {
+                attrs.external = true;
+                Ok(())
+            } else if meta.path.is_ident("readonly") {
+                attrs.readonly = true;
+                Ok(())
+            } else if meta.path.is_ident("marker") {
+                attrs.marker = true;
+                Ok(())
+            } else if meta.path.is_ident("on_mut") {
+                attrs.on_mut = Some(meta.value()?.parse::<syn::LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("flatten") {
+                attrs.flatten = Some(parse_field_list(meta.value()?)?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported borrow field attribute"))
             }
-        }
-    };
+        });
+        errors.extend(result.err());
+    }
+    attrs
+}
 
-    let out = quote! {
-        #has_fields_for_struct
-        #has_fields_ext_for_struct
+/// Type of the field as seen by partial borrows. Fields marked with `#[borrow(wrap = "...")]`
+/// are seen as the value inside of the lock, e.g. `T` for `Mutex<T>`, and fields marked with
+/// `#[borrow(external)]` as the referenced storage, e.g. `T` for `&'t mut T`.
+fn field_view_type(field: &syn::Field) -> TokenStream {
+    let ty = field_view_syn_type(field);
+    quote! { #ty }
+}
+
+fn field_view_syn_type(field: &syn::Field) -> Type {
+    let ty = &field.ty;
+    let attrs = parse_field_attrs(field);
+    let inner = if attrs.external {
+        external_inner_type(ty)
+    } else if let Some(wrap) = &attrs.wrap {
+        wrap_inner_type(ty, wrap)
+    } else {
+        None
     };
+    // Types not matching the attributes are reported by `validate`.
+    inner.unwrap_or(ty).clone()
+}
 
-    out.into()
+/// Checks whether the type is dynamically sized, i.e. it is a slice, `str`, a trait object, or a
+/// type parameter of the struct declared as `?Sized`. Such fields can be borrowed, but can not be
+/// stored by value, e.g. in test views.
+fn is_unsized_type(input: &DeriveInput, ty: &Type) -> bool {
+    let is_maybe_sized = |bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, Token![+]>| {
+        bounds.iter().any(|bound| matches!(bound,
+            syn::TypeParamBound::Trait(t) if matches!(t.modifier, syn::TraitBoundModifier::Maybe(_))
+        ))
+    };
+    match ty {
+        Type::Slice(_) | Type::TraitObject(_) => true,
+        Type::Paren(t) => is_unsized_type(input, &t.elem),
+        Type::Group(t) => is_unsized_type(input, &t.elem),
+        Type::Path(t) if t.qself.is_none() => {
+            let Some(ident) = t.path.get_ident() else { return false };
+            let inline = input.generics.type_params().any(|param|
+                param.ident == *ident && is_maybe_sized(&param.bounds)
+            );
+            let in_where_clause = input.generics.where_clause.iter()
+                .flat_map(|clause| &clause.predicates)
+                .any(|predicate| matches!(predicate,
+                    syn::WherePredicate::Type(p) if is_maybe_sized(&p.bounds)
+                        && matches!(&p.bounded_ty, Type::Path(b) if b.path.is_ident(ident))
+                ));
+            ident == "str" || inline || in_where_clause
+        }
+        _ => false,
+    }
 }
 
-// ======================
-// === Partial Derive ===
-// ======================
+/// Type referenced by a field marked with `#[borrow(external)]`, e.g. `T` for `&'t mut T`.
+fn external_inner_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(syn::TypeReference { mutability: Some(_), elem, .. }) => Some(elem),
+        _ => None,
+    }
+}
 
-// The internal macro documentation shows expansion parts for the following input:
-// ```
-// pub struct GeometryCtx {}
-// pub struct MaterialCtx {}
-// pub struct MeshCtx {}
-// pub struct SceneCtx {}
-//
-// #[derive(borrow::Partial)]
-// pub struct Ctx<'t, T: Debug> {
-//     pub version: &'t T,
-//     pub geometry: GeometryCtx,
-//     pub material: MaterialCtx,
-//     pub mesh: MeshCtx,
-//     pub scene: SceneCtx,
-// }
-//```
-#[allow(clippy::cognitive_complexity)]
-#[proc_macro_derive(Partial, attributes(module))]
-pub fn partial_borrow_derive(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+/// Type inside of the lock of a field marked with `#[borrow(wrap = "...")]`, e.g. `T` for
+/// `Mutex<T>`.
+fn wrap_inner_type<'t>(ty: &'t Type, wrap: &syn::LitStr) -> Option<&'t Type> {
+    let segment = match ty {
+        Type::Path(type_path) => type_path.path.segments.last(),
+        _ => None,
+    };
+    // The inner type is taken from the type arguments instead of `<#ty as borrow::Lock>::Inner`,
+    // as projections do not imply the lifetime bounds of generic parameters.
+    segment.filter(|s| s.ident == wrap.value()).and_then(|s| match &s.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        }),
+        _ => None,
+    })
+}
 
-    let input_raw2 = input_raw.clone();
-    let input = parse_macro_input!(input_raw2 as DeriveInput);
+/// Expression borrowing the field of `self` mutably, as seen by partial borrows. Flattened fields
+/// are moved out of the root view of the nested struct, bound by [`flattened_root_views`].
+fn field_view_mut(field: &syn::Field) -> TokenStream {
+    let ident = field_member(field);
+    let attrs = parse_field_attrs(field);
+    if let Some(member) = &attrs.flattened_from {
+        let view = flattened_view_ident(member);
+        quote! { #view.#ident.value_no_usage_tracking }
+    } else if attrs.wrap.is_some() {
+        quote! { borrow::Lock::inner_mut(&mut self.#ident) }
+    } else if attrs.external {
+        quote! { &mut *self.#ident }
+    } else {
+        quote! { &mut self.#ident }
+    }
+}
 
-    let path = input.attrs.iter()
-        .find_map(get_module_tokens)
-        .expect("Expected #[module(...)] attribute");
+/// Generic argument passing the parameter, e.g. `N` for `const N: usize`.
+fn get_param_arg(param: &syn::GenericParam) -> TokenStream {
+    match param {
+        syn::GenericParam::Lifetime(lt) => {
+            let lifetime = &lt.lifetime;
+            quote! {#lifetime}
+        }
+        syn::GenericParam::Type(ty) => {
+            let ident = &ty.ident;
+            quote! {#ident}
+        }
+        syn::GenericParam::Const(c) => {
+            let ident = &c.ident;
+            quote! {#ident}
+        }
+    }
+}
 
-    let ident = &input.ident;
-    let fields = get_fields(&input);
-    let params = get_params(&input);
-    let bounds = get_bounds(&input);
+/// Generic arguments of the struct, e.g. `'t, T, N` for `Grid<'t, T: Debug, const N: usize>`.
+fn get_params(input: &DeriveInput) -> TokenStream {
+    let params = input.generics.params.iter().map(get_param_arg).collect_vec();
+    quote! {#(#params,)*}
+}
 
-    let fields_vis = fields.iter().map(|f| f.vis.clone()).collect_vec();
-    let fields_ident = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect_vec();
-    let fields_ty = fields.iter().map(|f| &f.ty).collect_vec();
+/// Generic parameters of the struct as declared in impls, e.g. `'t, T, const N: usize` for
+/// `Grid<'t, T: Debug, const N: usize>`. Bounds of type parameters are provided by [`get_bounds`].
+fn get_params_decl(input: &DeriveInput) -> TokenStream {
+    let params = input.generics.params.iter().map(|t| match t {
+        syn::GenericParam::Const(c) => {
+            let (ident, ty) = (&c.ident, &c.ty);
+            quote! {const #ident: #ty}
+        }
+        _ => get_param_arg(t),
+    }).collect_vec();
+    quote! {#(#params,)*}
+}
 
-    // Fields in the form __$upper_case_field__
-    let fields_param = fields.iter().map(|f| {
-        let ident = f.ident.as_ref().unwrap();
-        Ident::new(&format!("__{}", snake_to_camel(&ident.to_string())), ident.span())
+/// Bounds of the generic parameters, used in where clauses of the generated impls. Defaults of
+/// type parameters, e.g. `T: Backend = Gl`, are not allowed there, so only `T: Backend` is kept.
+/// Lifetime relations declared inline, e.g. `'b: 'a`, are moved to the where clause as well.
+///
+/// All the bounds are used by every impl, as every impl using them names the struct, e.g.
+/// `HasField<0> for Ctx<T>` or `CtxRef<Ctx<T>, ...>`, and the struct type is well-formed only with
+/// its bounds. Impls generic over the struct, e.g. the ones of `#[borrow(derive(Debug))]`, do not
+/// use them.
+fn get_bounds(input: &DeriveInput) -> TokenStream {
+    let inline_bounds = input.generics.params.iter().filter_map(|t| match t {
+        syn::GenericParam::Type(ty) => {
+            let (ident, bounds) = (&ty.ident, &ty.bounds);
+            (!bounds.is_empty()).then_some(quote!{#ident: #bounds})
+        }
+        syn::GenericParam::Lifetime(lt) => {
+            let (lifetime, bounds) = (&lt.lifetime, &lt.bounds);
+            (!bounds.is_empty()).then_some(quote!{#lifetime: #bounds})
+        }
+        syn::GenericParam::Const(_) => None,
     }).collect_vec();
 
+    let where_bounds = input.generics.where_clause.as_ref().map(|t|
+        t.predicates.iter().map(|t| quote!{#t}).collect_vec()
+    ).unwrap_or_default();
 
+    // Predicates given with `#[borrow(bound = "...")]`.
+    let attr_bounds = parse_struct_attrs(input).bound;
 
-    let mut out: Vec<TokenStream> = vec![];
+    quote! {#(#inline_bounds,)* #(#where_bounds,)* #(#attr_bounds,)*}
+}
 
-    // === Ctx 1 ===
 
-    out.push(meta_derive(input_raw.clone()).into());
+// ==================
+// === Attributes ===
+// ==================
 
-    // === CtxRef 1 ===
+/// Options provided with the `#[borrow(...)]` struct attribute.
+#[derive(Default)]
+struct StructAttrs {
+    groups: Vec<Group>,
+    /// Set by `#[borrow(default = "mut field1, field2")]`. Fields borrowed by `p!` without
+    /// selectors, e.g. `p!(&Graph)`, stored as the `__default__` group.
+    default: Option<Group>,
+    /// Set by `#[borrow(non_exhaustive)]`. Selectors of unknown fields are ignored.
+    non_exhaustive: bool,
+    /// Set by `#[borrow(monomorphize)]`. Common shapes are instantiated in the defining crate.
+    monomorphize: bool,
+    /// Set by `#[borrow(track_by_default = false)]`. Only borrows declared with `!&` are tracked.
+    untracked_by_default: bool,
+    /// Set by `#[borrow(private_mut = "field1, field2")]`. Fields which can be selected as
+    /// mutable only in the crate defining the struct.
+    private_mut: Vec<Ident>,
+    /// Set by `#[borrow(readonly_in_release = "field1, field2")]`. Fields which can be selected
+    /// as mutable only in builds with `debug_assertions`.
+    readonly_in_release: Vec<Ident>,
+    /// Set by `#[borrow(dyn_state = "Name")]`. Name of the generated trait for state objects
+    /// exposing the struct fields.
+    dyn_state: Option<Ident>,
+    /// Set by `#[borrow(split_struct(ty = "Parts<'a>", fields = "mut field1, field2"))]`.
+    /// Hand-rolled split structs converted from and to partial borrows.
+    split_structs: Vec<SplitStruct>,
+    /// Set by `#[borrow(schema)]`. A JSON schema of the struct is written to `OUT_DIR`.
+    schema: bool,
+    /// Set by `#[borrow(derive(Debug, Clone))]`. Traits implemented for the ref struct.
+    derives: Vec<Ident>,
+    /// Set by `#[borrow(bound = "T: Clone")]`. Predicates added to all the generated impls.
+    bound: Vec<syn::WherePredicate>,
+    /// Set by `#[borrow(bound(as_refs_mut = "T: 'static"))]`. Predicates added to the
+    /// `AsRefsMut` impl only.
+    as_refs_mut_bound: Vec<syn::WherePredicate>,
+    /// Set by `#[borrow(transparent)]`. Partial borrows of the single-field wrapper struct are
+    /// partial borrows of its field.
+    transparent: bool,
+    /// Set by `#[borrow(vis = "pub(crate)")]`. Visibility of the generated ref struct, struct
+    /// macro, and methods of the struct. They are `pub` by default.
+    vis: Option<syn::Visibility>,
+    /// Set by `#[borrow(pub_field_methods)]`. The `borrow_$field`, `borrow_$field_mut`, and
+    /// `map_$field` methods get the visibility of the ref struct instead of the field.
+    pub_field_methods: bool,
+    /// Set by `#[borrow(macro_export = false)]`. The struct macro of `pub` structs is defined next
+    /// to the struct and re-exported with `pub(crate) use` instead of `#[macro_export]`.
+    local_macro: bool,
+    /// Set by `#[borrow(local)]`. The struct is defined in a function body, so it can not be named
+    /// by a path, and its struct macro is not exported with `#[macro_export]`.
+    local: bool,
+    /// Set by `#[borrow(macro_name = StateMacro)]`. Name of the struct macro, see
+    /// `struct_macro_ident`.
+    macro_name: Option<Ident>,
+    /// Set by `#[borrow(crate = ::framework::borrow)]`. Path of this crate used in the generated
+    /// code instead of `borrow`. The path can be given as a string as well, as in serde.
+    crate_path: Option<syn::Path>,
+}
 
-    let ref_ident = Ident::new(&format!("{ident}Ref"), ident.span());
+impl StructAttrs {
+    fn vis(&self) -> syn::Visibility {
+        self.vis.clone().unwrap_or_else(|| syn::parse_quote! { pub })
+    }
 
-    // Generates:
-    //
-    // ```
-    // pub struct CtxRef<__Self__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> {
-    //     pub version: borrow::Field<__Track__, __Version>,
-    //     pub geometry: borrow::Field<__Track__, __Geometry>,
-    //     pub material: borrow::Field<__Track__, __Material>,
-    //     pub mesh: borrow::Field<__Track__, __Mesh>,
-    //     pub scene: borrow::Field<__Track__, __Scene>,
-    //     pub marker: std::marker::PhantomData<__Self__>,
-    //     pub usage_tracker: borrow::UsageTracker,
-    // }
-    // ```
-    let ref_struct_def = {
-        quote! {
-            pub struct #ref_ident<__S__, __Track__, #(#fields_param,)*>
-            where __Track__: borrow::Bool {
-                #(#fields_vis #fields_ident: borrow::Field<__Track__, #fields_param>,)*
-                marker: std::marker::PhantomData<__S__>,
-                usage_tracker: borrow::UsageTracker,
+    /// Name of the struct macro. Macros exported with `#[macro_export]` live at the crate root, so
+    /// the name of the struct is suffixed with the hash of the module path given in
+    /// `#[module(...)]`, and `p!` uses the struct macro through its re-export under the name of
+    /// the struct. Structs without the module use `$StructMacro`, unless the name is given with
+    /// `#[borrow(macro_name = ...)]`.
+    fn struct_macro_ident(&self, ident: &Ident, module: Option<&TokenStream>) -> Ident {
+        match (&self.macro_name, module) {
+            (Some(name), _) => name.clone(),
+            (None, Some(module)) => {
+                let hash = fnv_hash(&tokens_string(module));
+                Ident::new(&format!("{ident}Macro_{hash:016x}"), ident.span())
             }
+            (None, None) => Ident::new(&format!("{ident}Macro"), ident.span()),
         }
-    };
+    }
+}
 
-    out.push(ref_struct_def.clone());
-    out.push(meta_derive(ref_struct_def.into()).into());
+/// Named set of fields declared with `#[borrow(group(name = "mut field1, field2"))]`.
+struct Group {
+    ident: Ident,
+    fields: Vec<Ident>,
+    /// Fields marked with `mut`, borrowed as mutable by the `@name` selector without an access.
+    mut_fields: Vec<Ident>,
+}
 
-    // Generates:
-    //
-    // ```
-    // #[macro_export]
-    // macro_rules! CtxMacro {
-    //     (@0 $pfx:tt $track:tt $s:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s [] [] [] [] [] $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt *        $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $n  $n  $n  $n  $n  $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt version  $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $n  $t1 $t2 $t3 $t4 $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt geometry $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $t0 $n  $t2 $t3 $t4 $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt material $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $t0 $t1 $n  $t3 $t4 $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt mesh     $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $t0 $t1 $t2 $n  $t4 $($ts)* } };
-    //     (@1 $pfx:tt $track:tt $s:tt $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt scene    $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 $pfx $track $s $t0 $t1 $t2 $t3 $n  $($ts)* } };
-    //     (@1 [$($pfx:tt)*] [$($track:tt)*] [$s:ty] [$($t0:tt)*] [$($t1:tt)*] [$($t2:tt)*] [$($t3:tt)*] [$($t4:tt)*] ) => {
-    //         $($pfx)* CtxRef<
-    //             $s,
-    //             $($track)*,
-    //             borrow::field!{$s, N0, $($t0)*},
-    //             borrow::field!{$s, N1, $($t1)*},
-    //             borrow::field!{$s, N2, $($t2)*},
-    //             borrow::field!{$s, N3, $($t3)*},
-    //             borrow::field!{$s, N4, $($t4)*}
-    //         >
-    //     };
-    // }
-    // pub use CtxMacro as Ctx;
-    // ```
-    out.push({
-        fn matcher(i: usize) -> Ident {
-            Ident::new(&format!("t{i}"), Span::call_site())
-        }
-        let macro_ident = Ident::new(&format!("{ident}Macro"), ident.span());
-        let matchers = (0..fields_ident.len()).map(matcher).map(|t| quote!{$#t:tt}).collect_vec();
-        let def_results  = (0..fields_ident.len()).map(matcher).map(|t| quote!{$#t}).collect_vec();
-        let init_rule = {
-            let all_empty = (0..fields_ident.len()).map(|_| quote!{[]}).collect_vec();
-            quote! {
-                (@0 $pfx:tt $track:tt $s:tt $($ts:tt)*) => {
-                    #path::#ident! { @1 $pfx $track $s #(#all_empty)* $($ts)* }
-                };
-            }
+impl Group {
+    /// Parses the fields of the group, e.g. `"mut geometry, mesh"`.
+    fn parse(ident: Ident, value: ParseStream) -> syn::Result<Self> {
+        let (fields, mutable): (Vec<_>, Vec<_>) =
+            parse_split_field_list(value)?.into_iter().unzip();
+        let mut_fields = fields.iter().zip(mutable).filter(|(_, m)| *m)
+            .map(|(field, _)| field.clone()).collect();
+        Ok(Group { ident, fields, mut_fields })
+    }
+}
+
+/// Hand-rolled struct of references to the fields, declared with
+/// `#[borrow(split_struct(ty = "GraphParts<'a>", fields = "mut nodes, edges"))]`. The first
+/// lifetime of the type is the lifetime of the references.
+struct SplitStruct {
+    ty: Type,
+    /// The fields and whether they are referenced mutably.
+    fields: Vec<(Ident, bool)>,
+}
+
+impl SplitStruct {
+    fn lifetime(&self) -> Option<&syn::Lifetime> {
+        let Type::Path(path) = &self.ty else { return None };
+        let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+            return None
         };
-        let field_rules = fields_ident.iter().enumerate().map(|(i, field)| {
-            let mut results = def_results.clone();
-            results[i] = quote! {$n};
-            quote! {
-                (@1 $pfx:tt $track:tt $s:tt #(#matchers)* #field $n:tt $($ts:tt)*) => {
-                    #path::#ident! { @1 $pfx $track $s #(#results)* $($ts)* }
-                };
-            }
-        });
-        let star_rule = {
-            let all_n_results = (0..fields_ident.len()).map(|_| quote!{$n}).collect_vec();
-            quote! {
-                (@1 $pfx:tt $track:tt $s:tt #(#matchers)* * $n:tt $($ts:tt)*) => {
-                    #path::#ident! { @1 $pfx $track $s #(#all_n_results)*  $($ts)* }
-                };
-            }
+        args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Lifetime(lifetime) => Some(lifetime),
+            _ => None,
+        })
+    }
+}
+
+/// Parses a comma-separated list of fields, optionally prefixed with `mut`, given as a string,
+/// e.g. `"mut field1, field2"`.
+fn parse_split_field_list(value: ParseStream) -> syn::Result<Vec<(Ident, bool)>> {
+    let parse_field = |input: ParseStream| {
+        let mutable = input.parse::<Option<Token![mut]>>()?.is_some();
+        let field = match input.parse::<syn::Member>()? {
+            syn::Member::Named(ident) => ident,
+            syn::Member::Unnamed(index) => positional_ident(index.index as usize),
         };
-        let production = {
-            let matchers_exp = (0..fields_ident.len()).map(matcher).map(|t|
-                quote!{[$($#t:tt)*]}
-            ).collect_vec();
-            let fields = def_results.iter().enumerate().map(|(i, t)| {
-                let n = Ident::new(&format!("N{i}"), Span::call_site());
-                quote! {
-                    borrow::field!{$s, #n, $(#t)*}
+        Ok((field, mutable))
+    };
+    let fields = value.parse::<syn::LitStr>()?.parse_with(|input: ParseStream| {
+        syn::punctuated::Punctuated::<(Ident, bool), Token![,]>::parse_terminated_with(
+            input, parse_field
+        )
+    })?;
+    Ok(fields.into_iter().collect())
+}
+
+/// Parses a comma-separated list of fields given as a string, e.g. `"field1, field2"`, or
+/// `"0, 1"` for tuple structs.
+fn parse_field_list(value: ParseStream) -> syn::Result<Vec<Ident>> {
+    let fields = value.parse::<syn::LitStr>()?.parse_with(
+        syn::punctuated::Punctuated::<syn::Member, Token![,]>::parse_terminated
+    )?;
+    Ok(fields.into_iter().map(|field| match field {
+        syn::Member::Named(ident) => ident,
+        syn::Member::Unnamed(index) => positional_ident(index.index as usize),
+    }).collect())
+}
+
+/// Parses a comma-separated list of where predicates given as a string, e.g. `"T: Clone, U: Eq"`.
+fn parse_predicates(value: ParseStream) -> syn::Result<Vec<syn::WherePredicate>> {
+    let predicates = value.parse::<syn::LitStr>()?.parse_with(
+        syn::punctuated::Punctuated::<syn::WherePredicate, Token![,]>::parse_terminated
+    )?;
+    Ok(predicates.into_iter().collect())
+}
+
+/// Parses the struct attributes, skipping the invalid ones. Errors are reported by [`validate`].
+fn parse_struct_attrs(input: &DeriveInput) -> StructAttrs {
+    parse_struct_attrs_with_errors(input, &mut vec![])
+}
+
+fn parse_struct_attrs_with_errors(input: &DeriveInput, errors: &mut Vec<syn::Error>) -> StructAttrs {
+    let mut attrs = StructAttrs::default();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("borrow")) {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("group") {
+                meta.parse_nested_meta(|group| {
+                    let ident = group.path.require_ident()?.clone();
+                    attrs.groups.push(Group::parse(ident, group.value()?)?);
+                    Ok(())
+                })
+            } else if meta.path.is_ident("default") {
+                let ident = Ident::new("__default__", meta.path.span());
+                attrs.default = Some(Group::parse(ident, meta.value()?)?);
+                Ok(())
+            } else if meta.path.is_ident("non_exhaustive") {
+                attrs.non_exhaustive = true;
+                Ok(())
+            } else if meta.path.is_ident("monomorphize") {
+                attrs.monomorphize = true;
+                Ok(())
+            } else if meta.path.is_ident("track_by_default") {
+                attrs.untracked_by_default = !meta.value()?.parse::<syn::LitBool>()?.value;
+                Ok(())
+            } else if meta.path.is_ident("private_mut") {
+                attrs.private_mut.extend(parse_field_list(meta.value()?)?);
+                Ok(())
+            } else if meta.path.is_ident("readonly_in_release") {
+                attrs.readonly_in_release.extend(parse_field_list(meta.value()?)?);
+                Ok(())
+            } else if meta.path.is_ident("dyn_state") {
+                attrs.dyn_state = Some(meta.value()?.parse::<syn::LitStr>()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("split_struct") {
+                let mut ty = None;
+                let mut fields = vec![];
+                meta.parse_nested_meta(|split| {
+                    if split.path.is_ident("ty") {
+                        ty = Some(split.value()?.parse::<syn::LitStr>()?.parse()?);
+                        Ok(())
+                    } else if split.path.is_ident("fields") {
+                        fields.extend(parse_split_field_list(split.value()?)?);
+                        Ok(())
+                    } else {
+                        Err(split.error("unsupported split_struct option, expected `ty` or `fields`"))
+                    }
+                })?;
+                let ty = ty.ok_or_else(|| meta.error("split_struct requires the `ty` option"))?;
+                attrs.split_structs.push(SplitStruct { ty, fields });
+                Ok(())
+            } else if meta.path.is_ident("derive") {
+                meta.parse_nested_meta(|derive| {
+                    attrs.derives.push(derive.path.require_ident()?.clone());
+                    Ok(())
+                })
+            } else if meta.path.is_ident("bound") {
+                if meta.input.peek(Token![=]) {
+                    attrs.bound.extend(parse_predicates(meta.value()?)?);
+                    Ok(())
+                } else {
+                    meta.parse_nested_meta(|bound| {
+                        if bound.path.is_ident("as_refs_mut") {
+                            attrs.as_refs_mut_bound.extend(parse_predicates(bound.value()?)?);
+                            Ok(())
+                        } else {
+                            Err(bound.error("unsupported bound, expected `as_refs_mut`"))
+                        }
+                    })
                 }
-            }).collect_vec();
-            quote! {
-                (@1 [$($pfx:tt)*] [$($track:tt)*] [$s:ty] #(#matchers_exp)* ) => {
-                    $($pfx)* #path::#ref_ident<$s, $($track)*, #(#fields,)*>
-                };
+            } else if meta.path.is_ident("schema") {
+                attrs.schema = true;
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                attrs.transparent = true;
+                Ok(())
+            } else if meta.path.is_ident("crate") || meta.path.is_ident("crate_path") {
+                // `crate_path` is the original name of the attribute, accepted as an alias.
+                let value = meta.value()?;
+                attrs.crate_path = Some(if value.peek(syn::LitStr) {
+                    value.parse::<syn::LitStr>()?.parse()?
+                } else {
+                    value.call(syn::Path::parse_mod_style)?
+                });
+                Ok(())
+            } else if meta.path.is_ident("macro_export") {
+                attrs.local_macro = !meta.value()?.parse::<syn::LitBool>()?.value;
+                Ok(())
+            } else if meta.path.is_ident("local") {
+                attrs.local = true;
+                Ok(())
+            } else if meta.path.is_ident("macro_name") {
+                attrs.macro_name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("pub_field_methods") {
+                attrs.pub_field_methods = true;
+                Ok(())
+            } else if meta.path.is_ident("vis") {
+                attrs.vis = Some(meta.value()?.parse::<syn::LitStr>()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported borrow attribute"))
             }
-        };
+        });
+        errors.extend(result.err());
+    }
+    attrs
+}
+
+/// Exports the struct macro under the name of the struct. Macros of `pub` structs are exported
+/// with `#[macro_export]`, so they can be used in other crates, unless the struct is marked with
+/// `#[borrow(macro_export = false)]`, in which case they are re-exported with `pub(crate) use`.
+/// Others are re-exported with the visibility given by `#[borrow(vis = "...")]`.
+fn export_struct_macro(
+    vis: &syn::Visibility,
+    attrs: &StructAttrs,
+    macro_ident: &Ident,
+    ident: &Ident,
+    rules: TokenStream,
+) -> TokenStream {
+    let is_pub = matches!(vis, syn::Visibility::Public(_));
+    if is_pub && (attrs.local_macro || attrs.local) {
+        quote! {
+            macro_rules! #macro_ident { #rules }
+            #[allow(unused_imports)]
+            pub(crate) use #macro_ident as #ident;
+        }
+    } else if is_pub {
         quote! {
             #[macro_export]
-            macro_rules! #macro_ident {
-                #init_rule
-                #star_rule
-                #(#field_rules)*
-                #production
-            }
+            macro_rules! #macro_ident { #rules }
             pub use #macro_ident as #ident;
         }
-    });
-
-    // Generates:
-    //
-    // ```
-    // impl<'t, T, __Version, __Geometry, __Material, __Mesh, __Scene>
-    // borrow::AsRefWithFields<borrow::HList![__Version, __Geometry, __Material, __Mesh, __Scene]>
-    // for Ctx<'t, T>
-    // where T: Debug {
-    //     type Output = CtxRef<Ctx<'t, T>, borrow::True, __Version, __Geometry, __Material, __Mesh, __Scene>;
-    // }
-    // ```
-    out.push(
+    } else {
         quote! {
-            impl<#params #(#fields_param,)*>
-            borrow::AsRefWithFields<borrow::HList![#(#fields_param,)*]>
-            for #ident<#params>
-            where #bounds {
-                type Output = #ref_ident<#ident<#params>, borrow::True, #(#fields_param,)*>;
-            }
+            macro_rules! #macro_ident { #rules }
+            #[allow(unused_imports)]
+            #vis use #macro_ident as #ident;
         }
-    );
+    }
+}
 
-    // Generates:
-    //
-    // ```
-    // impl<'__s__, __S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> borrow::CloneRef<'__s__>
-    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
-    // where
-    //     __Track__: borrow::Bool,
-    //     borrow::Field<__Track__, __Version>: borrow::CloneField<'__s__, __Track__>,
-    //     borrow::Field<__Track__, __Geometry>: borrow::CloneField<'__s__, __Track__>,
-    //     borrow::Field<__Track__, __Material>: borrow::CloneField<'__s__, __Track__>,
+fn get_module_tokens(attr: &syn::Attribute) -> Option<TokenStream> {
+    if !attr.path().is_ident("module") {
+        return None;
+    }
+
+    // Parse as Meta::List to get access to the tokens inside
+    match &attr.meta {
+        syn::Meta::List(syn::MetaList { tokens, .. }) => Some(tokens.clone()),
+        _ => None,
+    }
+}
+
+/// Replaces the leading `borrow` of paths in the generated code, e.g. `borrow::Field`, with the
+/// path given in `#[borrow(crate = ...)]`. Paths preceded by `::` or `.`, e.g. method calls
+/// like `.borrow::<T>()`, do not refer to the crate and are kept.
+fn with_crate_path(tokens: TokenStream, crate_path: &syn::Path) -> TokenStream {
+    use proc_macro2::TokenTree;
+    let tokens = tokens.into_iter().collect_vec();
+    let is_punct = |i: Option<usize>, c: char| matches!(
+        i.and_then(|i| tokens.get(i)), Some(TokenTree::Punct(punct)) if punct.as_char() == c
+    );
+    let mut out = TokenStream::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) => {
+                let stream = with_crate_path(group.stream(), crate_path);
+                let mut new_group = proc_macro2::Group::new(group.delimiter(), stream);
+                new_group.set_span(group.span());
+                out.extend([TokenTree::Group(new_group)]);
+            }
+            TokenTree::Ident(ident) if ident == "borrow" => {
+                let is_after_colons = is_punct(i.checked_sub(1), ':') && is_punct(i.checked_sub(2), ':');
+                let is_path_start = is_punct(Some(i + 1), ':') && is_punct(Some(i + 2), ':')
+                    && !is_after_colons && !is_punct(i.checked_sub(1), '.');
+                if is_path_start {
+                    out.extend(quote! { #crate_path });
+                } else {
+                    out.extend([token.clone()]);
+                }
+            }
+            _ => out.extend([token.clone()]),
+        }
+    }
+    out
+}
+
+/// Checks that the `borrow` crate is of the same version as this crate, so a version skew is
+/// reported by a single clear error instead of errors of the generated code.
+fn version_check() -> TokenStream {
+    let version = env!("CARGO_PKG_VERSION");
+    quote! { borrow::check_macro_version!(#version); }
+}
+
+/// FNV-1a hash of the field count and names, used to detect struct macros generated for another
+/// version of the struct.
+fn fields_fingerprint(fields: &[&Ident]) -> u64 {
+    fnv_hash(&format!("{}:{}", fields.len(), fields.iter().join(",")))
+}
+
+/// FNV-1a hash, stable across compilations, unlike the hashers of the standard library.
+fn fnv_hash(desc: &str) -> u64 {
+    desc.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Module path used in the generated `macro_rules!`. A leading `crate` is replaced with `$crate`, so
+/// the macro can be expanded in other crates, including in macros written by users.
+fn macro_module_path(path: TokenStream) -> TokenStream {
+    let mut tokens = path.into_iter().peekable();
+    let starts_with_crate = matches!(
+        tokens.peek(), Some(proc_macro2::TokenTree::Ident(ident)) if ident == "crate"
+    );
+    if starts_with_crate {
+        tokens.next();
+        let rest: TokenStream = tokens.collect();
+        quote! { $crate #rest }
+    } else {
+        tokens.collect()
+    }
+}
+
+// ==================
+// === Validation ===
+// ==================
+
+/// Checks the input of the derive, returning all the problems found, so they can be reported
+/// together, each pointing to the relevant part of the struct. The code generation assumes the
+/// input is valid.
+fn validate(input: &DeriveInput) -> Vec<syn::Error> {
+    let mut errors = vec![];
+    let mut error = |tokens: &dyn quote::ToTokens, message: String| {
+        errors.push(syn::Error::new_spanned(tokens, message));
+    };
+    let ident = &input.ident;
+    if !matches!(input.data, Data::Struct(_)) {
+        error(ident, "borrow::Partial can only be derived for structs.".into());
+    }
+    if let Some(module) = input.attrs.iter().find_map(get_module_tokens) {
+        let path = syn::parse::Parser::parse2(syn::Path::parse_mod_style, module.clone());
+        if let Err(err) = path {
+            error(&module, format!("Invalid module path in #[module(...)]: {err}."));
+        }
+    }
+
+    validate_fields(input, &mut errors);
+    validate_struct_attrs(input, &mut errors);
+    validate_groups(input, &mut errors);
+    validate_split_structs(input, &mut errors);
+    errors
+}
+
+fn validate_fields(input: &DeriveInput, errors: &mut Vec<syn::Error>) {
+    let mut attrs_errors = vec![];
+    let mut error = |tokens: &dyn quote::ToTokens, message: String| {
+        errors.push(syn::Error::new_spanned(tokens, message));
+    };
+    let mut dirty_flags_fields = vec![];
+    let all_fields = get_all_fields(input);
+    for field in &all_fields {
+        let attrs = parse_field_attrs_with_errors(field, &mut attrs_errors);
+        let label = field_label(field);
+        if attrs.dirty_flags {
+            dirty_flags_fields.push(field);
+        }
+        if attrs.wrap.is_some() && attrs.external {
+            error(field, format!("Field '{label}' can not be both wrap and external."));
+        }
+        if attrs.readonly && attrs.dirty_flags {
+            error(field, format!("Field '{label}' can not be both readonly and dirty_flags."));
+        }
+        if attrs.readonly && attrs.on_mut.is_some() {
+            error(field, format!("Field '{label}' can not be both readonly and on_mut."));
+        }
+        if attrs.external && external_inner_type(&field.ty).is_none() {
+            error(&field.ty, format!(
+                "Field '{label}' is marked with #[borrow(external)], but its type is not &mut _."
+            ));
+        }
+        if let Some(wrap) = &attrs.wrap {
+            if wrap_inner_type(&field.ty, wrap).is_none() {
+                error(&field.ty, format!(
+                    "Field '{label}' is marked with #[borrow(wrap = \"{}\")], but its type is \
+                    not {0}<...>.", wrap.value()
+                ));
+            }
+        }
+        if let Some(names) = &attrs.flatten {
+            let other = attrs.wrap.is_some() || attrs.external || attrs.readonly
+                || attrs.dirty_flags || attrs.marker || attrs.on_mut.is_some();
+            if other {
+                error(field, format!(
+                    "Field '{label}' is marked with #[borrow(flatten)], which can not be combined \
+                    with other borrow attributes."
+                ));
+            }
+            if names.is_empty() {
+                error(field, format!(
+                    "Field '{label}' is marked with #[borrow(flatten)], but no fields are listed."
+                ));
+            }
+        }
+    }
+    let mut seen_fields = std::collections::HashSet::new();
+    for field in get_fields(input) {
+        let label = field_label(&field);
+        if !seen_fields.insert(label.clone()) {
+            let tokens = parse_field_attrs(&field).flattened_from
+                .and_then(|member| all_fields.iter().find(|f| field_member(f) == member))
+                .unwrap_or(&field);
+            error(tokens, format!("Field '{label}' is defined more than once after flattening."));
+        }
+    }
+    for field in dirty_flags_fields.iter().skip(1) {
+        error(field, "Only one field can be marked with #[borrow(dirty_flags)].".into());
+    }
+    if !dirty_flags_fields.is_empty() && get_fields(input).len() > 64 {
+        let message = "#[borrow(dirty_flags)] supports structs with at most 64 fields.";
+        error(&input.ident, message.into());
+    }
+    errors.extend(attrs_errors);
+}
+
+fn validate_struct_attrs(input: &DeriveInput, errors: &mut Vec<syn::Error>) {
+    let mut attrs_errors = vec![];
+    let mut error = |tokens: &dyn quote::ToTokens, message: String| {
+        errors.push(syn::Error::new_spanned(tokens, message));
+    };
+    let attrs = parse_struct_attrs_with_errors(input, &mut attrs_errors);
+    let has_module = input.attrs.iter().any(|attr| attr.path().is_ident("module"));
+    let fields = get_fields(input);
+    let fields_ident = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect_vec();
+    let readonly_fields = fields.iter()
+        .filter(|f| parse_field_attrs(f).readonly)
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect_vec();
+    for field in &attrs.private_mut {
+        if !fields_ident.contains(&field) {
+            error(field, format!("#[borrow(private_mut)] refers to an unknown field '{field}'."));
+        }
+        if readonly_fields.contains(&field) {
+            error(field, format!("Field '{field}' can not be both private_mut and readonly."));
+        }
+        if !has_module {
+            error(field, format!("#[borrow(private_mut)] requires #[module(...)], as the macro of \
+                the struct refers to the crate-private type of the field '{field}' by its path."));
+        }
+    }
+    for field in &attrs.readonly_in_release {
+        if !fields_ident.contains(&field) {
+            error(field, format!(
+                "#[borrow(readonly_in_release)] refers to an unknown field '{field}'."
+            ));
+        }
+        if attrs.private_mut.contains(field) {
+            error(field, format!(
+                "Field '{field}' can not be both private_mut and readonly_in_release."
+            ));
+        }
+        if readonly_fields.contains(&field) {
+            error(field, format!(
+                "Field '{field}' can not be both readonly and readonly_in_release."
+            ));
+        }
+    }
+    let has_type_params =
+        input.generics.params.iter().any(|p| !matches!(p, syn::GenericParam::Lifetime(_)));
+    if attrs.monomorphize && has_type_params {
+        error(&input.generics, "#[borrow(monomorphize)] can only be used on structs without type \
+            parameters.".into());
+    }
+    if attrs.monomorphize {
+        for group in attrs.groups.iter().filter(|g| g.ident == "all") {
+            error(&group.ident, "Group 'all' conflicts with the shape generated by \
+                #[borrow(monomorphize)].".into());
+        }
+    }
+    if attrs.local && has_module {
+        error(&input.ident, "#[borrow(local)] can not be combined with #[module(...)], as \
+            function-local structs can not be named by a path.".into());
+    }
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("packed") {
+                error(attr, "#[repr(packed)] structs are not supported, as partial borrows \
+                    reference their fields, which can be unaligned.".into());
+            }
+            if meta.input.peek(syn::token::Paren) {
+                let _ = meta.input.parse::<proc_macro2::TokenTree>()?;
+            }
+            Ok(())
+        });
+    }
+    for derive in attrs.derives.iter().filter(|derive| *derive != "Debug" && *derive != "Clone") {
+        error(derive, format!("Trait '{derive}' can not be derived for the ref struct. Only Debug \
+            and Clone are supported."));
+    }
+    if let Some(state_ident) = &attrs.dyn_state {
+        if !input.generics.params.is_empty() {
+            error(state_ident, "#[borrow(dyn_state)] can only be used on structs without \
+                parameters.".into());
+        }
+    }
+    if attrs.transparent {
+        let has_other_attrs = !attrs.groups.is_empty() || attrs.non_exhaustive
+            || attrs.pub_field_methods
+            || attrs.monomorphize || attrs.untracked_by_default || !attrs.private_mut.is_empty()
+            || !attrs.readonly_in_release.is_empty() || attrs.dyn_state.is_some() || attrs.schema
+            || !attrs.derives.is_empty() || !attrs.split_structs.is_empty();
+        if has_other_attrs {
+            error(&input.ident, "#[borrow(transparent)] can not be combined with other borrow \
+                attributes.".into());
+        }
+        match get_fields(input).as_slice() {
+            [field] => match transparent_inner_path(&field.ty) {
+                None => error(&field.ty, "The field of a #[borrow(transparent)] struct must be a \
+                    struct deriving borrow::Partial, named by a path relative to the module of \
+                    the struct or starting with `crate`.".into()),
+                Some(inner) => if !has_module && !transparent_inner_is_absolute(&inner) {
+                    error(&field.ty, "The field of a #[borrow(transparent)] struct without \
+                        #[module(...)] must be named by a path starting with `crate` or `::`."
+                        .into());
+                }
+            }
+            _ => error(&input.ident, "#[borrow(transparent)] can only be used on structs with \
+                a single field, not counting marker fields.".into()),
+        }
+    }
+    errors.extend(attrs_errors);
+}
+
+fn validate_groups(input: &DeriveInput, errors: &mut Vec<syn::Error>) {
+    let mut error = |tokens: &dyn quote::ToTokens, message: String| {
+        errors.push(syn::Error::new_spanned(tokens, message));
+    };
+    let attrs = parse_struct_attrs(input);
+    let fields = get_fields(input);
+    let fields_ident = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect_vec();
+    let readonly_fields = fields.iter()
+        .filter(|f| parse_field_attrs(f).readonly)
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect_vec();
+    for field in attrs.default.iter().flat_map(|d| &d.fields) {
+        if !fields_ident.contains(&field) {
+            error(field, format!("#[borrow(default)] refers to an unknown field '{field}'."));
+        }
+    }
+    for field in attrs.default.iter().flat_map(|d| &d.mut_fields) {
+        if readonly_fields.contains(&field) {
+            error(field, format!("Readonly field '{field}' can not be borrowed mutably by default."));
+        }
+    }
+    for group in &attrs.groups {
+        let group_ident = &group.ident;
+        if fields_ident.contains(&group_ident) {
+            error(group_ident, format!("Group '{group_ident}' has the same name as a field."));
+        }
+        for field in group.fields.iter().filter(|field| !fields_ident.contains(field)) {
+            error(field, format!("Group '{group_ident}' refers to an unknown field '{field}'."));
+        }
+        for field in group.mut_fields.iter().filter(|field| readonly_fields.contains(field)) {
+            error(field, format!(
+                "Readonly field '{field}' can not be borrowed mutably by group '{group_ident}'."
+            ));
+        }
+    }
+}
+
+fn validate_split_structs(input: &DeriveInput, errors: &mut Vec<syn::Error>) {
+    let mut error = |tokens: &dyn quote::ToTokens, message: String| {
+        errors.push(syn::Error::new_spanned(tokens, message));
+    };
+    let attrs = parse_struct_attrs(input);
+    let fields = get_fields(input);
+    let fields_ident = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect_vec();
+    let readonly_fields = fields.iter()
+        .filter(|f| parse_field_attrs(f).readonly)
+        .map(|f| f.ident.as_ref().unwrap())
+        .collect_vec();
+    let struct_lifetimes = input.generics.lifetimes().map(|param| &param.lifetime).collect_vec();
+    for split in &attrs.split_structs {
+        match split.lifetime() {
+            None => error(&split.ty, "The type of a split struct must have a lifetime parameter, \
+                used as the lifetime of the references to the fields.".into()),
+            Some(lifetime) => if struct_lifetimes.contains(&lifetime) {
+                error(lifetime, format!("The lifetime '{}' of the split struct is a lifetime \
+                    parameter of the struct. Use another name.", lifetime.ident));
+            }
+        }
+        for (i, (field, mutable)) in split.fields.iter().enumerate() {
+            if !fields_ident.contains(&field) {
+                error(field, format!("Split struct refers to an unknown field '{field}'."));
+            }
+            if *mutable && readonly_fields.contains(&field) {
+                error(field, format!("Readonly field '{field}' can not be referenced mutably."));
+            }
+            if split.fields[..i].iter().any(|(other, _)| other == field) {
+                error(field, format!("Field '{field}' is listed twice."));
+            }
+        }
+    }
+}
+
+// ==============
+// === Schema ===
+// ==============
+
+/// Escapes the string and wraps it in quotes, so it can be used as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats tokens the way they are usually written, e.g. `Vec<Node>` instead of `Vec < Node >`.
+fn tokens_string(tokens: &TokenStream) -> String {
+    [(" :: ", "::"), (":: ", "::"), (" < ", "<"), ("< ", "<"), (" <", "<"), (" >", ">"), (" ,", ","),
+        ("& ", "&")]
+        .iter()
+        .fold(tokens.to_string(), |s, (from, to)| s.replace(from, to))
+}
+
+/// Writes the JSON schema of the struct marked with `#[borrow(schema)]` to
+/// `$OUT_DIR/borrow_schema/$module/$Struct.json`, where `$module` is the path given in
+/// `#[module(...)]` without the leading `crate`. For example:
+///
+/// ```text
+/// {
+///   "struct": "Graph",
+///   "module": "crate::graph",
+///   "params": ["T"],
+///   "fingerprint": 5381,
+///   "non_exhaustive": false,
+///   "track_by_default": true,
+///   "fields": [
+///     {"index": 0, "name": "nodes", "type": "Vec<T>", "wrap": null, "private_mut": false,
+///      "readonly_in_release": false, "readonly": false}
+///   ],
+///   "groups": [
+///     {"name": "topology", "fields": ["nodes", "edges"]}
+///   ]
+/// }
+/// ```
+fn write_schema(
+    input: &DeriveInput,
+    module: &TokenStream,
+    attrs: &StructAttrs,
+    fingerprint: u64,
+) -> syn::Result<()> {
+    let ident = &input.ident;
+    let error = |message: String| syn::Error::new_spanned(ident, message);
+    let out_dir = std::env::var("OUT_DIR").map_err(|_| error(
+        "#[borrow(schema)] requires OUT_DIR to be set, add a build script to the crate.".into()
+    ))?;
+    let params = input.generics.params.iter().map(|param| {
+        let param = match param {
+            syn::GenericParam::Type(ty) => ty.ident.to_string(),
+            syn::GenericParam::Lifetime(lt) => lt.lifetime.to_string(),
+            syn::GenericParam::Const(c) => c.ident.to_string(),
+        };
+        json_string(&param)
+    }).join(", ");
+    let fields = get_fields(input).iter().enumerate().map(|(index, field)| {
+        let name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_attrs = parse_field_attrs(field);
+        let wrap = field_attrs.wrap.map_or("null".to_string(), |w| json_string(&w.value()));
+        format!(
+            "    {{\"index\": {index}, \"name\": {}, \"type\": {}, \"wrap\": {wrap}, \
+            \"private_mut\": {}, \"readonly_in_release\": {}, \"readonly\": {}}}",
+            json_string(&field_label(field)),
+            json_string(&tokens_string(&quote! {#ty})),
+            attrs.private_mut.contains(name),
+            attrs.readonly_in_release.contains(name),
+            field_attrs.readonly,
+        )
+    }).join(",\n");
+    let groups = attrs.groups.iter().map(|group| {
+        let fields = group.fields.iter().map(|f| json_string(&f.unraw().to_string())).join(", ");
+        let name = json_string(&group.ident.unraw().to_string());
+        format!("    {{\"name\": {name}, \"fields\": [{fields}]}}")
+    }).join(",\n");
+    let schema = format!(
+        "{{\n  \"struct\": {},\n  \"module\": {},\n  \"params\": [{params}],\n  \
+        \"fingerprint\": {fingerprint},\n  \"non_exhaustive\": {},\n  \"track_by_default\": {},\n  \
+        \"fields\": [\n{fields}\n  ],\n  \"groups\": [\n{groups}\n  ]\n}}\n",
+        json_string(&ident.to_string()),
+        json_string(&tokens_string(module)),
+        attrs.non_exhaustive,
+        !attrs.untracked_by_default,
+    );
+
+    let mut path = std::path::PathBuf::from(out_dir).join("borrow_schema");
+    let module = tokens_string(module);
+    path.extend(module.split("::").filter(|segment| *segment != "crate"));
+    std::fs::create_dir_all(&path)
+        .map_err(|err| error(format!("Failed to create {}: {err}", path.display())))?;
+    path.push(format!("{ident}.json"));
+    // Unchanged schemas are not rewritten, so tools watching the files are not triggered.
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(schema.as_str()) {
+        std::fs::write(&path, schema)
+            .map_err(|err| error(format!("Failed to write {}: {err}", path.display())))?;
+    }
+    Ok(())
+}
+
+/// Appends the selectors of the expanded `p!` to the census file set with
+/// `BORROW_SELECTOR_CENSUS`, as a single JSON line, e.g.:
+///
+/// ```text
+/// {"struct": "Graph", "type": "Graph<T>", "selectors": ["mut nodes", "@topology", "- cache"],
+/// "file": "src/graph.rs", "line": 10, "column": 20}
+/// ```
+fn record_selectors(
+    path: &std::path::Path,
+    target_macro: &syn::Path,
+    target: &Type,
+    selectors: &Selectors,
+) -> syn::Result<()> {
+    use std::io::Write;
+    let selectors = match selectors {
+        Selectors::All => json_string("mut *"),
+        Selectors::Default => json_string("default"),
+        Selectors::List(selectors) => selectors.iter().map(|s| json_string(&selector_string(s))).join(", "),
+    };
+    let span = target.span();
+    let line = format!(
+        "{{\"struct\": {}, \"type\": {}, \"selectors\": [{selectors}], \"file\": {}, \"line\": {}, \
+        \"column\": {}}}\n",
+        json_string(&tokens_string(&quote! {#target_macro})),
+        json_string(&tokens_string(&quote! {#target})),
+        json_string(&span.file()),
+        span.start().line,
+        span.start().column + 1,
+    );
+    // Crates are compiled in parallel, so every selector is appended with a single write.
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|err| syn::Error::new(span, format!("Failed to write {}: {err}", path.display())))
+}
+
+/// The selector as written in `p!`, without its lifetime, e.g. `mut nodes` or `- <cache, @debug>`.
+fn selector_string(selector: &Selector) -> String {
+    let access = |access: &Access| match access {
+        Access::Ref => "",
+        Access::Mut => "mut ",
+        Access::Upgradeable => "upgradeable ",
+    };
+    match selector {
+        Selector::Field { access: a, field, .. } => format!("{}{}", access(a), quote! {#field}),
+        Selector::Star { access: a, .. } => format!("{}*", access(a)),
+        Selector::Group { access: a, group, .. } => format!("{}@{}", access(a), group.unraw()),
+        Selector::Exclude(excluded) => match excluded.as_slice() {
+            [selector] => format!("- {}", selector_string(selector)),
+            _ => format!("- <{}>", excluded.iter().map(selector_string).join(", ")),
+        },
+    }
+}
+
+// ===================
+// === Meta Derive ===
+// ===================
+
+fn meta_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let fields = get_fields(&input);
+    let params = get_params(&input);
+    let params_decl = get_params_decl(&input);
+    let bounds = get_bounds(&input);
+    let field_types = fields.iter().map(field_view_type).collect_vec();
+    // Lists can hold only sized types, so dynamically sized fields are listed as `PhantomData`.
+    let field_list_types = fields.iter().map(|field| {
+        let ty = field_view_syn_type(field);
+        if is_unsized_type(&input, &ty) {
+            quote! {std::marker::PhantomData<#ty>}
+        } else {
+            quote! {#ty}
+        }
+    }).collect_vec();
+
+    let has_fields_for_struct = quote! {
+        impl<#params_decl> borrow::HasFields for #ident<#params>
+        where #bounds {
+            type Fields = borrow::HList![#(#field_list_types,)*];
+        }
+    };
+
+    let has_fields_ext_for_struct = {
+        let fields_hidden = field_types.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+        let fields_ref    = field_types.iter().map(|t| quote! {&'__a #t}).collect_vec();
+        // Readonly fields are borrowed as shared by the root view as well, and so are private mut
+        // fields, which are borrowed mutably only by the root view of the defining crate, and
+        // readonly in release fields in builds without `debug_assertions`.
+        let attrs = parse_struct_attrs(&input);
+        let is_listed = |field: &syn::Field, list: &[Ident]| {
+            field.ident.as_ref().is_some_and(|f| list.contains(f))
+        };
+        let fields_mut = |release: bool| fields.iter().zip(&field_types).map(|(field, t)| {
+            let is_shared = parse_field_attrs(field).readonly
+                || is_listed(field, &attrs.private_mut)
+                || (release && is_listed(field, &attrs.readonly_in_release));
+            if is_shared {
+                quote! {&'__a #t}
+            } else {
+                quote! {&'__a mut #t}
+            }
+        }).collect_vec();
+        let has_fields_ext = |fields_mut: Vec<TokenStream>| quote! {
+            impl<#params_decl> borrow::HasFieldsExt for #ident<#params>
+            where #bounds {
+                type FieldsAsHidden = borrow::HList![ #(#fields_hidden,)* ];
+                type FieldsAsRef<'__a> = borrow::HList![ #(#fields_ref,)* ] where Self: '__a;
+                type FieldsAsMut<'__a> = borrow::HList![ #(#fields_mut,)* ] where Self: '__a;
+            }
+        };
+        if attrs.readonly_in_release.is_empty() {
+            has_fields_ext(fields_mut(false))
+        } else {
+            let debug = has_fields_ext(fields_mut(false));
+            let release = has_fields_ext(fields_mut(true));
+            quote! {
+                #[cfg(debug_assertions)]
+                #debug
+                #[cfg(not(debug_assertions))]
+                #release
+            }
+        }
+    };
+
+    let out = quote! {
+        #has_fields_for_struct
+        #has_fields_ext_for_struct
+    };
+
+    out.into()
+}
+
+// ======================
+// === Partial Derive ===
+// ======================
+
+// The internal macro documentation shows expansion parts for the following input:
+// ```
+// pub struct GeometryCtx {}
+// pub struct MaterialCtx {}
+// pub struct MeshCtx {}
+// pub struct SceneCtx {}
+//
+// #[derive(borrow::Partial)]
+// pub struct Ctx<'t, T: Debug> {
+//     pub version: &'t T,
+//     pub geometry: GeometryCtx,
+//     pub material: MaterialCtx,
+//     pub mesh: MeshCtx,
+//     pub scene: SceneCtx,
+// }
+//```
+#[proc_macro_derive(Partial, attributes(module, borrow))]
+pub fn partial_borrow_derive(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    partial_borrow_impl(input_raw)
+}
+
+/// Attribute form of the `borrow::Partial` derive. Struct options of `#[borrow(...)]` and the
+/// module of `#[module(...)]` can be given as its arguments, e.g.
+/// `#[borrow::partial_struct(module = crate, vis = "pub(crate)")]`. As the attribute rewrites the
+/// struct, the `#[borrow(...)]` and `#[module(...)]` attributes are removed from it, so the
+/// struct can be used with other attribute macros not aware of them. The name `partial` is taken
+/// by the `partial!` macro.
+#[proc_macro_attribute]
+pub fn partial_struct(
+    attr: proc_macro::TokenStream,
+    input_raw: proc_macro::TokenStream
+) -> proc_macro::TokenStream {
+    let mut input = parse_macro_input!(input_raw as DeriveInput);
+    let args = parse_macro_input!(
+        attr with syn::punctuated::Punctuated::<syn::Meta, Token![,]>::parse_terminated
+    );
+    for arg in args {
+        match arg {
+            syn::Meta::NameValue(arg) if arg.path.is_ident("module") => {
+                let module = &arg.value;
+                input.attrs.push(syn::parse_quote! { #[module(#module)] });
+            }
+            arg => input.attrs.push(syn::parse_quote! { #[borrow(#arg)] }),
+        }
+    }
+    let output: TokenStream = partial_borrow_impl(quote! { #input }.into()).into();
+    let is_helper = |attr: &syn::Attribute| {
+        attr.path().is_ident("borrow") || attr.path().is_ident("module")
+    };
+    input.attrs.retain(|attr| !is_helper(attr));
+    if let Data::Struct(data) = &mut input.data {
+        for field in &mut data.fields {
+            field.attrs.retain(|attr| !is_helper(attr));
+        }
+    }
+    quote! {
+        #input
+        #output
+    }.into()
+}
+
+#[allow(clippy::cognitive_complexity)]
+fn partial_borrow_impl(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input_raw2 = input_raw.clone();
+    let input = parse_macro_input!(input_raw2 as DeriveInput);
+    if let Some(error) = combine_errors(validate(&input)) {
+        return error.to_compile_error().into();
+    }
+
+    // Without `#[module(...)]`, the struct macro refers to itself by the path it was invoked with,
+    // and to the `Ref` struct by `borrow::HasRef`.
+    let path = input.attrs.iter().find_map(get_module_tokens).map(macro_module_path);
+
+    let attrs = parse_struct_attrs(&input);
+    let version_check = version_check();
+    if attrs.transparent {
+        let output = match transparent_derive(&input, path.as_ref()) {
+            Ok(output) => quote! { #version_check #output },
+            Err(error) => error.to_compile_error(),
+        };
+        return match &attrs.crate_path {
+            Some(crate_path) => with_crate_path(output, crate_path),
+            None => output,
+        }.into();
+    }
+
+    let ident = &input.ident;
+    let fields = get_fields(&input);
+    let params = get_params(&input);
+    let params_decl = get_params_decl(&input);
+    let bounds = get_bounds(&input);
+    let vis = attrs.vis();
+    let dirty_flags = get_dirty_flags_field(&input);
+
+    let fields_vis = fields.iter().map(|f| f.vis.clone()).collect_vec();
+    // Field methods give access to the field, so they are as visible as the field by default.
+    let fields_method_vis = if attrs.pub_field_methods {
+        fields.iter().map(|_| vis.clone()).collect_vec()
+    } else {
+        fields_vis.clone()
+    };
+    let fields_ident = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect_vec();
+    let fields_ty = fields.iter().map(field_view_type).collect_vec();
+    let fields_mut = fields.iter().map(field_view_mut).collect_vec();
+    let fields_label = fields.iter().map(field_label).collect_vec();
+    let fields_readonly = fields.iter().map(|f| parse_field_attrs(f).readonly).collect_vec();
+    // Fields as written in selectors, e.g. `nodes`, or `0` for tuple structs.
+    let fields_selector = fields.iter().map(field_member).collect_vec();
+
+    // Fields in the form __$upper_case_field__
+    let fields_param = fields.iter().map(|f| {
+        let ident = f.ident.as_ref().unwrap();
+        Ident::new(&format!("__{}", snake_to_camel(&ident.unraw().to_string())), ident.span())
+    }).collect_vec();
+
+    let fingerprint = proc_macro2::Literal::u64_unsuffixed(fields_fingerprint(&fields_ident));
+    if attrs.schema {
+        let module = input.attrs.iter().find_map(get_module_tokens).unwrap_or_default();
+        if let Err(error) = write_schema(&input, &module, &attrs, fields_fingerprint(&fields_ident)) {
+            return error.to_compile_error().into();
+        }
+    }
+
+    // Aliases of the types of fields marked with `#[borrow(private_mut = "...")]`, in the form
+    // __Ctx__private_mut__$field. Mutable selectors of these fields refer to the aliases, which are
+    // `pub(crate)`, so they can not be used outside the crate defining the struct.
+    let private_mut_aliases = fields_ident.iter().map(|field| {
+        attrs.private_mut.contains(field).then(|| {
+            let field = internal(&field.unraw().to_string());
+            let name = format!("{ident}{}{field}", internal("private_mut"));
+            Ident::new(&internal(&name), Span::call_site())
+        })
+    }).collect_vec();
+
+    // Aliases of the types of fields marked with `#[borrow(readonly_in_release = "...")]`, in the
+    // form __Ctx__readonly_in_release__$field. Mutable selectors of these fields refer to the
+    // aliases, which resolve to the field types only in builds with `debug_assertions`.
+    let readonly_aliases = fields_ident.iter().map(|field| {
+        attrs.readonly_in_release.contains(field).then(|| {
+            let field = internal(&field.unraw().to_string());
+            let name = format!("{ident}{}{field}", internal("readonly_in_release"));
+            Ident::new(&internal(&name), Span::call_site())
+        })
+    }).collect_vec();
+
+    // Aliases of the types of fields marked with `#[borrow(readonly)]`, in the form
+    // __Ctx__readonly__$field. Mutable selectors of these fields refer to the aliases, which never
+    // resolve to the field types.
+    let readonly_field_aliases = fields_ident.iter().zip(&fields_readonly).map(|(field, readonly)| {
+        readonly.then(|| {
+            let field = internal(&field.unraw().to_string());
+            let name = format!("{ident}{}{field}", internal("readonly"));
+            Ident::new(&internal(&name), Span::call_site())
+        })
+    }).collect_vec();
+
+    // Aliases used by mutable selectors instead of the field types.
+    let mut_aliases = private_mut_aliases.iter().zip(&readonly_aliases).zip(&readonly_field_aliases)
+        .map(|((private, readonly), readonly_field)| {
+            private.as_ref().or(readonly.as_ref()).or(readonly_field.as_ref())
+        })
+        .collect_vec();
+
+    // Aliases as used by the struct macro. Without `#[module(...)]`, the macro can not refer to the
+    // aliases by their path, so it uses the generic aliases of the library instead, e.g.
+    // `@ ReadonlyMutType`. Private mut fields require `#[module(...)]`.
+    let mut_alias_paths = mut_aliases.iter().enumerate().map(|(i, alias)| {
+        let alias = alias.as_ref()?;
+        Some(match &path {
+            Some(path) => quote! {#path::#alias},
+            None if fields_readonly[i] => quote! {@ ReadonlyMutType},
+            None => quote! {@ MutInReleaseType},
+        })
+    }).collect_vec();
+
+    let mut out: Vec<TokenStream> = vec![];
+
+    // === Ctx 1 ===
+
+    out.push(meta_derive(input_raw.clone()).into());
+
+    // === CtxRef 1 ===
+
+    let ref_ident = Ident::new(&format!("{ident}Ref"), ident.span());
+
+    // Generates:
+    //
+    // ```
+    // pub struct CtxRef<__Self__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> {
+    //     pub version: borrow::Field<__Track__, __Version>,
+    //     pub geometry: borrow::Field<__Track__, __Geometry>,
+    //     pub material: borrow::Field<__Track__, __Material>,
+    //     pub mesh: borrow::Field<__Track__, __Mesh>,
+    //     pub scene: borrow::Field<__Track__, __Scene>,
+    //     pub __marker__: std::marker::PhantomData<
+    //         fn() -> (std::marker::PhantomData<__Self__>, __Track__)
+    //     >,
+    //     pub __usage_tracker__: borrow::UsageTracker,
+    // }
+    // ```
+    let ref_struct_def = {
+        quote! {
+            // Fields of tuple structs are named after their position, e.g. `_0`.
+            #[allow(clippy::pub_underscore_fields)]
+            #vis struct #ref_ident<__S__: ?Sized, #(#fields_param,)* __Track__ = borrow::True>
+            where __Track__: borrow::Bool {
+                #(#fields_vis #fields_ident: borrow::Field<__Track__, #fields_param>,)*
+                // The struct is never accessed through the marker, so it should not affect the auto
+                // traits of the borrow. `__Track__` is used by the marker as well, as structs
+                // without fields do not use it otherwise.
+                __marker__: std::marker::PhantomData<
+                    fn() -> (std::marker::PhantomData<__S__>, __Track__)
+                >,
+                __usage_tracker__: borrow::UsageTracker,
+            }
+        }
+    };
+
+    out.push(ref_struct_def.clone());
+    out.push(meta_derive(ref_struct_def.into()).into());
+
+    // Generates, for `#[borrow(derive(Debug, Clone))]`:
+    //
+    // ```
+    // impl<__S__: ?Sized, __Version, __Geometry, __Track__: borrow::Bool> std::fmt::Debug
+    // for CtxRef<__S__, __Version, __Geometry, __Track__>
+    // where __Version: std::fmt::Debug, __Geometry: std::fmt::Debug {
+    //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    //         f.debug_struct("CtxRef")
+    //             .field("version", &self.version.value_no_usage_tracking)
+    //             .field("geometry", &self.geometry.value_no_usage_tracking)
+    //             .finish()
+    //     }
+    // }
+    //
+    // impl<__S__: ?Sized, __Version, __Geometry, __Track__: borrow::Bool> Clone
+    // for CtxRef<__S__, __Version, __Geometry, __Track__>
+    // where __Version: Clone, __Geometry: Clone {
+    //     fn clone(&self) -> Self {
+    //         Self {
+    //             version: self.version.clone_field(),
+    //             geometry: self.geometry.clone_field(),
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: self.__usage_tracker__.clone(),
+    //         }
+    //     }
+    // }
+    // ```
+    for derive in &attrs.derives {
+        let params = quote! {__S__, #(#fields_param,)* __Track__};
+        let params_decl = quote! {__S__: ?Sized, #(#fields_param,)* __Track__: borrow::Bool};
+        if derive == "Debug" {
+            let ref_name = ref_ident.to_string();
+            let labels = fields_ident.iter().map(|f| f.unraw().to_string());
+            out.push(quote! {
+                impl<#params_decl> std::fmt::Debug for #ref_ident<#params>
+                where #(#fields_param: std::fmt::Debug,)* {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.debug_struct(#ref_name)
+                            #(.field(#labels, &self.#fields_ident.value_no_usage_tracking))*
+                            .finish()
+                    }
+                }
+            });
+        } else if derive == "Clone" {
+            out.push(quote! {
+                impl<#params_decl> Clone for #ref_ident<#params>
+                where #(#fields_param: Clone,)* {
+                    fn clone(&self) -> Self {
+                        Self {
+                            #(#fields_ident: self.#fields_ident.clone_field(),)*
+                            __marker__: std::marker::PhantomData,
+                            __usage_tracker__: self.__usage_tracker__.clone(),
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    // Generates the following, where `CtxMacro` is suffixed with the hash of the module path, see
+    // `StructAttrs::struct_macro_ident`:
+    //
+    // ```
+    // #[macro_export]
+    // macro_rules! CtxMacro {
+    //     (@0 [$($m:tt)*] $pfx:tt [] $s:tt $($ts:tt)*) => { $crate::Ctx! { @0 [$($m)*] $pfx [borrow::True] $s $($ts)* } };
+    //     (@0 [$($m:tt)*] $pfx:tt [_] $s:tt $($ts:tt)*) => { $crate::Ctx! { @0 [$($m)*] $pfx [borrow::False] $s $($ts)* } };
+    //     (@0 [$($m:tt)*] $pfx:tt [!] $s:tt $($ts:tt)*) => { $crate::Ctx! { @0 [$($m)*] $pfx [borrow::True] $s $($ts)* } };
+    //     (@0 [$($m:tt)*] $pfx:tt $track:tt $s:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] *        $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (* $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] version  $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (0 $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] geometry $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (1 $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] material $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (2 $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] mesh     $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (3 $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] scene    $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s [$($a)* (4 $n)] $($ts)* } };
+    //     (@1 [$($m:tt)*] [$($pfx:tt)*] [$($track:tt)*] [$s:ty] $a:tt) => {
+    //         $($pfx)* CtxRef<
+    //             $s,
+    //             $crate::Ctx! { @2 [$($m)*] [$s] 0 [] $a },
+    //             $crate::Ctx! { @2 [$($m)*] [$s] 1 [] $a },
+    //             $crate::Ctx! { @2 [$($m)*] [$s] 2 [] $a },
+    //             $crate::Ctx! { @2 [$($m)*] [$s] 3 [] $a },
+    //             $crate::Ctx! { @2 [$($m)*] [$s] 4 [] $a },
+    //             $($track)*
+    //         >
+    //     };
+    //     (@2 [$($m:tt)*] $s:tt $i:tt $r:tt [(* $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s $i $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt 0 $r:tt [(0 $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 0 $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt 1 $r:tt [(1 $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 1 $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt 2 $r:tt [(2 $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 2 $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt 3 $r:tt [(3 $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 3 $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt 4 $r:tt [(4 $n:tt) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 4 $n [$($a)*] } };
+    //     (@2 [$($m:tt)*] $s:tt $i:tt $r:tt [$a0:tt $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s $i $r [$($a)*] } };
+    //     (@2 [$($m:tt)*] [$s:ty] $i:tt [$($r:tt)*] []) => { borrow::field!{$s, $i, 5381, $($r)*} };
+    // }
+    // pub use CtxMacro as Ctx;
+    // ```
+    //
+    // The `@1` rules append the selectors to the list of accesses `[$($a)*]`, e.g. `(4 [& 'a mut])`
+    // for `mut scene`, or `(* [& 'a])` for `*`. The `@1` rule without selectors generates the `Ref`
+    // struct, in which the `@2` rules resolve the access of every field to the last access of the
+    // list selecting it. This keeps the size of the macro linear in the number of fields, which
+    // matters for IDEs expanding it for every `p!` of the struct, see the `expansion_size` test.
+    //
+    // If the struct is marked with `#[borrow(non_exhaustive)]`, the following rule is inserted
+    // after the field rules, so selectors of fields unknown to this version of the struct are
+    // ignored (the fields are treated as hidden):
+    //
+    // ```
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt $f:ident $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s $a $($ts)* } };
+    // ```
+    //
+    // Fields marked with `#[borrow(private_mut = "...")]` get additional rules before their field
+    // rule and before the `*` rule, replacing mutable accesses with accesses referring to the
+    // crate-private field type alias, e.g. `(4 [[crate::__Ctx__private_mut__scene] & 'a mut])`.
+    // Fields marked with `#[borrow(readonly_in_release = "...")]` get the same rules, referring to
+    // the `__Ctx__readonly_in_release__scene` alias instead, and fields marked with
+    // `#[borrow(readonly)]` get them referring to the `__Ctx__readonly__scene` alias, except that
+    // their `*` rules borrow them as shared, e.g. `[& 'a]`. The `*` rules of these fields are `@2`
+    // rules placed before the generic `*` rule, e.g.:
+    //
+    // ```
+    //     (@2 [$($m:tt)*] $s:tt 4 $r:tt [(* [& $lt:lifetime mut]) $($a:tt)*]) => { $crate::Ctx! { @2 [$($m)*] $s 4 [[crate::__Ctx__private_mut__scene] & $lt mut] [$($a)*] } };
+    // ```
+    //
+    // Every group gets a rule replacing the group selector with the selectors of its fields, e.g.
+    // for `#[borrow(group(render = "geometry, mesh"))]`:
+    //
+    // ```
+    //     (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt @ render $n:tt $($ts:tt)*) => { $crate::Ctx! { @1 [$($m)*] $pfx $track $s $a geometry $n mesh $n $($ts)* } };
+    // ```
+    //
+    // Selectors excluding fields, e.g. `- mesh`, use the empty access, e.g. `mesh []`.
+    //
+    // The `[$($m:tt)*]` is the path the macro was invoked with, e.g. `[graph::Ctx]`, passed by `p!`.
+    // If the struct is not marked with `#[module(...)]`, the macro has no other way to refer to
+    // itself, so the rules invoke `$($m)*!` instead of `$crate::Ctx!`, and the `Ref` struct is
+    // named by `<$s as borrow::HasRef<(...), $($track)*>>::Ref` instead of `CtxRef<...>`.
+    //
+    // The `5381` passed to `borrow::field!` is the fingerprint of the struct fields, checked against
+    // the `borrow::HasFingerprint` impl of the struct.
+    //
+    // The first rule resolves borrows without the `_&` or `!&` prefix. If the struct is marked with
+    // `#[borrow(track_by_default = false)]`, it uses `borrow::False` instead. The following rules
+    // resolve the `_&` and `!&` prefixes, so that `p!` does not refer to the `borrow` crate, which
+    // may be used under another path, see `#[borrow(crate = ...)]`. For the same reason, the
+    // `@redundant` rule wraps borrows with redundant selectors in the deprecated alias reporting
+    // them, e.g. `borrow::DuplicateSelector`.
+    out.push({
+        let module = input.attrs.iter().find_map(get_module_tokens);
+        let macro_ident = attrs.struct_macro_ident(ident, module.as_ref());
+        let recurse = match &path {
+            Some(path) => quote! {#path::#ident!},
+            None => quote! {$($m)*!},
+        };
+        let indexes = (0..fields_ident.len()).map(proc_macro2::Literal::usize_unsuffixed).collect_vec();
+        let init_rule = {
+            let default_track = if attrs.untracked_by_default {
+                quote! { borrow::False }
+            } else {
+                quote! { borrow::True }
+            };
+            quote! {
+                (@0 [$($m:tt)*] $pfx:tt [] $s:tt $($ts:tt)*) => {
+                    #recurse { @0 [$($m)*] $pfx [#default_track] $s $($ts)* }
+                };
+                (@0 [$($m:tt)*] $pfx:tt [_] $s:tt $($ts:tt)*) => {
+                    #recurse { @0 [$($m)*] $pfx [borrow::False] $s $($ts)* }
+                };
+                (@0 [$($m:tt)*] $pfx:tt [!] $s:tt $($ts:tt)*) => {
+                    #recurse { @0 [$($m)*] $pfx [borrow::True] $s $($ts)* }
+                };
+                (@redundant $alias:ident $($ts:tt)*) => {
+                    borrow::$alias<$($ts)*>
+                };
+                (@0 [$($m:tt)*] $pfx:tt $track:tt $s:tt $($ts:tt)*) => {
+                    #recurse { @1 [$($m)*] $pfx $track $s [] $($ts)* }
+                };
+            }
+        };
+        // Mutable accesses of private mut fields, as the selector pattern and the result referring
+        // to the field type alias.
+        let private_mut_access = |alias: &TokenStream| [
+            (quote! {[& $lt:lifetime mut]}, quote! {[[#alias] & $lt mut]}),
+            (quote! {[upgradeable $lt:lifetime]}, quote! {[[#alias] upgradeable $lt]}),
+        ];
+        let field_rules = fields_selector.iter().zip(&indexes).enumerate().map(|(i, (field, n))| {
+            let private_rules = mut_alias_paths[i].iter().flat_map(private_mut_access).map(
+                |(access, result)| quote! {
+                    (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] #field #access $($ts:tt)*) => {
+                        #recurse { @1 [$($m)*] $pfx $track $s [$($a)* (#n #result)] $($ts)* }
+                    };
+                }
+            ).collect_vec();
+            quote! {
+                #(#private_rules)*
+                (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] #field $n:tt $($ts:tt)*) => {
+                    #recurse { @1 [$($m)*] $pfx $track $s [$($a)* (#n $n)] $($ts)* }
+                };
+            }
+        });
+        let star_rule = quote! {
+            (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt [$($a:tt)*] * $n:tt $($ts:tt)*) => {
+                #recurse { @1 [$($m)*] $pfx $track $s [$($a)* (* $n)] $($ts)* }
+            };
+        };
+        // The default selectors, used by `p!` without selectors, are the `__default__` group,
+        // which is empty for structs without `#[borrow(default = ...)]`.
+        let no_default_ident = Ident::new("__default__", ident.span());
+        let no_default = Group { ident: no_default_ident, fields: vec![], mut_fields: vec![] };
+        let default_group = attrs.default.as_ref().unwrap_or(&no_default);
+        let group_rules = attrs.groups.iter().chain([default_group]).map(|group| {
+            let group_ident = &group.ident;
+            let indexes = group.fields.iter().filter_map(|field| {
+                fields_ident.iter().position(|f| *f == field)
+            }).collect_vec();
+            let members = indexes.iter().map(|i| &fields_selector[*i]).collect_vec();
+            let has_readonly = indexes.iter().any(|i| fields_readonly[*i]);
+            // Readonly fields are borrowed as shared by `mut @group` and `upgradeable @group`.
+            let readonly_rules = if has_readonly {
+                let accesses = [quote! {[& $lt:lifetime mut]}, quote! {[upgradeable $lt:lifetime]}];
+                let results = [quote! {[& $lt mut]}, quote! {[upgradeable $lt]}];
+                accesses.into_iter().zip(results).map(|(access, result)| {
+                    let selectors = indexes.iter().zip(&members).map(|(i, member)| {
+                        if fields_readonly[*i] {
+                            quote! {#member [& $lt]}
+                        } else {
+                            quote! {#member #result}
+                        }
+                    }).collect_vec();
+                    quote! {
+                        (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt @ #group_ident #access $($ts:tt)*) => {
+                            #recurse { @1 [$($m)*] $pfx $track $s $a #(#selectors)* $($ts)* }
+                        };
+                    }
+                }).collect_vec()
+            } else {
+                vec![]
+            };
+            // Fields marked with `mut` in the group are borrowed as mutable by `@group`.
+            let mut_fields_rule = (!group.mut_fields.is_empty()).then(|| {
+                let selectors = indexes.iter().zip(&members).map(|(i, member)| {
+                    if group.mut_fields.contains(fields_ident[*i]) {
+                        quote! {#member [& $lt mut]}
+                    } else {
+                        quote! {#member [& $lt]}
+                    }
+                });
+                quote! {
+                    (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt @ #group_ident [& $lt:lifetime] $($ts:tt)*) => {
+                        #recurse { @1 [$($m)*] $pfx $track $s $a #(#selectors)* $($ts)* }
+                    };
+                }
+            });
+            quote! {
+                #(#readonly_rules)*
+                #mut_fields_rule
+                (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt @ #group_ident $n:tt $($ts:tt)*) => {
+                    #recurse { @1 [$($m)*] $pfx $track $s $a #(#members $n)* $($ts)* }
+                };
+            }
+        });
+        let unknown_field_rule = attrs.non_exhaustive.then(|| quote! {
+            (@1 [$($m:tt)*] $pfx:tt $track:tt $s:tt $a:tt $f:ident $n:tt $($ts:tt)*) => {
+                #recurse { @1 [$($m)*] $pfx $track $s $a $($ts)* }
+            };
+        });
+        let production = {
+            let fields = indexes.iter().map(|n| quote! { #recurse { @2 [$($m)*] [$s] #n [] $a } });
+            let ref_ty = match &path {
+                Some(path) => quote! {#path::#ref_ident<$s, #(#fields,)* $($track)*>},
+                None => quote! {<$s as borrow::HasRef<(#(#fields,)*), $($track)*>>::Ref},
+            };
+            quote! {
+                (@1 [$($m:tt)*] [$($pfx:tt)*] [$($track:tt)*] [$s:ty] $a:tt) => {
+                    $($pfx)* #ref_ty
+                };
+            }
+        };
+        // Mutable accesses of `*` of fields with mutable accesses referring to aliases, resolved
+        // before the generic `*` rule. Readonly fields are borrowed as shared by `mut *` and
+        // `upgradeable *`.
+        let star_access_rules = mut_alias_paths.iter().zip(&indexes).enumerate().filter_map(
+            |(i, (alias, n))| {
+                let alias = alias.as_ref()?;
+                let rules = private_mut_access(alias).map(|(access, result)| {
+                    let result = if fields_readonly[i] { quote! {[& $lt]} } else { result };
+                    quote! {
+                        (@2 [$($m:tt)*] $s:tt #n $r:tt [(* #access) $($a:tt)*]) => {
+                            #recurse { @2 [$($m)*] $s #n #result [$($a)*] }
+                        };
+                    }
+                });
+                Some(quote! { #(#rules)* })
+            }
+        );
+        let access_rules = quote! {
+            #(#star_access_rules)*
+            (@2 [$($m:tt)*] $s:tt $i:tt $r:tt [(* $n:tt) $($a:tt)*]) => {
+                #recurse { @2 [$($m)*] $s $i $n [$($a)*] }
+            };
+            #(
+                (@2 [$($m:tt)*] $s:tt #indexes $r:tt [(#indexes $n:tt) $($a:tt)*]) => {
+                    #recurse { @2 [$($m)*] $s #indexes $n [$($a)*] }
+                };
+            )*
+            (@2 [$($m:tt)*] $s:tt $i:tt $r:tt [$a0:tt $($a:tt)*]) => {
+                #recurse { @2 [$($m)*] $s $i $r [$($a)*] }
+            };
+            (@2 [$($m:tt)*] [$s:ty] $i:tt [$($r:tt)*] []) => {
+                borrow::field!{$s, $i, #fingerprint, $($r)*}
+            };
+        };
+        let rules = quote! {
+            #init_rule
+            #star_rule
+            #(#field_rules)*
+            #(#group_rules)*
+            #unknown_field_rule
+            #production
+            #access_rules
+        };
+        export_struct_macro(&vis, &attrs, &macro_ident, ident, rules)
+    });
+
+    // For every field marked with `#[borrow(private_mut = "...")]`, generates:
+    //
+    // ```
+    // #[doc(hidden)]
+    // #[allow(non_camel_case_types)]
+    // pub(crate) type __Ctx__private_mut__scene<__S__> = <__S__ as borrow::HasField<4>>::Type;
+    // ```
+    out.extend(private_mut_aliases.iter().enumerate().filter_map(|(i, alias)| {
+        let alias = alias.as_ref()?;
+        let n = proc_macro2::Literal::usize_unsuffixed(i);
+        Some(quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub(crate) type #alias<__S__: ?Sized> = <__S__ as borrow::HasField<#n>>::Type;
+        })
+    }));
+
+    // For every field marked with `#[borrow(readonly_in_release = "...")]`, generates:
+    //
+    // ```
+    // #[cfg(debug_assertions)]
+    // #[doc(hidden)]
+    // #[allow(non_camel_case_types)]
+    // pub type __Ctx__readonly_in_release__scene<__S__> = <__S__ as borrow::HasField<4>>::Type;
+    //
+    // #[cfg(not(debug_assertions))]
+    // #[doc(hidden)]
+    // #[allow(non_camel_case_types)]
+    // pub type __Ctx__readonly_in_release__scene<__S__> =
+    //     <__S__ as borrow::MutInRelease<4>>::Type;
+    // ```
+    //
+    // `borrow::MutInRelease` is not implemented for any type, so mutable selectors of the field
+    // are a compile error in release builds.
+    out.extend(readonly_aliases.iter().enumerate().filter_map(|(i, alias)| {
+        let alias = alias.as_ref()?;
+        let n = proc_macro2::Literal::usize_unsuffixed(i);
+        Some(quote! {
+            #[cfg(debug_assertions)]
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub type #alias<__S__: ?Sized> = <__S__ as borrow::HasField<#n>>::Type;
+
+            #[cfg(not(debug_assertions))]
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub type #alias<__S__: ?Sized> = <__S__ as borrow::MutInRelease<#n>>::Type;
+        })
+    }));
+
+    // For every field marked with `#[borrow(readonly)]`, generates:
+    //
+    // ```
+    // #[doc(hidden)]
+    // #[allow(non_camel_case_types)]
+    // pub type __Ctx__readonly__scene<__S__> = <__S__ as borrow::ReadonlyMut<4>>::Type;
+    // ```
+    //
+    // `borrow::ReadonlyMut` is not implemented for any type, so mutable selectors of the field are
+    // a compile error.
+    out.extend(readonly_field_aliases.iter().enumerate().filter_map(|(i, alias)| {
+        let alias = alias.as_ref()?;
+        let n = proc_macro2::Literal::usize_unsuffixed(i);
+        Some(quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            pub type #alias<__S__: ?Sized> = <__S__ as borrow::ReadonlyMut<#n>>::Type;
+        })
+    }));
+
+    // Generates:
+    //
+    // ```
+    // impl<'t, T> borrow::HasFingerprint<5381> for Ctx<'t, T>
+    // where T: Debug {
+    //     type Struct = Self;
+    // }
+    // ```
+    out.push(quote! {
+        impl<#params_decl> borrow::HasFingerprint<#fingerprint> for #ident<#params>
+        where #bounds {
+            type Struct = Self;
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // impl<'t, T, __Version, __Geometry, __Material, __Mesh, __Scene, __Track__>
+    // borrow::HasRef<(__Version, __Geometry, __Material, __Mesh, __Scene), __Track__> for Ctx<'t, T>
+    // where T: Debug, __Track__: borrow::Bool {
+    //     type Ref = CtxRef<Self, __Version, __Geometry, __Material, __Mesh, __Scene, __Track__>;
+    // }
+    // ```
+    out.push(quote! {
+        impl<#params_decl #(#fields_param,)* __Track__>
+        borrow::HasRef<(#(#fields_param,)*), __Track__> for #ident<#params>
+        where #bounds __Track__: borrow::Bool {
+            type Ref = #ref_ident<Self, #(#fields_param,)* __Track__>;
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // impl<'t, T> borrow::HasField<0> for Ctx<'t, T>
+    // where T: Debug {
+    //     type Type = &'t T;
+    // }
+    //
+    // // ... and similarly for all the other fields.
+    // ```
+    out.extend(fields_ty.iter().enumerate().map(|(i, ty)| {
+        let n = proc_macro2::Literal::usize_unsuffixed(i);
+        quote! {
+            impl<#params_decl> borrow::HasField<#n> for #ident<#params>
+            where #bounds {
+                type Type = #ty;
+            }
+        }
+    }));
+
+    // Generates:
+    //
+    // ```
+    // impl<'t, T, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // borrow::AsRefWithFields<borrow::HList![__Version, __Geometry, __Material, __Mesh, __Scene]>
+    // for Ctx<'t, T>
+    // where T: Debug {
+    //     type Output = CtxRef<Ctx<'t, T>, borrow::True, __Version, __Geometry, __Material, __Mesh, __Scene>;
+    // }
+    // ```
+    out.push(
+        quote! {
+            impl<#params_decl #(#fields_param,)*>
+            borrow::AsRefWithFields<borrow::HList![#(#fields_param,)*]>
+            for #ident<#params>
+            where #bounds {
+                type Output = #ref_ident<#ident<#params>, #(#fields_param,)* borrow::True>;
+            }
+        }
+    );
+
+    // Generates:
+    //
+    // ```
+    // impl<'__s__, __S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> borrow::CloneRef<'__s__>
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     borrow::Field<__Track__, __Version>: borrow::CloneField<'__s__, __Track__>,
+    //     borrow::Field<__Track__, __Geometry>: borrow::CloneField<'__s__, __Track__>,
+    //     borrow::Field<__Track__, __Material>: borrow::CloneField<'__s__, __Track__>,
     //     borrow::Field<__Track__, __Mesh>: borrow::CloneField<'__s__, __Track__>,
     //     borrow::Field<__Track__, __Scene>: borrow::CloneField<'__s__, __Track__>,
     // {
-    //     type Cloned = CtxRef<
+    //     type Cloned = CtxRef<
+    //         __S__,
+    //         __Track__,
+    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Version>, __Track__>,
+    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Geometry>, __Track__>,
+    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Material>, __Track__>,
+    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Mesh>, __Track__>,
+    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Scene>, __Track__>
+    //     >;
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     fn clone_ref_disabled_usage_tracking(&'__s__ mut self) -> Self::Cloned {
+    //         use borrow::CloneField;
+    //         CtxRef {
+    //             version: self.version.clone_field_disabled_usage_tracking(),
+    //             geometry: self.geometry.clone_field_disabled_usage_tracking(),
+    //             material: self.material.clone_field_disabled_usage_tracking(),
+    //             mesh: self.mesh.clone_field_disabled_usage_tracking(),
+    //             scene: self.scene.clone_field_disabled_usage_tracking(),
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: borrow::UsageTracker::new(),
+    //         }
+    //     }
+    // }
+    // ```
+    out.push(
+        quote! {
+            impl<'__s__, __S__: ?Sized, __Track__, #(#fields_param,)*> borrow::CloneRef<'__s__>
+            for #ref_ident<__S__, #(#fields_param,)* __Track__>
+            where
+                __Track__: borrow::Bool,
+                #(borrow::Field<__Track__, #fields_param>: borrow::CloneField<'__s__, __Track__>,)*
+            {
+                type Cloned = #ref_ident<
+                    __S__,
+                    #(borrow::ClonedField<'__s__, borrow::Field<__Track__, #fields_param>, __Track__>,)*
+                    __Track__,
+                >;
+                #[track_caller]
+                #[inline(always)]
+                fn clone_ref_disabled_usage_tracking(&'__s__ mut self) -> Self::Cloned {
+                    use borrow::CloneField;
+                    #ref_ident {
+                        #(#fields_ident: self.#fields_ident.clone_field_disabled_usage_tracking(),)*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__: borrow::UsageTracker::new(),
+                    }
+                }
+            }
+        }
+    );
+
+    // Generates:
+    //
+    // ```
+    // #[allow(non_camel_case_types)]
+    // #[allow(non_snake_case)]
+    // impl<__S__, __Track__, __Track__Target__,
+    //     __Version, __Geometry, __Material, __Mesh, __Scene,
+    //     __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target,
+    //     __Version__Rest, __Geometry__Rest, __Material__Rest, __Mesh__Rest, __Scene__Rest>
+    // borrow::IntoPartial<CtxRef<__S__, __Track__Target__, __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>>
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     __Track__Target__: borrow::Bool,
+    //     borrow::AcquireMarker: borrow::Acquire<__Version, __Version__Target, Rest=__Version__Rest>,
+    //     borrow::AcquireMarker: borrow::Acquire<__Geometry, __Geometry__Target, Rest=__Geometry__Rest>,
+    //     borrow::AcquireMarker: borrow::Acquire<__Material, __Material__Target, Rest=__Material__Rest>,
+    //     borrow::AcquireMarker: borrow::Acquire<__Mesh, __Mesh__Target, Rest=__Mesh__Rest>,
+    //     borrow::AcquireMarker: borrow::Acquire<__Scene, __Scene__Target, Rest=__Scene__Rest>,
+    // {
+    //     type Rest = CtxRef<__S__, __Track__, __Version__Rest, __Geometry__Rest, __Material__Rest, __Mesh__Rest, __Scene__Rest>;
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     fn into_split_impl(
+    //         mut self
+    //     ) -> (CtxRef<
+    //         __S__,
+    //         __Track__Target__,
+    //         __Version__Target,
+    //         __Geometry__Target,
+    //         __Material__Target,
+    //         __Mesh__Target,
+    //         __Scene__Target
+    //     >,
+    //         Self::Rest
+    //     ) {
+    //         use borrow::Acquire;
+    //         let __usage_tracker__ = borrow::UsageTracker::new();
+    //         let (version, __version__rest) = borrow::AcquireMarker::acquire(self.version, __usage_tracker__.clone());
+    //         let (geometry, __geometry__rest) = borrow::AcquireMarker::acquire(self.geometry, __usage_tracker__.clone());
+    //         let (material, __material__rest) = borrow::AcquireMarker::acquire(self.material, __usage_tracker__.clone());
+    //         let (mesh, __mesh__rest) = borrow::AcquireMarker::acquire(self.mesh, __usage_tracker__.clone());
+    //         let (scene, __scene__rest) = borrow::AcquireMarker::acquire(self.scene, __usage_tracker__.clone());
+    //         (
+    //             CtxRef {
+    //                 version,
+    //                 geometry,
+    //                 material,
+    //                 mesh,
+    //                 scene,
+    //                 __marker__: std::marker::PhantomData,
+    //                 __usage_tracker__
+    //             },
+    //             CtxRef {
+    //                 version: __version__rest,
+    //                 geometry: __geometry__rest,
+    //                 material: __material__rest,
+    //                 mesh: __mesh__rest,
+    //                 scene: __scene__rest,
+    //                 __marker__: std::marker::PhantomData,
+    //                 __usage_tracker__: borrow::UsageTracker::new(),
+    //             }
+    //         )
+    //     }
+    // }
+    // ```
+
+    out.push({
+        let field_params_target = fields_param.iter().map(|i| {
+            Ident::new(&format!("{i}{}", internal("Target")), i.span())
+        }).collect_vec();
+
+        let field_params_rest = fields_param.iter().map(|i| {
+            Ident::new(&format!("{i}{}", internal("Rest")), i.span())
+        }).collect_vec();
+
+        let fields_rest_ident = fields_ident.iter().map(|i| {
+            let name = format!("{}{}", internal(&i.unraw().to_string()), internal("rest"));
+            Ident::new(&name, i.span())
+        }).collect_vec();
+
+        // Warnings of generic structs include the concrete type, so that borrows of different
+        // instantiations can be told apart.
+        let is_generic = input.generics.type_params().next().is_some();
+        let new_usage_tracker = if is_generic {
+            quote! { borrow::UsageTracker::new_for::<__S__>() }
+        } else {
+            quote! { borrow::UsageTracker::new() }
+        };
+
+        quote! {
+            #[allow(non_camel_case_types)]
+            #[allow(non_snake_case)]
+            impl<__S__: ?Sized, __Track__, __Track__Target__,
+                #(#fields_param,)*
+                #(#field_params_target,)*
+                #(#field_params_rest,)*
+            >
+            borrow::IntoPartial<#ref_ident<__S__, #(#field_params_target,)* __Track__Target__>>
+            for #ref_ident<__S__, #(#fields_param,)* __Track__>
+            where
+                __Track__: borrow::Bool,
+                __Track__Target__: borrow::Bool,
+                #(
+                    borrow::AcquireMarker: borrow::Acquire<
+                        #fields_param,
+                        #field_params_target,
+                        Rest=#field_params_rest
+                    >,
+                )*
+            {
+                type Rest = #ref_ident<__S__, #(#field_params_rest,)* __Track__>;
+
+                #[track_caller]
+                #[inline(always)]
+                fn into_split_impl(
+                    mut self
+                ) -> (
+                    #ref_ident<__S__, #(#field_params_target,)* __Track__Target__>,
+                    Self::Rest
+                ) {
+                    use borrow::Acquire;
+                    let __usage_tracker__ = #new_usage_tracker;
+                    #(let (#fields_ident, #fields_rest_ident) =
+                        borrow::AcquireMarker::acquire(self.#fields_ident, __usage_tracker__.clone());)*
+                    (
+                        #ref_ident {
+                            #(#fields_ident,)*
+                            __marker__: std::marker::PhantomData,
+                            __usage_tracker__
+                        },
+                        #ref_ident {
+                            #(#fields_ident: #fields_rest_ident,)*
+                            __marker__: std::marker::PhantomData,
+                            __usage_tracker__: borrow::UsageTracker::new()
+                        }
+                    )
+                }
+            }
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // #[allow(non_camel_case_types)]
+    // #[allow(non_snake_case)]
+    // impl<__S__, __Track__, __Track__Target__,
+    //     __Version, __Geometry, __Material, __Mesh, __Scene,
+    //     __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>
+    // borrow::HideShared<CtxRef<__S__, __Track__Target__, __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>>
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     __Track__Target__: borrow::Bool,
+    //     __Version: borrow::HideSharedField<__Version__Target>,
+    //     __Geometry: borrow::HideSharedField<__Geometry__Target>,
+    //     __Material: borrow::HideSharedField<__Material__Target>,
+    //     __Mesh: borrow::HideSharedField<__Mesh__Target>,
+    //     __Scene: borrow::HideSharedField<__Scene__Target>,
+    // {
+    //     type Output = CtxRef<
+    //         __S__,
+    //         __Track__,
+    //         <__Version as borrow::HideSharedField<__Version__Target>>::Output,
+    //         <__Geometry as borrow::HideSharedField<__Geometry__Target>>::Output,
+    //         <__Material as borrow::HideSharedField<__Material__Target>>::Output,
+    //         <__Mesh as borrow::HideSharedField<__Mesh__Target>>::Output,
+    //         <__Scene as borrow::HideSharedField<__Scene__Target>>::Output
+    //     >;
+    //     #[inline(always)]
+    //     fn hide_shared(self) -> Self::Output {
+    //         CtxRef {
+    //             version: borrow::HideSharedField::hide_shared_field(self.version),
+    //             geometry: borrow::HideSharedField::hide_shared_field(self.geometry),
+    //             material: borrow::HideSharedField::hide_shared_field(self.material),
+    //             mesh: borrow::HideSharedField::hide_shared_field(self.mesh),
+    //             scene: borrow::HideSharedField::hide_shared_field(self.scene),
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: self.__usage_tracker__,
+    //         }
+    //     }
+    // }
+    // ```
+    out.push({
+        let field_params_target = fields_param.iter().map(|i| {
+            Ident::new(&format!("{i}{}", internal("Target")), i.span())
+        }).collect_vec();
+        quote! {
+            #[allow(non_camel_case_types)]
+            #[allow(non_snake_case)]
+            impl<__S__: ?Sized, __Track__, __Track__Target__,
+                #(#fields_param,)*
+                #(#field_params_target,)*
+            >
+            borrow::HideShared<#ref_ident<__S__, #(#field_params_target,)* __Track__Target__>>
+            for #ref_ident<__S__, #(#fields_param,)* __Track__>
+            where
+                __Track__: borrow::Bool,
+                __Track__Target__: borrow::Bool,
+                #(#fields_param: borrow::HideSharedField<#field_params_target>,)*
+            {
+                type Output = #ref_ident<
+                    __S__,
+                    #(<#fields_param as borrow::HideSharedField<#field_params_target>>::Output,)*
+                    __Track__
+                >;
+
+                #[inline(always)]
+                fn hide_shared(self) -> Self::Output {
+                    #ref_ident {
+                        #(#fields_ident: borrow::HideSharedField::hide_shared_field(self.#fields_ident),)*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__: self.__usage_tracker__,
+                    }
+                }
+            }
+        }
+    });
+
+    // Generates:
+
+    // ```
+    // #[allow(non_camel_case_types)]
+    // impl<'__a__, __S__, __Track__, __Target__,
+    //     __Version, __Geometry, __Material, __Mesh, __Scene>
+    // borrow::Partial<'__a__, __Target__>
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> where
+    //     __Track__: borrow::Bool,
+    //     Self: borrow::CloneRef<'__a__>,
+    //     borrow::ClonedRef<'__a__, Self>: borrow::IntoPartial<__Target__>
+    // {
+    //     type Rest = <borrow::ClonedRef<'__a__, Self> as borrow::IntoPartial<__Target__>>::Rest;
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     fn split_impl(&'__a__ mut self) -> (__Target__, Self::Rest) {
+    //         use borrow::CloneRef;
+    //         use borrow::IntoPartial;
+    //         // As the usage trackers are cloned and immediately destroyed by `into_split_impl`,
+    //         // we need to disable them.
+    //         let this = self.clone_ref_disabled_usage_tracking();
+    //         this.into_split_impl()
+    //     }
+    // }
+    // ```
+    out.push({
+        quote! {
+            #[allow(non_camel_case_types)]
+            impl<'__a__, __S__: ?Sized, __Track__, __Target__, #(#fields_param,)*>
+            borrow::Partial<'__a__, __Target__>
+            for #ref_ident<__S__, #(#fields_param,)* __Track__> where
+                __Track__: borrow::Bool,
+                Self: borrow::CloneRef<'__a__>,
+                borrow::ClonedRef<'__a__, Self>: borrow::IntoPartial<__Target__>
+            {
+                type Rest = <borrow::ClonedRef<'__a__, Self> as borrow::IntoPartial<__Target__>>::Rest;
+                #[track_caller]
+                #[inline(always)]
+                fn split_impl(&'__a__ mut self) -> (__Target__, Self::Rest) {
+                    use borrow::CloneRef;
+                    use borrow::IntoPartial;
+                    // As the usage trackers are cloned and immediately destroyed by `into_split_impl`,
+                    // we need to disable them.
+                    let this = self.clone_ref_disabled_usage_tracking();
+                    this.into_split_impl()
+                }
+            }
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // #[allow(non_camel_case_types)]
+    // impl<'__a__, __S__, __Track__, __Track__Target__,
+    //     __Version, __Geometry, __Material, __Mesh, __Scene,
+    //     __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>
+    // From<&'__a__ mut CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>>
+    // for CtxRef<__S__, __Track__Target__, __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>
+    // where
+    //     __Track__: borrow::Bool,
+    //     __Track__Target__: borrow::Bool,
+    //     CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>: borrow::CloneRef<'__a__>,
+    //     borrow::ClonedRef<'__a__, CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>>:
+    //         borrow::IntoPartial<Self>
+    // {
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     fn from(source: &'__a__ mut CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>) -> Self {
+    //         let source = borrow::CloneRef::clone_ref_disabled_usage_tracking(source);
+    //         borrow::IntoPartial::into_split_impl(source).0
+    //     }
+    // }
+    // ```
+    //
+    // The bounds mirror the `Partial` impl instead of referring to it, so that errors about
+    // incompatible borrows point to the `Acquire` trait and its diagnostic message.
+    out.push({
+        let field_params_target = fields_param.iter().map(|i| {
+            Ident::new(&format!("{i}{}", internal("Target")), i.span())
+        }).collect_vec();
+        quote! {
+            #[allow(non_camel_case_types)]
+            impl<'__a__, __S__: ?Sized, __Track__, __Track__Target__,
+                #(#fields_param,)*
+                #(#field_params_target,)*
+            >
+            From<&'__a__ mut #ref_ident<__S__, #(#fields_param,)* __Track__>>
+            for #ref_ident<__S__, #(#field_params_target,)* __Track__Target__>
+            where
+                __Track__: borrow::Bool,
+                __Track__Target__: borrow::Bool,
+                #ref_ident<__S__, #(#fields_param,)* __Track__>: borrow::CloneRef<'__a__>,
+                borrow::ClonedRef<'__a__, #ref_ident<__S__, #(#fields_param,)* __Track__>>:
+                    borrow::IntoPartial<Self>
+            {
+                #[track_caller]
+                #[inline(always)]
+                fn from(source: &'__a__ mut #ref_ident<__S__, #(#fields_param,)* __Track__>) -> Self {
+                    let source = borrow::CloneRef::clone_ref_disabled_usage_tracking(source);
+                    borrow::IntoPartial::into_split_impl(source).0
+                }
+            }
+        }
+    });
+
+    // For each field. For the 'version' field:
+    //
+    // ```
+    // impl<'__s__, '__tgt__, 't, T, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // CtxRef<Ctx<'t, T>, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     T: Debug,
+    //     &'t T: '__tgt__,
+    //     Self: borrow::CloneRef<'__s__>,
+    //     borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
+    //         CtxRef<
+    //             Ctx<'t, T>,
+    //             __Track__,
+    //             borrow::Hidden,
+    //             &'__tgt__ mut GeometryCtx,
+    //             borrow::Hidden,
+    //             borrow::Hidden,
+    //             borrow::Hidden
+    //         >
+    //     >
+    // {
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     pub fn extract_geometry2(&'__s__ mut self) -> (
+    //         borrow::FieldValue<__Track__, &'__tgt__ mut GeometryCtx>,
+    //         <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
+    //             CtxRef<
+    //                 Ctx<'t, T>,
+    //                 __Track__,
+    //                 borrow::Hidden,
+    //                 &'__tgt__ mut GeometryCtx,
+    //                 borrow::Hidden,
+    //                 borrow::Hidden,
+    //                 borrow::Hidden
+    //             >
+    //         >>::Rest
+    //     ) {
+    //         let split = borrow::IntoPartial::into_split_impl(
+    //             borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
+    //         );
+    //         (split.0.geometry.into_field_value(), split.1)
+    //     }
+    // }
+    // ```
+    out.extend((0..fields_param.len()).map(|i| {
+        let field_ident = &fields_ident[i];
+        let field_ty = &fields_ty[i];
+        let field_ref_mut = quote! {&'__tgt__ mut #field_ty};
+        let field_ref = quote! {&'__tgt__ #field_ty};
+
+        let mut params2 = fields_param.clone();
+        params2.remove(i);
+
+        let mut target_params_mut = fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+        target_params_mut[i] = field_ref_mut.clone();
+
+        let mut target_params = fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+        target_params[i] = field_ref.clone();
+
+        let field_label = &fields_label[i];
+        let fn_ident = Ident::new(&format!("borrow_{field_label}"), field_ident.span());
+        let fn_ident_mut = Ident::new(&format!("borrow_{field_label}_mut"), field_ident.span());
+        let readonly_cfg = readonly_aliases[i].is_some().then(|| quote! {#[cfg(debug_assertions)]});
+        let method_vis = &fields_method_vis[i];
+        // Private mut fields can be borrowed mutably only in the defining crate.
+        let method_vis_mut = match method_vis {
+            syn::Visibility::Public(_) if private_mut_aliases[i].is_some() => quote! {pub(crate)},
+            _ => quote! {#method_vis},
+        };
+
+        // Readonly fields can not be borrowed mutably, so they get only the shared variant.
+        let mut_impl = (!fields_readonly[i]).then(|| quote! {
+            #readonly_cfg
+            #[allow(non_camel_case_types)]
+            impl<'__s__, '__tgt__, #params_decl __Track__, #(#fields_param,)*>
+            #ref_ident<#ident<#params>, #(#fields_param,)* __Track__>
+            where
+                #bounds
+                __Track__: borrow::Bool,
+                #field_ty: '__tgt__,
+                Self: borrow::CloneRef<'__s__>,
+                borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
+                    #ref_ident<
+                        #ident<#params>,
+                        #(#target_params_mut,)*
+                        __Track__,
+                    >
+                >
+            {
+                #[track_caller]
+                #[inline(always)]
+                #method_vis_mut fn #fn_ident_mut(&'__s__ mut self) -> (
+                    borrow::FieldValue<__Track__, #field_ref_mut>,
+                        <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
+                            #ref_ident<
+                                #ident<#params>,
+                                #(#target_params_mut,)*
+                                __Track__,
+                            >
+                        >>::Rest
+                ) {
+                    let split = borrow::IntoPartial::into_split_impl(
+                        borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
+                    );
+                    (split.0.#field_ident.into_field_value(), split.1)
+                }
+            }
+        });
+
+        quote! {
+            #mut_impl
+
+            #[allow(non_camel_case_types)]
+            impl<'__s__, '__tgt__, #params_decl __Track__, #(#fields_param,)*>
+            #ref_ident<#ident<#params>, #(#fields_param,)* __Track__>
+            where
+                #bounds
+                __Track__: borrow::Bool,
+                #field_ty: '__tgt__,
+                Self: borrow::CloneRef<'__s__>,
+                borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
+                    #ref_ident<
+                        #ident<#params>,
+                        #(#target_params,)*
+                        __Track__,
+                    >
+                >
+            {
+                #[track_caller]
+                #[inline(always)]
+                #method_vis fn #fn_ident(&'__s__ mut self) -> (
+                    borrow::FieldValue<__Track__, #field_ref>,
+                        <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
+                            #ref_ident<
+                                #ident<#params>,
+                                #(#target_params,)*
+                                __Track__,
+                            >
+                        >>::Rest
+                ) {
+                    let split = borrow::IntoPartial::into_split_impl(
+                        borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
+                    );
+                    (split.0.#field_ident.into_field_value(), split.1)
+                }
+            }
+        }
+    }));
+
+    // For each field, e.g. `geometry`, generates:
+    //
+    // ```
+    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where __Track__: borrow::Bool {
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     pub fn map_geometry<__Target__>(self, f: impl FnOnce(__Geometry) -> __Target__)
+    //     -> CtxRef<__S__, __Track__, __Version, __Target__, __Material, __Mesh, __Scene> {
+    //         CtxRef {
+    //             version: self.version,
+    //             geometry: self.geometry.map_field(f),
+    //             material: self.material,
+    //             mesh: self.mesh,
+    //             scene: self.scene,
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: self.__usage_tracker__,
+    //         }
+    //     }
+    //
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     pub fn split_geometry_at(self, mid: usize) -> (
+    //         CtxRef<
+    //             __S__,
+    //             __Track__,
+    //             __Version,
+    //             <__Geometry as borrow::SplitAtField>::Part,
+    //             __Material,
+    //             __Mesh,
+    //             __Scene
+    //         >,
+    //         borrow::FieldValue<__Track__, <__Geometry as borrow::SplitAtField>::Part>,
+    //     )
+    //     where __Geometry: borrow::SplitAtField {
+    //         let (head, tail) = self.geometry.split_field_at(mid);
+    //         let view = CtxRef {
+    //             version: self.version,
+    //             geometry: head,
+    //             material: self.material,
+    //             mesh: self.mesh,
+    //             scene: self.scene,
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: self.__usage_tracker__,
+    //         };
+    //         (view, tail.into_field_value())
+    //     }
+    // }
+    // ```
+    out.push({
+        let map_fns = (0..fields_param.len()).map(|i| {
+            let field_ident = &fields_ident[i];
+            let param = &fields_param[i];
+            let fn_ident = Ident::new(&format!("map_{}", fields_label[i]), field_ident.span());
+            let method_vis = &fields_method_vis[i];
+            let mut target_params = fields_param.iter().map(|p| quote! {#p}).collect_vec();
+            target_params[i] = quote! {__Target__};
+            let other_fields = fields_ident.iter().filter(|f| *f != field_ident).collect_vec();
+            let split_fn_ident = Ident::new(&format!("split_{}_at", fields_label[i]), field_ident.span());
+            let part = quote! {<#param as borrow::SplitAtField>::Part};
+            let mut split_params = fields_param.iter().map(|p| quote! {#p}).collect_vec();
+            split_params[i] = part.clone();
+            quote! {
+                #[track_caller]
+                #[inline(always)]
+                #method_vis fn #fn_ident<__Target__>(self, f: impl FnOnce(#param) -> __Target__)
+                -> #ref_ident<__S__, #(#target_params,)* __Track__> {
+                    #ref_ident {
+                        #field_ident: self.#field_ident.map_field(f),
+                        #(#other_fields: self.#other_fields,)*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__: self.__usage_tracker__,
+                    }
+                }
+
+                #[track_caller]
+                #[inline(always)]
+                #method_vis fn #split_fn_ident(self, mid: usize) -> (
+                    #ref_ident<__S__, #(#split_params,)* __Track__>,
+                    borrow::FieldValue<__Track__, #part>,
+                )
+                where #param: borrow::SplitAtField {
+                    let (head, tail) = self.#field_ident.split_field_at(mid);
+                    let view = #ref_ident {
+                        #field_ident: head,
+                        #(#other_fields: self.#other_fields,)*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__: self.__usage_tracker__,
+                    };
+                    (view, tail.into_field_value())
+                }
+            }
+        }).collect_vec();
+        quote! {
+            #[allow(non_camel_case_types)]
+            impl<__S__: ?Sized, __Track__, #(#fields_param,)*> #ref_ident<__S__, #(#fields_param,)* __Track__>
+            where __Track__: borrow::Bool {
+                #(#map_fns)*
+            }
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where __Track__: borrow::Bool {
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     pub fn for_each_selected<__Visitor__>(&mut self, visitor: &mut __Visitor__)
+    //     where
+    //         __Version: borrow::SelectedField<__Visitor__>,
+    //         __Geometry: borrow::SelectedField<__Visitor__>,
+    //         __Material: borrow::SelectedField<__Visitor__>,
+    //         __Mesh: borrow::SelectedField<__Visitor__>,
+    //         __Scene: borrow::SelectedField<__Visitor__>,
+    //     {
+    //         self.version.visit_selected("version", visitor);
+    //         self.geometry.visit_selected("geometry", visitor);
+    //         self.material.visit_selected("material", visitor);
+    //         self.mesh.visit_selected("mesh", visitor);
+    //         self.scene.visit_selected("scene", visitor);
+    //     }
+    // }
+    // ```
+    out.push(quote! {
+        #[allow(non_camel_case_types)]
+        impl<__S__: ?Sized, __Track__, #(#fields_param,)*> #ref_ident<__S__, #(#fields_param,)* __Track__>
+        where __Track__: borrow::Bool {
+            #[track_caller]
+            #[inline(always)]
+            pub fn for_each_selected<__Visitor__>(&mut self, visitor: &mut __Visitor__)
+            where #(#fields_param: borrow::SelectedField<__Visitor__>,)* {
+                #(self.#fields_ident.visit_selected(#fields_label, visitor);)*
+            }
+        }
+    });
+
+    // For each group. For the `render` group declared as
+    // `#[borrow(group(render = "geometry, material"))]`:
+    //
+    // ```
+    // impl<'__s__, '__tgt__, 't, T, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // CtxRef<Ctx<'t, T>, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     T: Debug,
+    //     GeometryCtx: '__tgt__,
+    //     MaterialCtx: '__tgt__,
+    //     Self: borrow::CloneRef<'__s__>,
+    //     borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
+    //         CtxRef<
+    //             Ctx<'t, T>,
+    //             __Track__,
+    //             borrow::Hidden,
+    //             &'__tgt__ mut GeometryCtx,
+    //             &'__tgt__ mut MaterialCtx,
+    //             borrow::Hidden,
+    //             borrow::Hidden
+    //         >
+    //     >
+    // {
+    //     #[track_caller]
+    //     #[inline(always)]
+    //     pub fn borrow_render_mut(&'__s__ mut self) -> (
+    //         CtxRef<
+    //             Ctx<'t, T>,
+    //             __Track__,
+    //             borrow::Hidden,
+    //             &'__tgt__ mut GeometryCtx,
+    //             &'__tgt__ mut MaterialCtx,
+    //             borrow::Hidden,
+    //             borrow::Hidden
+    //         >,
+    //         <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
+    //             CtxRef<
+    //                 Ctx<'t, T>,
+    //                 __Track__,
+    //                 borrow::Hidden,
+    //                 &'__tgt__ mut GeometryCtx,
+    //                 &'__tgt__ mut MaterialCtx,
+    //                 borrow::Hidden,
+    //                 borrow::Hidden
+    //             >
+    //         >>::Rest
+    //     ) {
+    //         borrow::IntoPartial::into_split_impl(
+    //             borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
+    //         )
+    //     }
+    // }
+    // ```
+    // Methods borrowing private mut fields mutably are available only in the defining crate, and
+    // methods borrowing readonly in release fields mutably are available only in debug builds.
+    let mut_vis = |indexes: &[usize]| {
+        let is_private_mut = indexes.iter().any(|i| private_mut_aliases[*i].is_some());
+        let vis = if is_private_mut && matches!(vis, syn::Visibility::Public(_)) {
+            quote! {pub(crate)}
+        } else {
+            quote! {#vis}
+        };
+        if indexes.iter().any(|i| readonly_aliases[*i].is_some()) {
+            quote! {#[cfg(debug_assertions)] #vis}
+        } else {
+            vis
+        }
+    };
+    let mut group_indexes = vec![];
+    for group in &attrs.groups {
+        let group_ident = &group.ident;
+        let indexes = group.fields.iter().filter_map(|field| {
+            fields_ident.iter().position(|f| *f == field)
+        }).collect_vec();
+        let group_fields_ty = indexes.iter().map(|i| &fields_ty[*i]).collect_vec();
+        group_indexes.push(indexes.clone());
+
+        let group_name = group_ident.unraw();
+        let fn_ident = Ident::new(&format!("borrow_{group_name}"), group_ident.span());
+        let fn_ident_mut = Ident::new(&format!("borrow_{group_name}_mut"), group_ident.span());
+
+        let variants = [
+            (fn_ident, quote! {}, quote! {#vis}),
+            (fn_ident_mut, quote! {mut}, mut_vis(&indexes)),
+        ];
+        out.extend(variants.into_iter().map(
+            |(fn_ident, mutability, vis)| {
+                let mut target_params =
+                    fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+                for (i, ty) in indexes.iter().zip(&group_fields_ty) {
+                    // Readonly fields are borrowed as shared by the mutable variant as well.
+                    let mutability = (!fields_readonly[*i]).then_some(&mutability);
+                    target_params[*i] = quote! {&'__tgt__ #mutability #ty};
+                }
+                let target = quote! {
+                    #ref_ident<
+                        #ident<#params>,
+                        #(#target_params,)*
+                        __Track__,
+                    >
+                };
+                quote! {
+                    #[allow(non_camel_case_types)]
+                    impl<'__s__, '__tgt__, #params_decl __Track__, #(#fields_param,)*>
+                    #ref_ident<#ident<#params>, #(#fields_param,)* __Track__>
+                    where
+                        #bounds
+                        __Track__: borrow::Bool,
+                        #(#group_fields_ty: '__tgt__,)*
+                        Self: borrow::CloneRef<'__s__>,
+                        borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<#target>
+                    {
+                        #[track_caller]
+                        #[inline(always)]
+                        #vis fn #fn_ident(&'__s__ mut self) -> (
+                            #target,
+                            <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<#target>>::Rest
+                        ) {
+                            borrow::IntoPartial::into_split_impl(
+                                borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
+                            )
+                        }
+                    }
+                }
+            }
+        ));
+    }
+
+    // If the struct is marked with `#[borrow(monomorphize)]` (shown for `Ctx` with `T = usize`, as
+    // structs with type parameters are not supported). The methods are not generic, so they are
+    // compiled once in the defining crate instead of in every crate using the shapes:
+    //
+    // ```
+    // impl<'t> Ctx<'t> {
+    //     #[track_caller]
+    //     pub fn borrow_all_mut(&mut self) -> CtxRef<
+    //         Ctx<'t>,
+    //         borrow::True,
+    //         &mut &'t usize,
+    //         &mut GeometryCtx,
+    //         &mut MaterialCtx,
+    //         &mut MeshCtx,
+    //         &mut SceneCtx
+    //     > {
+    //         borrow::PartialHelper::partial_borrow(self)
+    //     }
+    //
+    //     // `borrow_all`, and for every group `borrow_$group` and `borrow_$group_mut`.
+    // }
+    // ```
+    if attrs.monomorphize {
+        let all_indexes = (0..fields_ident.len()).collect_vec();
+        let all_shape = (Ident::new("all", ident.span()), &all_indexes);
+        let group_shapes = attrs.groups.iter().map(|g| g.ident.clone()).zip(&group_indexes);
+        let shapes = std::iter::once(all_shape).chain(group_shapes).flat_map(|(name, indexes)| {
+            let fn_ident = Ident::new(&format!("borrow_{}", name.unraw()), name.span());
+            let fn_ident_mut = Ident::new(&format!("borrow_{}_mut", name.unraw()), name.span());
+            let variants = [
+                (fn_ident, quote! {}, quote! {#vis}),
+                (fn_ident_mut, quote! {mut}, mut_vis(indexes)),
+            ];
+            variants.map(|(fn_ident, mutability, vis)| {
+                let mut target_params =
+                    fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+                for i in indexes {
+                    let ty = &fields_ty[*i];
+                    let mutability = (!fields_readonly[*i]).then_some(&mutability);
+                    target_params[*i] = quote! {&'__s__ #mutability #ty};
+                }
+                quote! {
+                    #[track_caller]
+                    #vis fn #fn_ident<'__s__>(&'__s__ mut self) -> #ref_ident<
+                        #ident<#params>,
+                        #(#target_params,)*
+                        borrow::True,
+                    > {
+                        borrow::PartialHelper::partial_borrow(self)
+                    }
+                }
+            })
+        }).collect_vec();
+        out.push(quote! {
+            impl<#params_decl> #ident<#params> {
+                #(#shapes)*
+            }
+        });
+    }
+
+    // If the struct is marked with
+    // `#[borrow(split_struct(ty = "GeometryParts<'a>", fields = "mut geometry, version"))]`
+    // (shown for `Ctx` without parameters), converts partial borrows to the hand-rolled split
+    // struct, and the split struct to a new root partial borrow:
+    //
+    // ```
+    // impl<'a> From<GeometryParts<'a>>
+    // for CtxRef<Ctx, &'a usize, &'a mut GeometryCtx, borrow::Hidden, ..., borrow::True> {
+    //     #[track_caller]
+    //     fn from(parts: GeometryParts<'a>) -> Self {
+    //         let __usage_tracker__ = borrow::UsageTracker::new_root();
+    //         let struct_ref = CtxRef {
+    //             version: borrow::Field::new(
+    //                 "version",
+    //                 Some(borrow::Usage::Ref),
+    //                 parts.version,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             material: borrow::Field::new(
+    //                 "material",
+    //                 None,
+    //                 borrow::Hidden,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             ...
+    //         };
+    //         borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+    //         struct_ref
+    //     }
+    // }
+    //
+    // impl<'a, __Track__: borrow::Bool>
+    // From<CtxRef<Ctx, &'a usize, &'a mut GeometryCtx, borrow::Hidden, ..., __Track__>>
+    // for GeometryParts<'a> {
+    //     fn from(view: CtxRef<...>) -> Self {
+    //         Self { geometry: view.geometry.into_inner(), version: view.version.into_inner() }
+    //     }
+    // }
+    //
+    // impl<'a, '__v0, '__v1, __Track__: borrow::Bool>
+    // From<&'a mut CtxRef<Ctx, &'__v1 usize, &'__v0 mut GeometryCtx, borrow::Hidden, ..., __Track__>>
+    // for GeometryParts<'a> {
+    //     fn from(view: &'a mut CtxRef<...>) -> Self {
+    //         Self { geometry: &mut **view.geometry, version: &**view.version }
+    //     }
+    // }
+    // ```
+    for split in &attrs.split_structs {
+        let split_ty = &split.ty;
+        let Some(lt) = split.lifetime() else { continue };
+        let field_index = |field: &Ident| fields_ident.iter().position(|f| *f == field);
+        let split_indexes = split.fields.iter()
+            .filter_map(|(f, mutable)| Some((field_index(f)?, *mutable)))
+            .collect_vec();
+        let split_fields = split_indexes.iter().map(|(i, _)| fields_ident[*i]).collect_vec();
+        let split_fields_ty = split_indexes.iter().map(|(i, _)| &fields_ty[*i]).collect_vec();
+        // Lifetimes of the fields of reborrowed views, which are distinct if they are elided.
+        let split_lifetimes = (0..split_indexes.len()).map(|i| {
+            syn::Lifetime::new(&format!("'__v{i}"), Span::call_site())
+        }).collect_vec();
+        let target_params = |lifetimes: &[&syn::Lifetime]| {
+            let mut target_params = fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
+            for ((i, mutable), lt) in split_indexes.iter().zip(lifetimes) {
+                let ty = &fields_ty[*i];
+                let mutability = mutable.then(|| quote! {mut});
+                target_params[*i] = quote! {&#lt #mutability #ty};
+            }
+            target_params
+        };
+        let owned_params = target_params(&vec![lt; split_indexes.len()]);
+        let reborrowed_params = target_params(&split_lifetimes.iter().collect_vec());
+        let fields_value = fields_ident.iter().map(|field| {
+            match split.fields.iter().find(|(f, _)| f == *field) {
+                Some((_, true)) => (quote! {Some(borrow::Usage::Mut)}, quote! {parts.#field}),
+                Some((_, false)) => (quote! {Some(borrow::Usage::Ref)}, quote! {parts.#field}),
+                None => (quote! {None}, quote! {borrow::Hidden}),
+            }
+        }).collect_vec();
+        let fields_usage = fields_value.iter().map(|(usage, _)| usage);
+        let fields_value = fields_value.iter().map(|(_, value)| value);
+        let split_reborrows = split.fields.iter().map(|(field, mutable)| {
+            let mutability = mutable.then(|| quote! {mut});
+            quote! {&#mutability **view.#field}
+        }).collect_vec();
+        out.push(quote! {
+            impl<#lt, #params_decl> From<#split_ty>
+            for #ref_ident<#ident<#params>, #(#owned_params,)* borrow::True>
+            where #bounds #(#split_fields_ty: #lt,)* {
+                #[track_caller]
+                #[inline(always)]
+                fn from(parts: #split_ty) -> Self {
+                    let __usage_tracker__ = borrow::UsageTracker::new_root();
+                    let struct_ref = #ref_ident {
+                        #(
+                            #fields_ident: borrow::Field::new(
+                                #fields_label,
+                                #fields_usage,
+                                #fields_value,
+                                __usage_tracker__.clone(),
+                            ),
+                        )*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__
+                    };
+                    borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+                    struct_ref
+                }
+            }
+
+            impl<#lt, #params_decl __Track__: borrow::Bool>
+            From<#ref_ident<#ident<#params>, #(#owned_params,)* __Track__>> for #split_ty
+            where #bounds #(#split_fields_ty: #lt,)* {
+                #[inline(always)]
+                fn from(view: #ref_ident<#ident<#params>, #(#owned_params,)* __Track__>) -> Self {
+                    Self { #(#split_fields: view.#split_fields.into_inner(),)* }
+                }
+            }
+
+            impl<#lt, #(#split_lifetimes,)* #params_decl __Track__: borrow::Bool>
+            From<&#lt mut #ref_ident<#ident<#params>, #(#reborrowed_params,)* __Track__>>
+            for #split_ty
+            where #bounds #(#split_fields_ty: #split_lifetimes,)* #(#split_lifetimes: #lt,)* {
+                #[inline(always)]
+                fn from(
+                    view: &#lt mut #ref_ident<#ident<#params>, #(#reborrowed_params,)* __Track__>
+                ) -> Self {
+                    Self { #(#split_fields: #split_reborrows,)* }
+                }
+            }
+        });
+    }
+
+    // Generates:
+    //
+    // ```
+    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> borrow::HasUsageTrackedFields
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where __Track__: borrow::Bool {
+    //     #[inline(always)]
+    //     fn disable_field_usage_tracking(&self) {
+    //         borrow::if_field_usage_tracking! {
+    //             self.version.disable_usage_tracking();
+    //             self.geometry.disable_usage_tracking();
+    //             self.material.disable_usage_tracking();
+    //             self.mesh.disable_usage_tracking();
+    //             self.scene.disable_usage_tracking();
+    //         }
+    //     }
+    //
+    //     #[inline(always)]
+    //     fn mark_all_fields_as_used(&self) {
+    //         borrow::if_field_usage_tracking! {
+    //             self.version.mark_as_used();
+    //             self.geometry.mark_as_used();
+    //             self.material.mark_as_used();
+    //             self.mesh.mark_as_used();
+    //             self.scene.mark_as_used();
+    //         }
+    //     }
+    //
+    //     #[inline(always)]
+    //     fn deny_over_borrow(&self) {
+    //         self.__usage_tracker__.deny_over_borrow();
+    //     }
+    // }
+    // ```
+    out.push(quote! {
+        impl<__S__: ?Sized, __Track__, #(#fields_param,)*> borrow::HasUsageTrackedFields
+        for #ref_ident<__S__, #(#fields_param,)* __Track__>
+        where __Track__: borrow::Bool {
+            #[inline(always)]
+            fn disable_field_usage_tracking(&self) {
+                borrow::if_field_usage_tracking! {
+                    #(self.#fields_ident.disable_usage_tracking();)*
+                }
+            }
+            #[inline(always)]
+            fn mark_all_fields_as_used(&self) {
+                borrow::if_field_usage_tracking! {
+                    #(self.#fields_ident.mark_as_used();)*
+                }
+            }
+            #[inline(always)]
+            fn deny_over_borrow(&self) {
+                self.__usage_tracker__.deny_over_borrow();
+            }
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // impl<'t, T> borrow::HasFieldLabels for Ctx<'t, T>
+    // where T: Debug {
+    //     const FIELD_LABELS: &'static [borrow::Label] =
+    //         &["version", "geometry", "material", "mesh", "scene"];
+    // }
+    //
+    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // borrow::HasFieldMask
+    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // where
+    //     __Track__: borrow::Bool,
+    //     __Version: borrow::FieldAccess,
+    //     __Geometry: borrow::FieldAccess,
+    //     __Material: borrow::FieldAccess,
+    //     __Mesh: borrow::FieldAccess,
+    //     __Scene: borrow::FieldAccess,
+    // {
+    //     type Struct = __S__;
+    //     fn field_mask() -> borrow::FieldMask<__S__> {
+    //         borrow::FieldMask::from_access(vec![
+    //             <__Version as borrow::FieldAccess>::ACCESS,
+    //             <__Geometry as borrow::FieldAccess>::ACCESS,
+    //             <__Material as borrow::FieldAccess>::ACCESS,
+    //             <__Mesh as borrow::FieldAccess>::ACCESS,
+    //             <__Scene as borrow::FieldAccess>::ACCESS,
+    //         ])
+    //     }
+    // }
+    // ```
+    out.push(quote! {
+        impl<#params_decl> borrow::HasFieldLabels for #ident<#params>
+        where #bounds {
+            const FIELD_LABELS: &'static [borrow::Label] = &[#(#fields_label,)*];
+        }
+
+        #[allow(non_camel_case_types)]
+        impl<__S__: ?Sized, __Track__, #(#fields_param,)*> borrow::HasFieldMask
+        for #ref_ident<__S__, #(#fields_param,)* __Track__>
+        where
+            __Track__: borrow::Bool,
+            #(#fields_param: borrow::FieldAccess,)*
+        {
+            type Struct = __S__;
+            fn field_mask() -> borrow::FieldMask<__S__> {
+                borrow::FieldMask::from_access(vec![
+                    #(<#fields_param as borrow::FieldAccess>::ACCESS,)*
+                ])
+            }
+        }
+    });
+
+    // Generates:
+    //
+    // ```
+    // #[allow(non_camel_case_types)]
+    // impl<'__a__, __S__: ?Sized, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // borrow::Rebind<'__a__>
+    // for CtxRef<__S__, __Version, __Geometry, __Material, __Mesh, __Scene, __Track__>
+    // where
+    //     __Track__: borrow::Bool,
+    //     __Version: borrow::RebindField<'__a__>,
+    //     __Geometry: borrow::RebindField<'__a__>,
+    //     __Material: borrow::RebindField<'__a__>,
+    //     __Mesh: borrow::RebindField<'__a__>,
+    //     __Scene: borrow::RebindField<'__a__>,
+    // {
+    //     type Output = CtxRef<
     //         __S__,
+    //         borrow::ReboundField<'__a__, __Version>,
+    //         borrow::ReboundField<'__a__, __Geometry>,
+    //         borrow::ReboundField<'__a__, __Material>,
+    //         borrow::ReboundField<'__a__, __Mesh>,
+    //         borrow::ReboundField<'__a__, __Scene>,
     //         __Track__,
-    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Version>, __Track__>,
-    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Geometry>, __Track__>,
-    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Material>, __Track__>,
-    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Mesh>, __Track__>,
-    //         borrow::ClonedField<'__s__, borrow::Field<__Track__, __Scene>, __Track__>
     //     >;
-    //     fn clone_ref_disabled_usage_tracking(&'__s__ mut self) -> Self::Cloned {
-    //         use borrow::CloneField;
+    //     #[inline(always)]
+    //     fn rebind(self) -> Self::Output {
     //         CtxRef {
-    //             version: self.version.clone_field_disabled_usage_tracking(),
-    //             geometry: self.geometry.clone_field_disabled_usage_tracking(),
-    //             material: self.material.clone_field_disabled_usage_tracking(),
-    //             mesh: self.mesh.clone_field_disabled_usage_tracking(),
-    //             scene: self.scene.clone_field_disabled_usage_tracking(),
-    //             marker: std::marker::PhantomData,
-    //             usage_tracker: borrow::UsageTracker::new(),
+    //             version: self.version.rebind(),
+    //             geometry: self.geometry.rebind(),
+    //             material: self.material.rebind(),
+    //             mesh: self.mesh.rebind(),
+    //             scene: self.scene.rebind(),
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__: self.__usage_tracker__,
     //         }
     //     }
     // }
     // ```
-    out.push(
-        quote! {
-            impl<'__s__, __S__, __Track__, #(#fields_param,)*> borrow::CloneRef<'__s__>
-            for #ref_ident<__S__, __Track__, #(#fields_param,)*>
-            where
-                __Track__: borrow::Bool,
-                #(borrow::Field<__Track__, #fields_param>: borrow::CloneField<'__s__, __Track__>,)*
-            {
-                type Cloned = #ref_ident<
-                    __S__,
-                    __Track__,
-                    #(borrow::ClonedField<'__s__, borrow::Field<__Track__, #fields_param>, __Track__>,)*
-                >;
-                fn clone_ref_disabled_usage_tracking(&'__s__ mut self) -> Self::Cloned {
-                    use borrow::CloneField;
-                    #ref_ident {
-                        #(#fields_ident: self.#fields_ident.clone_field_disabled_usage_tracking(),)*
-                        marker: std::marker::PhantomData,
-                        usage_tracker: borrow::UsageTracker::new(),
-                    }
+    out.push(quote! {
+        #[allow(non_camel_case_types)]
+        impl<'__a__, __S__: ?Sized, __Track__, #(#fields_param,)*>
+        borrow::Rebind<'__a__>
+        for #ref_ident<__S__, #(#fields_param,)* __Track__>
+        where
+            __Track__: borrow::Bool,
+            #(#fields_param: borrow::RebindField<'__a__>,)*
+        {
+            type Output = #ref_ident<
+                __S__,
+                #(borrow::ReboundField<'__a__, #fields_param>,)*
+                __Track__,
+            >;
+            #[inline(always)]
+            fn rebind(self) -> Self::Output {
+                #ref_ident {
+                    #(#fields_ident: self.#fields_ident.rebind(),)*
+                    __marker__: std::marker::PhantomData,
+                    __usage_tracker__: self.__usage_tracker__,
                 }
             }
         }
-    );
+    });
 
     // Generates:
     //
     // ```
-    // #[allow(non_camel_case_types)]
-    // #[allow(non_snake_case)]
-    // impl<__S__, __Track__, __Track__Target__,
-    //     __Version, __Geometry, __Material, __Mesh, __Scene,
-    //     __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target,
-    //     __Version__Rest, __Geometry__Rest, __Material__Rest, __Mesh__Rest, __Scene__Rest>
-    // borrow::IntoPartial<CtxRef<__S__, __Track__Target__, __Version__Target, __Geometry__Target, __Material__Target, __Mesh__Target, __Scene__Target>>
+    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
+    // borrow::Snapshot
     // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
     // where
     //     __Track__: borrow::Bool,
-    //     __Track__Target__: borrow::Bool,
-    //     borrow::AcquireMarker: borrow::Acquire<__Version, __Version__Target, Rest=__Version__Rest>,
-    //     borrow::AcquireMarker: borrow::Acquire<__Geometry, __Geometry__Target, Rest=__Geometry__Rest>,
-    //     borrow::AcquireMarker: borrow::Acquire<__Material, __Material__Target, Rest=__Material__Rest>,
-    //     borrow::AcquireMarker: borrow::Acquire<__Mesh, __Mesh__Target, Rest=__Mesh__Rest>,
-    //     borrow::AcquireMarker: borrow::Acquire<__Scene, __Scene__Target, Rest=__Scene__Rest>,
+    //     __Version: borrow::SnapshotField,
+    //     __Geometry: borrow::SnapshotField,
+    //     __Material: borrow::SnapshotField,
+    //     __Mesh: borrow::SnapshotField,
+    //     __Scene: borrow::SnapshotField,
     // {
-    //     type Rest = CtxRef<__S__, __Track__, __Version__Rest, __Geometry__Rest, __Material__Rest, __Mesh__Rest, __Scene__Rest>;
-    //     #[track_caller]
-    //     #[inline(always)]
-    //     fn into_split_impl(
-    //         mut self
-    //     ) -> (CtxRef<
-    //         __S__,
-    //         __Track__Target__,
-    //         __Version__Target,
-    //         __Geometry__Target,
-    //         __Material__Target,
-    //         __Mesh__Target,
-    //         __Scene__Target
-    //     >,
-    //         Self::Rest
-    //     ) {
-    //         use borrow::Acquire;
-    //         let usage_tracker = borrow::UsageTracker::new();
-    //         let (version, __version__rest) = borrow::AcquireMarker::acquire(self.version, usage_tracker.clone());
-    //         let (geometry, __geometry__rest) = borrow::AcquireMarker::acquire(self.geometry, usage_tracker.clone());
-    //         let (material, __material__rest) = borrow::AcquireMarker::acquire(self.material, usage_tracker.clone());
-    //         let (mesh, __mesh__rest) = borrow::AcquireMarker::acquire(self.mesh, usage_tracker.clone());
-    //         let (scene, __scene__rest) = borrow::AcquireMarker::acquire(self.scene, usage_tracker.clone());
+    //     type Snapshot = (
+    //         <__Version as borrow::SnapshotField>::Snapshot,
+    //         <__Geometry as borrow::SnapshotField>::Snapshot,
+    //         <__Material as borrow::SnapshotField>::Snapshot,
+    //         <__Mesh as borrow::SnapshotField>::Snapshot,
+    //         <__Scene as borrow::SnapshotField>::Snapshot,
+    //     );
+    //     fn snapshot(&self) -> Self::Snapshot {
     //         (
-    //             CtxRef {
-    //                 version,
-    //                 geometry,
-    //                 material,
-    //                 mesh,
-    //                 scene,
-    //                 marker: std::marker::PhantomData,
-    //                 usage_tracker
-    //             },
-    //             CtxRef {
-    //                 version: __version__rest,
-    //                 geometry: __geometry__rest,
-    //                 material: __material__rest,
-    //                 mesh: __mesh__rest,
-    //                 scene: __scene__rest,
-    //                 marker: std::marker::PhantomData,
-    //                 usage_tracker: borrow::UsageTracker::new(),
-    //             }
+    //             self.version.value_no_usage_tracking.snapshot_field(),
+    //             self.geometry.value_no_usage_tracking.snapshot_field(),
+    //             self.material.value_no_usage_tracking.snapshot_field(),
+    //             self.mesh.value_no_usage_tracking.snapshot_field(),
+    //             self.scene.value_no_usage_tracking.snapshot_field(),
     //         )
     //     }
+    //     fn restore(&mut self, snapshot: Self::Snapshot) {
+    //         let (version, geometry, material, mesh, scene,) = snapshot;
+    //         self.version.value_no_usage_tracking.restore_field(version);
+    //         self.geometry.value_no_usage_tracking.restore_field(geometry);
+    //         self.material.value_no_usage_tracking.restore_field(material);
+    //         self.mesh.value_no_usage_tracking.restore_field(mesh);
+    //         self.scene.value_no_usage_tracking.restore_field(scene);
+    //     }
     // }
     // ```
+    out.push(quote! {
+        #[allow(non_camel_case_types)]
+        impl<__S__: ?Sized, __Track__, #(#fields_param,)*> borrow::Snapshot
+        for #ref_ident<__S__, #(#fields_param,)* __Track__>
+        where
+            __Track__: borrow::Bool,
+            #(#fields_param: borrow::SnapshotField,)*
+        {
+            type Snapshot = (#(<#fields_param as borrow::SnapshotField>::Snapshot,)*);
+            #[inline(always)]
+            // The snapshot of structs without fields is `()`.
+            #[allow(clippy::unused_unit)]
+            fn snapshot(&self) -> Self::Snapshot {
+                use borrow::SnapshotField;
+                (#(self.#fields_ident.value_no_usage_tracking.snapshot_field(),)*)
+            }
+            #[inline(always)]
+            fn restore(&mut self, snapshot: Self::Snapshot) {
+                use borrow::SnapshotField;
+                let (#(#fields_ident,)*) = snapshot;
+                #(self.#fields_ident.value_no_usage_tracking.restore_field(#fields_ident);)*
+            }
+        }
+    });
 
-    out.push({
-        let field_params_target = fields_param.iter().map(|i| {
-            Ident::new(&format!("{i}{}", internal("Target")), i.span())
-        }).collect_vec();
+    // If the struct has a field marked with `#[borrow(dirty_flags)]`, e.g. `dirty`, root fields
+    // are created with `.with_dirty_flag(self.dirty.flag(0))`, where `0` is the field index.
+    let fields_dirty_flag = (0..fields.len()).map(|i| {
+        dirty_flags.as_ref().filter(|_| !fields_readonly[i]).map(|dirty_flags| quote! { .with_dirty_flag(self.#dirty_flags.flag(#i)) })
+    }).collect_vec();
 
-        let field_params_rest = fields_param.iter().map(|i| {
-            Ident::new(&format!("{i}{}", internal("Rest")), i.span())
-        }).collect_vec();
+    // Fields marked with `#[borrow(on_mut = "hook")]` are created with `.with_mut_hook(hook)`.
+    let fields_mut_hook = fields.iter().map(|f| {
+        parse_field_attrs(f).on_mut.map(|hook| quote! { .with_mut_hook(#hook) })
+    }).collect_vec();
 
-        let fields_rest_ident = fields_ident.iter().map(|i|
-            Ident::new(&format!("{}{}", internal(&i.to_string()), internal("rest")), i.span())
-        ).collect_vec();
+    // Fields marked with `#[borrow(readonly)]` are borrowed as shared by the root view, e.g. with
+    // `&*&mut self.config` and `Some(borrow::Usage::Ref)`, and are never marked as dirty. Fields
+    // marked with `#[borrow(private_mut = "...")]` are borrowed as shared by the public root view
+    // as well, see the crate root view below.
+    let fields_private_mut = fields_ident.iter().map(|f| attrs.private_mut.contains(f)).collect_vec();
+    let root_usage = |shared: &[bool]| shared.iter().map(|shared| {
+        if *shared { quote! {Ref} } else { quote! {Mut} }
+    }).collect_vec();
+    let root_reborrow = |shared: &[bool]| shared.iter().map(|shared| {
+        shared.then(|| quote! {&*})
+    }).collect_vec();
+    let fields_root_shared = fields_readonly.iter().zip(&fields_private_mut)
+        .map(|(readonly, private_mut)| *readonly || *private_mut).collect_vec();
 
+    // Fields marked with `#[borrow(readonly_in_release = "...")]` are borrowed as shared by the
+    // root views in builds without `debug_assertions`. If there are such fields, the items creating
+    // root views are generated twice, with `#[cfg(debug_assertions)]` and `#[cfg(not(...))]`.
+    let fields_readonly_in_release = readonly_aliases.iter().map(Option::is_some).collect_vec();
+    let for_each_build = |shared: &[bool], item: &dyn Fn(&[bool]) -> TokenStream| {
+        if !fields_readonly_in_release.contains(&true) {
+            return item(shared);
+        }
+        let release_shared = shared.iter().zip(&fields_readonly_in_release)
+            .map(|(shared, readonly_in_release)| *shared || *readonly_in_release).collect_vec();
+        let debug = item(shared);
+        let release = item(&release_shared);
         quote! {
-            #[allow(non_camel_case_types)]
-            #[allow(non_snake_case)]
-            impl<__S__, __Track__, __Track__Target__,
-                #(#fields_param,)*
-                #(#field_params_target,)*
-                #(#field_params_rest,)*
-            >
-            borrow::IntoPartial<#ref_ident<__S__, __Track__Target__, #(#field_params_target,)*>>
-            for #ref_ident<__S__, __Track__, #(#fields_param,)*>
-            where
-                __Track__: borrow::Bool,
-                __Track__Target__: borrow::Bool,
-                #(
-                    borrow::AcquireMarker: borrow::Acquire<
-                        #fields_param,
-                        #field_params_target,
-                        Rest=#field_params_rest
-                    >,
-                )*
-            {
-                type Rest = #ref_ident<__S__, __Track__, #(#field_params_rest,)*>;
-
-                #[track_caller]
-                #[inline(always)]
-                fn into_split_impl(
-                    mut self
-                ) -> (
-                    #ref_ident<__S__, __Track__Target__, #(#field_params_target,)*>,
-                    Self::Rest
-                ) {
-                    use borrow::Acquire;
-                    let usage_tracker = borrow::UsageTracker::new();
-                    #(let (#fields_ident, #fields_rest_ident) =
-                        borrow::AcquireMarker::acquire(self.#fields_ident, usage_tracker.clone());)*
-                    (
-                        #ref_ident {
-                            #(#fields_ident,)*
-                            marker: std::marker::PhantomData,
-                            usage_tracker
-                        },
-                        #ref_ident {
-                            #(#fields_ident: #fields_rest_ident,)*
-                            marker: std::marker::PhantomData,
-                            usage_tracker: borrow::UsageTracker::new()
-                        }
-                    )
-                }
-            }
+            #[cfg(debug_assertions)]
+            #debug
+            #[cfg(not(debug_assertions))]
+            #release
         }
-    });
-
+    };
+    // Shared fields are never marked as dirty.
+    let root_dirty_flag = |shared: &[bool]| {
+        fields_dirty_flag.iter().zip(shared).map(|(flag, shared)| flag.as_ref().filter(|_| !*shared))
+            .collect_vec()
+    };
 
     // Generates:
-
-    // ```
-    // #[allow(non_camel_case_types)]
-    // impl<'__a__, __S__, __Track__, __Target__,
-    //     __Version, __Geometry, __Material, __Mesh, __Scene>
-    // borrow::Partial<'__a__, __Target__>
-    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> where
-    //     __Track__: borrow::Bool,
-    //     Self: borrow::CloneRef<'__a__>,
-    //     borrow::ClonedRef<'__a__, Self>: borrow::IntoPartial<__Target__>
-    // {
-    //     type Rest = <borrow::ClonedRef<'__a__, Self> as borrow::IntoPartial<__Target__>>::Rest;
+    //
+    // ```
+    // impl<'t, T> borrow::AsRefsMut for Ctx<'t, T>
+    // where T: Debug {
+    //     type Target<'__s> =
+    //     borrow::RefWithFields<Ctx<'t, T>, borrow::FieldsAsMut<'__s, Ctx<'t, T>>>
+    //     where Self: '__s;
     //     #[track_caller]
     //     #[inline(always)]
-    //     fn split_impl(&'__a__ mut self) -> (__Target__, Self::Rest) {
-    //         use borrow::CloneRef;
-    //         use borrow::IntoPartial;
-    //         // As the usage trackers are cloned and immediately destroyed by `into_split_impl`,
-    //         // we need to disable them.
-    //         let this = self.clone_ref_disabled_usage_tracking();
-    //         this.into_split_impl()
+    //     fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
+    //         let __usage_tracker__ = borrow::UsageTracker::new_root();
+    //         let struct_ref = CtxRef {
+    //             version: borrow::Field::new(
+    //                 "version",
+    //                 Some(borrow::Usage::Mut),
+    //                 &mut self.version,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             geometry: borrow::Field::new(
+    //                 "geometry",
+    //                 Some(borrow::Usage::Mut),
+    //                 &mut self.geometry,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             material: borrow::Field::new(
+    //                 "material",
+    //                 Some(borrow::Usage::Mut),
+    //                 &mut self.material,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             mesh: borrow::Field::new(
+    //                 "mesh",
+    //                 Some(borrow::Usage::Mut),
+    //                 &mut self.mesh,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             scene: borrow::Field::new(
+    //                 "scene",
+    //                 Some(borrow::Usage::Mut),
+    //                 &mut self.scene,
+    //                 __usage_tracker__.clone()
+    //             ),
+    //             __marker__: std::marker::PhantomData,
+    //             __usage_tracker__,
+    //         };
+    //         borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+    //         struct_ref
     //     }
     // }
     // ```
-    out.push({
+    let as_refs_mut_bound = &attrs.as_refs_mut_bound;
+    let flattened_root_views = flattened_root_views(&input);
+    out.push(for_each_build(&fields_root_shared, &|shared| {
+        let fields_root_usage = root_usage(shared);
+        let fields_root_reborrow = root_reborrow(shared);
+        let fields_dirty_flag = root_dirty_flag(shared);
         quote! {
-            #[allow(non_camel_case_types)]
-            impl<'__a__, __S__, __Track__, __Target__, #(#fields_param,)*>
-            borrow::Partial<'__a__, __Target__>
-            for #ref_ident<__S__, __Track__, #(#fields_param,)*> where
-                __Track__: borrow::Bool,
-                Self: borrow::CloneRef<'__a__>,
-                borrow::ClonedRef<'__a__, Self>: borrow::IntoPartial<__Target__>
-            {
-                type Rest = <borrow::ClonedRef<'__a__, Self> as borrow::IntoPartial<__Target__>>::Rest;
+            impl<#params_decl> borrow::AsRefsMut for #ident<#params>
+            where #bounds #(#as_refs_mut_bound,)* {
+                type Target<'__s> =
+                    borrow::RefWithFields<#ident<#params>, borrow::FieldsAsMut<'__s, #ident<#params>>>
+                where Self: '__s;
                 #[track_caller]
                 #[inline(always)]
-                fn split_impl(&'__a__ mut self) -> (__Target__, Self::Rest) {
-                    use borrow::CloneRef;
-                    use borrow::IntoPartial;
-                    // As the usage trackers are cloned and immediately destroyed by `into_split_impl`,
-                    // we need to disable them.
-                    let this = self.clone_ref_disabled_usage_tracking();
-                    this.into_split_impl()
+                fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
+                    #flattened_root_views
+                    let __usage_tracker__ = borrow::UsageTracker::new_root();
+                    let struct_ref = #ref_ident {
+                        #(
+                            #fields_ident: borrow::Field::new(
+                                #fields_label,
+                                Some(borrow::Usage::#fields_root_usage),
+                                #fields_root_reborrow #fields_mut,
+                                __usage_tracker__.clone(),
+                            )#fields_dirty_flag #fields_mut_hook,
+                        )*
+                        __marker__: std::marker::PhantomData,
+                        __usage_tracker__
+                    };
+                    borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+                    struct_ref
                 }
             }
         }
-    });
+    }));
 
-    // For each field. For the 'version' field:
+    // If the struct has fields marked with `#[borrow(private_mut = "scene")]`, generates the
+    // crate root view, borrowing them mutably. Its methods are `pub(crate)`, so in the defining
+    // crate they take precedence over the trait methods of the same names, while other crates use
+    // the public root view borrowing the fields as shared:
     //
     // ```
-    // impl<'__s__, '__tgt__, 't, T, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
-    // CtxRef<Ctx<'t, T>, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
-    // where
-    //     __Track__: borrow::Bool,
-    //     T: Debug,
-    //     &'t T: '__tgt__,
-    //     Self: borrow::CloneRef<'__s__>,
-    //     borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
-    //         CtxRef<
-    //             Ctx<'t, T>,
-    //             __Track__,
-    //             borrow::Hidden,
-    //             &'__tgt__ mut GeometryCtx,
-    //             borrow::Hidden,
-    //             borrow::Hidden,
-    //             borrow::Hidden
-    //         >
-    //     >
-    // {
+    // #[allow(dead_code)]
+    // impl<'t, T> Ctx<'t, T>
+    // where T: Debug {
     //     #[track_caller]
     //     #[inline(always)]
-    //     pub fn extract_geometry2(&'__s__ mut self) -> (
-    //         borrow::Field<__Track__, &'__tgt__ mut GeometryCtx>,
-    //         <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
-    //             CtxRef<
-    //                 Ctx<'t, T>,
-    //                 __Track__,
-    //                 borrow::Hidden,
-    //                 &'__tgt__ mut GeometryCtx,
-    //                 borrow::Hidden,
-    //                 borrow::Hidden,
-    //                 borrow::Hidden
-    //             >
-    //         >>::Rest
-    //     ) {
-    //         let split = borrow::IntoPartial::into_split_impl(
-    //             borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
-    //         );
-    //         (split.0.geometry, split.1)
+    //     pub(crate) fn as_refs_mut<'__s>(&'__s mut self) -> borrow::RefWithFields<
+    //         Ctx<'t, T>,
+    //         borrow::HList![&'__s mut &'t T, ..., &'__s mut SceneCtx,]
+    //     > {
+    //         // The same as in the `AsRefsMut` impl, with `Some(borrow::Usage::Mut)` for `scene`.
     //     }
+    //
+    //     pub(crate) fn partial_borrow<'__s, __Target__>(&'__s mut self) -> __Target__ where ...
+    //     pub(crate) fn split<'__s, __Target__>(&'__s mut self) -> (__Target__, ...) where ...
     // }
     // ```
-    out.extend((0..fields_param.len()).map(|i| {
-        let field_ident = &fields_ident[i];
-        let field_ty = &fields_ty[i];
-        let field_ref_mut = quote! {&'__tgt__ mut #field_ty};
-        let field_ref = quote! {&'__tgt__ #field_ty};
+    if fields_private_mut.contains(&true) {
+        out.push(for_each_build(&fields_readonly, &|shared| {
+            let fields_crate_root_usage = root_usage(shared);
+            let fields_crate_root_reborrow = root_reborrow(shared);
+            let fields_dirty_flag = root_dirty_flag(shared);
+            let fields_crate_root_ty = fields_ty.iter().zip(shared).map(|(t, shared)| {
+                if *shared { quote! {&'__s #t} } else { quote! {&'__s mut #t} }
+            });
+            let root = quote! {
+                borrow::RefWithFields<#ident<#params>, borrow::HList![#(#fields_crate_root_ty,)*]>
+            };
+            quote! {
+                #[allow(dead_code)]
+                impl<#params_decl> #ident<#params>
+                where #bounds #(#as_refs_mut_bound,)* {
+                    /// Creates the root view of the defining crate, borrowing the private mut fields
+                    /// mutably.
+                    #[track_caller]
+                    #[inline(always)]
+                    pub(crate) fn as_refs_mut<'__s>(&'__s mut self) -> #root {
+                        #flattened_root_views
+                        let __usage_tracker__ = borrow::UsageTracker::new_root();
+                        let struct_ref = #ref_ident {
+                            #(
+                                #fields_ident: borrow::Field::new(
+                                    #fields_label,
+                                    Some(borrow::Usage::#fields_crate_root_usage),
+                                    #fields_crate_root_reborrow #fields_mut,
+                                    __usage_tracker__.clone(),
+                                )#fields_dirty_flag #fields_mut_hook,
+                            )*
+                            __marker__: std::marker::PhantomData,
+                            __usage_tracker__
+                        };
+                        borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+                        struct_ref
+                    }
 
-        let mut params2 = fields_param.clone();
-        params2.remove(i);
+                    /// Borrows the struct partially, see [`borrow::PartialHelper::partial_borrow`].
+                    #[track_caller]
+                    #[inline(always)]
+                    pub(crate) fn partial_borrow<'__s, __Target__>(&'__s mut self) -> __Target__
+                    where #root: borrow::IntoPartial<__Target__> {
+                        borrow::IntoPartial::into_split_impl(self.as_refs_mut()).0
+                    }
 
-        let mut target_params_mut = fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
-        target_params_mut[i] = field_ref_mut.clone();
+                    /// Splits the struct, see [`borrow::SplitHelper::split`].
+                    #[track_caller]
+                    #[inline(always)]
+                    pub(crate) fn split<'__s, __Target__>(&'__s mut self)
+                    -> (__Target__, <#root as borrow::IntoPartial<__Target__>>::Rest)
+                    where #root: borrow::IntoPartial<__Target__> {
+                        borrow::IntoPartial::into_split_impl(self.as_refs_mut())
+                    }
+                }
+            }
+        }));
+    }
 
-        let mut target_params = fields_param.iter().map(|_| quote! {borrow::Hidden}).collect_vec();
-        target_params[i] = field_ref.clone();
+    // If the struct is marked with `#[borrow(dyn_state = "CtxState")]` (shown for `Ctx` without
+    // parameters, as structs with parameters are not supported), generates a trait for state
+    // objects exposing the struct fields, so views can be borrowed from `dyn CtxState`:
+    //
+    // ```
+    // pub trait CtxState {
+    //     fn fields_mut(&mut self) -> (
+    //         &mut GeometryCtx,
+    //         &mut MaterialCtx,
+    //         &mut MeshCtx,
+    //         &mut SceneCtx,
+    //     );
+    // }
+    //
+    // impl CtxState for Ctx {
+    //     fn fields_mut(&mut self) -> (...) {
+    //         (&mut self.geometry, &mut self.material, &mut self.mesh, &mut self.scene)
+    //     }
+    // }
+    //
+    // impl borrow::AsRefsMut for dyn CtxState + '_ {
+    //     // The same as for `Ctx`, with the fields returned by `fields_mut` and the tracker
+    //     // created with `borrow::UsageTracker::new_root_for::<Ctx>()`.
+    // }
+    // ```
+    //
+    // Fields borrowed as shared by the root view, e.g. readonly and private mut fields, are
+    // returned by `fields_mut` as shared as well, so it can not be used to mutate them.
+    if let Some(state_ident) = &attrs.dyn_state {
+        let vis = &input.vis;
+        let doc = format!(
+            "State object exposing the fields of [`{ident}`], so partial borrows of them can be \
+            created from trait objects."
+        );
+        let fields_state_ty = |shared: &[bool]| fields_ty.iter().zip(shared).map(|(t, shared)| {
+            if *shared { quote! {&#t} } else { quote! {&mut #t} }
+        }).collect_vec();
+        out.push(for_each_build(&fields_root_shared, &|shared| {
+            let fields_state_ty = fields_state_ty(shared);
+            quote! {
+                #[doc = #doc]
+                #vis trait #state_ident {
+                    /// References to all the fields, in the declaration order. Fields borrowed as
+                    /// shared by the root view of the struct are shared.
+                    fn fields_mut(&mut self) -> (#(#fields_state_ty,)*);
+                }
+            }
+        }));
+        out.push(for_each_build(&fields_root_shared, &|shared| {
+            let fields_state_ty = fields_state_ty(shared);
+            let fields_root_reborrow = root_reborrow(shared);
+            quote! {
+                impl #state_ident for #ident {
+                    #[inline(always)]
+                    fn fields_mut(&mut self) -> (#(#fields_state_ty,)*) {
+                        #flattened_root_views
+                        (#(#fields_root_reborrow #fields_mut,)*)
+                    }
+                }
+            }
+        }));
+        out.push(for_each_build(&fields_root_shared, &|shared| {
+            let fields_root_usage = root_usage(shared);
+            quote! {
+                impl borrow::AsRefsMut for dyn #state_ident + '_ {
+                    type Target<'__s> =
+                        borrow::RefWithFields<#ident, borrow::FieldsAsMut<'__s, #ident>>
+                    where Self: '__s;
+                    #[track_caller]
+                    #[inline(always)]
+                    fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
+                        let (#(#fields_ident,)*) = #state_ident::fields_mut(self);
+                        let __usage_tracker__ = borrow::UsageTracker::new_root_for::<#ident>();
+                        let struct_ref = #ref_ident {
+                            #(
+                                #fields_ident: borrow::Field::new(
+                                    #fields_label,
+                                    Some(borrow::Usage::#fields_root_usage),
+                                    #fields_ident,
+                                    __usage_tracker__.clone(),
+                                ),
+                            )*
+                            __marker__: std::marker::PhantomData,
+                            __usage_tracker__
+                        };
+                        borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+                        struct_ref
+                    }
+                }
+            }
+        }));
+    }
 
-        let fn_ident = Ident::new(&format!("borrow_{field_ident}"), field_ident.span());
-        let fn_ident_mut = Ident::new(&format!("borrow_{field_ident}_mut"), field_ident.span());
+    // Generates a test view, a builder of partial borrows backed by owned field values, so pass
+    // functions can be unit-tested without creating the whole struct. It is generated only in
+    // `cfg(test)`, unless the `test_view` feature is enabled:
+    //
+    // ```
+    // pub struct CtxTestView<__Version, __Geometry, __Material, __Mesh, __Scene> {
+    //     version: __Version,
+    //     geometry: __Geometry,
+    //     material: __Material,
+    //     mesh: __Mesh,
+    //     scene: __Scene,
+    //     __marker__: std::marker::PhantomData<fn() -> Ctx>,
+    // }
+    //
+    // impl CtxRef<Ctx, borrow::True, borrow::Hidden, ...> {
+    //     pub fn test_view() -> CtxTestView<borrow::DefaultField<usize>, ...> {
+    //         CtxTestView { version: borrow::DefaultField::new(), ... }
+    //     }
+    // }
+    //
+    // impl<__Version, __Geometry, __Material, __Mesh, __Scene>
+    // CtxTestView<__Version, __Geometry, __Material, __Mesh, __Scene> {
+    //     pub fn with_version(self, version: usize)
+    //     -> CtxTestView<usize, __Geometry, __Material, __Mesh, __Scene> {
+    //         CtxTestView { version, geometry: self.geometry, ... }
+    //     }
+    //
+    //     pub fn version_mut(&mut self) -> &mut usize
+    //     where __Version: borrow::TestField<usize> {
+    //         borrow::TestField::test_field_mut(&mut self.version)
+    //     }
+    //     ...
+    // }
+    //
+    // impl<__Version, __Geometry, __Material, __Mesh, __Scene> borrow::AsRefsMut
+    // for CtxTestView<__Version, __Geometry, __Material, __Mesh, __Scene>
+    // where __Version: borrow::TestField<usize>, ... {
+    //     // The same as for `Ctx`, with the fields returned by `borrow::TestField::test_field_mut`.
+    // }
+    // ```
+    // Test views store the fields by value, so they are not generated for structs with unsized
+    // fields.
+    let has_unsized_field = fields.iter().any(|f| is_unsized_type(&input, &field_view_syn_type(f)));
+    if !has_unsized_field {
+        out.push({
+            let test_view_ident = Ident::new(&format!("{ident}TestView"), ident.span());
+            let vis = &input.vis;
+            let gate = if cfg!(feature = "test_view") { quote! {} } else { quote! { #[cfg(test)] } };
+            let doc = format!(
+                "Builder of partial borrows of [`{ident}`] backed by owned field values, created with \
+                [`{ref_ident}::test_view`]. Fields not provided with the `with_*` methods are created \
+                with [`Default`]."
+            );
+            let hidden_fields = fields_param.iter().map(|_| quote! {borrow::Hidden});
+            let default_params = fields_ty.iter().map(|ty| quote! { borrow::DefaultField<#ty> });
+            let with_fns = fields_ident.iter().enumerate().map(|(i, field)| {
+                let fn_ident = Ident::new(&format!("with_{}", fields_label[i]), field.span());
+                let ty = &fields_ty[i];
+                let mut target_params = fields_param.iter().map(|p| quote! {#p}).collect_vec();
+                target_params[i] = quote! {#ty};
+                let other_fields = fields_ident.iter().filter(|f| *f != field);
+                quote! {
+                    pub fn #fn_ident(self, #field: #ty) -> #test_view_ident<#params #(#target_params,)*> {
+                        #test_view_ident {
+                            #field,
+                            #(#other_fields: self.#other_fields,)*
+                            __marker__: std::marker::PhantomData,
+                        }
+                    }
+                }
+            }).collect_vec();
+            let mut_fns = fields_ident.iter().enumerate().map(|(i, field)| {
+                let fn_ident = Ident::new(&format!("{}_mut", field.unraw()), field.span());
+                let ty = &fields_ty[i];
+                let param = &fields_param[i];
+                quote! {
+                    pub fn #fn_ident(&mut self) -> &mut #ty
+                    where #param: borrow::TestField<#ty> {
+                        borrow::TestField::test_field_mut(&mut self.#field)
+                    }
+                }
+            }).collect_vec();
+            let as_refs_mut_impl = for_each_build(&fields_root_shared, &|shared| {
+                let fields_root_usage = root_usage(shared);
+                let fields_root_reborrow = root_reborrow(shared);
+                quote! {
+                    #gate
+                    impl<#params_decl #(#fields_param,)*> borrow::AsRefsMut
+                    for #test_view_ident<#params #(#fields_param,)*>
+                    where #bounds #(#fields_param: borrow::TestField<#fields_ty>,)* {
+                        type Target<'__s> =
+                            borrow::RefWithFields<#ident<#params>, borrow::FieldsAsMut<'__s, #ident<#params>>>
+                        where Self: '__s;
+                        #[track_caller]
+                        #[inline(always)]
+                        fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
+                            let __usage_tracker__ = borrow::UsageTracker::new_root();
+                            let struct_ref = #ref_ident {
+                                #(
+                                    #fields_ident: borrow::Field::new(
+                                        #fields_label,
+                                        Some(borrow::Usage::#fields_root_usage),
+                                        #fields_root_reborrow borrow::TestField::test_field_mut(
+                                            &mut self.#fields_ident
+                                        ),
+                                        __usage_tracker__.clone(),
+                                    ),
+                                )*
+                                __marker__: std::marker::PhantomData,
+                                __usage_tracker__
+                            };
+                            borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
+                            struct_ref
+                        }
+                    }
+                }
+            });
+            quote! {
+                #gate
+                #[doc = #doc]
+                #vis struct #test_view_ident<#params_decl #(#fields_param,)*>
+                where #bounds {
+                    #(#fields_ident: #fields_param,)*
+                    __marker__: std::marker::PhantomData<fn() -> #ident<#params>>,
+                }
 
-        quote! {
-            #[allow(non_camel_case_types)]
-            impl<'__s__, '__tgt__, #params __Track__, #(#fields_param,)*>
-            #ref_ident<#ident<#params>, __Track__, #(#fields_param,)*>
-            where
-                #bounds
-                __Track__: borrow::Bool,
-                #field_ty: '__tgt__,
-                Self: borrow::CloneRef<'__s__>,
-                borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
-                    #ref_ident<
-                        #ident<#params>,
-                        __Track__,
-                        #(#target_params_mut,)*
-                    >
-                >
-            {
-                #[track_caller]
-                #[inline(always)]
-                pub fn #fn_ident_mut(&'__s__ mut self) -> (
-                    borrow::Field<__Track__, #field_ref_mut>,
-                        <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
-                            #ref_ident<
-                                #ident<#params>,
-                                __Track__,
-                                #(#target_params_mut,)*
-                            >
-                        >>::Rest
-                ) {
-                    let split = borrow::IntoPartial::into_split_impl(
-                        borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
-                    );
-                    (split.0.#field_ident, split.1)
+                #gate
+                impl<#params_decl> #ref_ident<#ident<#params>, #(#hidden_fields,)* borrow::True>
+                where #bounds {
+                    /// Creates a builder of partial borrows backed by owned field values, so functions
+                    /// taking partial borrows can be tested without creating the whole struct.
+                    pub fn test_view() -> #test_view_ident<#params #(#default_params,)*> {
+                        #test_view_ident {
+                            #(#fields_ident: borrow::DefaultField::new(),)*
+                            __marker__: std::marker::PhantomData,
+                        }
+                    }
+                }
+
+                #gate
+                impl<#params_decl #(#fields_param,)*> #test_view_ident<#params #(#fields_param,)*>
+                where #bounds {
+                    #(#with_fns)*
+                    #(#mut_fns)*
                 }
+
+                #as_refs_mut_impl
             }
+        });
+    }
 
-            #[allow(non_camel_case_types)]
-            impl<'__s__, '__tgt__, #params __Track__, #(#fields_param,)*>
-            #ref_ident<#ident<#params>, __Track__, #(#fields_param,)*>
-            where
-                #bounds
-                __Track__: borrow::Bool,
-                #field_ty: '__tgt__,
-                Self: borrow::CloneRef<'__s__>,
-                borrow::ClonedRef<'__s__, Self>: borrow::IntoPartial<
-                    #ref_ident<
-                        #ident<#params>,
-                        __Track__,
-                        #(#target_params,)*
-                    >
-                >
-            {
-                #[track_caller]
-                #[inline(always)]
-                pub fn #fn_ident(&'__s__ mut self) -> (
-                    borrow::Field<__Track__, #field_ref>,
-                        <borrow::ClonedRef<'__s__, Self> as borrow::IntoPartial<
-                            #ref_ident<
-                                #ident<#params>,
-                                __Track__,
-                                #(#target_params,)*
-                            >
-                        >>::Rest
-                ) {
-                    let split = borrow::IntoPartial::into_split_impl(
-                        borrow::CloneRef::clone_ref_disabled_usage_tracking(self)
-                    );
-                    (split.0.#field_ident, split.1)
+    // If the `registry` feature is enabled, registers the struct for development tools:
+    //
+    // ```
+    // borrow::inventory::submit! {
+    //     borrow::RegisteredStruct {
+    //         name: "Ctx",
+    //         module: module_path!(),
+    //         fields: &[
+    //             borrow::RegisteredField { label: "version", ty: "&'t T", readonly: false },
+    //             borrow::RegisteredField { label: "geometry", ty: "GeometryCtx", readonly: false },
+    //             ...
+    //         ],
+    //         groups: &[],
+    //     }
+    // }
+    // ```
+    if cfg!(feature = "registry") {
+        let name = ident.to_string();
+        let fields_ty_str = fields.iter().map(|f| {
+            let ty = &f.ty;
+            tokens_string(&quote! {#ty})
+        }).collect_vec();
+        let groups = attrs.groups.iter().map(|group| {
+            let name = group.ident.unraw().to_string();
+            let labels = group.fields.iter()
+                .filter_map(|field| fields_ident.iter().position(|f| *f == field))
+                .map(|i| &fields_label[i]);
+            quote! { borrow::RegisteredGroup { name: #name, fields: &[#(#labels,)*] } }
+        }).collect_vec();
+        out.push(quote! {
+            borrow::inventory::submit! {
+                borrow::RegisteredStruct {
+                    name: #name,
+                    module: module_path!(),
+                    fields: &[
+                        #(borrow::RegisteredField {
+                            label: #fields_label,
+                            ty: #fields_ty_str,
+                            readonly: #fields_readonly,
+                        },)*
+                    ],
+                    groups: &[#(#groups,)*],
                 }
             }
-        }
-    }));
+        });
+    }
+
+    let mut output = quote! {
+        #version_check
+        #(#out)*
+    };
+    if let Some(crate_path) = &attrs.crate_path {
+        output = with_crate_path(output, crate_path);
+    }
+
+    // println!("OUTPUT:\n{}", output);
+    output.into()
+}
+
+// ==========================
+// === Transparent Derive ===
+// ==========================
+
+/// Path of the struct wrapped by a `#[borrow(transparent)]` struct, without generic arguments,
+/// e.g. `inner::Inner` for `inner::Inner<T>`. It is used to name the struct macro of the wrapped
+/// struct, so paths starting with `self` or `super` are not supported, as they can not follow the
+/// module path.
+fn transparent_inner_path(ty: &Type) -> Option<syn::Path> {
+    let Type::Path(type_path) = ty else { return None };
+    let mut path = type_path.path.clone();
+    let is_relative = path.segments.first().is_some_and(|s| s.ident == "self" || s.ident == "super");
+    if type_path.qself.is_some() || is_relative {
+        return None;
+    }
+    for segment in &mut path.segments {
+        segment.arguments = syn::PathArguments::None;
+    }
+    Some(path)
+}
 
+/// Whether the path of the struct wrapped by a `#[borrow(transparent)]` struct starts with `crate`
+/// or `::`, so it does not depend on the module of the wrapper.
+fn transparent_inner_is_absolute(inner: &syn::Path) -> bool {
+    inner.leading_colon.is_some() || inner.segments.first().is_some_and(|s| s.ident == "crate")
+}
 
-    // Generates:
-    //
-    // ```
-    // impl<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene> borrow::HasUsageTrackedFields
-    // for CtxRef<__S__, __Track__, __Version, __Geometry, __Material, __Mesh, __Scene>
-    // where __Track__: borrow::Bool {
-    //     #[inline(always)]
-    //     fn disable_field_usage_tracking(&self) {
-    //         self.version.disable_usage_tracking();
-    //         self.geometry.disable_usage_tracking();
-    //         self.material.disable_usage_tracking();
-    //         self.mesh.disable_usage_tracking();
-    //         self.scene.disable_usage_tracking();
-    //     }
-    //
-    //     #[inline(always)]
-    //     fn mark_all_fields_as_used(&self) {
-    //         self.version.mark_as_used();
-    //         self.geometry.mark_as_used();
-    //         self.material.mark_as_used();
-    //         self.mesh.mark_as_used();
-    //         self.scene.mark_as_used();
-    //     }
-    // }
-    // ```
-    out.push(quote! {
-        impl<__S__, __Track__, #(#fields_param,)*> borrow::HasUsageTrackedFields
-        for #ref_ident<__S__, __Track__, #(#fields_param,)*>
-        where __Track__: borrow::Bool {
-            #[inline(always)]
-            fn disable_field_usage_tracking(&self) {
-                #(self.#fields_ident.disable_usage_tracking();)*
-            }
-            #[inline(always)]
-            fn mark_all_fields_as_used(&self) {
-                #(self.#fields_ident.mark_as_used();)*
-            }
-        }
+/// Path of the struct macro of the struct wrapped by a `#[borrow(transparent)]` struct, as used in
+/// the generated `macro_rules!`. Paths not starting with `crate` or `::` are resolved relative to
+/// the module of the wrapper, which is required for them.
+fn transparent_inner_macro(
+    module: Option<&TokenStream>,
+    inner: &syn::Path,
+) -> syn::Result<TokenStream> {
+    match module {
+        _ if inner.leading_colon.is_some() => Ok(quote! { #inner }),
+        _ if transparent_inner_is_absolute(inner) => Ok(macro_module_path(quote! { #inner })),
+        Some(module) => Ok(quote! { #module::#inner }),
+        None => Err(syn::Error::new_spanned(inner, "Expected #[module(...)] attribute.")),
+    }
+}
+
+/// Derive for single-field wrapper structs marked with `#[borrow(transparent)]`. The wrapper does
+/// not get its own partial borrow struct. Instead, its struct macro forwards the selectors to the
+/// struct macro of the wrapped struct, and its root view is the root view of the wrapped struct.
+fn transparent_derive(
+    input: &DeriveInput,
+    module: Option<&TokenStream>,
+) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let params = get_params(input);
+    let params_decl = get_params_decl(input);
+    let bounds = get_bounds(input);
+    let fields = get_fields(input);
+    let field = fields.first()
+        .ok_or_else(|| syn::Error::new_spanned(ident, "Expected a single field."))?;
+    let member = field_member(field);
+    let inner = &field.ty;
+    let inner_path = transparent_inner_path(inner)
+        .ok_or_else(|| syn::Error::new_spanned(inner, "Expected a path to the inner struct."))?;
+    let inner_macro = transparent_inner_macro(module, &inner_path)?;
+    let attrs = parse_struct_attrs(input);
+    let macro_ident = attrs.struct_macro_ident(ident, module);
+    let as_refs_mut_bound = &attrs.as_refs_mut_bound;
+    let vis = attrs.vis();
+    let export = export_struct_macro(&vis, &attrs, &macro_ident, ident, quote! {
+        (@0 $m:tt $pfx:tt $track:tt [$s:ty] $($ts:tt)*) => {
+            #inner_macro! { @0 [#inner_macro] $pfx $track [<$s as borrow::Transparent>::Inner] $($ts)* }
+        };
+        (@redundant $alias:ident $($ts:tt)*) => {
+            borrow::$alias<$($ts)*>
+        };
     });
 
-    // Generates:
+    // For `struct World(Inner)` defined in `crate::world`, generates:
     //
     // ```
-    // impl<'t, T> borrow::AsRefsMut for Ctx<'t, T>
-    // where T: Debug {
-    //     type Target<'__s> =
-    //     borrow::RefWithFields<Ctx<'t, T>, borrow::FieldsAsMut<'__s, Ctx<'t, T>>>
-    //     where Self: '__s;
+    // impl borrow::Transparent for World {
+    //     type Inner = Inner;
+    // }
+    //
+    // impl borrow::AsRefsMut for World
+    // where Inner: borrow::AsRefsMut {
+    //     type Target<'__s> = <Inner as borrow::AsRefsMut>::Target<'__s> where Self: '__s;
     //     #[track_caller]
     //     #[inline(always)]
     //     fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
-    //         let usage_tracker = borrow::UsageTracker::new();
-    //         let struct_ref = CtxRef {
-    //             version: borrow::Field::new(
-    //                 "version",
-    //                 Some(borrow::Usage::Mut),
-    //                 &mut self.version,
-    //                 usage_tracker.clone()
-    //             ),
-    //             geometry: borrow::Field::new(
-    //                 "geometry",
-    //                 Some(borrow::Usage::Mut),
-    //                 &mut self.geometry,
-    //                 usage_tracker.clone()
-    //             ),
-    //             material: borrow::Field::new(
-    //                 "material",
-    //                 Some(borrow::Usage::Mut),
-    //                 &mut self.material,
-    //                 usage_tracker.clone()
-    //             ),
-    //             mesh: borrow::Field::new(
-    //                 "mesh",
-    //                 Some(borrow::Usage::Mut),
-    //                 &mut self.mesh,
-    //                 usage_tracker.clone()
-    //             ),
-    //             scene: borrow::Field::new(
-    //                 "scene",
-    //                 Some(borrow::Usage::Mut),
-    //                 &mut self.scene,
-    //                 usage_tracker.clone()
-    //             ),
-    //             marker: std::marker::PhantomData,
-    //             usage_tracker,
-    //         };
-    //         borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
-    //         struct_ref
+    //         borrow::AsRefsMut::as_refs_mut(&mut self.0)
     //     }
     // }
+    //
+    // #[macro_export]
+    // macro_rules! WorldMacro {
+    //     (@0 $m:tt $pfx:tt $track:tt [$s:ty] $($ts:tt)*) => {
+    //         $crate::world::Inner! {
+    //             @0 [$crate::world::Inner] $pfx $track [<$s as borrow::Transparent>::Inner] $($ts)*
+    //         }
+    //     };
+    // }
+    // pub use WorldMacro as World;
     // ```
-    out.push(quote! {
-        impl<#params> borrow::AsRefsMut for #ident<#params>
+    Ok(quote! {
+        impl<#params_decl> borrow::Transparent for #ident<#params>
         where #bounds {
-            type Target<'__s> =
-                borrow::RefWithFields<#ident<#params>, borrow::FieldsAsMut<'__s, #ident<#params>>>
-            where Self: '__s;
+            type Inner = #inner;
+        }
+
+        impl<#params_decl> borrow::AsRefsMut for #ident<#params>
+        where #bounds #(#as_refs_mut_bound,)* #inner: borrow::AsRefsMut {
+            type Target<'__s> = <#inner as borrow::AsRefsMut>::Target<'__s> where Self: '__s;
             #[track_caller]
             #[inline(always)]
             fn as_refs_mut<'__s>(&'__s mut self) -> Self::Target<'__s> {
-                let usage_tracker = borrow::UsageTracker::new();
-                let struct_ref = #ref_ident {
-                    #(
-                        #fields_ident: borrow::Field::new(
-                            stringify!(#fields_ident),
-                            Some(borrow::Usage::Mut),
-                            &mut self.#fields_ident,
-                            usage_tracker.clone(),
-                        ),
-                    )*
-                    marker: std::marker::PhantomData,
-                    usage_tracker
-                };
-                borrow::HasUsageTrackedFields::disable_field_usage_tracking(&struct_ref);
-                struct_ref
+                borrow::AsRefsMut::as_refs_mut(&mut self.#member)
             }
         }
-    });
-
-    let output = quote! {
-        #(#out)*
-    };
 
-    // println!("OUTPUT:\n{}", output);
-    output.into()
+        #export
+    })
 }
 
 // ======================
 // === partial! Macro ===
 // ======================
 
-#[derive(Debug)]
 enum Selector {
-    Ident { lifetime: Option<TokenStream>, is_mut: bool, ident: Ident },
-    Star { lifetime: Option<TokenStream>, is_mut: bool }
+    /// Field selected by name, e.g. `nodes`, or by position in tuple structs, e.g. `0`.
+    Field { lifetime: Option<TokenStream>, access: Access, field: syn::Member },
+    Star { lifetime: Option<TokenStream>, access: Access },
+    /// Group declared with `#[borrow(group(...))]`, e.g. `@topology`.
+    Group { lifetime: Option<TokenStream>, access: Access, group: Ident },
+    /// Fields and groups hidden again, e.g. `- cache` or `- <cache, @debug>`.
+    Exclude(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Ref,
+    Mut,
+    /// Set by the `upgradeable` keyword. Borrowed as shared, but can be upgraded to mutable.
+    Upgradeable,
+}
+
+impl Access {
+    fn parse(input: ParseStream) -> Self {
+        if input.parse::<Token![mut]>().is_ok() {
+            return Access::Mut;
+        }
+        let fork = input.fork();
+        let is_upgradeable = fork.parse::<Ident>().is_ok_and(|ident| ident == "upgradeable")
+            && (fork.peek(Ident) || fork.peek(syn::LitInt) || fork.peek(Token![*])
+                || fork.peek(Token![@]));
+        if is_upgradeable {
+            input.parse::<Ident>().ok();
+            Access::Upgradeable
+        } else {
+            Access::Ref
+        }
+    }
+
+    /// Tokens passed to the `borrow::field!` macro.
+    fn field_tokens(self, lifetime: &TokenStream) -> TokenStream {
+        match self {
+            Access::Ref => quote! { & #lifetime },
+            Access::Mut => quote! { & #lifetime mut },
+            Access::Upgradeable => quote! { upgradeable #lifetime },
+        }
+    }
 }
 
 enum Selectors {
     List(Vec<Selector>),
-    All
+    All,
+    /// No selectors, e.g. `p!(&Graph)`, borrowing the fields given in `#[borrow(default = ...)]`.
+    Default,
 }
 
 // #[derive(Debug)]
 struct MyInput {
     has_underscore: bool,
+    /// Set by `!&`. Forces tracking of structs with `#[borrow(track_by_default = false)]`.
+    has_bang: bool,
     has_amp: bool,
     lifetime: Option<TokenStream>,
     selectors: Selectors,
+    /// Selector spec declared with `borrow::spec!`, used instead of the selectors.
+    spec: Option<syn::Path>,
     target: Type,
 }
 
@@ -874,49 +3975,264 @@ fn parse_angled_list<T: Parse>(input: ParseStream) -> Vec<T> {
 
 impl Parse for Selector {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.parse::<Token![-]>().is_ok() {
+            return parse_excluded(input).map(Selector::Exclude);
+        }
         let lifetime = input.parse::<syn::Lifetime>().ok().map(|t| quote! { #t });
-        let is_mut = input.parse::<Token![mut]>().is_ok();
+        let access = Access::parse(input);
         if input.parse::<Token![*]>().is_ok() {
-            Ok(Selector::Star{ lifetime, is_mut })
+            Ok(Selector::Star{ lifetime, access })
+        } else if input.parse::<Token![@]>().is_ok() {
+            let group = input.parse()?;
+            Ok(Selector::Group{ lifetime, access, group })
         } else {
-            let ident: Ident = input.parse()?;
-            Ok(Selector::Ident{ lifetime, is_mut, ident })
+            let field: syn::Member = input.parse()?;
+            reject_range_selector(input)?;
+            Ok(Selector::Field{ lifetime, access, field })
+        }
+    }
+}
+
+/// Reports range selectors, e.g. `<mut nodes[0..n]>`. Ranges are runtime values, so they can not
+/// be a part of the selector types, and fields are split with `split_$field_at` instead.
+fn reject_range_selector(input: ParseStream) -> syn::Result<()> {
+    if input.peek(syn::token::Bracket) {
+        return Err(input.error(
+            "Range selectors, e.g. `nodes[0..n]`, are not supported, as ranges are runtime values. \
+            Select the whole field and split it with `split_$field_at` instead."
+        ));
+    }
+    Ok(())
+}
+
+/// Parses the fields and groups after `-`, either a single one, e.g. `cache`, or a list, e.g.
+/// `<cache, @debug>`. Accesses are not allowed, as the fields are not borrowed.
+fn parse_excluded(input: ParseStream) -> syn::Result<Vec<Selector>> {
+    let excluded = if input.parse::<Token![<]>().is_ok() {
+        let excluded = parse_angled_list::<Selector>(input);
+        input.parse::<Token![>]>()?;
+        excluded
+    } else {
+        vec![input.parse()?]
+    };
+    for selector in &excluded {
+        let is_plain = matches!(selector,
+            Selector::Field { lifetime: None, access: Access::Ref, .. } |
+            Selector::Group { lifetime: None, access: Access::Ref, .. }
+        );
+        if !is_plain {
+            return Err(input.error(
+                "Only fields and groups without accesses can be excluded, e.g. `- <cache, @debug>`."
+            ));
         }
     }
+    Ok(excluded)
 }
 
 impl Parse for MyInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let has_underscore = input.parse::<Token![_]>().is_ok();
+        let has_bang = !has_underscore && input.parse::<Token![!]>().is_ok();
         let has_amp = input.parse::<Token![&]>().is_ok();
 
         let lifetime = input.parse::<syn::Lifetime>().ok().map(|t| quote! { #t });
 
+        let mut spec = None;
         let selectors = if input.parse::<Token![mut]>().is_ok() {
             Selectors::All
         } else if input.parse::<Token![<]>().is_ok() {
             let selectors = parse_angled_list::<Selector>(input);
+            reject_range_selector(input)?;
             input.parse::<Token![>]>()?;
             Selectors::List(selectors)
+        } else if input.peek(syn::token::Brace) {
+            // Alternative spelling, `p!(&{mut a, b} Ty)`, which is not mistaken for generic
+            // arguments by rustfmt and syntax highlighters.
+            let content;
+            syn::braced!(content in input);
+            let mut selectors = vec![];
+            while !content.is_empty() {
+                selectors.push(content.parse::<Selector>()?);
+                // Exclusions may follow selectors without a comma, e.g. `{mut * - cache}`.
+                if !content.is_empty() && !content.peek(Token![-]) {
+                    content.parse::<Token![,]>()?;
+                }
+            }
+            Selectors::List(selectors)
+        } else if input.parse::<Token![_]>().is_ok() {
+            // Explicit spelling of the default selectors, `p!(&_ Graph)`.
+            Selectors::Default
         } else {
-            Selectors::List(vec![])
+            spec = parse_spec(input);
+            Selectors::Default
         };
 
         let target: Type = input.parse()?;
 
         Ok(MyInput {
             has_underscore,
+            has_bang,
             has_amp,
             lifetime,
             selectors,
+            spec,
             target,
         })
     }
 }
 
+/// Input of `p!(move expr)`, converting an owned partial borrow into another one. Unlike the
+/// other forms, the target is an expression, e.g. `p!(move graph.as_refs_mut())`.
+struct MoveInput {
+    expr: syn::Expr,
+}
+
+impl Parse for MoveInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![move]>()?;
+        let expr = input.parse()?;
+        Ok(MoveInput { expr })
+    }
+}
+
+/// Selector which does not change the borrowed shape, reported by `p!`. Only the selectors of
+/// fields are checked, as the fields of groups and `*` are not known to `p!`.
+enum Redundancy {
+    /// The field is selected again by a following selector, which overrides it, e.g. the first
+    /// selector of `<nodes, mut nodes>`.
+    Duplicate,
+    /// The field is selected before `*`, which overrides it, e.g. `<nodes, mut *>`.
+    Overridden,
+    /// The field is selected after `*` with the same access, e.g. `<mut *, mut nodes>`.
+    SameAsStar,
+}
+
+impl Redundancy {
+    /// Deprecated alias defined in the `borrow` crate, used to report the redundancy as a warning.
+    fn alias(&self) -> &'static str {
+        match self {
+            Redundancy::Duplicate => "DuplicateSelector",
+            Redundancy::Overridden => "OverriddenSelector",
+            Redundancy::SameAsStar => "StarSelector",
+        }
+    }
+
+    fn message(&self, field: &syn::Member) -> String {
+        let field = tokens_string(&quote! { #field });
+        match self {
+            Redundancy::Duplicate => format!(
+                "Selector of field '{field}' is overridden by a following selector of the same \
+                field."
+            ),
+            Redundancy::Overridden => format!(
+                "Selector of field '{field}' is overridden by the following `*` selector."
+            ),
+            Redundancy::SameAsStar => format!(
+                "Field '{field}' is already selected by the preceding `*` selector with the same \
+                access."
+            ),
+        }
+    }
+}
+
+/// Finds field selectors which do not change the borrowed shape. Selectors are applied from left
+/// to right, so a field excluded with `-` can be selected again, and of the selectors of the same
+/// field, all but the last one are redundant.
+fn redundant_selectors<'t>(
+    selectors: &'t [Selector],
+    default_lifetime: &TokenStream,
+) -> Vec<(&'t syn::Member, Redundancy)> {
+    let resolve = |lifetime: &Option<TokenStream>| {
+        lifetime.as_ref().unwrap_or(default_lifetime).to_string()
+    };
+    let mut out = vec![];
+    let mut listed: Vec<&syn::Member> = vec![];
+    let mut excluded: Vec<&syn::Member> = vec![];
+    let mut star = None;
+    for selector in selectors {
+        match selector {
+            Selector::Field { lifetime, access, field } => {
+                if let Some(index) = listed.iter().position(|listed_field| *listed_field == field) {
+                    out.push((listed.remove(index), Redundancy::Duplicate));
+                } else if star == Some((*access, resolve(lifetime))) && !excluded.contains(&field) {
+                    out.push((field, Redundancy::SameAsStar));
+                }
+                listed.push(field);
+            }
+            Selector::Star { lifetime, access } => {
+                out.extend(listed.drain(..).map(|field| (field, Redundancy::Overridden)));
+                excluded.clear();
+                star = Some((*access, resolve(lifetime)));
+            }
+            Selector::Exclude(selectors) => {
+                for selector in selectors {
+                    if let Selector::Field { field, .. } = selector {
+                        listed.retain(|listed_field| *listed_field != field);
+                        excluded.push(field);
+                    }
+                }
+            }
+            Selector::Group { .. } => {}
+        }
+    }
+    out
+}
+
+/// Types of the standard library most often passed to `p!` by mistake.
+const STD_TYPES: &[&str] = &[
+    "String", "Vec", "VecDeque", "HashMap", "HashSet", "BTreeMap", "BTreeSet", "Option", "Result",
+    "Box", "Rc", "Arc",
+];
+
+/// Error explaining that the target of `p!` has to derive `borrow::Partial`, reported for targets
+/// which can not derive it, e.g. references, tuples, and common standard library types. For other
+/// types, the compiler reports that the struct macro generated by the derive can not be found.
+/// `Self` is reported as well, unless it is resolved by `#[borrow::partial_impl]`.
+fn not_derived_error(target: &Type) -> Option<syn::Error> {
+    if matches!(target, Type::Path(type_path) if type_path.path.is_ident("Self")) {
+        return Some(syn::Error::new_spanned(target, "p! can not resolve `Self` to the struct on its \
+            own. Mark the impl block with #[borrow::partial_impl], or spell out the struct with \
+            its generic arguments instead, e.g. `p!(&<mut nodes> Graph<'a, T>)`."));
+    }
+    let help = "The target must be a struct marked with #[derive(borrow::Partial)].";
+    let target_str = tokens_string(&quote! { #target });
+    let reason = match target {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let path = &type_path.path;
+            let is_std_path = path.segments.len() == 1 || path.segments.first()
+                .is_some_and(|s| s.ident == "std" || s.ident == "core" || s.ident == "alloc");
+            let ident = &path.segments.last()?.ident;
+            let is_std = is_std_path && STD_TYPES.iter().any(|t| ident == t);
+            is_std.then(|| format!("`{ident}` is a standard library type"))?
+        }
+        _ => format!("`{target_str}` is not a path to a struct"),
+    };
+    Some(syn::Error::new_spanned(target, format!(
+        "p! can only borrow structs deriving borrow::Partial, but {reason}. {help}"
+    )))
+}
+
+/// Parses the spec path in `p!(&SPEC Target)`. The spec is followed by the target type, while a
+/// target type without a spec is not followed by a path.
+fn parse_spec(input: ParseStream) -> Option<syn::Path> {
+    let fork = input.fork();
+    fork.call(syn::Path::parse_mod_style).ok()?;
+    let followed_by_target = fork.peek(Ident::peek_any) || fork.peek(Token![::]);
+    followed_by_target.then(|| input.call(syn::Path::parse_mod_style).ok()).flatten()
+}
+
 #[allow(clippy::cognitive_complexity)]
 #[proc_macro]
 pub fn partial(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let first_token = input_raw.clone().into_iter().next();
+    let is_move = matches!(first_token, Some(proc_macro::TokenTree::Ident(i)) if i.to_string() == "move");
+    if is_move {
+        let input = parse_macro_input!(input_raw as MoveInput);
+        let expr = &input.expr;
+        return quote! {
+            (#expr).into_partial_borrow()
+        }.into();
+    }
     let input = parse_macro_input!(input_raw as MyInput);
 
     let target_ident = match &input.target {
@@ -928,53 +4244,91 @@ pub fn partial(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
         _ => None,
     };
 
-    let out = if let Some(target_ident) = target_ident {
+    let out = if let Some(spec) = &input.spec {
+        let underscore = input.has_underscore.then(|| quote! { _ });
+        let bang = input.has_bang.then(|| quote! { ! });
+        let amp = input.has_amp.then(|| quote! { & });
+        let lifetime = &input.lifetime;
+        let target = &input.target;
+        quote! {
+            #spec!{[#underscore #bang #amp #lifetime] [#target]}
+        }
+    } else if let Some(target_ident) = target_ident {
         quote! {
             &mut #target_ident.partial_borrow()
         }
     } else {
-        let target_ident = match &input.target {
-            Type::Path(type_path) if type_path.path.segments.len() == 1 => {
-                &type_path.path.segments[0].ident
+        // The struct macro is re-exported next to the struct, so its path is the path of the
+        // struct without generic arguments, e.g. `$crate::graph::Graph` for
+        // `$crate::graph::Graph<T>`.
+        if let Some(error) = not_derived_error(&input.target) {
+            return error.to_compile_error().into();
+        }
+        let target_macro = match &input.target {
+            Type::Path(type_path) => {
+                let mut path = type_path.path.clone();
+                for segment in &mut path.segments {
+                    segment.arguments = syn::PathArguments::None;
+                }
+                path
             }
-            _ => panic!()
+            target => return syn::Error::new_spanned(target, "Expected a path to a struct \
+                deriving borrow::Partial.").to_compile_error().into(),
         };
 
         let target = &input.target;
         let default_lifetime = input.lifetime.unwrap_or_else(|| quote!{ '_ });
         let mut out = quote! { };
         match &input.selectors {
-            Selectors::All => out = quote! {
-                borrow::FieldsAsMut <#default_lifetime, #target>
-            },
+            // `p!(&mut Graph)` is the same as `p!(&<mut *> Graph)`.
+            Selectors::All => {
+                let field = Access::Mut.field_tokens(&default_lifetime);
+                out = quote! { * [#field] };
+            }
+            // `p!(&Graph)` borrows the `__default__` group, see `#[borrow(default = ...)]`.
+            Selectors::Default => {
+                let access = Access::Ref.field_tokens(&default_lifetime);
+                out = quote! { @ __default__ [#access] };
+            }
             Selectors::List(selectors) => {
                 for selector in selectors {
                     out = match selector {
-                        Selector::Ident { lifetime, is_mut, ident } => {
+                        Selector::Field { lifetime, access, field } => {
+                            let lt = lifetime.as_ref().unwrap_or(&default_lifetime);
+                            let access = access.field_tokens(lt);
+                            quote! { #out #field [#access] }
+                        }
+                        Selector::Star { lifetime, access } => {
                             let lt = lifetime.as_ref().unwrap_or(&default_lifetime);
-                            if *is_mut {
-                                quote! { #out #ident [& #lt mut]   }
-                            } else {
-                                quote! { #out #ident [& #lt]   }
-                            }
+                            let field = access.field_tokens(lt);
+                            quote! { * [#field] }
                         }
-                        Selector::Star { lifetime, is_mut } => {
+                        Selector::Group { lifetime, access, group } => {
                             let lt = lifetime.as_ref().unwrap_or(&default_lifetime);
-                            if *is_mut {
-                                quote! { * [& #lt mut]    }
-                            } else {
-                                quote! { * [& #lt]   }
-                            }
+                            let access = access.field_tokens(lt);
+                            quote! { #out @ #group [#access] }
+                        }
+                        // Excluded fields are hidden by the empty access, e.g. `cache []`.
+                        Selector::Exclude(excluded) => {
+                            let excluded = excluded.iter().map(|selector| match selector {
+                                Selector::Group { group, .. } => quote! { @ #group [] },
+                                Selector::Field { field, .. } => quote! { #field [] },
+                                _ => quote! {},
+                            });
+                            quote! { #out #(#excluded)* }
                         }
                     }
                 }
             }
         }
 
+        // Without a prefix, the struct's `track_by_default` setting is used.
         let track = if input.has_underscore {
-            quote! { borrow::False }
+            quote! { _ }
+        } else if input.has_bang {
+            quote! { ! }
         } else {
-            quote! { borrow::True }
+            quote! {}
         };
         let pfx = if input.has_amp {
             quote! { [& #default_lifetime mut] }
@@ -983,11 +4337,464 @@ pub fn partial(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
         };
 
         out = quote! {
-            #target_ident!{@0 #pfx [#track] [#target] #out}
+            #target_macro!{@0 [#target_macro] #pfx [#track] [#target] #out}
         };
+
+        // Redundant selectors are reported by the deprecated aliases, pointing to the selector. The
+        // aliases are referred to by the struct macro, which knows the path of the `borrow` crate.
+        if let Selectors::List(selectors) = &input.selectors {
+            let redundant = redundant_selectors(selectors, &default_lifetime);
+            if cfg!(feature = "deny_redundant_selectors") {
+                let errors = redundant.iter().map(|(field, redundancy)| {
+                    syn::Error::new_spanned(field, redundancy.message(field))
+                });
+                if let Some(error) = combine_errors(errors) {
+                    return error.to_compile_error().into();
+                }
+            }
+            for (field, redundancy) in redundant {
+                let alias = Ident::new(redundancy.alias(), field.span());
+                out = quote! { #target_macro!{@redundant #alias #out} };
+            }
+        }
+        if let Some(path) = std::env::var_os("BORROW_SELECTOR_CENSUS") {
+            let path = std::path::Path::new(&path);
+            if let Err(error) = record_selectors(path, &target_macro, target, &input.selectors) {
+                return error.to_compile_error().into();
+            }
+        }
         out
     };
 
     // println!("{}", out);
     out.into()
 }
+
+// ===================
+// === spec! Macro ===
+// ===================
+
+struct SpecInput {
+    vis: syn::Visibility,
+    ident: Ident,
+    selectors: TokenStream,
+}
+
+impl Parse for SpecInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let selectors = input.parse()?;
+        Ok(SpecInput { vis, ident, selectors })
+    }
+}
+
+/// Generates a macro substituting the selectors in `p!(&SPEC Target)`. Called by `borrow::spec!`
+/// with the `$crate` path of `borrow`, so the generated macro works in crates which do not depend
+/// on `borrow` directly or rename it. For `spec!(pub RENDER = <mut geometry, mesh>)`:
+///
+/// ```text
+/// #[doc(hidden)]
+/// #[macro_export]
+/// macro_rules! RENDER {
+///     ([$($pfx:tt)*] [$($target:tt)*]) => {
+///         $crate::partial!($($pfx)* <mut geometry, mesh> $($target)*)
+///     };
+/// }
+/// ```
+///
+/// Specs without visibility are usable in the current crate only.
+#[doc(hidden)]
+#[proc_macro]
+pub fn spec_with_crate(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = proc_macro2::TokenStream::from(input_raw).into_iter().collect_vec();
+    let (crate_path, input) = match input.split_first() {
+        Some((proc_macro2::TokenTree::Group(group), rest))
+            if group.delimiter() == proc_macro2::Delimiter::Bracket =>
+            (group.stream(), rest.iter().cloned().collect::<TokenStream>()),
+        _ => return quote! { compile_error!("Use `borrow::spec!` to declare specs."); }.into(),
+    };
+    let SpecInput { vis, ident, selectors } = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let rule = quote! {
+        ([$($pfx:tt)*] [$($target:tt)*]) => {
+            #crate_path::partial!($($pfx)* #selectors $($target)*)
+        };
+    };
+    let out = match vis {
+        syn::Visibility::Inherited => quote! {
+            macro_rules! #ident { #rule }
+            #[allow(unused_imports)]
+            pub(crate) use #ident;
+        },
+        _ => quote! {
+            #[doc(hidden)]
+            #[macro_export]
+            macro_rules! #ident { #rule }
+        },
+    };
+    out.into()
+}
+
+// ====================
+// === shape! Macro ===
+// ====================
+
+/// Generates a marker type implementing `borrow::Shape`. For
+/// `shape!(pub Render = <mut geometry, mesh> Ctx)`:
+///
+/// ```text
+/// #[derive(Clone, Copy, Debug, Default)]
+/// pub struct Render;
+/// impl borrow::Shape<Ctx> for Render {
+///     type Ref<'__a__> = borrow::partial!('__a__ <mut geometry, mesh> Ctx) where Ctx: '__a__;
+/// }
+/// ```
+#[proc_macro]
+pub fn shape(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let SpecInput { vis, ident, selectors } = parse_macro_input!(input_raw as SpecInput);
+    let target = match syn::parse2::<MyInput>(selectors.clone()) {
+        Ok(input) => input.target,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let out = quote! {
+        #[derive(Clone, Copy, Debug, Default)]
+        #vis struct #ident;
+        impl borrow::Shape<#target> for #ident {
+            type Ref<'__a__> = borrow::partial!('__a__ #selectors) where #target: '__a__;
+        }
+    };
+    out.into()
+}
+
+// ====================
+// === bench! Macro ===
+// ====================
+
+#[cfg(feature = "bench")]
+struct BenchInput {
+    vis: syn::Visibility,
+    ident: Ident,
+    selectors: Vec<Selector>,
+    target: Type,
+    state: syn::Expr,
+    view: syn::Expr,
+    params: syn::Expr,
+}
+
+#[cfg(feature = "bench")]
+impl Parse for BenchInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        input.parse::<Token![<]>()?;
+        let selectors = parse_angled_list::<Selector>(input);
+        input.parse::<Token![>]>()?;
+        let target = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let (mut state, mut view, mut params) = (None, None, None);
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            content.parse::<Token![:]>()?;
+            let value = Some(content.parse::<syn::Expr>()?);
+            match key.to_string().as_str() {
+                "state" => state = value,
+                "view" => view = value,
+                "params" => params = value,
+                _ => return Err(syn::Error::new(key.span(), "expected `state`, `view`, or `params`")),
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        let missing = |key: &str| syn::Error::new(ident.span(), format!("missing `{key}`"));
+        let state = state.ok_or_else(|| missing("state"))?;
+        let view = view.ok_or_else(|| missing("view"))?;
+        let params = params.ok_or_else(|| missing("params"))?;
+        Ok(BenchInput { vis, ident, selectors, target, state, view, params })
+    }
+}
+
+/// Generates a criterion benchmark comparing a function taking a partial borrow with its
+/// equivalent taking the borrowed fields as separate parameters. For
+/// `bench!(pub bench_connect = <nodes, mut edges> Graph { state: ..., view: ..., params: ... })`:
+///
+/// ```text
+/// pub fn bench_connect(c: &mut criterion::Criterion) {
+///     use borrow::traits::*;
+///     let mut group = c.benchmark_group("bench_connect");
+///     group.bench_function("view", |b| {
+///         let mut state: Graph = ...;
+///         b.iter(|| std::hint::black_box((view)(borrow::partial!(&mut state))));
+///     });
+///     group.bench_function("params", |b| {
+///         let mut state: Graph = ...;
+///         b.iter(|| std::hint::black_box((params)(&state.nodes, &mut state.edges)));
+///     });
+///     group.finish();
+/// }
+/// ```
+#[cfg(feature = "bench")]
+#[proc_macro]
+pub fn bench(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let BenchInput { vis, ident, selectors, target, state, view, params } =
+        parse_macro_input!(input_raw as BenchInput);
+    let mut errors = vec![];
+    let args = selectors.iter().filter_map(|selector| match selector {
+        Selector::Field { access: Access::Ref, field, .. } => Some(quote! { &state.#field }),
+        Selector::Field { access: Access::Mut, field, .. } => Some(quote! { &mut state.#field }),
+        Selector::Field { access: Access::Upgradeable, field, .. } => {
+            let field_str = tokens_string(&quote! {#field});
+            let message = format!("bench! does not support upgradeable fields, found '{field_str}'.");
+            errors.push(syn::Error::new_spanned(field, message));
+            None
+        }
+        Selector::Star { .. } | Selector::Group { .. } | Selector::Exclude(_) => {
+            let message = "bench! requires the borrowed fields to be listed.";
+            errors.push(syn::Error::new(ident.span(), message));
+            None
+        }
+    }).collect_vec();
+    if let Some(error) = combine_errors(errors) {
+        return error.to_compile_error().into();
+    }
+    let name = ident.to_string();
+    let out = quote! {
+        #vis fn #ident(c: &mut criterion::Criterion) {
+            use borrow::traits::*;
+            let mut group = c.benchmark_group(#name);
+            group.bench_function("view", |b| {
+                let mut state: #target = #state;
+                b.iter(|| std::hint::black_box((#view)(borrow::partial!(&mut state))));
+            });
+            group.bench_function("params", |b| {
+                let mut state: #target = #state;
+                b.iter(|| std::hint::black_box((#params)(#(#args),*)));
+            });
+            group.finish();
+        }
+    };
+    out.into()
+}
+
+// =============================
+// === expansion_size! Macro ===
+// =============================
+
+/// Number of tokens of the token stream, counting groups and their contents.
+#[cfg(feature = "bench")]
+fn token_count(tokens: TokenStream) -> usize {
+    tokens.into_iter().map(|token| match token {
+        proc_macro2::TokenTree::Group(group) => 1 + token_count(group.stream()),
+        _ => 1,
+    }).sum()
+}
+
+/// Measures the code generated by `#[derive(borrow::Partial)]` for the given struct, which is what
+/// IDEs have to expand for every derive. Expands to `(total, struct_macro)`, the number of tokens
+/// of the whole output and of the rules of the struct macro, expanded by every `p!` of the struct.
+/// Used by the `expansion_size` test to catch regressions of the output size.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+#[proc_macro]
+pub fn expansion_size(input_raw: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let output = TokenStream::from(partial_borrow_impl(input_raw));
+    let total = token_count(output.clone());
+    let tokens = output.into_iter().collect_vec();
+    let struct_macro = tokens.windows(4).find_map(|window| match window {
+        [proc_macro2::TokenTree::Ident(keyword), _, _, proc_macro2::TokenTree::Group(rules)]
+            if keyword == "macro_rules" => Some(token_count(rules.stream())),
+        _ => None,
+    }).unwrap_or_default();
+    quote! { (#total, #struct_macro) }.into()
+}
+
+// ====================================
+// === partial_impl Attribute Macro ===
+// ====================================
+
+/// Error reported by attribute macros without arguments, pointing to the given arguments.
+fn no_arguments_error(attr: proc_macro::TokenStream, name: &str) -> Option<syn::Error> {
+    let attr = TokenStream::from(attr);
+    (!attr.is_empty()).then(|| syn::Error::new_spanned(attr, format!("{name} does not accept arguments.")))
+}
+
+/// Replaces every `'_` lifetime in the tokens with a fresh named lifetime, e.g. `'__l0__`.
+fn name_elided_lifetimes(tokens: TokenStream, lifetimes: &mut Vec<syn::Lifetime>) -> TokenStream {
+    let mut out = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                let is_elided = matches!(
+                    tokens.peek(), Some(proc_macro2::TokenTree::Ident(ident)) if ident == "_"
+                );
+                if is_elided {
+                    let ident = tokens.next().unwrap();
+                    let name = format!("'__l{}__", lifetimes.len());
+                    let lifetime = syn::Lifetime::new(&name, ident.span());
+                    out.extend(quote! {#lifetime});
+                    lifetimes.push(lifetime);
+                } else {
+                    out.push(proc_macro2::TokenTree::Punct(punct));
+                }
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                let stream = name_elided_lifetimes(group.stream(), lifetimes);
+                let mut new_group = proc_macro2::Group::new(group.delimiter(), stream);
+                new_group.set_span(group.span());
+                out.push(proc_macro2::TokenTree::Group(new_group));
+            }
+            token => out.push(token),
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// Replaces the `Self` target of the `p!` and `partial!` invocations in the tokens with the given
+/// type. Only targets are replaced, i.e. `Self` being the last token of the macro input, so
+/// expressions such as `p!(move Self::view(x))` are kept intact.
+fn resolve_self_target(tokens: TokenStream, self_ty: &TokenStream) -> TokenStream {
+    let tokens = tokens.into_iter().collect_vec();
+    let mut out = TokenStream::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let proc_macro2::TokenTree::Group(group) = token else {
+            out.extend([token.clone()]);
+            continue;
+        };
+        let is_partial_input = i >= 2
+            && matches!(&tokens[i - 1], proc_macro2::TokenTree::Punct(p) if p.as_char() == '!')
+            && matches!(&tokens[i - 2], proc_macro2::TokenTree::Ident(m) if m == "p" || m == "partial");
+        let mut stream = group.stream().into_iter().collect_vec();
+        let has_self_target = matches!(stream.last(), Some(proc_macro2::TokenTree::Ident(t)) if t == "Self");
+        let stream = if is_partial_input && has_self_target {
+            stream.pop();
+            stream.into_iter().chain(self_ty.clone()).collect()
+        } else {
+            resolve_self_target(group.stream(), self_ty)
+        };
+        let mut new_group = proc_macro2::Group::new(group.delimiter(), stream);
+        new_group.set_span(group.span());
+        out.extend([proc_macro2::TokenTree::Group(new_group)]);
+    }
+    out
+}
+
+/// Allows `'_` in the target type of a partial borrow used as the self type of an impl block,
+/// e.g. `impl p!(<mut nodes> Graph<'_>)`. The `p!` macro repeats the target type for every
+/// field, and every `'_` in an impl header is a distinct lifetime, so the fields would refer to
+/// the struct with different lifetimes. Generates:
+///
+/// ```text
+/// impl<'__l0__> p!(<mut nodes> Graph<'__l0__>) { ... }
+/// ```
+///
+/// In impl blocks of structs, e.g. `impl<T> Graph<'_, T>`, it resolves the `Self` target of
+/// `p!` in the block to the self type, as `p!` can not resolve it on its own. For
+/// `fn nodes(&mut self) -> p!(&<mut nodes> Self)`, generates:
+///
+/// ```text
+/// impl<'__l0__, T> Graph<'__l0__, T> {
+///     fn nodes(&mut self) -> p!(&<mut nodes> Graph<'__l0__, T>) { ... }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn partial_impl(
+    attr: proc_macro::TokenStream,
+    input_raw: proc_macro::TokenStream
+) -> proc_macro::TokenStream {
+    if let Some(error) = no_arguments_error(attr, "#[borrow::partial_impl]") {
+        return error.to_compile_error().into();
+    }
+    let mut item = parse_macro_input!(input_raw as syn::ItemImpl);
+    let mut lifetimes = vec![];
+    let self_ty = match &mut *item.self_ty {
+        Type::Macro(self_ty) => {
+            let tokens = std::mem::take(&mut self_ty.mac.tokens);
+            self_ty.mac.tokens = name_elided_lifetimes(tokens, &mut lifetimes);
+            None
+        }
+        self_ty => {
+            let tokens = name_elided_lifetimes(quote! { #self_ty }, &mut lifetimes);
+            *self_ty = match syn::parse2(tokens.clone()) {
+                Ok(self_ty) => self_ty,
+                Err(error) => return error.to_compile_error().into(),
+            };
+            Some(tokens)
+        }
+    };
+    for (i, lifetime) in lifetimes.into_iter().enumerate() {
+        let param = syn::GenericParam::Lifetime(syn::LifetimeParam::new(lifetime));
+        item.generics.params.insert(i, param);
+    }
+    match self_ty {
+        Some(self_ty) => resolve_self_target(quote! { #item }, &self_ty).into(),
+        None => quote! { #item }.into(),
+    }
+}
+
+// ==================================
+// === deny_over_borrow Attribute ===
+// ==================================
+
+/// Makes the usage tracker panic instead of printing a warning when a partial borrow parameter of
+/// the function borrows fields it does not use. Parameters are recognized by their type spelled
+/// with `p!` or `partial!`. For `fn add_node(graph: p!(&<mut nodes> Graph))`, generates:
+///
+/// ```text
+/// fn add_node(graph: p!(&<mut nodes> Graph)) {
+///     {
+///         use borrow::HasUsageTrackedFields as _;
+///         graph.deny_over_borrow();
+///     }
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn deny_over_borrow(
+    attr: proc_macro::TokenStream,
+    input_raw: proc_macro::TokenStream
+) -> proc_macro::TokenStream {
+    let mut errors = vec![];
+    errors.extend(no_arguments_error(attr, "#[borrow::deny_over_borrow]"));
+    let mut item = parse_macro_input!(input_raw as syn::ItemFn);
+    let partial_params = item.sig.inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(pat_type) => Some(pat_type),
+        syn::FnArg::Receiver(_) => None,
+    }).filter(|pat_type| match &*pat_type.ty {
+        Type::Macro(ty) => ty.mac.path.segments.last()
+            .is_some_and(|segment| segment.ident == "p" || segment.ident == "partial"),
+        _ => false,
+    }).collect_vec();
+    if partial_params.is_empty() {
+        errors.push(syn::Error::new_spanned(&item.sig, "#[borrow::deny_over_borrow] expects a \
+            function with partial borrow parameters, e.g. \
+            `fn add_node(graph: p!(&<mut nodes> Graph))`."));
+    }
+    let params = partial_params.iter().filter_map(|pat_type| match &*pat_type.pat {
+        syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+        pat => {
+            errors.push(syn::Error::new_spanned(pat, format!(
+                "#[borrow::deny_over_borrow] requires partial borrow parameters to be bound to \
+                identifiers, found '{}'.", tokens_string(&quote! { #pat })
+            )));
+            None
+        }
+    }).collect_vec();
+    if let Some(error) = combine_errors(errors) {
+        return error.to_compile_error().into();
+    }
+    let stmt: syn::Stmt = syn::parse_quote! {
+        {
+            use borrow::HasUsageTrackedFields as _;
+            #(#params.deny_over_borrow();)*
+        }
+    };
+    item.block.stmts.insert(0, stmt);
+    quote! { #item }.into()
+}
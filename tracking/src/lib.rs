@@ -0,0 +1,253 @@
+//! Usage tracking hooks of the [`borrow`](https://docs.rs/borrow) crate.
+//!
+//! When a partial borrow is dropped, the usage tracker of the `borrow` crate reports which of its
+//! fields were requested and how they were used to the [`Tracker`] installed with
+//! [`set_tracker`]. The default one, [`WarningTracker`], prints a warning for every borrow
+//! requesting fields it does not use. Custom trackers can forward the reports elsewhere, e.g. to
+//! `tracing` spans or metrics:
+//!
+//! ```
+//! use borrow_tracking::BorrowReport;
+//! use std::sync::atomic::AtomicUsize;
+//! use std::sync::atomic::Ordering;
+//!
+//! struct OverBorrowCounter {
+//!     count: AtomicUsize,
+//! }
+//!
+//! impl borrow_tracking::Tracker for OverBorrowCounter {
+//!     fn borrow_dropped(&self, report: &BorrowReport) {
+//!         if report.is_over_borrow() {
+//!             self.count.fetch_add(1, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//!
+//! static COUNTER: OverBorrowCounter = OverBorrowCounter { count: AtomicUsize::new(0) };
+//!
+//! fn main() {
+//!     borrow_tracking::set_tracker(&COUNTER);
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::panic::Location;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+// ==============
+// === Access ===
+// ==============
+
+/// Access to a field requested by a partial borrow, or needed by the code using it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Access {
+    Ref,
+    Mut,
+}
+
+// ===================
+// === FieldReport ===
+// ===================
+
+/// Usage of a single field of a dropped partial borrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldReport {
+    pub label: &'static str,
+    /// Access requested by the borrow, or [`None`] if the field was not borrowed.
+    pub requested: Option<Access>,
+    /// The widest access needed by the code using the borrow, or [`None`] if the field was not
+    /// used.
+    pub used: Option<Access>,
+}
+
+impl FieldReport {
+    /// Checks whether the field was requested with a wider access than it was used with.
+    pub fn is_over_borrowed(&self) -> bool {
+        self.requested > self.used
+    }
+}
+
+// ====================
+// === BorrowReport ===
+// ====================
+
+/// Usage of the fields of a dropped partial borrow, passed to [`Tracker::borrow_dropped`].
+#[derive(Clone, Copy, Debug)]
+pub struct BorrowReport<'a> {
+    /// Callsite creating the borrow.
+    pub location: &'static Location<'static>,
+    /// Type name of the borrowed struct, set for generic structs only.
+    pub struct_name: Option<&'static str>,
+    /// The tracked fields, in the order they are listed in warnings.
+    pub fields: &'a [FieldReport],
+}
+
+#[cfg(not(feature = "wasm"))]
+macro_rules! warning_body {
+    ($s:ident, $($ts:tt)*) => {
+        $s.push_str("\n    ");
+        $s.push_str(&format!($($ts)*));
+    };
+}
+
+#[cfg(feature = "wasm")]
+macro_rules! warning_body {
+    ($s:ident, $($ts:tt)*) => {
+        $s.push_str("\n");
+        $s.push_str(&format!($($ts)*));
+    };
+}
+
+impl BorrowReport<'_> {
+    /// Fields borrowed, but not used.
+    pub fn not_used(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.fields.iter().filter(|f| f.is_over_borrowed() && f.used.is_none()).map(|f| f.label)
+    }
+
+    /// Fields borrowed as mutable, but used as shared.
+    pub fn used_as_ref(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.fields.iter().filter(|f| f.is_over_borrowed() && f.used.is_some()).map(|f| f.label)
+    }
+
+    /// Checks whether the borrow requested fields it did not need. Borrows which were not used at
+    /// all are not reported, as they are probably created in conditional code, and otherwise
+    /// Clippy reports them as unused variables.
+    pub fn is_over_borrow(&self) -> bool {
+        let over_borrowed = self.fields.iter().any(FieldReport::is_over_borrowed);
+        let used = self.fields.iter().any(|f| f.used.is_some());
+        over_borrowed && used
+    }
+
+    /// The callsite and the borrowed struct, e.g. `[src/main.rs:10] (Storage<String>)`.
+    pub fn header(&self) -> String {
+        let loc = format!("{}:{}", self.location.file(), self.location.line());
+        match self.struct_name {
+            Some(name) => format!("[{loc}] ({})", short_type_name(name)),
+            None => format!("[{loc}]"),
+        }
+    }
+
+    /// Description of the over-borrowed fields and of the selector fixing them, printed by
+    /// [`WarningTracker`] after the [header](Self::header). Empty if the borrow is not an
+    /// over-borrow.
+    pub fn message(&self) -> String {
+        let mut msg = String::new();
+        if !self.is_over_borrow() {
+            return msg;
+        }
+        let not_used = self.not_used().collect::<Vec<_>>();
+        if !not_used.is_empty() {
+            warning_body!(msg, "Borrowed but not used: {}.", not_used.join(", "));
+        }
+        let used_as_ref = self.used_as_ref().collect::<Vec<_>>();
+        if !used_as_ref.is_empty() {
+            warning_body!(msg, "Borrowed as mut but used as ref: {}.", used_as_ref.join(", "));
+        }
+        let required = self.fields.iter().filter_map(|f| {
+            f.used.map(|usage| match usage {
+                Access::Ref => f.label.to_string(),
+                Access::Mut => format!("mut {}", f.label),
+            })
+        }).collect::<Vec<_>>();
+        warning_body!(msg, "To fix the issue, use: &<{}>.", required.join(", "));
+        msg
+    }
+}
+
+/// Removes module paths from the type name, e.g. `crate::Storage<alloc::string::String>` becomes
+/// `Storage<String>`.
+fn short_type_name(name: &str) -> String {
+    let mut out = String::new();
+    let mut segment_start = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            out.truncate(segment_start);
+        } else {
+            out.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                segment_start = out.len();
+            }
+        }
+    }
+    out
+}
+
+// ===============
+// === Tracker ===
+// ===============
+
+/// Receiver of the usage reports of dropped partial borrows. See the crate docs for an example.
+pub trait Tracker: Sync {
+    /// Called when a partial borrow is dropped, unless the borrow panics with the report because
+    /// it was passed to a function marked with `#[borrow::deny_over_borrow]`.
+    fn borrow_dropped(&self, report: &BorrowReport);
+}
+
+static TRACKER: RwLock<&'static dyn Tracker> = RwLock::new(&WarningTracker);
+
+/// Replaces the tracker receiving the usage reports. [`WarningTracker`] is used by default.
+pub fn set_tracker(tracker: &'static dyn Tracker) {
+    *TRACKER.write().unwrap_or_else(PoisonError::into_inner) = tracker;
+}
+
+/// The tracker receiving the usage reports. See [`set_tracker`].
+pub fn tracker() -> &'static dyn Tracker {
+    *TRACKER.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+// ======================
+// === WarningTracker ===
+// ======================
+
+/// The default tracker, printing a warning for every over-borrow. Warnings are printed to stderr,
+/// or to the browser console with the `wasm` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WarningTracker;
+
+impl Tracker for WarningTracker {
+    fn borrow_dropped(&self, report: &BorrowReport) {
+        if report.is_over_borrow() {
+            warning(&format!("Warning {}:{}", report.header(), report.message()));
+        }
+    }
+}
+
+// ===============
+// === Logging ===
+// ===============
+
+fn warning(msg: &str) {
+    if inc_and_check_warning_count() {
+        warning_no_count_check(msg)
+    }
+}
+
+fn warning_no_count_check(msg: &str) {
+    #[cfg(feature = "wasm")]
+    web_sys::console::warn_1(&msg.into());
+    #[cfg(not(feature = "wasm"))]
+    eprintln!("{msg}");
+}
+
+/// We don't want to flood users with warnings, especially in interactive apps, where warnings can
+/// be emitted per frame.
+const MAX_WARNING_COUNT: usize = 100;
+
+thread_local! {
+    static WARNING_COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn inc_and_check_warning_count() -> bool {
+    WARNING_COUNT.with(|count| {
+        let new_count = count.get() + 1;
+        count.set(new_count);
+        let ok = new_count < MAX_WARNING_COUNT;
+        if !ok && new_count == MAX_WARNING_COUNT {
+            warning_no_count_check("Too many warnings, suppressing further ones.");
+        }
+        ok
+    })
+}